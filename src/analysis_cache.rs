@@ -0,0 +1,193 @@
+use crate::xai::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// How long an analysis stays reusable for a relisted market.
+const ANALYSIS_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedAnalysis {
+    /// Raw xAI response text, parsed again on reuse.
+    text: String,
+    #[serde(default)]
+    sources: Vec<String>,
+    /// Epoch seconds.
+    analyzed_at: u64,
+}
+
+/// xAI analyses keyed on normalized question text plus a hash of the
+/// description, so a market deleted and relisted under a new id reuses the
+/// earlier analysis instead of paying for it again.
+#[derive(Debug, Default)]
+pub struct AnalysisCache {
+    entries: HashMap<String, CachedAnalysis>,
+    /// Where the cache is persisted; `None` keeps it in memory only.
+    path: Option<PathBuf>,
+}
+
+fn analysis_cache_file_path() -> PathBuf {
+    crate::bot::data_dir().join("question_cache.json")
+}
+
+/// Lowercase, drop punctuation and collapse whitespace, so trivially edited
+/// relistings ("Will X happen?" vs "will x happen") share a key.
+pub fn normalize_question(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `DefaultHasher`, so persisted keys stay valid.
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Cache key: the normalized question plus a hash of the normalized description,
+/// so a relisting with materially different resolution criteria is re-analyzed.
+fn analysis_key(question: &str, description: Option<&str>) -> String {
+    let description = normalize_question(description.unwrap_or_default());
+    format!(
+        "{}#{:016x}",
+        normalize_question(question),
+        fnv1a(&description)
+    )
+}
+
+impl AnalysisCache {
+    pub fn load() -> Self {
+        let path = analysis_cache_file_path();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(
+            path,
+            serde_json::to_string(&self.entries).unwrap_or_default(),
+        );
+    }
+
+    /// A prior analysis of the same question and description, if still fresh.
+    pub fn get(&self, question: &str, description: Option<&str>, now: u64) -> Option<SearchResult> {
+        self.entries
+            .get(&analysis_key(question, description))
+            .filter(|a| now.saturating_sub(a.analyzed_at) < ANALYSIS_TTL_SECS)
+            .map(|a| SearchResult {
+                text: a.text.clone(),
+                sources: a.sources.clone(),
+            })
+    }
+
+    pub fn insert(
+        &mut self,
+        question: &str,
+        description: Option<&str>,
+        result: &SearchResult,
+        now: u64,
+    ) {
+        self.entries
+            .retain(|_, a| now.saturating_sub(a.analyzed_at) < ANALYSIS_TTL_SECS);
+        self.entries.insert(
+            analysis_key(question, description),
+            CachedAnalysis {
+                text: result.text.clone(),
+                sources: result.sources.clone(),
+                analyzed_at: now,
+            },
+        );
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_question() {
+        assert_eq!(
+            normalize_question("  Will GPT-5 launch   before July?"),
+            "will gpt 5 launch before july"
+        );
+        assert_eq!(
+            normalize_question("Will GPT-5 launch before July?"),
+            normalize_question("will gpt 5 launch before july")
+        );
+        assert_eq!(normalize_question(""), "");
+    }
+
+    fn result(text: &str) -> SearchResult {
+        SearchResult {
+            text: text.to_string(),
+            sources: vec!["https://x.com/a/status/1".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_keying() {
+        let mut cache = AnalysisCache::default();
+        let now = 1_700_000_000;
+        cache.insert(
+            "Will X happen by 2026?",
+            Some("Resolves YES if X happens."),
+            &result("{}"),
+            now,
+        );
+
+        // Relisting with cosmetic edits hits
+        assert!(cache
+            .get(
+                "will x happen by 2026",
+                Some("Resolves YES if X happens"),
+                now
+            )
+            .is_some());
+        // Materially changed description misses
+        assert!(cache
+            .get(
+                "Will X happen by 2026?",
+                Some("Resolves YES if Y happens."),
+                now
+            )
+            .is_none());
+        assert!(cache.get("Will X happen by 2026?", None, now).is_none());
+        // Expired entries miss
+        assert!(cache
+            .get(
+                "Will X happen by 2026?",
+                Some("Resolves YES if X happens."),
+                now + ANALYSIS_TTL_SECS
+            )
+            .is_none());
+
+        // Missing and empty descriptions share a key
+        cache.insert("Q", None, &result("a"), now);
+        let hit = cache.get("Q", Some(""), now).unwrap();
+        assert_eq!(hit.text, "a");
+        assert_eq!(hit.sources, ["https://x.com/a/status/1"]);
+    }
+}
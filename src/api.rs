@@ -1,7 +1,11 @@
+use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 
 const BASE_URL: &str = "https://api.manifold.markets/v0";
 
+/// Identifies the bot to Manifold and xAI on every request.
+pub const DEFAULT_USER_AGENT: &str = concat!("manifold-domination/", env!("CARGO_PKG_VERSION"));
+
 #[derive(Clone)]
 pub struct ManifoldClient {
     api_key: String,
@@ -21,16 +25,89 @@ pub struct User {
 #[serde(rename_all = "camelCase")]
 pub struct Market {
     pub id: String,
+    #[serde(default)]
+    pub slug: String,
     pub question: String,
     pub url: String,
     pub probability: Option<f64>,
     pub outcome_type: String,
     pub mechanism: String,
     pub is_resolved: bool,
+    /// `YES`, `NO`, `MKT` or `CANCEL` once resolved.
+    pub resolution: Option<String>,
     pub close_time: Option<u64>,
     pub creator_username: String,
+    #[serde(default)]
+    pub creator_id: String,
+    #[serde(default)]
+    pub creator_name: String,
+    #[serde(default)]
+    pub created_time: u64,
+    #[serde(default)]
+    pub visibility: String,
+    pub volume: Option<f64>,
     pub total_liquidity: Option<f64>,
+    /// Liquidity the creator seeded the market with, when the payload has it.
+    pub initial_liquidity: Option<f64>,
     pub text_description: Option<String>,
+    /// Epoch milliseconds of the latest bet.
+    pub last_bet_time: Option<u64>,
+}
+
+/// Manifold `outcomeType`s. Only `Binary` can be bet with a simple YES/NO
+/// limit order; every other type carries an explicit reason for skipping it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutcomeType {
+    Binary,
+    MultipleChoice,
+    FreeResponse,
+    PseudoNumeric,
+    Number,
+    MultiNumeric,
+    Date,
+    Stonk,
+    BountiedQuestion,
+    Poll,
+    QuadraticFunding,
+    Cert,
+    Unknown(String),
+}
+
+impl OutcomeType {
+    pub fn parse(outcome_type: &str) -> Self {
+        match outcome_type {
+            "BINARY" => Self::Binary,
+            "MULTIPLE_CHOICE" => Self::MultipleChoice,
+            "FREE_RESPONSE" => Self::FreeResponse,
+            "PSEUDO_NUMERIC" => Self::PseudoNumeric,
+            "NUMBER" => Self::Number,
+            "MULTI_NUMERIC" => Self::MultiNumeric,
+            "DATE" => Self::Date,
+            "STONK" => Self::Stonk,
+            "BOUNTIED_QUESTION" => Self::BountiedQuestion,
+            "POLL" => Self::Poll,
+            "QUADRATIC_FUNDING" => Self::QuadraticFunding,
+            "CERT" => Self::Cert,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+
+    /// Why the bot doesn't trade this type; `None` for `Binary`.
+    pub fn skip_reason(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Binary => return None,
+            Self::MultipleChoice => "one probability per answer, no single YES/NO price",
+            Self::FreeResponse => "user-submitted answers, no single YES/NO price",
+            Self::PseudoNumeric => "price is a numeric estimate, not a probability",
+            Self::Number | Self::MultiNumeric => "bets are on numeric ranges",
+            Self::Date => "bets are on date ranges",
+            Self::Stonk => "never resolves, price is not a probability",
+            Self::BountiedQuestion => "bounty question, no trading",
+            Self::Poll => "poll, votes instead of bets",
+            Self::QuadraticFunding | Self::Cert => "legacy type, no longer tradable",
+            Self::Unknown(_) => "unrecognized outcome type",
+        })
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -41,30 +118,250 @@ pub struct BetRequest {
     pub outcome: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit_prob: Option<f64>,
+    /// Epoch millis at which an unfilled limit order is cancelled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Cancel an unfilled limit order this many millis after placement.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_millis_after: Option<u64>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Body of `POST /v0/managram`: send mana to other users.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagramRequest {
+    pub amount: f64,
+    pub to_ids: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellRequest {
+    pub outcome: String,
+    /// `None` sells every share held in `outcome`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shares: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BetResponse {
     #[serde(alias = "betId")]
     pub bet_id: Option<String>,
     pub amount: Option<f64>,
+    pub shares: Option<f64>,
     pub outcome: Option<String>,
     pub contract_id: Option<String>,
+    /// For limit orders: whether the full order amount was matched.
+    pub is_filled: Option<bool>,
+}
+
+/// Public profile of any user (`/v0/user/{username}`). Manifold doesn't
+/// return bet or market counts here; `creator_traders` is the activity
+/// signal it does expose. Only the fields the creator filter reads are kept,
+/// all defaulted, so a sparse profile still passes the lookup.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserProfile {
+    #[serde(default)]
+    pub username: String,
+    /// Epoch milliseconds.
+    #[serde(default)]
+    pub created_time: u64,
+    #[serde(default)]
+    pub balance: f64,
+    /// Unique traders across the user's markets.
+    pub creator_traders: Option<PeriodCounts>,
+    /// Vetted by Manifold moderators.
+    #[serde(default)]
+    pub is_trustworthy: bool,
+    #[serde(default)]
+    pub is_bot: bool,
+    #[serde(default)]
+    pub is_banned_from_posting: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodCounts {
+    #[serde(default)]
+    pub all_time: u64,
+}
+
+/// A bet from a market's history (`/v0/bets`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketBet {
+    pub prob_after: f64,
+}
+
+/// One of a user's bets across all markets (`/v0/bets?userId=`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBet {
+    pub id: String,
+    pub contract_id: String,
+    pub outcome: String,
+    /// Mana paid; negative for sales.
+    pub amount: f64,
+    /// Shares bought; negative for sales.
+    #[serde(default)]
+    pub shares: f64,
+    /// Epoch milliseconds.
+    pub created_time: u64,
+}
+
+/// One of the user's limit orders (`/v0/bets?kinds=open-limit`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitOrder {
+    pub id: String,
+    pub contract_id: String,
+    pub outcome: String,
+    /// Mana filled so far.
+    pub amount: f64,
+    /// Mana the order was placed for.
+    #[serde(default)]
+    pub order_amount: f64,
+    /// Shares bought by the filled part.
+    #[serde(default)]
+    pub shares: f64,
+    pub limit_prob: f64,
+    #[serde(default)]
+    pub is_filled: bool,
+    #[serde(default)]
+    pub is_cancelled: bool,
+}
+
+impl LimitOrder {
+    /// Mana still waiting to fill.
+    pub fn unfilled(&self) -> f64 {
+        (self.order_amount - self.amount).max(0.0)
+    }
+}
+
+/// Error body Manifold returns on non-2xx responses (`{ "message": ..., "details": ... }`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ManifoldApiError {
+    #[serde(skip)]
+    pub status: u16,
+    pub message: String,
+}
+
+impl ManifoldApiError {
+    /// Parse an error body, falling back to the raw body when it isn't the usual JSON.
+    pub fn from_body(status: u16, body: &str) -> Self {
+        match serde_json::from_str::<ManifoldApiError>(body) {
+            Ok(err) => Self { status, ..err },
+            Err(_) => Self {
+                status,
+                message: body.to_string(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ManifoldApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "API error {}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for ManifoldApiError {}
+
+const GET_ME_ATTEMPTS: u32 = 4;
+const GET_ME_INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Whether a failed request is worth retrying: no response at all (network
+/// error), 429, or 5xx.
+fn is_retryable(status: Option<u16>) -> bool {
+    match status {
+        None => true,
+        Some(status) => status == 429 || status >= 500,
+    }
+}
+
+/// Whether a failure means the API key was rejected.
+pub fn is_auth_error(err: &reqwest::Error) -> bool {
+    matches!(err.status().map(|s| s.as_u16()), Some(401 | 403))
+}
+
+/// An item of a list endpoint paginated by `before=<id>`.
+pub trait Cursor {
+    fn cursor(&self) -> &str;
+}
+
+impl Cursor for UserBet {
+    fn cursor(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Cursor for Market {
+    fn cursor(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Hard cap on items `fetch_paginated` returns, whatever the caller asks for.
+pub const MAX_PAGINATED_ITEMS: usize = 50_000;
+
+/// Page through a newest-first list endpoint. `fetch_page(before, limit)`
+/// fetches one page after the item with id `before`; each page's last item is
+/// the next cursor. Stops at a short page, at `max_items`, or at the first item
+/// `keep` rejects (e.g. one older than a time boundary).
+pub async fn fetch_paginated<T, E, F, Fut>(
+    page_size: u32,
+    max_items: usize,
+    keep: impl Fn(&T) -> bool,
+    mut fetch_page: F,
+) -> Result<Vec<T>, E>
+where
+    T: Cursor,
+    F: FnMut(Option<String>, u32) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<T>, E>>,
+{
+    let max_items = max_items.min(MAX_PAGINATED_ITEMS);
+    let mut items: Vec<T> = Vec::new();
+    let mut before: Option<String> = None;
+    while items.len() < max_items {
+        let limit = page_size.min(u32::try_from(max_items - items.len()).unwrap_or(u32::MAX));
+        let page = fetch_page(before.take(), limit).await?;
+        let full = page.len() >= limit as usize;
+        let Some(last) = page.last() else {
+            break;
+        };
+        let next = last.cursor().to_string();
+        for item in page {
+            if !keep(&item) || items.len() >= max_items {
+                return Ok(items);
+            }
+            items.push(item);
+        }
+        if !full {
+            break;
+        }
+        before = Some(next);
+    }
+    Ok(items)
 }
 
 impl ManifoldClient {
     pub fn new(api_key: String) -> Self {
+        Self::with_user_agent(api_key, DEFAULT_USER_AGENT)
+    }
+
+    pub fn with_user_agent(api_key: String, user_agent: &str) -> Self {
         Self {
             api_key,
-            client: reqwest::Client::new(),
+            client: reqwest::Client::builder()
+                .user_agent(user_agent)
+                .build()
+                .unwrap_or_default(),
         }
     }
 
-    pub fn api_key(&self) -> &str {
-        &self.api_key
-    }
-
     pub async fn get_me(&self) -> Result<User, reqwest::Error> {
         self.client
             .get(format!("{BASE_URL}/me"))
@@ -76,6 +373,23 @@ impl ManifoldClient {
             .await
     }
 
+    /// `get_me` retried with exponential backoff on network errors, rate
+    /// limits and server errors. Auth failures and other client errors are
+    /// returned immediately.
+    pub async fn get_me_with_retry(&self) -> Result<User, reqwest::Error> {
+        let mut delay = GET_ME_INITIAL_BACKOFF;
+        for _ in 1..GET_ME_ATTEMPTS {
+            match self.get_me().await {
+                Err(e) if is_retryable(e.status().map(|s| s.as_u16())) => {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+        self.get_me().await
+    }
+
     pub async fn get_market(&self, id: &str) -> Result<Market, reqwest::Error> {
         self.client
             .get(format!("{BASE_URL}/market/{id}"))
@@ -86,10 +400,104 @@ impl ManifoldClient {
             .await
     }
 
+    pub async fn get_user(&self, username: &str) -> Result<UserProfile, reqwest::Error> {
+        self.client
+            .get(format!("{BASE_URL}/user/{username}"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// The most recent `limit` bets on a market, newest first.
+    pub async fn get_bets(
+        &self,
+        contract_id: &str,
+        limit: u32,
+    ) -> Result<Vec<MarketBet>, reqwest::Error> {
+        self.client
+            .get(format!("{BASE_URL}/bets"))
+            .query(&[
+                ("contractId", contract_id),
+                ("limit", limit.to_string().as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Up to `limit` of `user_id`'s bets across all markets, newest first,
+    /// starting after the bet with id `before` if given.
+    pub async fn get_user_bets(
+        &self,
+        user_id: &str,
+        before: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<UserBet>, reqwest::Error> {
+        let limit = limit.to_string();
+        let mut query = vec![("userId", user_id), ("limit", limit.as_str())];
+        if let Some(before) = before {
+            query.push(("before", before));
+        }
+        self.client
+            .get(format!("{BASE_URL}/bets"))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// `user_id`'s unfilled, uncancelled limit orders across all markets.
+    pub async fn get_open_orders(&self, user_id: &str) -> Result<Vec<LimitOrder>, reqwest::Error> {
+        self.client
+            .get(format!("{BASE_URL}/bets"))
+            .query(&[("userId", user_id), ("kinds", "open-limit")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// Up to `limit` markets, newest first, starting after the market with
+    /// id `before` if given. Unlike search, includes closed markets.
+    pub async fn get_markets(
+        &self,
+        before: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<Market>, reqwest::Error> {
+        let limit = limit.to_string();
+        let mut query = vec![("limit", limit.as_str())];
+        if let Some(before) = before {
+            query.push(("before", before));
+        }
+        self.client
+            .get(format!("{BASE_URL}/markets"))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
     pub async fn place_bet(
         &self,
         request: &BetRequest,
     ) -> Result<BetResponse, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(serde_json::from_value(self.place_bet_raw(request).await?)?)
+    }
+
+    /// Place a bet and return the response body untyped, for inspecting API changes.
+    pub async fn place_bet_raw(
+        &self,
+        request: &BetRequest,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let resp = self
             .client
             .post(format!("{BASE_URL}/bet"))
@@ -101,9 +509,505 @@ impl ManifoldClient {
         let status = resp.status();
         if !status.is_success() {
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Bet API error {status}: {body}").into());
+            return Err(ManifoldApiError::from_body(status.as_u16(), &body).into());
         }
 
         Ok(resp.json().await?)
     }
+
+    /// Cancel the unfilled remainder of a limit order.
+    pub async fn cancel_bet(
+        &self,
+        bet_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let resp = self
+            .client
+            .post(format!("{BASE_URL}/bet/cancel/{bet_id}"))
+            .header("Authorization", format!("Key {}", self.api_key))
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ManifoldApiError::from_body(status.as_u16(), &body).into());
+        }
+        Ok(())
+    }
+
+    /// Send `amount` mana to another user.
+    pub async fn send_managram(
+        &self,
+        to_user_id: &str,
+        amount: f64,
+        message: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let request = ManagramRequest {
+            amount,
+            to_ids: vec![to_user_id.to_string()],
+            message: message.to_string(),
+        };
+        let resp = self
+            .client
+            .post(format!("{BASE_URL}/managram"))
+            .header("Authorization", format!("Key {}", self.api_key))
+            .json(&request)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ManifoldApiError::from_body(status.as_u16(), &body).into());
+        }
+        Ok(())
+    }
+
+    /// Sell shares of `outcome` back to the pool. The response is the sale as a
+    /// bet, with a negative `amount` (the mana received).
+    pub async fn sell_shares(
+        &self,
+        contract_id: &str,
+        request: &SellRequest,
+    ) -> Result<BetResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let resp = self
+            .client
+            .post(format!("{BASE_URL}/market/{contract_id}/sell"))
+            .header("Authorization", format!("Key {}", self.api_key))
+            .json(request)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ManifoldApiError::from_body(status.as_u16(), &body).into());
+        }
+        Ok(resp.json().await?)
+    }
+}
+
+/// Error of a `ManifoldApi` call.
+pub type ApiError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The Manifold calls the bot makes once running. `ManifoldClient` makes them
+/// against the API; simulation mode swaps in `simulate::SimulatedManifold`.
+pub trait ManifoldApi: Send + Sync {
+    /// Redacted from anything the bot writes out.
+    fn api_key(&self) -> &str;
+
+    fn get_me(&self) -> BoxFuture<'_, Result<User, ApiError>>;
+
+    fn get_market<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Market, ApiError>>;
+
+    fn get_user<'a>(&'a self, username: &'a str) -> BoxFuture<'a, Result<UserProfile, ApiError>>;
+
+    fn get_bets<'a>(
+        &'a self,
+        contract_id: &'a str,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<MarketBet>, ApiError>>;
+
+    fn get_user_bets<'a>(
+        &'a self,
+        user_id: &'a str,
+        before: Option<&'a str>,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<UserBet>, ApiError>>;
+
+    fn get_open_orders<'a>(
+        &'a self,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<LimitOrder>, ApiError>>;
+
+    fn get_markets<'a>(
+        &'a self,
+        before: Option<&'a str>,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<Market>, ApiError>>;
+
+    fn place_bet<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, ApiError>>;
+
+    fn cancel_bet<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), ApiError>>;
+
+    fn send_managram<'a>(
+        &'a self,
+        to_user_id: &'a str,
+        amount: f64,
+        message: &'a str,
+    ) -> BoxFuture<'a, Result<(), ApiError>>;
+
+    fn sell_shares<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, ApiError>>;
+}
+
+impl ManifoldApi for ManifoldClient {
+    fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    fn get_me(&self) -> BoxFuture<'_, Result<User, ApiError>> {
+        Box::pin(async move { Ok(ManifoldClient::get_me(self).await?) })
+    }
+
+    fn get_market<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Market, ApiError>> {
+        Box::pin(async move { Ok(ManifoldClient::get_market(self, id).await?) })
+    }
+
+    fn get_user<'a>(&'a self, username: &'a str) -> BoxFuture<'a, Result<UserProfile, ApiError>> {
+        Box::pin(async move { Ok(ManifoldClient::get_user(self, username).await?) })
+    }
+
+    fn get_bets<'a>(
+        &'a self,
+        contract_id: &'a str,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<MarketBet>, ApiError>> {
+        Box::pin(async move { Ok(ManifoldClient::get_bets(self, contract_id, limit).await?) })
+    }
+
+    fn get_user_bets<'a>(
+        &'a self,
+        user_id: &'a str,
+        before: Option<&'a str>,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<UserBet>, ApiError>> {
+        Box::pin(
+            async move { Ok(ManifoldClient::get_user_bets(self, user_id, before, limit).await?) },
+        )
+    }
+
+    fn get_open_orders<'a>(
+        &'a self,
+        user_id: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<LimitOrder>, ApiError>> {
+        Box::pin(async move { Ok(ManifoldClient::get_open_orders(self, user_id).await?) })
+    }
+
+    fn get_markets<'a>(
+        &'a self,
+        before: Option<&'a str>,
+        limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<Market>, ApiError>> {
+        Box::pin(async move { Ok(ManifoldClient::get_markets(self, before, limit).await?) })
+    }
+
+    fn place_bet<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, ApiError>> {
+        Box::pin(ManifoldClient::place_bet(self, request))
+    }
+
+    fn cancel_bet<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(ManifoldClient::cancel_bet(self, bet_id))
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        to_user_id: &'a str,
+        amount: f64,
+        message: &'a str,
+    ) -> BoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(ManifoldClient::send_managram(
+            self, to_user_id, amount, message,
+        ))
+    }
+
+    fn sell_shares<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, ApiError>> {
+        Box::pin(ManifoldClient::sell_shares(self, contract_id, request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A newest-first list served in pages, like `/v0/bets?before=`.
+    fn serve_pages(
+        bets: &[UserBet],
+        requests: &std::cell::RefCell<Vec<(Option<String>, u32)>>,
+        before: Option<String>,
+        limit: u32,
+    ) -> Result<Vec<UserBet>, String> {
+        requests.borrow_mut().push((before.clone(), limit));
+        let start = match before {
+            Some(id) => bets.iter().position(|b| b.id == id).ok_or("bad cursor")? + 1,
+            None => 0,
+        };
+        Ok(bets
+            .iter()
+            .skip(start)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    #[test]
+    fn test_fetch_paginated() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let bets: Vec<UserBet> = (0..25u64)
+            .rev()
+            .map(|i| UserBet {
+                id: format!("b{i}"),
+                contract_id: "m".to_string(),
+                outcome: "YES".to_string(),
+                amount: 1.0,
+                shares: 2.0,
+                created_time: i * 1000,
+            })
+            .collect();
+        let requests = std::cell::RefCell::new(Vec::new());
+        let fetch = |keep: &dyn Fn(&UserBet) -> bool, page_size: u32, max_items: usize| {
+            requests.borrow_mut().clear();
+            rt.block_on(fetch_paginated(
+                page_size,
+                max_items,
+                keep,
+                |before, limit| {
+                    let page = serve_pages(&bets, &requests, before, limit);
+                    async move { page }
+                },
+            ))
+            .unwrap()
+        };
+
+        // Everything, following the cursor until the short last page
+        let all = fetch(&|_| true, 10, 1000);
+        assert_eq!(all.len(), 25);
+        assert_eq!((all[0].id.as_str(), all[24].id.as_str()), ("b24", "b0"));
+        assert_eq!(
+            *requests.borrow(),
+            [
+                (None, 10),
+                (Some("b15".to_string()), 10),
+                (Some("b5".to_string()), 10)
+            ]
+        );
+
+        // A count bound shrinks the last request
+        let capped = fetch(&|_| true, 10, 15);
+        assert_eq!(capped.len(), 15);
+        assert_eq!(requests.borrow()[1], (Some("b15".to_string()), 5));
+
+        // A time boundary stops at the first older item
+        let recent = fetch(&|b| b.created_time >= 12_000, 10, 1000);
+        assert_eq!(recent.len(), 13);
+        assert_eq!(recent.last().unwrap().id, "b12");
+        assert_eq!(requests.borrow().len(), 2);
+
+        // A page exactly filling the list ends on the empty one after it
+        let exact = fetch(&|_| true, 5, 1000);
+        assert_eq!(exact.len(), 25);
+        assert_eq!(requests.borrow().len(), 6);
+
+        // Errors are passed through
+        let failed = rt.block_on(fetch_paginated(
+            10,
+            100,
+            |_: &UserBet| true,
+            |_, _| async { Err::<Vec<UserBet>, _>("down") },
+        ));
+        assert_eq!(failed.unwrap_err(), "down");
+    }
+
+    #[test]
+    fn test_managram_request_json() {
+        let request = ManagramRequest {
+            amount: 250.0,
+            to_ids: vec!["vault-user-id".to_string()],
+            message: "Profit sweep".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "amount": 250.0,
+                "toIds": ["vault-user-id"],
+                "message": "Profit sweep",
+            })
+        );
+    }
+
+    #[test]
+    fn test_user_profile_json() {
+        let json = r#"{
+            "id": "igi2zGXsfxYPgB0DJTXVJVmwCOr2",
+            "createdTime": 1639011767273,
+            "name": "Austin",
+            "username": "Austin",
+            "url": "https://manifold.markets/Austin",
+            "avatarUrl": "https://example.com/a.png",
+            "bio": "",
+            "twitterHandle": "akrolsmir",
+            "isBot": false,
+            "isAdmin": true,
+            "isTrustworthy": true,
+            "isBannedFromPosting": false,
+            "balance": 26543.7,
+            "totalDeposits": 30120.5,
+            "lastBetTime": 1718900000000,
+            "currentBettingStreak": 3,
+            "profitCached": {"daily": 12.5, "weekly": -3.0, "monthly": 80.2, "allTime": 9120.0},
+            "creatorTraders": {"daily": 1, "weekly": 4, "monthly": 20, "allTime": 1500}
+        }"#;
+        let profile: UserProfile = serde_json::from_str(json).unwrap();
+        assert_eq!(profile.username, "Austin");
+        assert_eq!(profile.created_time, 1639011767273);
+        assert_eq!(profile.balance, 26543.7);
+        assert_eq!(profile.creator_traders.unwrap().all_time, 1500);
+        assert!(profile.is_trustworthy);
+        assert!(!profile.is_bot && !profile.is_banned_from_posting);
+
+        // Deleted or minimal accounts may omit the balance
+        let minimal = r#"{"id": "u1", "createdTime": 0, "name": "X", "username": "x"}"#;
+        let profile: UserProfile = serde_json::from_str(minimal).unwrap();
+        assert_eq!(profile.balance, 0.0);
+        assert!(profile.creator_traders.is_none() && !profile.is_trustworthy);
+
+        // A profile missing fields the filter doesn't need still parses
+        let sparse = r#"{"username": "x", "balance": 10.0}"#;
+        let profile: UserProfile = serde_json::from_str(sparse).unwrap();
+        assert_eq!(profile.created_time, 0);
+        assert_eq!(profile.balance, 10.0);
+    }
+
+    #[test]
+    fn test_limit_order_json() {
+        let json = r#"[{
+            "id": "b1",
+            "userId": "u1",
+            "contractId": "c1",
+            "createdTime": 1718900000000,
+            "amount": 4.0,
+            "orderAmount": 10.0,
+            "shares": 5.9,
+            "outcome": "YES",
+            "probBefore": 0.62,
+            "probAfter": 0.68,
+            "limitProb": 0.68,
+            "isFilled": false,
+            "isCancelled": false,
+            "fills": [],
+            "isRedemption": false
+        }]"#;
+        let orders: Vec<LimitOrder> = serde_json::from_str(json).unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].contract_id, "c1");
+        assert_eq!(orders[0].limit_prob, 0.68);
+        assert!(!orders[0].is_filled && !orders[0].is_cancelled);
+        assert_eq!(orders[0].unfilled(), 6.0);
+    }
+
+    #[test]
+    fn test_sell_request_json() {
+        let all = SellRequest {
+            outcome: "NO".to_string(),
+            shares: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&all).unwrap(),
+            serde_json::json!({"outcome": "NO"})
+        );
+        let some = SellRequest {
+            outcome: "YES".to_string(),
+            shares: Some(12.5),
+        };
+        assert_eq!(
+            serde_json::to_value(&some).unwrap(),
+            serde_json::json!({"outcome": "YES", "shares": 12.5})
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(None));
+        assert!(is_retryable(Some(429)));
+        assert!(is_retryable(Some(500)));
+        assert!(is_retryable(Some(503)));
+        assert!(!is_retryable(Some(401)));
+        assert!(!is_retryable(Some(403)));
+        assert!(!is_retryable(Some(404)));
+    }
+
+    #[test]
+    fn test_outcome_type_skip_reasons() {
+        assert_eq!(OutcomeType::parse("BINARY"), OutcomeType::Binary);
+        assert_eq!(OutcomeType::Binary.skip_reason(), None);
+
+        let known = [
+            ("MULTIPLE_CHOICE", OutcomeType::MultipleChoice),
+            ("FREE_RESPONSE", OutcomeType::FreeResponse),
+            ("PSEUDO_NUMERIC", OutcomeType::PseudoNumeric),
+            ("NUMBER", OutcomeType::Number),
+            ("MULTI_NUMERIC", OutcomeType::MultiNumeric),
+            ("DATE", OutcomeType::Date),
+            ("STONK", OutcomeType::Stonk),
+            ("BOUNTIED_QUESTION", OutcomeType::BountiedQuestion),
+            ("POLL", OutcomeType::Poll),
+            ("QUADRATIC_FUNDING", OutcomeType::QuadraticFunding),
+            ("CERT", OutcomeType::Cert),
+        ];
+        for (name, outcome_type) in known {
+            assert_eq!(OutcomeType::parse(name), outcome_type);
+            assert!(outcome_type.skip_reason().is_some(), "{name}");
+        }
+
+        let unknown = OutcomeType::parse("SOMETHING_NEW");
+        assert_eq!(unknown, OutcomeType::Unknown("SOMETHING_NEW".to_string()));
+        assert_eq!(unknown.skip_reason(), Some("unrecognized outcome type"));
+        // Case matters: Manifold always sends upper snake case
+        assert!(OutcomeType::parse("binary").skip_reason().is_some());
+    }
+
+    #[test]
+    fn test_bet_request_expiry_serialization() {
+        let mut request = BetRequest {
+            contract_id: "abc".to_string(),
+            amount: 10.0,
+            outcome: "YES".to_string(),
+            limit_prob: Some(0.6),
+            expires_at: None,
+            expires_millis_after: None,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("expiresMillisAfter").is_none());
+        assert!(json.get("expires_millis_after").is_none());
+
+        request.expires_millis_after = Some(60_000);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["expiresMillisAfter"], 60_000);
+        assert_eq!(json["limitProb"], 0.6);
+    }
+
+    #[test]
+    fn test_manifold_api_error_from_body() {
+        let err = ManifoldApiError::from_body(
+            403,
+            r#"{"message":"Insufficient balance.","details":{"balance":3}}"#,
+        );
+        assert_eq!(err.status, 403);
+        assert_eq!(err.message, "Insufficient balance.");
+
+        let err = ManifoldApiError::from_body(403, r#"{"message":"Trading is closed."}"#);
+        assert_eq!(err.message, "Trading is closed.");
+
+        // Not the usual JSON shape: keep the raw body
+        let err = ManifoldApiError::from_body(502, "<html>Bad Gateway</html>");
+        assert_eq!(err.message, "<html>Bad Gateway</html>");
+        let err = ManifoldApiError::from_body(400, r#"{"error":"nope"}"#);
+        assert_eq!(err.message, r#"{"error":"nope"}"#);
+        assert_eq!(err.to_string(), r#"API error 400: {"error":"nope"}"#);
+    }
 }
@@ -1,27 +1,691 @@
-use crate::api::{BetRequest, ManifoldClient};
-use crate::ws::{BetData, NewContractBroadcast, WsEvent};
-use crate::xai::{self, XaiClient};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::analysis_cache::AnalysisCache;
+use crate::api::{
+    fetch_paginated, ApiError, BetRequest, BetResponse, LimitOrder, ManifoldApi, ManifoldApiError,
+    ManifoldClient, Market, OutcomeType, SellRequest, UserBet, UserProfile, DEFAULT_USER_AGENT,
+};
+use crate::execution::{self, ExecutionBackend, ExecutionMode};
+use crate::paper::{PaperEntry, PaperLedger};
+use crate::pnl::{Calibration, LedgerEntry, PnlTracker, Position, Strategy};
+use crate::sanity::SanityCheck;
+use crate::simulate::{SimulatedExecutor, SimulatedManifold, Simulation, Simulator};
+#[cfg(feature = "sqlite")]
+use crate::store::Store;
+use crate::trace::DecisionTrace;
+use crate::ws::{self, BetData, ContractData, CreatorData, NewContractBroadcast, WsEvent};
+use crate::xai::{self, ResearchParams, SearchResult, XaiClient};
+use crate::xai_health::{XaiErrorKind, XaiHealth, XaiHealthStats};
+use crate::xai_replay::{ReplayMode, XaiReplay};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
 
 #[derive(Debug, Clone)]
 pub enum BotLogEntry {
     Info(String),
     Trade(String),
     Error(String),
+    /// Hypothetical decision from a shadow config; never placed.
+    Shadow(String),
 }
 
+#[derive(Debug, Clone)]
+pub enum BotCommand {
+    /// Resume trading after an auto-pause.
+    Resume,
+    /// Resume analysis after too many xAI errors, starting a fresh error window.
+    ResumeAnalysis,
+    /// Write the daily report for the day so far.
+    Report,
+    /// Re-run the full pipeline for a market in the failed list.
+    Retry(String),
+    /// Forget every finished analysis so markets are analyzed again.
+    ClearCache,
+    /// Forget the finished analysis of one market.
+    Forget(String),
+    /// Send a `diagnostics_snapshot` back as `BotStatus::Diagnostics`.
+    Diagnostics,
+    /// Swap in a new config without restarting the feed. Applies to events
+    /// from now on; `STARTUP_ONLY_SETTINGS` keep their running values.
+    UpdateConfig(Box<BotConfig>),
+}
+
+/// A failed Manifold call, classified so the bot can decide whether to retry,
+/// back off or stop trading.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotError {
+    InsufficientBalance,
+    /// The market is closed or resolved.
+    MarketClosed,
+    /// Manifold refused the limit order itself (e.g. price out of range).
+    LimitOrderRejected(String),
+    /// Any other API error response.
+    Api {
+        status: u16,
+        message: String,
+    },
+    /// The request never got an API response (network, timeout, bad JSON).
+    Request(String),
+}
+
+impl From<&ManifoldApiError> for BotError {
+    fn from(err: &ManifoldApiError) -> Self {
+        let message = err.message.to_lowercase();
+        if message.contains("insufficient balance") {
+            BotError::InsufficientBalance
+        } else if message.contains("trading is closed") || message.contains("market is closed") {
+            BotError::MarketClosed
+        } else if message.contains("limit order") {
+            BotError::LimitOrderRejected(err.message.clone())
+        } else {
+            BotError::Api {
+                status: err.status,
+                message: err.message.clone(),
+            }
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for BotError {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        match err.downcast_ref::<ManifoldApiError>() {
+            Some(api) => api.into(),
+            None => BotError::Request(err.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotError::InsufficientBalance => write!(f, "insufficient balance"),
+            BotError::MarketClosed => write!(f, "market is closed"),
+            BotError::LimitOrderRejected(msg) => write!(f, "limit order rejected: {msg}"),
+            BotError::Api { status, message } => write!(f, "API error {status}: {message}"),
+            BotError::Request(msg) => write!(f, "request failed: {msg}"),
+        }
+    }
+}
+
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// State updates for the dashboard, separate from the human-readable log.
+#[derive(Debug, Clone)]
+pub enum BotStatus {
+    /// Consecutive resolved losses and whether trading is auto-paused.
+    LossStreak { losses: u32, paused: bool },
+    /// The bot loop is alive. Sent every `HEARTBEAT_INTERVAL` even when no
+    /// market events arrive, so a quiet feed can be told apart from a dead bot.
+    Heartbeat,
+    /// A market the model skipped that may still be worth a human look.
+    NeedsHuman(NeedsHumanMarket),
+    /// Every analysis currently in progress, sent whenever the set changes.
+    Analyzing(Vec<InFlightAnalysis>),
+    /// Recently failed markets that can be retried, sent whenever the list changes.
+    Failed(Vec<FailedMarket>),
+    /// The balance while too low for the configured bets, or `None` once it
+    /// has recovered.
+    InsufficientBalance(Option<f64>),
+    /// Watched markets closing within `BotConfig::urgent_close`, soonest first.
+    ClosingSoon(Vec<ClosingMarket>),
+    /// Analyzed-cache size, sent on every heartbeat and after clearing.
+    Cache(CacheStats),
+    /// Recent xAI error rate and whether analysis is paused because of it,
+    /// sent after every xAI call and on every heartbeat.
+    Xai { stats: XaiHealthStats, paused: bool },
+    /// The bot's overall condition, sent whenever it changes.
+    State(BotState),
+    /// Answer to `BotCommand::Diagnostics`.
+    Diagnostics(serde_json::Value),
+}
+
+/// Everything that affects whether the bot is trading, folded into one
+/// indicator. Front ends show `NotStarted` and `Stopped` themselves; the bot
+/// sends the rest.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum BotState {
+    #[default]
+    NotStarted,
+    /// Waiting for the WebSocket feed (at start or after a drop).
+    Connecting,
+    Running,
+    /// Not betting for now, and will resume on its own (short balance, blackout).
+    Paused(String),
+    /// Running, but most recent xAI calls are failing.
+    Degraded(String),
+    /// A circuit breaker stopped trading or analysis until resumed manually.
+    Halted(String),
+    Stopped,
+}
+
+impl std::fmt::Display for BotState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotState::NotStarted => write!(f, "Not started"),
+            BotState::Connecting => write!(f, "Connecting..."),
+            BotState::Running => write!(f, "Running"),
+            BotState::Paused(why) => write!(f, "Paused: {why}"),
+            BotState::Degraded(why) => write!(f, "Degraded: {why}"),
+            BotState::Halted(why) => write!(f, "Halted: {why}"),
+            BotState::Stopped => write!(f, "Stopped"),
+        }
+    }
+}
+
+/// The state for these conditions; the one most in need of attention wins.
+fn derive_state(
+    connected: bool,
+    trading_paused: bool,
+    analysis_paused: bool,
+    short_balance: Option<f64>,
+    blackout: Option<&Blackout>,
+    xai: &XaiHealthStats,
+) -> BotState {
+    if trading_paused {
+        BotState::Halted("loss circuit breaker tripped".to_string())
+    } else if analysis_paused {
+        BotState::Halted("analysis stopped after repeated xAI errors".to_string())
+    } else if let Some(balance) = short_balance {
+        BotState::Paused(format!("insufficient balance (M${balance:.0})"))
+    } else if let Some(blackout) = blackout {
+        BotState::Paused(format!("blackout {blackout}"))
+    } else if xai.is_spiking() {
+        BotState::Degraded(format!(
+            "{:.0}% of recent xAI calls failing",
+            xai.error_rate() * 100.0
+        ))
+    } else if !connected {
+        BotState::Connecting
+    } else {
+        BotState::Running
+    }
+}
+
+/// Recompute the bot's state and send it if it changed.
+fn publish_state(ctx: &BotContext) {
+    let state = derive_state(
+        ctx.connected.load(Ordering::SeqCst),
+        ctx.paused.load(Ordering::SeqCst),
+        ctx.analysis_paused.load(Ordering::SeqCst),
+        *ctx.short_balance.lock().unwrap(),
+        active_blackout(&ctx.config.blackouts, now_epoch_secs()),
+        &ctx.xai_health.lock().unwrap().stats(),
+    );
+    let mut current = ctx.state.lock().unwrap();
+    if *current != state {
+        *current = state.clone();
+        let _ = ctx.status_tx.send(BotStatus::State(state));
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    /// Markets with a finished analysis.
+    pub markets: usize,
+    /// Markets claimed by a queued or running analysis.
+    pub in_progress: usize,
+    /// Epoch seconds of the oldest and newest finished analysis.
+    pub oldest: Option<u64>,
+    pub newest: Option<u64>,
+}
+
+/// A market whose research or bet placement failed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailedMarket {
+    pub contract_id: String,
+    pub question: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InFlightAnalysis {
+    pub contract_id: String,
+    pub question: String,
+    /// Epoch seconds.
+    pub started_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosingMarket {
+    pub contract_id: String,
+    pub question: String,
+    /// Epoch seconds.
+    pub close_time: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeedsHumanMarket {
+    pub contract_id: String,
+    pub question: String,
+    pub reason: String,
+    pub liquidity: f64,
+}
+
+/// Channels connecting `run_bot` to the WebSocket task and the UI.
+pub struct BotChannels {
+    pub ws_rx: mpsc::UnboundedReceiver<WsEvent>,
+    pub cmd_rx: mpsc::UnboundedReceiver<BotCommand>,
+    pub log_tx: mpsc::UnboundedSender<BotLogEntry>,
+    pub status_tx: mpsc::UnboundedSender<BotStatus>,
+}
+
+/// The consumer side of a running bot: everything a front end (the dashboard
+/// or headless stdout) reads from or sends to it.
+pub struct BotHandles {
+    /// Every WebSocket event, as also seen by the bot.
+    pub ws_events: mpsc::UnboundedReceiver<WsEvent>,
+    pub logs: mpsc::UnboundedReceiver<BotLogEntry>,
+    pub status: mpsc::UnboundedReceiver<BotStatus>,
+    pub commands: mpsc::UnboundedSender<BotCommand>,
+    /// Live changes to the WebSocket subscriptions.
+    pub topics: mpsc::UnboundedSender<ws::TopicCommand>,
+}
+
+/// Owns the WebSocket feed and bot tasks and the channels between them, so any
+/// front end can drive the bot without knowing how it is wired.
+pub struct Orchestrator {
+    manifold_key: String,
+    xai_key: String,
+    config: BotConfig,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Orchestrator {
+    pub fn new(manifold_key: String, xai_key: String, config: BotConfig) -> Self {
+        Self {
+            manifold_key,
+            xai_key,
+            config,
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Spawn the WebSocket feed and the bot on the current tokio runtime,
+    /// stopping any previous run first. Refuses a config that fails
+    /// `BotConfig::validate`.
+    pub fn start(&mut self) -> Result<BotHandles, String> {
+        self.stop();
+        self.config.validate()?;
+
+        let user_agent = &self.config.user_agent;
+        let manifold = ManifoldClient::with_user_agent(self.manifold_key.clone(), user_agent);
+        let xai = XaiClient::new(self.xai_key.clone(), user_agent)
+            .with_params(self.config.research.clone());
+
+        let (ws_internal_tx, mut ws_internal_rx) = mpsc::unbounded_channel::<WsEvent>();
+        let (ws_to_bot_tx, ws_to_bot_rx) = mpsc::unbounded_channel::<WsEvent>();
+        let (ws_to_ui_tx, ws_to_ui_rx) = mpsc::unbounded_channel::<WsEvent>();
+        let (log_tx, log_rx) = mpsc::unbounded_channel::<BotLogEntry>();
+        let (cmd_tx, cmd_rx) = mpsc::unbounded_channel::<BotCommand>();
+        let (status_tx, status_rx) = mpsc::unbounded_channel::<BotStatus>();
+        let (topic_tx, topic_rx) = mpsc::unbounded_channel::<ws::TopicCommand>();
+
+        self.tasks.push(tokio::spawn(ws::run_ws(
+            ws_internal_tx,
+            self.config.user_agent.clone(),
+            self.config.ws_topics.clone(),
+            topic_rx,
+            self.config.ws,
+        )));
+
+        // Fan each WebSocket event out to the front end and the bot
+        self.tasks.push(tokio::spawn(async move {
+            while let Some(event) = ws_internal_rx.recv().await {
+                let _ = ws_to_ui_tx.send(event.clone());
+                if ws_to_bot_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        }));
+
+        let channels = BotChannels {
+            ws_rx: ws_to_bot_rx,
+            cmd_rx,
+            log_tx,
+            status_tx,
+        };
+        self.tasks.push(tokio::spawn(run_bot(
+            Arc::new(manifold),
+            xai,
+            channels,
+            self.config.clone(),
+        )));
+
+        Ok(BotHandles {
+            ws_events: ws_to_ui_rx,
+            logs: log_rx,
+            status: status_rx,
+            commands: cmd_tx,
+            topics: topic_tx,
+        })
+    }
+
+    /// Abort the feed and the bot loop. Every task the bot started (analyses,
+    /// the bet executor, background rounds) goes with it.
+    pub fn stop(&mut self) {
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for Orchestrator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Shared handles cloned into each spawned market handler.
 #[derive(Clone)]
+struct BotContext {
+    /// The Manifold API, or its stand-in in simulation mode.
+    manifold: Arc<dyn ManifoldApi>,
+    xai: XaiClient,
+    log_tx: mpsc::UnboundedSender<BotLogEntry>,
+    status_tx: mpsc::UnboundedSender<BotStatus>,
+    config: BotConfig,
+    pnl: Arc<Mutex<PnlTracker>>,
+    analyzed: Arc<Mutex<AnalyzedCache>>,
+    /// Every would-be decision, when `BotConfig::paper_ledger` is on.
+    paper: Option<Arc<Mutex<PaperLedger>>>,
+    /// Set by the loss-streak breaker; no bets are placed while true.
+    paused: Arc<AtomicBool>,
+    xai_health: Arc<Mutex<XaiHealth>>,
+    /// Set by `xai_pause`; no xAI research is started while true.
+    analysis_paused: Arc<AtomicBool>,
+    /// Whether the WebSocket feed is up.
+    connected: Arc<AtomicBool>,
+    /// Last state sent to the front end.
+    state: Arc<Mutex<BotState>>,
+    analyses: Arc<Mutex<AnalysisCache>>,
+    counters: Arc<Mutex<CounterState>>,
+    /// Analyses in progress, for the dashboard.
+    in_flight: Arc<Mutex<Vec<InFlightAnalysis>>>,
+    /// Recent failures with what to re-run, newest last.
+    failed: Arc<Mutex<Vec<(FailedMarket, AnalysisJob)>>>,
+    /// Analyses that ran into `xai_pause`, rerun once analysis resumes.
+    held: Arc<Mutex<Vec<AnalysisJob>>>,
+    /// Last known balance while it is too low to place bets at full size.
+    short_balance: Arc<Mutex<Option<f64>>>,
+    /// Balance for the exposure check, refetched every `BALANCE_TTL`.
+    balance: Arc<Mutex<BalanceCache>>,
+    /// How many times each limit order's market has been re-priced.
+    repriced: Arc<Mutex<HashMap<String, u32>>>,
+    /// When each held market was last re-evaluated (epoch seconds).
+    reevaluated: Arc<Mutex<HashMap<String, u64>>>,
+    /// When the last `place_bet` call was made, for `min_bet_interval_secs`.
+    last_bet_at: Arc<Mutex<Option<Instant>>>,
+    /// Creator profiles by username, with when each was fetched.
+    creators: Arc<Mutex<HashMap<String, (UserProfile, u64)>>>,
+    /// Places, simulates or emits each bet, per `BotConfig::execution`.
+    executor: Arc<dyn ExecutionBackend>,
+    /// Set in simulation mode; stands in for xAI research.
+    simulator: Option<Arc<Simulator>>,
+    /// Set by `xai_replay`.
+    replay: Option<Arc<Mutex<XaiReplay>>>,
+    /// Queue of decided bets for the executor. Weak, so the executor exits once
+    /// `run_bot` (the only strong sender) stops.
+    orders: mpsc::WeakUnboundedSender<BetOrder>,
+    /// Write-through SQLite copy of analyses, bets and resolutions.
+    #[cfg(feature = "sqlite")]
+    store: Option<Arc<Mutex<Store>>>,
+}
+
+/// Time-in-force for the bot's limit orders.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum OrderTif {
+    /// Good-til-cancelled: unfilled remainder rests on the book.
+    #[default]
+    Gtc,
+    /// Immediate-or-cancel: keep whatever fills now, cancel the rest.
+    Ioc,
+    /// Fill-or-kill: intended all-or-nothing. Manifold cannot unwind a partial
+    /// fill, so the remainder is cancelled and the partial fill is flagged.
+    Fok,
+}
+
+/// How long an IOC/FOK order may rest before Manifold expires it, as a backstop
+/// in case the explicit cancel fails.
+const IMMEDIATE_EXPIRY_MS: u64 = 1000;
+
+/// Loaded from `config.json` in the data directory (see `BotConfig::load`);
+/// missing fields keep their defaults.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BotConfig {
     pub bet_amount: f64,
     /// Bet size for existing markets discovered via new-bet events.
     pub reversion_amount: f64,
+    /// Whether bet-triggered analysis bets any edge or only with the move.
+    /// Momentum bets are sized like reversion bets.
+    pub bet_triggered_mode: BetTriggeredMode,
+    /// Scale reversion bets by how far the market overshot the prediction
+    /// instead of betting a flat `reversion_amount`. Momentum bets are scaled
+    /// the same way, by how far the prediction runs ahead of the market.
+    pub reversion_scaling: Option<EdgeScaling>,
     /// Minimum absolute edge (prediction vs market) to place a bet.
     pub min_edge: f64,
+    /// Extra edge required per year until the market closes, so capital goes to
+    /// faster-resolving markets (0.05: a market closing in a year needs
+    /// `min_edge + 5%`).
+    pub min_edge_per_year: f64,
     /// Minimum pool liquidity (mana) to consider a market worth trading.
     pub min_liquidity: f64,
+    /// Minimum liquidity the creator seeded the market with, as a sign it's a
+    /// serious market rather than a drive-by question. Markets whose payload
+    /// lacks the seed are judged by their current pool instead.
+    pub min_initial_liquidity: Option<f64>,
+    /// Longest outage (seconds) to backfill after a reconnect; older markets are ignored.
+    pub backfill_max_secs: u64,
+    /// Maximum number of markets fetched per backfill.
+    pub backfill_limit: u32,
+    /// When a new-contract event has no liquidity field, fetch the market over
+    /// REST (one extra call) instead of treating it as zero liquidity.
+    pub fetch_missing_liquidity: bool,
+    /// Cap on how far (absolute probability) a limit price may sit from the market.
+    pub max_limit_distance: Option<f64>,
+    /// How often to check held markets for resolution.
+    pub resolution_poll_secs: u64,
+    /// Pause trading after this many consecutive resolved losses; resume is manual.
+    /// Checked alongside `calibration_breaker` when both are set.
+    pub max_loss_streak: Option<u32>,
+    /// Pause trading when, over enough recently resolved markets, the win
+    /// rate falls significantly short of what the predictions imply. Unlike a
+    /// raw loss streak, this doesn't trip on a few unlucky bets.
+    pub calibration_breaker: Option<CalibrationBreaker>,
+    /// New markets younger than this are deferred until they reach this age.
+    pub min_market_age_secs: u64,
+    /// Bet-triggered analysis skips markets older than this.
+    pub max_market_age_secs: Option<u64>,
+    /// Skip markets whose close time has passed (closed, not yet resolved)
+    /// before analyzing them.
+    pub skip_closed_markets: bool,
+    /// Only analyze markets priced within this range (e.g. `(0.2, 0.8)`), so
+    /// capital goes where there's real disagreement. `(0.0, 1.0)` allows all.
+    pub tradable_price_range: (f64, f64),
+    /// Minimum time between any two `place_bet` calls (ladder rungs included);
+    /// bets ready sooner wait in the execution queue.
+    pub min_bet_interval_secs: Option<u64>,
+    /// Drop new-bet events that waited longer than this before being handled,
+    /// since the move they react to is likely gone.
+    pub max_bet_event_age_secs: Option<u64>,
+    pub order_tif: OrderTif,
+    /// Server-side expiry for resting (GTC) limit orders; `None` rests forever.
+    pub limit_expiry_ms: Option<u64>,
+    /// Cap on open (unresolved) exposure as a fraction of bankroll (balance + open exposure).
+    /// The balance is fetched at most once a minute, less the bets placed since.
+    pub max_total_exposure_fraction: Option<f64>,
+    /// Hold unresolved bets in at most this many markets; new markets wait
+    /// until some resolve, while held ones can still be bet.
+    pub max_open_positions: Option<usize>,
+    /// At startup, add positions the account already holds in markets the
+    /// ledger doesn't know to it, so exposure limits count them. They're
+    /// never sold, re-priced or counted in the bot's P&L.
+    pub restore_positions: bool,
+    /// Market visibilities the bot will trade (`public`, `unlisted`, `private`).
+    pub trade_visibility: Vec<String>,
+    /// Market mechanisms the bot will trade. Edge and limit pricing assume
+    /// `cpmm-1`; others (e.g. legacy `dpm-2`) price and fill differently.
+    pub trade_mechanisms: Vec<String>,
+    /// ISO 639-3 codes (`eng`, `spa`, ...) of question languages worth
+    /// analyzing; research works best in English. Empty allows any.
+    pub allowed_languages: Vec<String>,
+    /// Manifold `outcomeType`s the bot will attempt. Only `BINARY` is priced
+    /// correctly today; others are opt-in as support lands.
+    pub tradable_outcome_types: Vec<String>,
+    /// Skip markets whose creator's account is too new or too poor, a common
+    /// sign of a market set up to be manipulated.
+    pub creator_filter: Option<CreatorFilter>,
+    /// Show developer tools (the manual bet panel) in the dashboard.
+    pub dev_tools: bool,
+    /// Reuse a prior analysis of a market with the same question and description
+    /// (e.g. a relisting under a new id) instead of calling xAI again.
+    pub reuse_analysis_by_question: bool,
+    /// Stream xAI responses and decide as soon as the prediction is complete;
+    /// falls back to a regular request if the stream fails. Ignored when
+    /// `min_sources` is set, since streamed results carry no sources.
+    pub xai_streaming: bool,
+    /// Refuse to fade markets priced outside a band unless the edge is very large.
+    pub extreme_price_guard: Option<ExtremePriceGuard>,
+    /// `User-Agent` sent on Manifold REST/WebSocket and xAI requests.
+    pub user_agent: String,
+    /// WebSocket topics subscribed at start; editable live via `BotHandles::topics`.
+    pub ws_topics: Vec<String>,
+    /// WebSocket ping interval and staleness timeout.
+    pub ws: ws::WsConfig,
+    /// Wait this long after a new-bet event, then re-fetch the market and skip it
+    /// if the triggering move has mostly reverted.
+    pub reversion_recheck_secs: Option<u64>,
+    /// Treat a fetched market price more than this far from the triggering
+    /// bet's `prob_after` as predating that bet, and don't compute edge on it.
+    pub stale_prob_tolerance: Option<f64>,
+    /// On a stale price, re-fetch once after this long before falling back
+    /// to the triggering bet's `prob_after`.
+    pub stale_prob_refetch_ms: Option<u64>,
+    /// Look this many bets back before fading a move, and skip moves that
+    /// continue a steady trend rather than spike against it.
+    pub reversion_trend_lookback: Option<u32>,
+    /// Only analyze a bet-moved market once several bets in a row push it the
+    /// same way, so a single manipulative bet doesn't trigger a fade.
+    pub move_confirmation: Option<MoveConfirmation>,
+    /// Veto bets whose prediction contradicts a cheap heuristic, e.g.
+    /// `sanity::base_rate_check`.
+    #[serde(skip)]
+    pub sanity_check: Option<SanityCheck>,
+    /// Place queued bets with the largest edge first instead of in decision order.
+    pub prioritize_bets_by_edge: bool,
+    /// How each stake is sized; `Fixed` uses `bet_amount` / `reversion_amount`.
+    pub sizing: SizingStrategy,
+    /// Floor for balance-fraction stakes.
+    pub min_bet_amount: f64,
+    /// Ceiling for balance-fraction stakes.
+    pub max_bet_amount: Option<f64>,
+    /// Write a summary report at the end of each UTC day.
+    pub daily_report: bool,
+    /// Also POST each report (as JSON) to this URL.
+    pub report_webhook_url: Option<String>,
+    /// Estimated USD cost of one xAI research call, for the report.
+    pub xai_cost_per_call: f64,
+    /// How many recent xAI calls the error rate covers.
+    pub xai_error_window: usize,
+    /// Stop analyzing when most recent xAI calls fail, instead of hammering a
+    /// dead key; resume is manual.
+    pub xai_pause: Option<XaiPause>,
+    /// Treat a prediction citing fewer distinct sources than this as a skip,
+    /// so the bot doesn't bet on the model's prior alone.
+    pub min_sources: Option<usize>,
+    /// Treat a prediction the model says rests on little or no information as
+    /// a skip, however far it is from the market. Without it, a 0% or 100%
+    /// guess on a market nobody has discussed looks like a huge edge.
+    pub skip_low_information: bool,
+    /// Treat a prediction whose reasoning is shorter than this many characters
+    /// as a skip; a one-word or empty reason usually means no real research.
+    pub min_reasoning_len: Option<usize>,
+    /// Re-ask xAI once, nudging for the final answer, when a response has no
+    /// answer text at all (e.g. the model only ran searches).
+    pub retry_empty_responses: bool,
+    /// xAI temperature and X search recency / result cap for research.
+    pub research: ResearchParams,
+    /// Stake caps by pool liquidity, so thin markets get smaller bets. Empty
+    /// means no caps.
+    pub liquidity_tiers: Vec<LiquidityTier>,
+    /// Never stake more than this fraction of a market's liquidity (0.05: at
+    /// most M$5 into a M$100 pool), so the bot doesn't swamp small markets
+    /// and take a terrible fill.
+    pub max_stake_liquidity_fraction: Option<f64>,
+    /// Save a per-market decision trace (prompt, response, edge, decision,
+    /// bet response) to `traces/<contract_id>.json`, API keys redacted.
+    pub decision_traces: bool,
+    /// Also record every decision, skips and guardrail-blocked bets included,
+    /// to `paper_ledger.jsonl`, to measure what the guardrails cost.
+    pub paper_ledger: bool,
+    /// Alternative configs evaluated on every market this one analyzes,
+    /// logging what each would have bet without betting. Their own
+    /// `shadows` are ignored.
+    pub shadows: Vec<BotConfig>,
+    /// Split each bet into this many equal limit orders at evenly spaced prices
+    /// between the market and the limit price; 1 places a single order.
+    pub ladder_levels: u32,
+    /// Round limit prices to this increment so Manifold doesn't reject odd
+    /// values. Should divide 1 evenly; `None` sends the unrounded price.
+    pub limit_tick: Option<f64>,
+    /// Throttle analysis starts for a while after each connect, so a backlog
+    /// burst doesn't fire dozens of xAI calls at once.
+    pub warm_up: Option<WarmUp>,
+    /// Give markets the model skips a second chance instead of dropping them.
+    pub skip_escalation: Option<SkipEscalation>,
+    /// Predictions outside this band are treated as model overconfidence.
+    pub prediction_bounds: Option<PredictionBounds>,
+    /// Collect decided bets over a short window and place only the best,
+    /// instead of placing each as soon as it is decided.
+    pub bet_batch: Option<BetBatch>,
+    /// Ask a second, independent model run to confirm large bets.
+    pub confirmation: Option<Confirmation>,
+    /// Windows in which the bot observes but places no bets, e.g. around
+    /// scheduled events where prices whipsaw.
+    pub blackouts: Vec<Blackout>,
+    /// Periodically send balance above a ceiling to another account. Moves
+    /// real mana, so it only runs when explicitly configured.
+    pub profit_sweep: Option<ProfitSweep>,
+    /// Periodically re-analyze held positions and sell those the model no
+    /// longer backs, so a wrong call isn't held to resolution.
+    pub reevaluation: Option<Reevaluation>,
+    /// Periodically cancel and re-place resting limit orders the market has
+    /// reached without filling them, at the current price while the edge
+    /// still holds, to improve fill rates.
+    pub reprice: Option<Reprice>,
+    /// Flag markets from the feed that are about to close, and analyze them
+    /// ahead of the queue: their edges pay out soonest.
+    pub urgent_close: Option<UrgentClose>,
+    /// Drop deferred markets, in-progress claims and other per-market
+    /// bookkeeping older than this, so a long session doesn't accumulate
+    /// markets that never come due. Closing-soon watchlist entries are kept
+    /// until their market closes. `None` keeps them all.
+    pub watchlist_ttl_secs: Option<u64>,
+    /// Run at most this many analyses at once, queueing the rest highest
+    /// liquidity first. `None` starts every analysis immediately.
+    pub analysis_workers: Option<usize>,
+    /// After an insufficient-balance error, scale bets down to the remaining
+    /// balance instead of holding them until it recovers.
+    pub shrink_bets_to_balance: bool,
+    /// Place bets on Manifold, simulate them, or only emit them as JSON
+    /// instructions for external execution. Cancels, sales and managrams go
+    /// through the same backend. Only live bets are recorded in the ledger, and
+    /// re-pricing, re-evaluation and profit sweeps only run when bets are live.
+    pub execution: ExecutionMode,
+    /// Append every bet request and its result to `executions.jsonl`.
+    pub record_executions: bool,
+    /// Replace xAI research, the Manifold API and bet execution with simulated
+    /// calls of the given latency and error rate, for load testing. Overrides
+    /// `execution` and `record_executions`; the xAI error window isn't persisted.
+    pub simulation: Option<Simulation>,
+    /// Record every xAI research response to `xai_recordings.json`, or answer
+    /// research from those recordings instead of calling xAI.
+    pub xai_replay: Option<ReplayMode>,
 }
 
 impl Default for BotConfig {
@@ -29,385 +693,9381 @@ impl Default for BotConfig {
         Self {
             bet_amount: 10.0,
             reversion_amount: 25.0,
+            bet_triggered_mode: BetTriggeredMode::Reversion,
+            reversion_scaling: None,
             min_edge: 0.10,
+            min_edge_per_year: 0.0,
             min_liquidity: 100.0,
+            min_initial_liquidity: None,
+            backfill_max_secs: 10 * 60,
+            backfill_limit: 50,
+            fetch_missing_liquidity: false,
+            max_limit_distance: None,
+            resolution_poll_secs: 5 * 60,
+            max_loss_streak: Some(5),
+            calibration_breaker: None,
+            min_market_age_secs: 0,
+            max_market_age_secs: None,
+            skip_closed_markets: true,
+            tradable_price_range: (0.0, 1.0),
+            max_bet_event_age_secs: None,
+            min_bet_interval_secs: None,
+            order_tif: OrderTif::Gtc,
+            limit_expiry_ms: None,
+            max_total_exposure_fraction: None,
+            max_open_positions: None,
+            restore_positions: false,
+            trade_visibility: vec!["public".to_string()],
+            trade_mechanisms: vec!["cpmm-1".to_string()],
+            allowed_languages: Vec::new(),
+            tradable_outcome_types: vec!["BINARY".to_string()],
+            creator_filter: None,
+            dev_tools: false,
+            reuse_analysis_by_question: false,
+            xai_streaming: false,
+            extreme_price_guard: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            ws_topics: ws::DEFAULT_TOPICS.map(String::from).to_vec(),
+            ws: ws::WsConfig::default(),
+            reversion_recheck_secs: None,
+            reversion_trend_lookback: None,
+            move_confirmation: None,
+            stale_prob_tolerance: Some(0.02),
+            stale_prob_refetch_ms: None,
+            sanity_check: None,
+            prioritize_bets_by_edge: false,
+            sizing: SizingStrategy::Fixed,
+            min_bet_amount: 1.0,
+            max_bet_amount: None,
+            daily_report: false,
+            report_webhook_url: None,
+            xai_cost_per_call: 0.0,
+            xai_error_window: 20,
+            xai_pause: Some(XaiPause {
+                max_error_rate: 0.8,
+                min_calls: 10,
+            }),
+            min_sources: None,
+            skip_low_information: true,
+            min_reasoning_len: None,
+            retry_empty_responses: true,
+            liquidity_tiers: Vec::new(),
+            max_stake_liquidity_fraction: None,
+            decision_traces: false,
+            research: ResearchParams::default(),
+            paper_ledger: false,
+            shadows: Vec::new(),
+            ladder_levels: 1,
+            limit_tick: Some(0.01),
+            warm_up: None,
+            skip_escalation: None,
+            prediction_bounds: None,
+            bet_batch: None,
+            confirmation: None,
+            blackouts: Vec::new(),
+            profit_sweep: None,
+            reevaluation: None,
+            reprice: None,
+            urgent_close: None,
+            watchlist_ttl_secs: Some(SECS_PER_DAY),
+            analysis_workers: None,
+            shrink_bets_to_balance: false,
+            execution: ExecutionMode::Manifold,
+            record_executions: false,
+            simulation: None,
+            xai_replay: None,
         }
     }
 }
 
-const CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+fn config_file_path() -> PathBuf {
+    data_dir().join("config.json")
+}
 
-fn cache_file_path() -> PathBuf {
-    dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("manifold-domination")
-        .join("analyzed_cache.json")
+impl BotConfig {
+    /// Read `config.json` from the data directory. A missing file gives the
+    /// defaults; a file that doesn't parse is an error rather than a silent
+    /// fallback.
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(&config_file_path())
+    }
+
+    fn load_from(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    /// Reject values no strategy could mean, e.g. a negative stake.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, amount) in [
+            ("bet_amount", self.bet_amount),
+            ("reversion_amount", self.reversion_amount),
+            ("min_bet_amount", self.min_bet_amount),
+            ("min_liquidity", self.min_liquidity),
+        ] {
+            if !amount.is_finite() || amount < 0.0 {
+                return Err(format!("{name} {amount} must be a non-negative amount"));
+            }
+        }
+        if let Some(max) = self.max_bet_amount {
+            if max < self.min_bet_amount {
+                return Err(format!(
+                    "max_bet_amount {max} is below min_bet_amount {}",
+                    self.min_bet_amount
+                ));
+            }
+        }
+        if !(0.0..1.0).contains(&self.min_edge) {
+            return Err(format!("min_edge {} is outside 0–1", self.min_edge));
+        }
+        if self.resolution_poll_secs == 0 {
+            return Err("resolution_poll_secs must be non-zero".to_string());
+        }
+        self.research.validate()?;
+        let ws::WsConfig {
+            ping_interval_secs,
+            stale_timeout_secs,
+        } = self.ws;
+        if ping_interval_secs == 0 || stale_timeout_secs <= ping_interval_secs {
+            return Err(format!(
+                "ws stale_timeout_secs {stale_timeout_secs} must exceed a non-zero ping_interval_secs {ping_interval_secs}"
+            ));
+        }
+        let (low, high) = self.tradable_price_range;
+        let range_valid = (0.0..=1.0).contains(&low) && (low..=1.0).contains(&high);
+        if !range_valid {
+            return Err(format!(
+                "tradable_price_range ({low}, {high}) is not a range within 0–1"
+            ));
+        }
+        if let Some(fraction) = self.max_stake_liquidity_fraction {
+            let fraction_valid = fraction > 0.0 && fraction <= 1.0;
+            if !fraction_valid {
+                return Err(format!(
+                    "max_stake_liquidity_fraction {fraction} is outside 0–1"
+                ));
+            }
+        }
+        if let Some(breaker) = &self.calibration_breaker {
+            if breaker.window < breaker.min_samples {
+                return Err(format!(
+                    "calibration_breaker window {} can never reach min_samples {}",
+                    breaker.window, breaker.min_samples
+                ));
+            }
+        }
+        // A small move near certainty can cost many times the pool
+        let impact_capped =
+            self.max_bet_amount.is_some() || self.max_stake_liquidity_fraction.is_some();
+        if matches!(self.sizing, SizingStrategy::TargetImpact(_)) && !impact_capped {
+            return Err(
+                "TargetImpact sizing needs max_bet_amount or max_stake_liquidity_fraction"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
 }
 
-fn now_epoch_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+// Via the redacted JSON, so the webhook URL never ends up in a log
+impl std::fmt::Debug for BotConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BotConfig {}", config_json(self))
+    }
 }
 
-fn load_cache() -> HashMap<String, u64> {
-    let path = cache_file_path();
-    let data = match std::fs::read_to_string(&path) {
-        Ok(d) => d,
-        Err(_) => return HashMap::new(),
-    };
-    let cache: HashMap<String, u64> = match serde_json::from_str(&data) {
-        Ok(c) => c,
-        Err(_) => return HashMap::new(),
-    };
-    let now = now_epoch_secs();
-    cache
-        .into_iter()
-        .filter(|(_, ts)| now.saturating_sub(*ts) < CACHE_TTL_SECS)
-        .collect()
+/// How the stake for each bet is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum SizingStrategy {
+    /// The configured absolute amount.
+    #[default]
+    Fixed,
+    /// This fraction of the current balance, clamped to
+    /// `[min_bet_amount, max_bet_amount]`, so risk scales with the bankroll.
+    BalanceFraction(f64),
+    /// Whatever moves the market by at most this much probability (e.g. 0.02),
+    /// clamped to `[min_bet_amount, max_bet_amount]`, so slippage stays bounded
+    /// whatever the pool size.
+    TargetImpact(f64),
 }
 
-fn save_cache(cache: &HashMap<String, u64>) {
-    let path = cache_file_path();
-    if let Some(parent) = path.parent() {
-        let _ = std::fs::create_dir_all(parent);
+/// Stake for a bet whose fixed size would be `fixed`, given free `balance`.
+/// Only `BalanceFraction` depends on the balance; `TargetImpact` is sized by
+/// `impact_size`.
+pub fn bet_size(fixed: f64, balance: f64, config: &BotConfig) -> f64 {
+    match config.sizing {
+        SizingStrategy::BalanceFraction(fraction) => clamp_stake(fraction * balance, config),
+        SizingStrategy::Fixed | SizingStrategy::TargetImpact(_) => fixed,
     }
-    let _ = std::fs::write(&path, serde_json::to_string(cache).unwrap_or_default());
 }
 
-pub async fn run_bot(
-    manifold: ManifoldClient,
-    xai: XaiClient,
-    mut ws_rx: mpsc::UnboundedReceiver<WsEvent>,
-    log_tx: mpsc::UnboundedSender<BotLogEntry>,
-    config: BotConfig,
-) {
-    let _ = log_tx.send(BotLogEntry::Info(format!(
-        "Bot started (M${:.0}/new, M${:.0}/reversion, {:.0}% min edge, M${:.0} min liquidity)",
-        config.bet_amount,
-        config.reversion_amount,
-        config.min_edge * 100.0,
-        config.min_liquidity,
-    )));
+/// Most the next order can cost at `balance`: `bet_size` before the
+/// market-specific caps, which only lower it (a ladder splits the same total).
+/// Unbounded for `TargetImpact` without `max_bet_amount`, since that depends
+/// on the pool.
+fn next_order_ceiling(balance: f64, config: &BotConfig) -> f64 {
+    match config.sizing {
+        SizingStrategy::TargetImpact(_) => config.max_bet_amount.unwrap_or(f64::INFINITY),
+        SizingStrategy::Fixed | SizingStrategy::BalanceFraction(_) => bet_size(
+            config.bet_amount.max(config.reversion_amount),
+            balance,
+            config,
+        ),
+    }
+}
 
-    // Track which markets we've already analyzed (market_id -> epoch secs), persisted to disk
-    let mut analyzed_cache = load_cache();
+fn clamp_stake(stake: f64, config: &BotConfig) -> f64 {
+    let stake = stake.max(config.min_bet_amount);
+    config.max_bet_amount.map_or(stake, |max| stake.min(max))
+}
 
-    while let Some(event) = ws_rx.recv().await {
-        match event {
-            WsEvent::Connected => {
-                let _ = log_tx.send(BotLogEntry::Info("WebSocket connected".to_string()));
-            }
-            WsEvent::Disconnected => {
-                let _ = log_tx.send(BotLogEntry::Info(
-                    "WebSocket disconnected, reconnecting...".to_string(),
-                ));
-            }
-            WsEvent::NewContract(broadcast) => {
-                let contract = &broadcast.contract;
-                let creator = &broadcast.creator;
+/// Highest price `size_for_impact` sizes a move to; targets past it stop here.
+const MAX_IMPACT_PRICE: f64 = 0.99;
 
-                if contract.outcome_type == "BINARY" {
-                    let liquidity = contract.total_liquidity.unwrap_or(0.0);
-                    if liquidity < config.min_liquidity {
-                        let _ = log_tx.send(BotLogEntry::Info(format!(
-                            "Skipping low-liquidity market (M${:.0}): \"{}\"",
-                            liquidity, contract.question
-                        )));
-                        continue;
-                    }
+/// Mana that moves a CPMM pool's price of the bought outcome from `price` to
+/// `price + target_impact`, or to `MAX_IMPACT_PRICE` if that's lower. Zero
+/// without known liquidity. Always finite, but near certainty it can be many
+/// times the pool, so `validate` requires a stake cap alongside it.
+pub fn size_for_impact(target_impact: f64, liquidity: f64, price: f64) -> f64 {
+    let target = (price + target_impact).min(MAX_IMPACT_PRICE);
+    if liquidity <= 0.0 || target <= price || !(0.0..1.0).contains(&price) {
+        return 0.0;
+    }
+    // The other side's pool is `liquidity * sqrt(p / (1 - p))` and grows by
+    // exactly the amount bet (see `cpmm_buy_shares`)
+    let other_pool = |p: f64| liquidity * (p / (1.0 - p)).sqrt();
+    other_pool(target) - other_pool(price)
+}
 
-                    let _ = log_tx.send(BotLogEntry::Info(format!(
-                        "New binary market (M${:.0} liq): \"{}\" by {}",
-                        liquidity, contract.question, creator.username
-                    )));
-                    // Mark as analyzed so bet events don't re-trigger
-                    analyzed_cache.insert(contract.id.clone(), now_epoch_secs());
-                    save_cache(&analyzed_cache);
-                    let manifold = manifold.clone();
-                    let xai = xai.clone();
-                    let log_tx = log_tx.clone();
-                    let broadcast = broadcast.clone();
-                    let config = config.clone();
-                    tokio::spawn(async move {
-                        handle_new_market(&manifold, &xai, &log_tx, &broadcast, &config).await;
-                    });
-                } else {
-                    let _ = log_tx.send(BotLogEntry::Info(format!(
-                        "Skipping non-binary market: \"{}\" [{}]",
-                        contract.question, contract.outcome_type
-                    )));
-                }
-            }
-            WsEvent::NewBet(bet) => {
-                // Evict stale cache entries periodically
-                let now = now_epoch_secs();
-                analyzed_cache.retain(|_, ts| now.saturating_sub(*ts) < CACHE_TTL_SECS);
+/// `TargetImpact` stake for buying at `price`, clamped to the stake limits.
+fn impact_size(target_impact: f64, liquidity: f64, price: f64, config: &BotConfig) -> f64 {
+    clamp_stake(size_for_impact(target_impact, liquidity, price), config)
+}
 
-                if analyzed_cache.contains_key(&bet.contract_id) {
-                    let _ = log_tx.send(BotLogEntry::Info(format!(
-                        "Already analyzed market {} — skipping (prob {:.0}% → {:.0}%)",
-                        bet.contract_id,
-                        bet.prob_before * 100.0,
-                        bet.prob_after * 100.0,
-                    )));
-                    continue;
-                }
-                analyzed_cache.insert(bet.contract_id.clone(), now);
-                save_cache(&analyzed_cache);
+/// Markets with less than `below` mana of liquidity are bet at most `max_bet`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityTier {
+    pub below: f64,
+    pub max_bet: f64,
+}
 
-                let manifold = manifold.clone();
-                let xai = xai.clone();
-                let log_tx = log_tx.clone();
-                let config = config.clone();
-                let bet = *bet;
-                tokio::spawn(async move {
-                    handle_bet_triggered(&manifold, &xai, &log_tx, &bet, &config).await;
-                });
-            }
-            WsEvent::Error(e) => {
-                let _ = log_tx.send(BotLogEntry::Error(e));
-            }
-        }
+impl LiquidityTier {
+    /// `<M$200 -> M$5`, `<M$1000 -> M$25`, otherwise `M$50`.
+    #[allow(dead_code)] // opt-in via `BotConfig::liquidity_tiers`
+    pub fn defaults() -> Vec<LiquidityTier> {
+        vec![
+            LiquidityTier {
+                below: 200.0,
+                max_bet: 5.0,
+            },
+            LiquidityTier {
+                below: 1000.0,
+                max_bet: 25.0,
+            },
+            LiquidityTier {
+                below: f64::INFINITY,
+                max_bet: 50.0,
+            },
+        ]
     }
 }
 
-async fn handle_new_market(
-    manifold: &ManifoldClient,
-    xai: &XaiClient,
-    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
-    broadcast: &NewContractBroadcast,
-    config: &BotConfig,
-) {
-    let question = &broadcast.contract.question;
-    let contract_id = &broadcast.contract.id;
+/// Cap from the lowest tier whose threshold `liquidity` is under, if any.
+fn liquidity_cap(liquidity: f64, tiers: &[LiquidityTier]) -> Option<f64> {
+    tiers
+        .iter()
+        .filter(|t| liquidity < t.below)
+        .min_by(|a, b| a.below.total_cmp(&b.below))
+        .map(|t| t.max_bet)
+}
 
-    let _ = log_tx.send(BotLogEntry::Info(format!(
-        "Researching \"{question}\"...",
-    )));
+/// Largest stake `max_stake_liquidity_fraction` allows in a pool this deep.
+/// Unknown (non-positive) liquidity isn't capped; `min_liquidity` covers it.
+fn liquidity_fraction_cap(liquidity: f64, config: &BotConfig) -> Option<f64> {
+    config
+        .max_stake_liquidity_fraction
+        .filter(|_| liquidity > 0.0)
+        .map(|fraction| fraction * liquidity)
+}
 
-    let description = broadcast.contract.text_description.as_deref();
-    let result = match xai.research_market(question, description).await {
-        Ok(r) => r,
-        Err(e) => {
-            let _ = log_tx.send(BotLogEntry::Error(format!(
-                "xAI research failed for \"{question}\": {e}",
+/// `amount` clamped to `liquidity_fraction_cap`, logging when the cap binds.
+fn cap_stake_to_liquidity(
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    question: &str,
+    amount: f64,
+    liquidity: f64,
+    config: &BotConfig,
+) -> f64 {
+    match liquidity_fraction_cap(liquidity, config) {
+        Some(cap) if amount > cap => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] stake M${amount:.0} capped to M${cap:.0} ({:.0}% of M${liquidity:.0} liquidity)",
+                config.max_stake_liquidity_fraction.unwrap_or_default() * 100.0,
             )));
-            return;
+            cap
         }
-    };
+        _ => amount,
+    }
+}
 
-    let prediction = match xai::parse_prediction(&result.text) {
-        Some(xai::PredictionResult::Predict(p)) => p,
-        Some(xai::PredictionResult::Skip(reason)) => {
-            let _ = log_tx.send(BotLogEntry::Info(format!(
-                "Skipping unevaluable market: \"{question}\" | {reason}",
-            )));
-            return;
+/// `bet_size` with a live balance, fetched only when the strategy needs it, then
+/// capped by the market's liquidity tier. `price` is what the favoured outcome
+/// costs now, for `TargetImpact`.
+/// `None` (logged) if the balance can't be fetched.
+async fn stake_for(
+    ctx: &BotContext,
+    fixed: f64,
+    liquidity: f64,
+    price: f64,
+    question: &str,
+) -> Option<f64> {
+    let cap = |stake: f64| {
+        liquidity_cap(liquidity, &ctx.config.liquidity_tiers).map_or(stake, |max| stake.min(max))
+    };
+    match ctx.config.sizing {
+        SizingStrategy::Fixed => return Some(cap(fixed)),
+        SizingStrategy::TargetImpact(impact) => {
+            return Some(cap(impact_size(impact, liquidity, price, &ctx.config)))
         }
-        None => {
-            let _ = log_tx.send(BotLogEntry::Error(format!(
-                "Could not parse prediction for \"{question}\"",
+        SizingStrategy::BalanceFraction(_) => {}
+    }
+    match ctx.manifold.get_me().await {
+        Ok(user) => Some(cap(bet_size(fixed, user.balance, &ctx.config))),
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Could not fetch balance to size bet on \"{question}\": {e}"
             )));
-            let truncated = &result.text[..result.text.len().min(300)];
-            let _ = log_tx.send(BotLogEntry::Info(format!("xAI response: {truncated}")));
-            return;
+            None
         }
-    };
-
-    let market_prob = broadcast.contract.probability.unwrap_or(0.5);
-    let edge = prediction.probability - market_prob;
-    let abs_edge = edge.abs();
+    }
+}
 
-    let reasoning = if prediction.reasoning.is_empty() {
-        "No reasoning provided".to_string()
-    } else {
-        prediction.reasoning
-    };
+/// How the bet-triggered path trades a market a new bet just moved.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum BetTriggeredMode {
+    /// Bet the model's edge whichever way it points, which is contrarian
+    /// whenever the move overshot.
+    #[default]
+    Reversion,
+    /// Treat the move as information: bet only in its direction, and only
+    /// when the model agrees the price has further to go.
+    Momentum,
+}
 
-    if abs_edge < config.min_edge {
-        let _ = log_tx.send(BotLogEntry::Info(format!(
-            "[{question}] {:.0}% (market {:.0}%), edge {:.1}% < {:.0}% min — skipping | {reasoning}",
-            prediction.probability * 100.0,
-            market_prob * 100.0,
-            abs_edge * 100.0,
-            config.min_edge * 100.0,
-        )));
-        return;
+impl BetTriggeredMode {
+    fn strategy(self) -> Strategy {
+        match self {
+            BetTriggeredMode::Reversion => Strategy::Reversion,
+            BetTriggeredMode::Momentum => Strategy::Momentum,
+        }
     }
 
-    let (outcome, limit_prob) = if edge > 0.0 {
-        ("YES", prediction.probability)
-    } else {
-        ("NO", prediction.probability)
-    };
-
-    let _ = log_tx.send(BotLogEntry::Info(format!(
-        "[{question}] {:.0}% (market {:.0}%) -> {outcome} limit@{:.0}% | {reasoning}",
-        prediction.probability * 100.0,
-        market_prob * 100.0,
-        limit_prob * 100.0,
-    )));
+    /// Whether this mode trades a `prediction` against `market_prob` after
+    /// `bet` moved the market.
+    fn allows(self, bet: &BetData, prediction: f64, market_prob: f64) -> bool {
+        match self {
+            BetTriggeredMode::Reversion => true,
+            BetTriggeredMode::Momentum => {
+                (bet.prob_after - bet.prob_before) * (prediction - market_prob) > 0.0
+            }
+        }
+    }
+}
 
-    // Clamp limit_prob to valid range (1-99%)
-    let limit_prob = limit_prob.clamp(0.01, 0.99);
+/// Stake proportional to edge: `reference_edge` of edge stakes the base amount,
+/// twice that edge twice as much, up to `max_amount`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EdgeScaling {
+    pub reference_edge: f64,
+    pub max_amount: f64,
+}
 
-    let bet = BetRequest {
-        contract_id: contract_id.clone(),
-        amount: config.bet_amount,
-        outcome: outcome.to_string(),
-        limit_prob: Some(limit_prob),
+/// The bet-triggered stake for a market `abs_edge` away from the prediction:
+/// `base` scaled by `reversion_scaling`, still within the bet size limits and
+/// the liquidity tier cap.
+fn reversion_stake(base: f64, abs_edge: f64, liquidity: f64, config: &BotConfig) -> f64 {
+    let Some(scaling) = &config.reversion_scaling else {
+        return base;
     };
+    if scaling.reference_edge <= 0.0 {
+        return base;
+    }
+    let scaled = clamp_stake(
+        (base * abs_edge / scaling.reference_edge).min(scaling.max_amount),
+        config,
+    );
+    liquidity_cap(liquidity, &config.liquidity_tiers).map_or(scaled, |max| scaled.min(max))
+}
 
-    match manifold.place_bet(&bet).await {
-        Ok(resp) => {
-            let filled = resp.amount.unwrap_or(0.0);
-            let _ = log_tx.send(BotLogEntry::Trade(format!(
-                "BET PLACED: {outcome} M${:.0} on \"{question}\" limit@{:.0}% (filled M${filled:.0})",
-                config.bet_amount,
-                limit_prob * 100.0,
+/// Betting against a near-certain market (e.g. YES at 2%, NO at 97%) usually
+/// loses even when the model disagrees, so such bets need a much larger edge.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ExtremePriceGuard {
+    /// Markets below this probability are not bet YES.
+    pub lower: f64,
+    /// Markets above this probability are not bet NO.
+    pub upper: f64,
+    /// Post-cost edge at which the model is trusted anyway.
+    pub override_edge: f64,
+}
+
+impl Default for ExtremePriceGuard {
+    fn default() -> Self {
+        Self {
+            lower: 0.05,
+            upper: 0.95,
+            override_edge: 0.30,
+        }
+    }
+}
+
+/// The range of predictions the bot trusts. Extreme predictions are usually
+/// overconfidence, and the edge they imply is mostly fictional.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PredictionBounds {
+    pub lower: f64,
+    pub upper: f64,
+    /// Skip markets predicted outside the band instead of clamping into it.
+    pub skip: bool,
+}
+
+impl Default for PredictionBounds {
+    fn default() -> Self {
+        Self {
+            lower: 0.05,
+            upper: 0.95,
+            skip: false,
+        }
+    }
+}
+
+/// `probability` clamped into `bounds`, or `None` if out of bounds and `bounds.skip`.
+fn apply_prediction_bounds(probability: f64, bounds: &PredictionBounds) -> Option<f64> {
+    if (bounds.lower..=bounds.upper).contains(&probability) {
+        Some(probability)
+    } else if bounds.skip {
+        None
+    } else {
+        Some(probability.clamp(bounds.lower, bounds.upper))
+    }
+}
+
+/// Apply `prediction_bounds` before the edge is computed, logging a clamp or
+/// rejection. `None` means skip the market.
+fn bound_prediction(
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    question: &str,
+    probability: f64,
+    config: &BotConfig,
+) -> Option<f64> {
+    let Some(bounds) = &config.prediction_bounds else {
+        return Some(probability);
+    };
+    let bounded = apply_prediction_bounds(probability, bounds);
+    let band = format!("{:.0}-{:.0}%", bounds.lower * 100.0, bounds.upper * 100.0);
+    match bounded {
+        None => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] {:.0}% prediction outside {band} — overconfident, skipping",
+                probability * 100.0,
+            )));
+        }
+        Some(p) if p != probability => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] {:.0}% prediction clamped to {:.0}% ({band} band)",
+                probability * 100.0,
+                p * 100.0,
+            )));
+        }
+        Some(_) => {}
+    }
+    bounded
+}
+
+/// Portfolio selection: bets decided within `window_secs` of the first are
+/// ranked by post-cost edge and the top `top_k` placed, within `budget`.
+/// The rest are deferred (dropped) rather than spending mana on weaker edges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BetBatch {
+    pub window_secs: u64,
+    pub top_k: usize,
+    /// Total mana the placed bets of one batch may stake; `None` is unlimited.
+    pub budget: Option<f64>,
+}
+
+impl Default for BetBatch {
+    fn default() -> Self {
+        Self {
+            window_secs: 30,
+            top_k: 3,
+            budget: None,
+        }
+    }
+}
+
+/// A second xAI call before bets of at least `min_stake`. The bet goes ahead
+/// only if it picks the same side of the market and lands within `tolerance`
+/// of the first prediction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Confirmation {
+    pub min_stake: f64,
+    pub tolerance: f64,
+    /// Model for the confirming call; `None` reuses the research model.
+    pub model: Option<String>,
+    /// On disagreement, bet this fraction of the stake instead of skipping.
+    pub reduce_to: Option<f64>,
+}
+
+impl Default for Confirmation {
+    fn default() -> Self {
+        Self {
+            min_stake: 50.0,
+            tolerance: 0.15,
+            model: None,
+            reduce_to: None,
+        }
+    }
+}
+
+/// Sweep balance above `ceiling` to `to_user_id` (e.g. a vault account)
+/// every `interval_secs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfitSweep {
+    pub to_user_id: String,
+    pub ceiling: f64,
+    pub interval_secs: u64,
+}
+
+/// Markets closing within `window_secs` are urgent; watched markets are
+/// re-checked every `scan_secs`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrgentClose {
+    pub window_secs: u64,
+    pub scan_secs: u64,
+}
+
+/// Seconds until a millisecond-epoch `close_time`, if the market is still
+/// open and closes within `window_secs`.
+fn closes_within(close_time_ms: Option<u64>, now_ms: u64, window_secs: u64) -> Option<u64> {
+    let remaining = close_time_ms?.checked_sub(now_ms)? / 1000;
+    (remaining > 0 && remaining <= window_secs).then_some(remaining)
+}
+
+/// Smallest sweep worth sending.
+const MIN_MANAGRAM: f64 = 10.0;
+
+/// Whole mana above `ceiling`, if enough to send.
+fn sweep_amount(balance: f64, ceiling: f64) -> Option<f64> {
+    let excess = (balance - ceiling).floor();
+    (excess >= MIN_MANAGRAM).then_some(excess)
+}
+
+/// Send any balance above the ceiling to the sweep account. Failures are only
+/// logged; trading carries on either way. Skipped when bets aren't live.
+async fn sweep_profits(ctx: &BotContext, sweep: &ProfitSweep) {
+    if !ctx.executor.is_live() {
+        return;
+    }
+    let balance = match ctx.manifold.get_me().await {
+        Ok(user) => user.balance,
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Profit sweep: could not fetch balance: {e}"
+            )));
+            return;
+        }
+    };
+    let Some(amount) = sweep_amount(balance, sweep.ceiling) else {
+        return;
+    };
+    match ctx
+        .executor
+        .send_managram(&sweep.to_user_id, amount, "Profit sweep")
+        .await
+    {
+        Ok(()) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Trade(format!(
+                "Profit sweep: sent M${amount:.0} to {} (balance M${balance:.0}, ceiling M${:.0})",
+                sweep.to_user_id, sweep.ceiling
+            )));
+        }
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Profit sweep of M${amount:.0} to {} failed: {e}",
+                sweep.to_user_id
             )));
         }
+    }
+}
+
+/// Every `interval_secs`, re-research up to `max_per_round` held positions,
+/// least recently checked first, and sell any whose new prediction sits at
+/// least `exit_edge` on the other side of the market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reevaluation {
+    pub interval_secs: u64,
+    pub max_per_round: usize,
+    pub exit_edge: f64,
+}
+
+/// Every `interval_secs`, check the bot's open limit orders and re-price any
+/// the market has reached without filling, at most `max_attempts` times per
+/// market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reprice {
+    pub interval_secs: u64,
+    pub max_attempts: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RepriceAction {
+    /// Not stuck: the market hasn't reached the limit.
+    Keep,
+    /// Cancel and re-place at this limit.
+    Replace(f64),
+    /// Cancel: at the current price the edge is gone.
+    Cancel,
+}
+
+/// What to do with a resting `outcome` order at `limit` when the market is at
+/// `market_prob`. A YES order fills as the price falls to its limit (NO as it
+/// rises), so a market at or past the limit with the order unfilled means
+/// others queued ahead of it. The new limit is rounded toward the fill and
+/// must keep `required_edge` after the taker fee.
+fn reprice_action(
+    outcome: &str,
+    limit: f64,
+    market_prob: f64,
+    prediction: f64,
+    required_edge: f64,
+    tick: Option<f64>,
+) -> RepriceAction {
+    let yes = outcome == "YES";
+    let stuck = if yes {
+        market_prob <= limit
+    } else {
+        market_prob >= limit
+    };
+    if !stuck {
+        return RepriceAction::Keep;
+    }
+    let new_limit = tick.map_or(market_prob, |tick| snap_to_tick(market_prob, tick, yes));
+    let edge = if yes {
+        prediction - new_limit
+    } else {
+        new_limit - prediction
+    };
+    let fee = TAKER_FEE_RATE * new_limit * (1.0 - new_limit);
+    if edge - fee >= required_edge {
+        RepriceAction::Replace(new_limit)
+    } else {
+        RepriceAction::Cancel
+    }
+}
+
+/// One re-pricing round over the bot's open limit orders.
+async fn reprice_orders(ctx: &BotContext, reprice: &Reprice) {
+    // Orders only rest on the book when bets are live
+    if !ctx.executor.is_live() {
+        return;
+    }
+    let log_tx = &ctx.log_tx;
+    let orders = match ctx.manifold.get_me().await {
+        Ok(me) => ctx.manifold.get_open_orders(&me.id).await,
+        Err(e) => Err(e),
+    };
+    let orders = match orders {
+        Ok(orders) => orders,
         Err(e) => {
             let _ = log_tx.send(BotLogEntry::Error(format!(
-                "Failed to place bet on \"{question}\": {e}",
+                "Re-pricing: failed to fetch open orders: {e}"
             )));
+            return;
         }
+    };
+    for order in orders.iter().filter(|o| !o.is_filled && !o.is_cancelled) {
+        reprice_order(ctx, order, reprice).await;
     }
 }
 
-async fn handle_bet_triggered(
-    manifold: &ManifoldClient,
-    xai: &XaiClient,
-    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
-    bet_data: &BetData,
-    config: &BotConfig,
-) {
-    let market = match manifold.get_market(&bet_data.contract_id).await {
+async fn reprice_order(ctx: &BotContext, order: &LimitOrder, reprice: &Reprice) {
+    let log_tx = &ctx.log_tx;
+    // Only orders the bot placed, with the prediction behind them
+    let Some(entry) = ctx
+        .pnl
+        .lock()
+        .unwrap()
+        .entries()
+        .iter()
+        .find(|e| e.order_id.as_deref() == Some(order.id.as_str()) && e.resolution.is_none())
+        .cloned()
+    else {
+        return;
+    };
+    let question = &entry.question;
+    let attempts = ctx
+        .repriced
+        .lock()
+        .unwrap()
+        .get(&order.contract_id)
+        .copied()
+        .unwrap_or(0);
+    if attempts >= reprice.max_attempts {
+        return;
+    }
+    let market = match ctx.manifold.get_market(&order.contract_id).await {
         Ok(m) => m,
         Err(e) => {
             let _ = log_tx.send(BotLogEntry::Error(format!(
-                "Failed to fetch market {}: {e}",
-                bet_data.contract_id
+                "Re-pricing: failed to fetch \"{question}\": {e}"
             )));
             return;
         }
     };
-
-    if market.is_resolved {
-        let _ = log_tx.send(BotLogEntry::Info(format!(
-            "Skipping resolved market (bet-triggered): \"{}\"",
-            market.question
-        )));
+    let Some(market_prob) = market.probability.filter(|_| !market.is_resolved) else {
+        return;
+    };
+    let required = required_edge(
+        days_to_close(market.close_time, now_epoch_millis()),
+        &ctx.config,
+    );
+    let action = reprice_action(
+        &order.outcome,
+        order.limit_prob,
+        market_prob,
+        entry.prediction,
+        required,
+        ctx.config.limit_tick,
+    );
+    if action == RepriceAction::Keep {
         return;
     }
-
-    if market.outcome_type != "BINARY" {
-        let _ = log_tx.send(BotLogEntry::Info(format!(
-            "Skipping non-binary market (bet-triggered): \"{}\" [{}]",
-            market.question, market.outcome_type
+    if let RepriceAction::Replace(_) = action {
+        if ctx.paused.load(Ordering::SeqCst) {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "Re-pricing: trading paused — leaving stuck order on \"{question}\""
+            )));
+            return;
+        }
+    }
+    if let Err(e) = ctx.executor.cancel(&order.id).await {
+        let _ = log_tx.send(BotLogEntry::Error(format!(
+            "Re-pricing: failed to cancel stuck order on \"{question}\": {e}"
         )));
         return;
     }
-
-    let liquidity = market.total_liquidity.unwrap_or(0.0);
-    if liquidity < config.min_liquidity {
+    // A failed cancel leaves the order as it was, so it doesn't use up an attempt
+    *ctx.repriced
+        .lock()
+        .unwrap()
+        .entry(order.contract_id.clone())
+        .or_insert(0) += 1;
+    // The old order now only holds what filled; the replacement is a new bet
+    ctx.pnl
+        .lock()
+        .unwrap()
+        .cancel_order(&order.id, order.amount, order.shares);
+    let remaining = order.unfilled();
+    let RepriceAction::Replace(new_limit) = action else {
         let _ = log_tx.send(BotLogEntry::Info(format!(
-            "Skipping low-liquidity market (bet-triggered, M${liquidity:.0}): \"{}\"",
-            market.question
+            "Re-pricing: cancelled {} M${remaining:.0} @{:.0}% on \"{question}\" — no edge at market {:.0}% (predicted {:.0}%)",
+            order.outcome,
+            order.limit_prob * 100.0,
+            market_prob * 100.0,
+            entry.prediction * 100.0,
         )));
         return;
+    };
+    // Caps are checked once the old order no longer counts against them
+    if check_can_place(ctx, remaining, &order.contract_id, question)
+        .await
+        .is_err()
+    {
+        return;
     }
-
-    let question = &market.question;
     let _ = log_tx.send(BotLogEntry::Info(format!(
-        "Analyzing market (bet-triggered, M${liquidity:.0} liq): \"{question}\""
+        "Re-pricing: {} M${remaining:.0} on \"{question}\" {:.0}% -> {:.0}% (attempt {}/{})",
+        order.outcome,
+        order.limit_prob * 100.0,
+        new_limit * 100.0,
+        attempts + 1,
+        reprice.max_attempts,
     )));
+    let bet = build_bet(
+        &order.contract_id,
+        remaining,
+        &order.outcome,
+        new_limit,
+        &ctx.config,
+    );
+    let _ = place_and_record(
+        ctx,
+        &bet,
+        question,
+        entry.prediction,
+        market_prob,
+        entry.strategy,
+    )
+    .await;
+}
 
-    let description = market.text_description.as_deref();
-    let result = match xai.research_market(question, description).await {
-        Ok(r) => r,
+/// The loss streak after `entry` resolves. Imported holdings weren't the
+/// bot's calls, so they neither extend nor reset it.
+fn next_loss_streak(streak: u32, entry: &LedgerEntry) -> u32 {
+    match entry.won() {
+        _ if entry.is_imported() => streak,
+        Some(true) => 0,
+        Some(false) => streak + 1,
+        None => streak,
+    }
+}
+
+/// Log line for a resolved ledger entry.
+fn resolution_line(resolution: &str, entry: &LedgerEntry) -> String {
+    if entry.is_imported() {
+        return format!(
+            "Resolved {resolution}: \"{}\" (imported {} position)",
+            entry.question, entry.outcome
+        );
+    }
+    format!(
+        "Resolved {resolution}: \"{}\" (bet {} @{:.0}%, predicted {:.0}%)",
+        entry.question,
+        entry.outcome,
+        entry.limit_prob * 100.0,
+        entry.prediction * 100.0,
+    )
+}
+
+/// Bets fetched per page when restoring positions (Manifold's maximum).
+const USER_BETS_PAGE: u32 = 1000;
+/// Stop paging back through history after this many bets.
+const MAX_RESTORED_BETS: usize = 50_000;
+/// Markets looked up per restore; the most recently traded come first.
+const MAX_RESTORED_MARKETS: usize = 200;
+
+/// Shares of one outcome of one market, netted from the user's bets.
+#[derive(Debug, Clone, PartialEq)]
+struct Holding {
+    contract_id: String,
+    outcome: String,
+    shares: f64,
+    /// Mana paid net of sales.
+    cost: f64,
+    /// Epoch milliseconds of the latest bet.
+    last_bet_at: u64,
+}
+
+/// Net `bets` into holdings per market and outcome, ordered by both.
+/// Outcomes sold down to dust are dropped.
+fn net_holdings(bets: &[UserBet]) -> Vec<Holding> {
+    let mut holdings: BTreeMap<(&str, &str), Holding> = BTreeMap::new();
+    for bet in bets {
+        let holding = holdings
+            .entry((bet.contract_id.as_str(), bet.outcome.as_str()))
+            .or_insert_with(|| Holding {
+                contract_id: bet.contract_id.clone(),
+                outcome: bet.outcome.clone(),
+                shares: 0.0,
+                cost: 0.0,
+                last_bet_at: 0,
+            });
+        holding.shares += bet.shares;
+        holding.cost += bet.amount;
+        holding.last_bet_at = holding.last_bet_at.max(bet.created_time);
+    }
+    holdings
+        .into_values()
+        .filter(|h| h.shares >= 0.01)
+        .collect()
+}
+
+/// A ledger entry for a holding the bot didn't place, priced at `market_prob`.
+fn imported_entry(holding: &Holding, question: &str, market_prob: f64) -> LedgerEntry {
+    let cost = holding.cost.max(0.0);
+    LedgerEntry {
+        contract_id: holding.contract_id.clone(),
+        question: question.to_string(),
+        outcome: holding.outcome.clone(),
+        amount: cost,
+        filled: cost,
+        shares: holding.shares,
+        limit_prob: market_prob,
+        prediction: market_prob,
+        market_prob,
+        placed_at: holding.last_bet_at / 1000,
+        resolution: None,
+        strategy: Strategy::Imported,
+        sold_for: None,
+        order_id: None,
+    }
+}
+
+/// Seed the ledger with open positions it has no record of, from the
+/// account's bet history (`BotConfig::restore_positions`).
+async fn restore_positions(ctx: &BotContext) {
+    let log_tx = &ctx.log_tx;
+    let me = match ctx.manifold.get_me().await {
+        Ok(me) => me,
         Err(e) => {
             let _ = log_tx.send(BotLogEntry::Error(format!(
-                "xAI research failed for \"{question}\": {e}"
+                "Restoring positions: failed to fetch user: {e}"
             )));
             return;
         }
     };
-
-    let prediction = match xai::parse_prediction(&result.text) {
-        Some(xai::PredictionResult::Predict(p)) => p,
-        Some(xai::PredictionResult::Skip(reason)) => {
-            let _ = log_tx.send(BotLogEntry::Info(format!(
-                "Skipping unevaluable market: \"{question}\" | {reason}",
-            )));
-            return;
-        }
-        None => {
+    let (manifold, user_id) = (&ctx.manifold, me.id.as_str());
+    let bets = fetch_paginated(
+        USER_BETS_PAGE,
+        MAX_RESTORED_BETS,
+        |_| true,
+        |before, limit| async move {
+            manifold
+                .get_user_bets(user_id, before.as_deref(), limit)
+                .await
+        },
+    )
+    .await;
+    let bets = match bets {
+        Ok(bets) => bets,
+        Err(e) => {
             let _ = log_tx.send(BotLogEntry::Error(format!(
-                "Could not parse prediction for \"{question}\""
+                "Restoring positions: failed to fetch bets: {e}"
             )));
             return;
         }
     };
 
-    let market_prob = market.probability.unwrap_or(0.5);
-    let edge = prediction.probability - market_prob;
-    let abs_edge = edge.abs();
+    let known: HashSet<String> = ctx
+        .pnl
+        .lock()
+        .unwrap()
+        .entries()
+        .iter()
+        .map(|e| e.contract_id.clone())
+        .collect();
+    let mut holdings: Vec<Holding> = net_holdings(&bets)
+        .into_iter()
+        .filter(|h| !known.contains(&h.contract_id))
+        .collect();
+    holdings.sort_by_key(|h| std::cmp::Reverse(h.last_bet_at));
+    if holdings.len() > MAX_RESTORED_MARKETS {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Restoring positions: checking the {MAX_RESTORED_MARKETS} most recently traded of {} held markets",
+            holdings.len()
+        )));
+        holdings.truncate(MAX_RESTORED_MARKETS);
+    }
+    let mut entries = Vec::new();
+    for holding in &holdings {
+        match ctx.manifold.get_market(&holding.contract_id).await {
+            Ok(market) if !market.is_resolved => entries.push(imported_entry(
+                holding,
+                &market.question,
+                market.probability.unwrap_or(0.5),
+            )),
+            Ok(_) => {}
+            Err(e) => {
+                let _ = log_tx.send(BotLogEntry::Error(format!(
+                    "Restoring positions: failed to fetch {}: {e}",
+                    holding.contract_id
+                )));
+            }
+        }
+    }
+    let added = ctx.pnl.lock().unwrap().seed(entries);
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "Restored {added} open position(s) from {} bet(s)",
+        bets.len()
+    )));
+}
 
-    let reasoning = if prediction.reasoning.is_empty() {
-        "No reasoning provided".to_string()
+/// Whether a re-evaluated `prediction` now favours the other side of a held
+/// `outcome` by at least `exit_edge`.
+fn position_flipped(outcome: &str, prediction: f64, market_prob: f64, exit_edge: f64) -> bool {
+    if outcome == "YES" {
+        prediction <= market_prob - exit_edge
     } else {
-        prediction.reasoning
-    };
+        prediction >= market_prob + exit_edge
+    }
+}
 
-    if abs_edge < config.min_edge {
-        let _ = log_tx.send(BotLogEntry::Info(format!(
-            "[bet-triggered] [{question}] {:.0}% (market {:.0}%), edge {:.1}% < {:.0}% min — skipping | {reasoning}",
-            prediction.probability * 100.0,
-            market_prob * 100.0,
-            abs_edge * 100.0,
-            config.min_edge * 100.0,
-        )));
+/// One re-evaluation round. Stops early if analysis gets paused. The ledger
+/// only holds live bets, so there is nothing to manage when bets aren't live.
+async fn reevaluate_positions(ctx: &BotContext, reevaluation: &Reevaluation) {
+    if !ctx.executor.is_live() {
         return;
     }
+    let mut positions = ctx.pnl.lock().unwrap().positions();
+    {
+        let reevaluated = ctx.reevaluated.lock().unwrap();
+        positions.sort_by_key(|p| reevaluated.get(&p.contract_id).copied().unwrap_or(0));
+    }
+    for position in positions.into_iter().take(reevaluation.max_per_round) {
+        if ctx.analysis_paused.load(Ordering::SeqCst) {
+            break;
+        }
+        ctx.reevaluated
+            .lock()
+            .unwrap()
+            .insert(position.contract_id.clone(), now_epoch_secs());
+        reevaluate_position(ctx, &position, reevaluation.exit_edge).await;
+    }
+}
 
-    let (outcome, limit_prob) = if edge > 0.0 {
-        ("YES", prediction.probability)
-    } else {
-        ("NO", prediction.probability)
+async fn reevaluate_position(ctx: &BotContext, position: &Position, exit_edge: f64) {
+    let log_tx = &ctx.log_tx;
+    let question = &position.question;
+    let market = match ctx.manifold.get_market(&position.contract_id).await {
+        Ok(m) => m,
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "Re-evaluation: failed to fetch \"{question}\": {e}"
+            )));
+            return;
+        }
     };
-
-    let _ = log_tx.send(BotLogEntry::Info(format!(
-        "[bet-triggered] [{question}] {:.0}% (market {:.0}%) -> {outcome} limit@{:.0}% | {reasoning}",
+    let Some(market_prob) = market.probability.filter(|_| !market.is_resolved) else {
+        return;
+    };
+    // The cached analysis is the one being re-evaluated, so always ask again
+    let description = market.text_description.as_deref();
+    let result = match research_fresh(ctx, question, description).await {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "Re-evaluation: xAI research failed for \"{question}\": {e}"
+            )));
+            return;
+        }
+    };
+    let Some(xai::PredictionResult::Predict(prediction)) =
+        require_information(xai::parse_prediction(&result.text), &ctx.config)
+    else {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Re-evaluation: no prediction for \"{question}\", keeping {} {:.0} shares",
+            position.outcome, position.shares
+        )));
+        return;
+    };
+    let summary = format!(
+        "{} {:.0} shares, now predicted {:.0}% vs market {:.0}%",
+        position.outcome,
+        position.shares,
         prediction.probability * 100.0,
-        market_prob * 100.0,
-        limit_prob * 100.0,
-    )));
-
-    let limit_prob = limit_prob.clamp(0.01, 0.99);
-
-    let bet = BetRequest {
-        contract_id: bet_data.contract_id.clone(),
-        amount: config.reversion_amount,
-        outcome: outcome.to_string(),
-        limit_prob: Some(limit_prob),
+        market_prob * 100.0
+    );
+    if !position_flipped(
+        &position.outcome,
+        prediction.probability,
+        market_prob,
+        exit_edge,
+    ) {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Re-evaluation: keeping \"{question}\" ({summary})"
+        )));
+        return;
+    }
+    if ctx.paused.load(Ordering::SeqCst) {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Re-evaluation: trading paused — not selling \"{question}\" ({summary})"
+        )));
+        return;
+    }
+    // Resting remainders would refill the position after the sale
+    let order_ids = ctx
+        .pnl
+        .lock()
+        .unwrap()
+        .order_ids(&position.contract_id, &position.outcome);
+    cancel_resting_orders(ctx, &order_ids, question).await;
+    // Only the bot's shares; the account may hold more of this outcome
+    let request = SellRequest {
+        outcome: position.outcome.clone(),
+        shares: Some(position.shares),
     };
-
-    match manifold.place_bet(&bet).await {
+    match ctx.executor.sell(&position.contract_id, &request).await {
         Ok(resp) => {
-            let filled = resp.amount.unwrap_or(0.0);
+            // A sale comes back as a bet of negative amount
+            let proceeds = -resp.amount.unwrap_or(0.0);
+            let closed =
+                ctx.pnl
+                    .lock()
+                    .unwrap()
+                    .close(&position.contract_id, &position.outcome, proceeds);
+            if !closed.is_empty() {
+                store_resolution(ctx, &position.contract_id, "SOLD");
+            }
             let _ = log_tx.send(BotLogEntry::Trade(format!(
-                "BET PLACED (reversion): {outcome} M${:.0} on \"{question}\" limit@{:.0}% (filled M${filled:.0})",
-                config.reversion_amount,
-                limit_prob * 100.0,
+                "Re-evaluation: sold \"{question}\" for M${proceeds:.0} (paid M${:.0}; {summary}) | {}",
+                position.filled, prediction.reasoning
             )));
         }
         Err(e) => {
             let _ = log_tx.send(BotLogEntry::Error(format!(
-                "Failed to place bet on \"{question}\": {e}"
+                "Re-evaluation: selling \"{question}\" failed ({summary}): {e}"
+            )));
+        }
+    }
+}
+
+/// Cancel whichever of `order_ids` are still resting on the book.
+async fn cancel_resting_orders(ctx: &BotContext, order_ids: &[String], question: &str) {
+    if order_ids.is_empty() {
+        return;
+    }
+    let orders = match ctx.manifold.get_me().await {
+        Ok(me) => ctx.manifold.get_open_orders(&me.id).await,
+        Err(e) => Err(e),
+    };
+    let orders = match orders {
+        Ok(orders) => orders,
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Re-evaluation: failed to fetch open orders for \"{question}\": {e}"
             )));
+            return;
+        }
+    };
+    let resting = orders
+        .iter()
+        .filter(|o| !o.is_filled && !o.is_cancelled && order_ids.contains(&o.id));
+    for order in resting {
+        if let Err(e) = ctx.executor.cancel(&order.id).await {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Re-evaluation: failed to cancel order on \"{question}\": {e}"
+            )));
+        }
+    }
+}
+
+/// A no-betting window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Blackout {
+    /// Every day, from `start` to `end` in seconds since UTC midnight. Wraps
+    /// past midnight when `start > end`.
+    Daily { start: u64, end: u64 },
+    /// Once, between two epoch-second timestamps.
+    Between { start: u64, end: u64 },
+}
+
+impl Blackout {
+    fn contains(&self, now: u64) -> bool {
+        match *self {
+            Blackout::Daily { start, end } => {
+                let t = now % SECS_PER_DAY;
+                if start <= end {
+                    (start..end).contains(&t)
+                } else {
+                    t >= start || t < end
+                }
+            }
+            Blackout::Between { start, end } => (start..end).contains(&now),
         }
     }
 }
+
+impl std::fmt::Display for Blackout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hhmm = |secs: u64| format!("{:02}:{:02}", secs / 3600, secs % 3600 / 60);
+        match *self {
+            Blackout::Daily { start, end } => {
+                write!(f, "daily {}-{} UTC", hhmm(start), hhmm(end))
+            }
+            Blackout::Between { start, end } => write!(f, "{start}-{end}"),
+        }
+    }
+}
+
+/// The first blackout window covering `now`, if any.
+fn active_blackout(blackouts: &[Blackout], now: u64) -> Option<&Blackout> {
+    blackouts.iter().find(|b| b.contains(now))
+}
+
+/// Whether a confirming prediction backs the first: same side of the market
+/// and within `tolerance` of it.
+fn confirmation_agrees(prediction: f64, confirming: f64, market_prob: f64, tolerance: f64) -> bool {
+    let same_side = (prediction - market_prob).signum() == (confirming - market_prob).signum();
+    same_side && (prediction - confirming).abs() <= tolerance + 1e-9
+}
+
+/// What to do when the model skips a market liquid enough to matter.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SkipEscalation {
+    /// Only skips on markets with at least this much liquidity are escalated.
+    pub min_liquidity: f64,
+    /// Re-ask once with a prompt emphasizing objective resolvability.
+    pub retry: bool,
+    /// Send markets still skipped to the dashboard's needs-human queue.
+    pub needs_human: bool,
+}
+
+impl Default for SkipEscalation {
+    fn default() -> Self {
+        Self {
+            min_liquidity: 1000.0,
+            retry: true,
+            needs_human: true,
+        }
+    }
+}
+
+/// Cold-start throttle: right after connecting, analyses start at most
+/// `starts_per_min`, easing linearly to unthrottled over `secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WarmUp {
+    pub secs: u64,
+    pub starts_per_min: f64,
+}
+
+impl Default for WarmUp {
+    fn default() -> Self {
+        Self {
+            secs: 120,
+            starts_per_min: 6.0,
+        }
+    }
+}
+
+/// Schedules analysis starts against the last `WsEvent::Connected`.
+#[derive(Debug, Default)]
+struct WarmUpThrottle {
+    connected_at: Option<Instant>,
+    next_start: Option<Instant>,
+}
+
+impl WarmUpThrottle {
+    fn connected(&mut self, now: Instant) {
+        self.connected_at = Some(now);
+        self.next_start = None;
+    }
+
+    /// How long the analysis starting at `now` should wait. Each start pushes
+    /// the next one back by the gap for the current point in the warm-up.
+    fn delay(&mut self, now: Instant, warm_up: &WarmUp) -> Duration {
+        let Some(connected_at) = self.connected_at else {
+            return Duration::ZERO;
+        };
+        let elapsed = now.saturating_duration_since(connected_at).as_secs_f64();
+        if elapsed >= warm_up.secs as f64 || warm_up.starts_per_min <= 0.0 {
+            return Duration::ZERO;
+        }
+        let ramp = 1.0 - elapsed / warm_up.secs as f64;
+        let gap = Duration::from_secs_f64(60.0 / warm_up.starts_per_min * ramp);
+        let start = self.next_start.map_or(now, |next| next.max(now));
+        self.next_start = Some(start + gap);
+        start - now
+    }
+}
+
+/// `min_edge` plus `min_edge_per_year` scaled by the time until close. Markets
+/// without a close time only need `min_edge`.
+pub fn required_edge(days_to_close: Option<f64>, config: &BotConfig) -> f64 {
+    let days = days_to_close.unwrap_or(0.0).max(0.0);
+    config.min_edge + config.min_edge_per_year * days / 365.0
+}
+
+/// Whether a millisecond-epoch `close_time` has passed and `skip_closed_markets`
+/// is on. No close time means the market never closes.
+fn closed_before_analysis(close_time_ms: Option<u64>, now_ms: u64, config: &BotConfig) -> bool {
+    config.skip_closed_markets && close_time_ms.is_some_and(|close| close <= now_ms)
+}
+
+/// Whether the market price is within `tradable_price_range`. An unknown price
+/// counts as 50%, as it does when the edge is computed.
+fn price_tradable(probability: Option<f64>, config: &BotConfig) -> bool {
+    let (low, high) = config.tradable_price_range;
+    (low..=high).contains(&probability.unwrap_or(0.5))
+}
+
+/// Days from `now_ms` until a millisecond-epoch `close_time`.
+fn days_to_close(close_time_ms: Option<u64>, now_ms: u64) -> Option<f64> {
+    close_time_ms.map(|close| close.saturating_sub(now_ms) as f64 / 86_400_000.0)
+}
+
+/// The threshold for logs, with the time to close when it raised it.
+fn min_edge_note(days_to_close: Option<f64>, config: &BotConfig) -> String {
+    let required = required_edge(days_to_close, config);
+    match days_to_close {
+        Some(days) if required > config.min_edge => {
+            format!("{:.1}% min ({days:.0}d to close)", required * 100.0)
+        }
+        _ => format!("{:.0}% min", required * 100.0),
+    }
+}
+
+/// Result of weighing a prediction against the current market price.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BetDecision {
+    /// Post-cost edge is below `min_edge`, or `guarded` by `extreme_price_guard`.
+    Skip {
+        abs_edge: f64,
+        net_edge: f64,
+        guarded: bool,
+    },
+    /// Place a limit order on `outcome` at `limit_prob` (already clamped to 1-99%).
+    /// `distance_capped` is set when `max_limit_distance` pulled the price in.
+    Bet {
+        outcome: &'static str,
+        limit_prob: f64,
+        abs_edge: f64,
+        net_edge: f64,
+        distance_capped: bool,
+    },
+}
+
+/// Manifold's taker fee: this rate times `p * (1 - p)` per share bought at price `p`.
+const TAKER_FEE_RATE: f64 = 0.07;
+
+/// Shares received for `amount` mana bought on the side priced at `prob`.
+/// The pool is approximated as a p=0.5 CPMM whose constant product is
+/// `liquidity^2`; unknown (non-positive) liquidity means no price impact.
+fn cpmm_buy_shares(prob: f64, amount: f64, liquidity: f64) -> f64 {
+    if liquidity <= 0.0 {
+        return amount / prob;
+    }
+    // Pool of shares on the bought side (`y`) and the other side (`n`)
+    let y = liquidity * ((1.0 - prob) / prob).sqrt();
+    let n = liquidity * (prob / (1.0 - prob)).sqrt();
+    y + amount - (y * n) / (n + amount)
+}
+
+/// Current price of the outcome the prediction favours.
+fn bought_price(prediction: f64, market_prob: f64) -> f64 {
+    if prediction >= market_prob {
+        market_prob
+    } else {
+        1.0 - market_prob
+    }
+}
+
+/// Edge left after slippage and fees if `amount` is bet in the direction the
+/// prediction favours. Negative when costs eat the whole edge.
+pub fn effective_edge(prediction: f64, market_prob: f64, amount: f64, liquidity: f64) -> f64 {
+    // Express both sides as "probability of the outcome we'd buy"
+    let price = bought_price(prediction, market_prob);
+    let prediction = if prediction >= market_prob {
+        prediction
+    } else {
+        1.0 - prediction
+    };
+    if amount <= 0.0 || price <= 0.0 || price >= 1.0 {
+        return prediction - price;
+    }
+    let avg_price = amount / cpmm_buy_shares(price, amount, liquidity);
+    let fee = TAKER_FEE_RATE * avg_price * (1.0 - avg_price);
+    prediction - (avg_price + fee)
+}
+
+/// Decide whether (and how) to bet `amount` given a predicted and a market
+/// probability. The edge must clear `required_edge` after estimated costs.
+pub fn decide_bet(
+    prediction: f64,
+    market_prob: f64,
+    amount: f64,
+    liquidity: f64,
+    days_to_close: Option<f64>,
+    config: &BotConfig,
+) -> BetDecision {
+    let edge = prediction - market_prob;
+    let abs_edge = edge.abs();
+    let net_edge = effective_edge(prediction, market_prob, amount, liquidity);
+    // NaN compares false below, so reject it (and runaway stakes) outright
+    let sane = amount.is_finite() && net_edge.is_finite();
+    if !sane || net_edge < required_edge(days_to_close, config) {
+        return BetDecision::Skip {
+            abs_edge,
+            net_edge,
+            guarded: false,
+        };
+    }
+    if let Some(guard) = &config.extreme_price_guard {
+        let fading =
+            (market_prob < guard.lower && edge > 0.0) || (market_prob > guard.upper && edge < 0.0);
+        if fading && net_edge < guard.override_edge {
+            return BetDecision::Skip {
+                abs_edge,
+                net_edge,
+                guarded: true,
+            };
+        }
+    }
+    let outcome = if edge > 0.0 { "YES" } else { "NO" };
+
+    let mut limit_prob = prediction;
+    let mut distance_capped = false;
+    if let Some(max_dist) = config.max_limit_distance {
+        if abs_edge > max_dist {
+            limit_prob = market_prob + max_dist * edge.signum();
+            distance_capped = true;
+        }
+    }
+
+    let mut limit_prob = limit_prob.clamp(0.01, 0.99);
+    if let Some(tick) = config.limit_tick {
+        limit_prob = round_to_tick(limit_prob, tick);
+    }
+
+    BetDecision::Bet {
+        outcome,
+        limit_prob,
+        abs_edge,
+        net_edge,
+        distance_capped,
+    }
+}
+
+/// Round `prob` to the nearest multiple of `tick`, staying strictly between 0
+/// and 1. Divides by the tick count so whole percents come out exact (0.7, not
+/// 0.7000000000000001).
+fn round_to_tick(prob: f64, tick: f64) -> f64 {
+    let steps = (1.0 / tick).round();
+    if steps < 2.0 {
+        return prob;
+    }
+    (prob * steps).round().clamp(1.0, steps - 1.0) / steps
+}
+
+/// Like `round_to_tick`, but rounding up (`up`) or down to the tick, e.g. so
+/// a limit never lands on the unmarketable side of a price.
+fn snap_to_tick(prob: f64, tick: f64, up: bool) -> f64 {
+    let steps = (1.0 / tick).round();
+    if steps < 2.0 {
+        return prob;
+    }
+    // Tolerate float noise in prices already on a tick
+    let scaled = prob * steps;
+    let snapped = if up {
+        (scaled - 1e-9).ceil()
+    } else {
+        (scaled + 1e-9).floor()
+    };
+    snapped.clamp(1.0, steps - 1.0) / steps
+}
+
+/// Build the limit order for a decision, applying the configured time-in-force.
+pub fn build_bet(
+    contract_id: &str,
+    amount: f64,
+    outcome: &str,
+    limit_prob: f64,
+    config: &BotConfig,
+) -> BetRequest {
+    let expires_millis_after = match config.order_tif {
+        OrderTif::Gtc => config.limit_expiry_ms,
+        OrderTif::Ioc | OrderTif::Fok => Some(IMMEDIATE_EXPIRY_MS),
+    };
+    BetRequest {
+        contract_id: contract_id.to_string(),
+        amount,
+        outcome: outcome.to_string(),
+        limit_prob: Some(limit_prob),
+        expires_at: None,
+        expires_millis_after,
+    }
+}
+
+/// Smallest order in a ladder, so rungs don't go below Manifold's minimum bet.
+const MIN_LADDER_RUNG: f64 = 1.0;
+
+/// `(amount, limit_prob)` per rung: `amount` split evenly across up to `levels`
+/// prices stepping from `market_prob` to `limit_prob`, the last at `limit_prob`.
+/// Rung prices are rounded to `tick` (`BotConfig::limit_tick`); rungs that
+/// round to the same price are merged.
+pub fn ladder_rungs(
+    amount: f64,
+    market_prob: f64,
+    limit_prob: f64,
+    levels: u32,
+    tick: Option<f64>,
+) -> Vec<(f64, f64)> {
+    let max_levels = (amount / MIN_LADDER_RUNG).floor() as u32;
+    let levels = levels.min(max_levels).max(1);
+    if levels == 1 {
+        return vec![(amount, limit_prob)];
+    }
+    let step = (limit_prob - market_prob) / levels as f64;
+    let rung_amount = amount / levels as f64;
+    let mut rungs: Vec<(f64, f64)> = Vec::new();
+    for i in 1..=levels {
+        let price = (market_prob + step * i as f64).clamp(0.01, 0.99);
+        let price = tick.map_or(price, |tick| round_to_tick(price, tick));
+        // Prices are monotonic, so equal ones are adjacent
+        match rungs.last_mut() {
+            Some((amount, last)) if *last == price => *amount += rung_amount,
+            _ => rungs.push((rung_amount, price)),
+        }
+    }
+    rungs
+}
+
+/// The orders for one decided bet: a single limit order, or a ladder of them
+/// per `ladder_levels`.
+fn build_ladder(
+    contract_id: &str,
+    amount: f64,
+    outcome: &str,
+    market_prob: f64,
+    limit_prob: f64,
+    config: &BotConfig,
+) -> Vec<BetRequest> {
+    ladder_rungs(
+        amount,
+        market_prob,
+        limit_prob,
+        config.ladder_levels,
+        config.limit_tick,
+    )
+    .into_iter()
+    .map(|(amount, price)| build_bet(contract_id, amount, outcome, price, config))
+    .collect()
+}
+
+/// Whether a market's visibility is tradable. A missing field is Manifold's default, `public`.
+fn visibility_allowed(visibility: &str, config: &BotConfig) -> bool {
+    let visibility = if visibility.is_empty() {
+        "public"
+    } else {
+        visibility
+    };
+    config.trade_visibility.iter().any(|v| v == visibility)
+}
+
+/// Whether a market's mechanism is tradable.
+fn mechanism_allowed(mechanism: &str, config: &BotConfig) -> bool {
+    config.trade_mechanisms.iter().any(|m| m == mechanism)
+}
+
+/// The question's language if it isn't in `allowed_languages`. Questions
+/// too short to detect reliably pass rather than risk skipping English ones.
+fn disallowed_language(question: &str, config: &BotConfig) -> Option<whatlang::Lang> {
+    if config.allowed_languages.is_empty() {
+        return None;
+    }
+    let info = whatlang::detect(question).filter(|info| info.is_reliable())?;
+    let lang = info.lang();
+    (!config
+        .allowed_languages
+        .iter()
+        .any(|code| code == lang.code()))
+    .then_some(lang)
+}
+
+/// Why a market's outcome type isn't attempted; `None` if it's in
+/// `tradable_outcome_types`.
+fn outcome_type_skip_reason(outcome_type: &str, config: &BotConfig) -> Option<&'static str> {
+    if config
+        .tradable_outcome_types
+        .iter()
+        .any(|t| t == outcome_type)
+    {
+        return None;
+    }
+    Some(
+        OutcomeType::parse(outcome_type)
+            .skip_reason()
+            .unwrap_or("not in tradable outcome types"),
+    )
+}
+
+/// Minimum creator reputation for a market to be traded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreatorFilter {
+    pub min_account_age_days: f64,
+    pub min_balance: f64,
+}
+
+/// Why `profile` falls short of `filter`; `None` if the creator passes.
+fn creator_rejection(profile: &UserProfile, filter: &CreatorFilter, now_ms: u64) -> Option<String> {
+    if profile.is_banned_from_posting {
+        return Some("banned from posting".to_string());
+    }
+    let age_days =
+        now_ms.saturating_sub(profile.created_time) as f64 / (SECS_PER_DAY * 1000) as f64;
+    if age_days < filter.min_account_age_days {
+        return Some(format!(
+            "account {age_days:.0}d old < {:.0}d",
+            filter.min_account_age_days
+        ));
+    }
+    if profile.balance < filter.min_balance {
+        return Some(format!(
+            "balance M${:.0} < M${:.0}",
+            profile.balance, filter.min_balance
+        ));
+    }
+    None
+}
+
+/// Short trust summary for logs, e.g. `2y old, M$1500 balance, trusted`.
+fn creator_hint(profile: &UserProfile, now_ms: u64) -> String {
+    let days = now_ms.saturating_sub(profile.created_time) / (SECS_PER_DAY * 1000);
+    let age = match days {
+        0..=29 => format!("{days}d"),
+        30..=364 => format!("{}mo", days / 30),
+        _ => format!("{}y", days / 365),
+    };
+    let mut hint = format!("{age} old, M${:.0} balance", profile.balance);
+    if let Some(traders) = &profile.creator_traders {
+        hint.push_str(&format!(", {} traders", traders.all_time));
+    }
+    for (flag, label) in [
+        (profile.is_trustworthy, "trusted"),
+        (profile.is_bot, "bot"),
+        (profile.is_banned_from_posting, "banned"),
+    ] {
+        if flag {
+            hint.push_str(", ");
+            hint.push_str(label);
+        }
+    }
+    hint
+}
+
+/// How long a fetched creator profile is reused.
+const CREATOR_TTL_SECS: u64 = 6 * 60 * 60;
+
+/// `username`'s profile, from the cache if fetched within `CREATOR_TTL_SECS`.
+/// A failed lookup is logged and returns `None`.
+async fn creator_profile(ctx: &BotContext, username: &str) -> Option<UserProfile> {
+    let now = now_epoch_secs();
+    if let Some((profile, fetched_at)) = ctx.creators.lock().unwrap().get(username) {
+        if now.saturating_sub(*fetched_at) < CREATOR_TTL_SECS {
+            return Some(profile.clone());
+        }
+    }
+    match ctx.manifold.get_user(username).await {
+        Ok(profile) => {
+            let mut creators = ctx.creators.lock().unwrap();
+            creators.retain(|_, (_, at)| now.saturating_sub(*at) < CREATOR_TTL_SECS);
+            creators.insert(username.to_string(), (profile.clone(), now));
+            Some(profile)
+        }
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Failed to look up creator {username}: {e}"
+            )));
+            None
+        }
+    }
+}
+
+/// Why `creator_filter` rules out markets by this creator, logged; `None` if
+/// it doesn't. An unknown creator (failed lookup) isn't blocked.
+fn creator_untrusted(
+    ctx: &BotContext,
+    creator: Option<&UserProfile>,
+    question: &str,
+) -> Option<String> {
+    let (Some(filter), Some(profile)) = (&ctx.config.creator_filter, creator) else {
+        return None;
+    };
+    let reason = creator_rejection(profile, filter, now_epoch_millis())?;
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "Skipping market by untrusted creator {} ({reason}): \"{question}\"",
+        profile.username
+    )));
+    Some(format!("Untrusted creator: {reason}"))
+}
+
+/// Pause once at least `min_samples` markets have resolved and the win count
+/// is `z_threshold` or more standard deviations below what the predictions
+/// imply, judged over the last `window` resolved markets so an old run of
+/// luck neither masks nor keeps tripping on the current model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationBreaker {
+    pub min_samples: usize,
+    pub z_threshold: f64,
+    pub window: usize,
+}
+
+fn calibration_trips(calibration: &Calibration, breaker: &CalibrationBreaker) -> bool {
+    calibration.samples >= breaker.min_samples
+        && calibration
+            .z_score()
+            .is_some_and(|z| z <= -breaker.z_threshold)
+}
+
+/// Whether adding `amount` keeps open exposure within `fraction` of the bankroll.
+/// `balance` is free mana, so the bankroll is `balance + open_exposure`.
+fn exposure_allows(open_exposure: f64, amount: f64, balance: f64, fraction: f64) -> bool {
+    open_exposure + amount <= fraction * (balance + open_exposure)
+}
+
+/// Whether a bet on `contract_id` keeps the markets held within `max`: always
+/// for a market already in `open`, otherwise only below the cap.
+fn positions_allow(open: &[String], contract_id: &str, max: usize) -> bool {
+    open.iter().any(|id| id == contract_id) || open.len() < max
+}
+
+fn log_distance_cap(
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    question: &str,
+    prediction: f64,
+    limit_prob: f64,
+    market_prob: f64,
+    config: &BotConfig,
+) {
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "[{question}] limit capped {:.0}% -> {:.0}% (max {:.0}% from market {:.0}%)",
+        prediction * 100.0,
+        limit_prob * 100.0,
+        config.max_limit_distance.unwrap_or_default() * 100.0,
+        market_prob * 100.0,
+    )));
+}
+
+/// One log line per evidence item; `render_log_text` makes the URLs clickable.
+fn log_evidence(log_tx: &mpsc::UnboundedSender<BotLogEntry>, evidence: &[xai::Evidence]) {
+    for item in evidence {
+        let line = match &item.source_url {
+            Some(url) => format!("  • {} — {url}", item.claim),
+            None => format!("  • {}", item.claim),
+        };
+        let _ = log_tx.send(BotLogEntry::Info(line));
+    }
+}
+
+fn log_extreme_price_guard(
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    question: &str,
+    prediction: f64,
+    market_prob: f64,
+    net_edge: f64,
+    config: &BotConfig,
+) {
+    let override_edge = config
+        .extreme_price_guard
+        .map(|g| g.override_edge)
+        .unwrap_or_default();
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "[{question}] extreme-price guard: not fading market {:.0}% with {:.0}% prediction (edge {:.1}% < {:.0}% override)",
+        market_prob * 100.0,
+        prediction * 100.0,
+        net_edge * 100.0,
+        override_edge * 100.0,
+    )));
+}
+
+/// Run shadow configs over an analysis the active config already paid for and
+/// log what each would have done. `reversion` selects the bet-triggered sizing.
+fn log_shadow_decisions(
+    ctx: &BotContext,
+    question: &str,
+    prediction: f64,
+    market_prob: f64,
+    liquidity: f64,
+    days_to_close: Option<f64>,
+    reversion: bool,
+) {
+    let log_tx = &ctx.log_tx;
+    for (i, shadow) in ctx.config.shadows.iter().enumerate() {
+        let tag = format!("[shadow #{}] [{question}]", i + 1);
+        if liquidity < shadow.min_liquidity {
+            let _ = log_tx.send(BotLogEntry::Shadow(format!(
+                "{tag} would skip: liquidity M${liquidity:.0} < M${:.0} min",
+                shadow.min_liquidity,
+            )));
+            continue;
+        }
+        let amount = if reversion {
+            shadow.reversion_amount
+        } else {
+            shadow.bet_amount
+        };
+        let msg = match decide_bet(
+            prediction,
+            market_prob,
+            amount,
+            liquidity,
+            days_to_close,
+            shadow,
+        ) {
+            BetDecision::Skip { guarded: true, .. } => format!(
+                "{tag} would skip: market {:.0}% outside the extreme-price band",
+                market_prob * 100.0,
+            ),
+            BetDecision::Skip {
+                abs_edge, net_edge, ..
+            } => format!(
+                "{tag} would skip: edge {:.1}% ({:.1}% after costs) < {}",
+                abs_edge * 100.0,
+                net_edge * 100.0,
+                min_edge_note(days_to_close, shadow),
+            ),
+            BetDecision::Bet {
+                outcome,
+                limit_prob,
+                abs_edge,
+                net_edge,
+                ..
+            } => format!(
+                "{tag} would bet {outcome} M${amount:.0} limit@{:.0}% (edge {:.1}%, {:.1}% after costs)",
+                limit_prob * 100.0,
+                abs_edge * 100.0,
+                net_edge * 100.0,
+            ),
+        };
+        let _ = log_tx.send(BotLogEntry::Shadow(msg));
+    }
+}
+
+const CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Directory for the bot's persisted state.
+#[cfg(not(test))]
+pub fn data_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("manifold-domination")
+}
+
+/// Tests that start `run_bot` get scratch state instead of the user's.
+#[cfg(test)]
+pub fn data_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("manifold-domination-test-{}", std::process::id()))
+}
+
+fn cache_file_path() -> PathBuf {
+    data_dir().join("analyzed_cache.json")
+}
+
+pub fn now_epoch_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+pub fn now_epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Markets already analyzed (market_id -> epoch secs), persisted to disk.
+/// Shared behind a lock so claiming a market is one check-and-insert: of any
+/// number of duplicate events (replays, backfill overlap), only one proceeds.
+/// A claim stays in memory until the analysis reaches a decision, so a crash
+/// mid-analysis leaves the market to be picked up again next session.
+#[derive(Debug, Default)]
+struct AnalyzedCache {
+    /// Finished analyses; the only part persisted.
+    entries: HashMap<String, u64>,
+    /// Claimed markets whose analysis is queued or running (epoch secs
+    /// claimed).
+    in_progress: HashMap<String, u64>,
+    /// Where the cache is persisted; `None` keeps it in memory only.
+    path: Option<PathBuf>,
+}
+
+impl AnalyzedCache {
+    fn load() -> Self {
+        Self::load_from(cache_file_path())
+    }
+
+    fn load_from(path: PathBuf) -> Self {
+        let cache: HashMap<String, u64> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok())
+            .unwrap_or_default();
+        let mut cache = Self {
+            entries: cache,
+            in_progress: HashMap::new(),
+            path: Some(path),
+        };
+        cache.evict_expired(now_epoch_secs());
+        cache
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(
+            path,
+            serde_json::to_string(&self.entries).unwrap_or_default(),
+        );
+    }
+
+    /// Analyzed, or claimed for analysis.
+    fn contains(&self, contract_id: &str) -> bool {
+        self.entries.contains_key(contract_id) || self.in_progress.contains_key(contract_id)
+    }
+
+    /// Claim `contract_id` for analysis unless it's already analyzed or
+    /// claimed. True if this call claimed it, i.e. the caller should analyze it.
+    fn claim(&mut self, contract_id: &str) -> bool {
+        if self.contains(contract_id) {
+            return false;
+        }
+        self.in_progress
+            .insert(contract_id.to_string(), now_epoch_secs());
+        true
+    }
+
+    /// Claim `contract_id` for a rerun even if it's already analyzed. False
+    /// only while another analysis of it is in flight.
+    fn reclaim(&mut self, contract_id: &str) -> bool {
+        if self.in_progress.contains_key(contract_id) {
+            return false;
+        }
+        self.in_progress
+            .insert(contract_id.to_string(), now_epoch_secs());
+        true
+    }
+
+    /// Drop a claim without recording an analysis.
+    fn release(&mut self, contract_id: &str) {
+        self.in_progress.remove(contract_id);
+    }
+
+    /// Record a finished analysis (bet, skip or hard failure) and persist it.
+    fn complete(&mut self, contract_id: &str, now: u64) {
+        self.in_progress.remove(contract_id);
+        self.entries.insert(contract_id.to_string(), now);
+        self.save();
+    }
+
+    /// Forget every finished analysis. Claims of analyses still running are
+    /// kept, so clearing never lets a duplicate start alongside them.
+    fn clear(&mut self) -> usize {
+        let cleared = self.entries.len();
+        self.entries.clear();
+        self.save();
+        cleared
+    }
+
+    /// Forget one finished analysis; false if there was none.
+    fn forget(&mut self, contract_id: &str) -> bool {
+        let removed = self.entries.remove(contract_id).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            markets: self.entries.len(),
+            in_progress: self.in_progress.len(),
+            oldest: self.entries.values().min().copied(),
+            newest: self.entries.values().max().copied(),
+        }
+    }
+
+    fn evict_expired(&mut self, now: u64) {
+        self.entries
+            .retain(|_, ts| now.saturating_sub(*ts) < CACHE_TTL_SECS);
+    }
+
+    /// Drop claims older than `ttl`: an analysis that never reached a
+    /// decision (a lost task, a path that forgot to release) would otherwise
+    /// block its market for the rest of the session.
+    fn evict_stale_claims(&mut self, now: u64, ttl: u64) -> usize {
+        evict_stale_entries(&mut self.in_progress, now, ttl, |at| *at)
+    }
+}
+
+const PENDING_STATE_VERSION: u32 = 1;
+/// Pending items queued longer ago than this are dropped on reload.
+const PENDING_TTL_SECS: u64 = 6 * 60 * 60;
+
+/// A new market waiting for `min_market_age_secs` before analysis.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeferredMarket {
+    pub contract_id: String,
+    pub question: String,
+    /// Epoch seconds when it was deferred.
+    pub queued_at: u64,
+    /// Epoch seconds when it should be analyzed.
+    pub due_at: u64,
+}
+
+/// In-flight work that would otherwise be lost on restart, persisted to disk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingState {
+    pub version: u32,
+    pub deferred: Vec<DeferredMarket>,
+}
+
+impl Default for PendingState {
+    fn default() -> Self {
+        Self {
+            version: PENDING_STATE_VERSION,
+            deferred: Vec::new(),
+        }
+    }
+}
+
+fn pending_file_path() -> PathBuf {
+    data_dir().join("pending.json")
+}
+
+impl PendingState {
+    pub fn load() -> Self {
+        std::fs::read_to_string(pending_file_path())
+            .map(|data| Self::parse(&data, now_epoch_secs()))
+            .unwrap_or_default()
+    }
+
+    /// Parse a saved state, discarding unknown versions and items past the TTL.
+    fn parse(data: &str, now: u64) -> Self {
+        let mut state: Self = match serde_json::from_str(data) {
+            Ok(s) => s,
+            Err(_) => return Self::default(),
+        };
+        if state.version != PENDING_STATE_VERSION {
+            return Self::default();
+        }
+        state
+            .deferred
+            .retain(|d| now.saturating_sub(d.queued_at) < PENDING_TTL_SECS);
+        state
+    }
+
+    pub fn save(&self) {
+        let path = pending_file_path();
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(&path, serde_json::to_string(self).unwrap_or_default());
+    }
+
+    /// Drop a deferred market; returns whether it was present.
+    pub fn remove(&mut self, contract_id: &str) -> bool {
+        let before = self.deferred.len();
+        self.deferred.retain(|d| d.contract_id != contract_id);
+        self.deferred.len() != before
+    }
+}
+
+pub async fn run_bot(
+    manifold: Arc<dyn ManifoldApi>,
+    xai: XaiClient,
+    channels: BotChannels,
+    mut config: BotConfig,
+) {
+    let BotChannels {
+        mut ws_rx,
+        mut cmd_rx,
+        log_tx,
+        status_tx,
+    } = channels;
+
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "Bot started (M${:.0}/new, M${:.0}/reversion, {:.0}% min edge, M${:.0} min liquidity)",
+        config.bet_amount,
+        config.reversion_amount,
+        config.min_edge * 100.0,
+        config.min_liquidity,
+    )));
+    for (i, shadow) in config.shadows.iter().enumerate() {
+        let _ = log_tx.send(BotLogEntry::Shadow(format!(
+            "Shadow #{} active (M${:.0}/new, M${:.0}/reversion, {:.0}% min edge, M${:.0} min liquidity)",
+            i + 1,
+            shadow.bet_amount,
+            shadow.reversion_amount,
+            shadow.min_edge * 100.0,
+            shadow.min_liquidity,
+        )));
+    }
+
+    let analyzed = AnalyzedCache::load();
+    let pnl = PnlTracker::load();
+    let paper = config.paper_ledger.then(|| {
+        let paper = PaperLedger::load();
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Paper ledger (unconstrained, not real bets): {}",
+            paper.summary()
+        )));
+        Arc::new(Mutex::new(paper))
+    });
+
+    #[cfg(feature = "sqlite")]
+    let store = match Store::open(
+        &crate::store::store_file_path(),
+        &analyzed.entries,
+        pnl.entries(),
+    ) {
+        Ok(store) => Some(Arc::new(Mutex::new(store))),
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "SQLite store unavailable, continuing without it: {e}"
+            )));
+            None
+        }
+    };
+
+    // Bets are decided concurrently but placed one at a time by the executor
+    let (order_tx, order_rx) = mpsc::unbounded_channel::<BetOrder>();
+    // Every task the bot starts, so they all stop with it (see `Orchestrator::stop`)
+    let mut tasks: JoinSet<()> = JoinSet::new();
+
+    let simulator = config
+        .simulation
+        .clone()
+        .map(|s| Arc::new(Simulator::new(s)));
+    let (manifold, executor): (Arc<dyn ManifoldApi>, Arc<dyn ExecutionBackend>) = match &simulator {
+        Some(simulator) => {
+            let _ = log_tx.send(BotLogEntry::Error(
+                "SIMULATION MODE: xAI research, Manifold calls and bets are simulated, nothing reaches the account"
+                    .to_string(),
+            ));
+            (
+                Arc::new(SimulatedManifold::new(simulator.clone())),
+                Arc::new(SimulatedExecutor::new(simulator.clone())),
+            )
+        }
+        None => {
+            let executor = execution::backend(
+                &config.execution,
+                config.record_executions,
+                manifold.clone(),
+            );
+            (manifold, executor)
+        }
+    };
+    if !executor.is_live()
+        && (config.reprice.is_some()
+            || config.reevaluation.is_some()
+            || config.profit_sweep.is_some())
+    {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Bets aren't live ({}): re-pricing, re-evaluation and profit sweeps are off",
+            executor.name()
+        )));
+    }
+    let replay = config.xai_replay.map(XaiReplay::load);
+    match &replay {
+        Some(replay) if replay.mode() == ReplayMode::Replay && replay.is_empty() => {
+            let _ = log_tx.send(BotLogEntry::Error(
+                "xAI replay: no recorded responses, every analysis will fail".to_string(),
+            ));
+        }
+        Some(replay) if replay.mode() == ReplayMode::Replay => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "xAI replay: answering research from {} recorded response(s)",
+                replay.len()
+            )));
+        }
+        _ => {}
+    }
+    let xai_health = if simulator.is_some() {
+        XaiHealth::in_memory(config.xai_error_window)
+    } else {
+        XaiHealth::load(config.xai_error_window)
+    };
+    let mut ctx = BotContext {
+        manifold,
+        xai,
+        log_tx: log_tx.clone(),
+        status_tx: status_tx.clone(),
+        config: config.clone(),
+        pnl: Arc::new(Mutex::new(pnl)),
+        analyzed: Arc::new(Mutex::new(analyzed)),
+        paper,
+        paused: Arc::new(AtomicBool::new(false)),
+        xai_health: Arc::new(Mutex::new(xai_health)),
+        analysis_paused: Arc::new(AtomicBool::new(false)),
+        connected: Arc::new(AtomicBool::new(false)),
+        state: Arc::new(Mutex::new(BotState::NotStarted)),
+        analyses: Arc::new(Mutex::new(AnalysisCache::load())),
+        counters: Arc::new(Mutex::new(CounterState::new(
+            now_epoch_secs() / SECS_PER_DAY,
+        ))),
+        in_flight: Arc::new(Mutex::new(Vec::new())),
+        failed: Arc::new(Mutex::new(Vec::new())),
+        held: Arc::new(Mutex::new(Vec::new())),
+        short_balance: Arc::new(Mutex::new(None)),
+        balance: Arc::new(Mutex::new(BalanceCache::default())),
+        last_bet_at: Arc::new(Mutex::new(None)),
+        reevaluated: Arc::new(Mutex::new(HashMap::new())),
+        repriced: Arc::new(Mutex::new(HashMap::new())),
+        creators: Arc::new(Mutex::new(HashMap::new())),
+        executor,
+        simulator,
+        replay: replay.map(|r| Arc::new(Mutex::new(r))),
+        orders: order_tx.downgrade(),
+        #[cfg(feature = "sqlite")]
+        store,
+    };
+    // The executor outlives config updates, so it's sent each one
+    let (config_tx, config_rx) = tokio::sync::watch::channel(config.clone());
+    tasks.spawn(run_executor(ctx.clone(), order_rx, config_rx));
+
+    // In the background: one market lookup per holding would delay the feed
+    if config.restore_positions {
+        let ctx = ctx.clone();
+        tasks.spawn(async move {
+            restore_positions(&ctx).await;
+        });
+    }
+
+    // Events the bot feeds back to itself: markets created while the WebSocket was
+    // down, and new markets deferred until they are old enough to price
+    let (replay_tx, mut replay_rx) = mpsc::unbounded_channel::<WsEvent>();
+    let mut disconnected_at: Option<u64> = None;
+    let mut warm_up = WarmUpThrottle::default();
+    let mut move_runs: HashMap<String, MoveRun> = HashMap::new();
+    let mut analysis_queue = AnalysisQueue::default();
+    let analysis_slots = Arc::new(tokio::sync::Semaphore::new(
+        config.analysis_workers.unwrap_or(1).max(1),
+    ));
+
+    // Resume deferrals that were pending when the app last stopped
+    let mut pending = PendingState::load();
+    if !pending.deferred.is_empty() {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Restored {} deferred market(s) from last session",
+            pending.deferred.len()
+        )));
+    }
+    let now = now_epoch_secs();
+    for deferred in &pending.deferred {
+        let manifold = ctx.manifold.clone();
+        let replay_tx = replay_tx.clone();
+        let contract_id = deferred.contract_id.clone();
+        let wait = deferred.due_at.saturating_sub(now);
+        tasks.spawn(async move {
+            requeue_after(manifold.as_ref(), &replay_tx, contract_id, None, wait).await;
+        });
+    }
+    pending.save();
+
+    // Resolutions of markets we hold bets on: (contract_id, resolution)
+    let (resolution_tx, mut resolution_rx) = mpsc::unbounded_channel::<(String, String)>();
+    let mut resolution_interval =
+        tokio::time::interval(Duration::from_secs(config.resolution_poll_secs.max(1)));
+    let mut sweep_interval = tokio::time::interval(Duration::from_secs(
+        config
+            .profit_sweep
+            .as_ref()
+            .map_or(SECS_PER_DAY, |s| s.interval_secs.max(1)),
+    ));
+    // Markets from the feed that passed the filters, with whether they've
+    // been flagged as closing soon
+    let mut watched: HashMap<String, WatchedMarket> = HashMap::new();
+    // Markets whose new-contract event is being fed back through `replay_tx`
+    // after it was already counted as seen
+    let mut requeued: HashSet<String> = HashSet::new();
+    let mut urgent_interval = tokio::time::interval(Duration::from_secs(
+        config
+            .urgent_close
+            .as_ref()
+            .map_or(SECS_PER_DAY, |u| u.scan_secs.max(1)),
+    ));
+    // The first round comes one interval after start, not at start
+    let reevaluation_period = Duration::from_secs(
+        config
+            .reevaluation
+            .as_ref()
+            .map_or(SECS_PER_DAY, |r| r.interval_secs.max(1)),
+    );
+    let mut reevaluation_interval = tokio::time::interval_at(
+        tokio::time::Instant::now() + reevaluation_period,
+        reevaluation_period,
+    );
+    let mut reevaluation_task: Option<AbortHandle> = None;
+    let mut reprice_interval = tokio::time::interval(Duration::from_secs(
+        config
+            .reprice
+            .as_ref()
+            .map_or(SECS_PER_DAY, |r| r.interval_secs.max(1)),
+    ));
+    let mut reprice_task: Option<AbortHandle> = None;
+    let mut loss_streak: u32 = 0;
+    let _ = status_tx.send(BotStatus::LossStreak {
+        losses: 0,
+        paused: false,
+    });
+    publish_state(&ctx);
+
+    let report_at = tokio::time::sleep(until_next_utc_day());
+    tokio::pin!(report_at);
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut maintenance = tokio::time::interval(MAINTENANCE_INTERVAL);
+
+    loop {
+        let event = tokio::select! {
+            event = ws_rx.recv() => match event {
+                Some(e) => e,
+                None => break,
+            },
+            Some(event) = replay_rx.recv() => event,
+            // Reap finished tasks so the set doesn't grow
+            Some(_) = tasks.join_next(), if !tasks.is_empty() => continue,
+            Some(cmd) = cmd_rx.recv() => {
+                match cmd {
+                    BotCommand::Resume => {
+                        ctx.paused.store(false, Ordering::SeqCst);
+                        loss_streak = 0;
+                        let _ = log_tx.send(BotLogEntry::Info("Trading resumed".to_string()));
+                        publish_state(&ctx);
+                    }
+                    BotCommand::ResumeAnalysis => {
+                        ctx.xai_health.lock().unwrap().reset();
+                        ctx.analysis_paused.store(false, Ordering::SeqCst);
+                        let _ = log_tx.send(BotLogEntry::Info("Analysis resumed".to_string()));
+                        publish_xai_health(&ctx);
+                        resume_held(&ctx, &mut analysis_queue);
+                    }
+                    BotCommand::Retry(contract_id) => {
+                        retry_failed(&ctx, &contract_id, &mut analysis_queue, &analysis_slots);
+                    }
+                    BotCommand::ClearCache => {
+                        let cleared = ctx.analyzed.lock().unwrap().clear();
+                        let _ = log_tx.send(BotLogEntry::Info(format!(
+                            "Cleared {cleared} market(s) from the analyzed cache"
+                        )));
+                        let _ = status_tx.send(BotStatus::Cache(ctx.analyzed.lock().unwrap().stats()));
+                    }
+                    BotCommand::Forget(contract_id) => {
+                        let mut analyzed = ctx.analyzed.lock().unwrap();
+                        let message = if analyzed.forget(&contract_id) {
+                            format!("Removed {contract_id} from the analyzed cache")
+                        } else if analyzed.contains(&contract_id) {
+                            format!("{contract_id} is being analyzed; not removed")
+                        } else {
+                            format!("{contract_id} is not in the analyzed cache")
+                        };
+                        let _ = log_tx.send(BotLogEntry::Info(message));
+                        let _ = status_tx.send(BotStatus::Cache(analyzed.stats()));
+                    }
+                    BotCommand::Diagnostics => {
+                        let _ = status_tx.send(BotStatus::Diagnostics(diagnostics_snapshot(&ctx)));
+                    }
+                    BotCommand::UpdateConfig(new) => match prepare_config_update(&config, *new) {
+                        Ok((new, ignored)) => {
+                            let changes = config_changes(&config, &new);
+                            let _ = log_tx.send(BotLogEntry::Info(if changes.is_empty() {
+                                "Config update: nothing changed".to_string()
+                            } else {
+                                format!("Config updated: {}", changes.join(", "))
+                            }));
+                            if !ignored.is_empty() {
+                                let _ = log_tx.send(BotLogEntry::Info(format!(
+                                    "Restart to apply: {}",
+                                    ignored.join(", ")
+                                )));
+                            }
+                            ctx.config = new.clone();
+                            let _ = config_tx.send(new.clone());
+                            config = new;
+                        }
+                        Err(e) => {
+                            let _ = log_tx.send(BotLogEntry::Error(format!(
+                                "Config update rejected: {e}"
+                            )));
+                        }
+                    },
+                    BotCommand::Report => {
+                        let report = generate_daily_report(
+                            &ctx.pnl.lock().unwrap(),
+                            &ctx.counters.lock().unwrap(),
+                        );
+                        let ctx = ctx.clone();
+                        tasks.spawn(async move {
+                            publish_report(&ctx, &report).await;
+                        });
+                    }
+                }
+                let _ = status_tx.send(BotStatus::LossStreak {
+                    losses: loss_streak,
+                    paused: ctx.paused.load(Ordering::SeqCst),
+                });
+                continue;
+            }
+            _ = resolution_interval.tick() => {
+                let mut open = ctx.pnl.lock().unwrap().open_contract_ids();
+                if let Some(paper) = &ctx.paper {
+                    open.extend(paper.lock().unwrap().open_contract_ids());
+                    open.sort();
+                    open.dedup();
+                }
+                if !open.is_empty() {
+                    let manifold = ctx.manifold.clone();
+                    let resolution_tx = resolution_tx.clone();
+                    tasks.spawn(async move {
+                        poll_resolutions(manifold.as_ref(), open, &resolution_tx).await;
+                    });
+                }
+                continue;
+            }
+            _ = sweep_interval.tick(), if config.profit_sweep.is_some() => {
+                if let Some(sweep) = config.profit_sweep.clone() {
+                    let ctx = ctx.clone();
+                    tasks.spawn(async move {
+                        sweep_profits(&ctx, &sweep).await;
+                    });
+                }
+                continue;
+            }
+            _ = reevaluation_interval.tick(), if config.reevaluation.is_some() => {
+                let running = reevaluation_task.as_ref().is_some_and(|t| !t.is_finished());
+                if let (Some(reevaluation), false) = (config.reevaluation.clone(), running) {
+                    let ctx = ctx.clone();
+                    reevaluation_task = Some(tasks.spawn(async move {
+                        reevaluate_positions(&ctx, &reevaluation).await;
+                    }));
+                }
+                continue;
+            }
+            _ = reprice_interval.tick(), if config.reprice.is_some() => {
+                let running = reprice_task.as_ref().is_some_and(|t| !t.is_finished());
+                if let (Some(reprice), false) = (config.reprice.clone(), running) {
+                    let ctx = ctx.clone();
+                    reprice_task = Some(tasks.spawn(async move {
+                        reprice_orders(&ctx, &reprice).await;
+                    }));
+                }
+                continue;
+            }
+            _ = urgent_interval.tick(), if config.urgent_close.is_some() => {
+                let window = config.urgent_close.as_ref().map_or(0, |u| u.window_secs);
+                let now_ms = now_epoch_millis();
+                evict_closed(&mut watched, now_ms);
+                let mut closing = Vec::new();
+                for WatchedMarket { broadcast, flagged, .. } in watched.values_mut() {
+                    let contract = &broadcast.contract;
+                    let Some(remaining) = closes_within(contract.close_time, now_ms, window) else {
+                        continue;
+                    };
+                    closing.push(ClosingMarket {
+                        contract_id: contract.id.clone(),
+                        question: contract.question.clone(),
+                        close_time: contract.close_time.unwrap_or_default() / 1000,
+                    });
+                    if std::mem::replace(flagged, true) {
+                        continue;
+                    }
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Closing in {}m: \"{}\"",
+                        remaining / 60,
+                        contract.question
+                    )));
+                    // Not analyzed lately (or ever): take a last look at current prices
+                    let deferred = pending.deferred.iter().any(|d| d.contract_id == contract.id);
+                    if !ctx.analyzed.lock().unwrap().contains(&contract.id) && !deferred {
+                        requeued.insert(contract.id.clone());
+                        let manifold = ctx.manifold.clone();
+                        let replay_tx = replay_tx.clone();
+                        let contract_id = contract.id.clone();
+                        let original = (**broadcast).clone();
+                        tasks.spawn(async move {
+                            requeue_after(manifold.as_ref(), &replay_tx, contract_id, Some(original), 0).await;
+                        });
+                    }
+                }
+                closing.sort_by_key(|m| m.close_time);
+                let _ = status_tx.send(BotStatus::ClosingSoon(closing));
+                continue;
+            }
+            Ok(permit) = analysis_slots.clone().acquire_owned(), if !analysis_queue.is_empty() => {
+                dispatch_analysis(&ctx, &mut tasks, &mut analysis_queue, &mut warm_up, permit);
+                continue;
+            }
+            _ = maintenance.tick(), if config.watchlist_ttl_secs.is_some() => {
+                let ttl = config.watchlist_ttl_secs.unwrap_or_default();
+                let now = now_epoch_secs();
+                let deferred = evict_stale_items(&mut pending.deferred, now, ttl, |d| d.queued_at);
+                if deferred > 0 {
+                    pending.save();
+                }
+                let mut analyzed = ctx.analyzed.lock().unwrap();
+                analyzed.evict_expired(now);
+                let evicted = [
+                    ("watched", evict_closed(&mut watched, now_epoch_millis())),
+                    ("deferred", deferred),
+                    (
+                        "re-evaluation",
+                        evict_stale_entries(&mut *ctx.reevaluated.lock().unwrap(), now, ttl, |at| *at),
+                    ),
+                    ("in-progress claim", analyzed.evict_stale_claims(now, ttl)),
+                ];
+                drop(analyzed);
+                let summary: Vec<String> = evicted
+                    .iter()
+                    .filter(|(_, n)| *n > 0)
+                    .map(|(what, n)| format!("{n} {what}"))
+                    .collect();
+                if !summary.is_empty() {
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Evicted entries older than {}h: {}",
+                        ttl / 3600,
+                        summary.join(", ")
+                    )));
+                }
+                continue;
+            }
+            _ = heartbeat.tick() => {
+                let _ = status_tx.send(BotStatus::Heartbeat);
+                let _ = status_tx.send(BotStatus::Cache(ctx.analyzed.lock().unwrap().stats()));
+                publish_xai_health(&ctx);
+                let expired = expire_in_flight(&mut ctx.in_flight.lock().unwrap(), now_epoch_secs());
+                if expired {
+                    publish_in_flight(&ctx.in_flight, &status_tx);
+                }
+                continue;
+            }
+            _ = &mut report_at, if config.daily_report => {
+                // Close out the finished day and start counting the new one
+                let report = {
+                    let mut counters = ctx.counters.lock().unwrap();
+                    let day = CounterState::new(now_epoch_secs() / SECS_PER_DAY);
+                    let finished = std::mem::replace(&mut *counters, day);
+                    generate_daily_report(&ctx.pnl.lock().unwrap(), &finished)
+                };
+                let ctx = ctx.clone();
+                tasks.spawn(async move {
+                    publish_report(&ctx, &report).await;
+                });
+                report_at
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + until_next_utc_day());
+                continue;
+            }
+            Some((contract_id, resolution)) = resolution_rx.recv() => {
+                let resolved = ctx.pnl.lock().unwrap().resolve(&contract_id, &resolution);
+                let any_resolved = !resolved.is_empty();
+                let paper_resolved = ctx
+                    .paper
+                    .as_ref()
+                    .is_some_and(|paper| paper.lock().unwrap().resolve(&contract_id, &resolution));
+                if any_resolved && ctx.short_balance.lock().unwrap().is_some() {
+                    // Payouts may have refilled the balance
+                    let ctx = ctx.clone();
+                    tasks.spawn(async move {
+                        if let Ok(user) = ctx.manifold.get_me().await {
+                            let full = next_order_ceiling(user.balance, &ctx.config);
+                            set_short_balance(&ctx, (user.balance < full).then_some(user.balance));
+                        }
+                    });
+                }
+                if any_resolved {
+                    store_resolution(&ctx, &contract_id, &resolution);
+                    ctx.counters.lock().unwrap().resolved.extend(resolved.iter().cloned());
+                }
+                for entry in resolved {
+                    loss_streak = next_loss_streak(loss_streak, &entry);
+                    let _ = log_tx.send(BotLogEntry::Info(resolution_line(&resolution, &entry)));
+                }
+                if any_resolved {
+                    let pnl = ctx.pnl.lock().unwrap();
+                    let mut line = format!("P&L: {}", pnl.summary());
+                    for (strategy, summary) in pnl.summary_by_strategy() {
+                        line.push_str(&format!(" | {strategy}: {summary}"));
+                    }
+                    let _ = log_tx.send(BotLogEntry::Info(line));
+                }
+                if let Some(paper) = ctx.paper.as_ref().filter(|_| any_resolved || paper_resolved) {
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Paper (unconstrained, not real bets): {}",
+                        paper.lock().unwrap().summary()
+                    )));
+                }
+                if let Some(breaker) = &config.calibration_breaker {
+                    let calibration = ctx.pnl.lock().unwrap().calibration(breaker.window);
+                    if calibration_trips(&calibration, breaker)
+                        && !ctx.paused.swap(true, Ordering::SeqCst)
+                    {
+                        let _ = log_tx.send(BotLogEntry::Error(format!(
+                            "TRADING PAUSED: won {} of the last {} resolved markets vs {:.1} predicted (z {:.2}, Brier {:.3}) — check the model/strategy, then resume manually",
+                            calibration.wins,
+                            calibration.samples,
+                            calibration.expected_wins,
+                            calibration.z_score().unwrap_or_default(),
+                            calibration.brier,
+                        )));
+                    }
+                }
+                if let Some(max) = config.max_loss_streak {
+                    if loss_streak >= max && !ctx.paused.swap(true, Ordering::SeqCst) {
+                        let _ = log_tx.send(BotLogEntry::Error(format!(
+                            "TRADING PAUSED: {loss_streak} resolved losses in a row — check the model/strategy, then resume manually"
+                        )));
+                    }
+                }
+                let _ = status_tx.send(BotStatus::LossStreak {
+                    losses: loss_streak,
+                    paused: ctx.paused.load(Ordering::SeqCst),
+                });
+                publish_state(&ctx);
+                continue;
+            }
+        };
+
+        match event {
+            WsEvent::Connected => {
+                let _ = log_tx.send(BotLogEntry::Info("WebSocket connected".to_string()));
+                ctx.connected.store(true, Ordering::SeqCst);
+                publish_state(&ctx);
+                if let Some(w) = &config.warm_up {
+                    warm_up.connected(Instant::now());
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Warm-up: analyses limited to {:.0}/min, easing off over {}s",
+                        w.starts_per_min, w.secs
+                    )));
+                }
+                if let Some(since) = disconnected_at.take() {
+                    let manifold = ctx.manifold.clone();
+                    let log_tx = log_tx.clone();
+                    let replay_tx = replay_tx.clone();
+                    let config = config.clone();
+                    tasks.spawn(async move {
+                        backfill_markets(manifold.as_ref(), &log_tx, &replay_tx, since, &config)
+                            .await;
+                    });
+                }
+            }
+            WsEvent::Disconnected => {
+                let _ = log_tx.send(BotLogEntry::Info(
+                    "WebSocket disconnected, reconnecting...".to_string(),
+                ));
+                disconnected_at.get_or_insert_with(now_epoch_millis);
+                ctx.connected.store(false, Ordering::SeqCst);
+                publish_state(&ctx);
+            }
+            WsEvent::NewContract(broadcast) => {
+                let contract = &broadcast.contract;
+                let creator = &broadcast.creator;
+
+                let deferred = pending.remove(&contract.id);
+                if deferred {
+                    pending.save();
+                }
+                // Deferred and re-fetched markets were counted on arrival
+                let replayed = requeued.remove(&contract.id) || deferred;
+
+                // Backfilled markets may overlap with ones already seen live
+                if ctx.analyzed.lock().unwrap().contains(&contract.id) {
+                    continue;
+                }
+                if !replayed {
+                    ctx.counters.lock().unwrap().markets_seen += 1;
+                }
+
+                let trace = DecisionTrace::new(
+                    &contract.id,
+                    &contract.question,
+                    Strategy::NewMarket,
+                    now_epoch_secs(),
+                );
+                if let Some((log, reason)) =
+                    listing_skip(contract.into(), now_epoch_millis(), &config, None)
+                {
+                    let _ = log_tx.send(BotLogEntry::Info(log));
+                    finish_skip(&ctx, trace, reason);
+                    continue;
+                }
+
+                let Some(liquidity) = known_liquidity(contract.total_liquidity, &config) else {
+                    requeued.insert(contract.id.clone());
+                    let manifold = ctx.manifold.clone();
+                    let replay_tx = replay_tx.clone();
+                    let original = (*broadcast).clone();
+                    tasks.spawn(async move {
+                        let fetched = manifold.get_market(&original.contract.id).await.ok();
+                        let _ = replay_tx.send(WsEvent::NewContract(Box::new(
+                            with_fetched_liquidity(original, fetched),
+                        )));
+                    });
+                    continue;
+                };
+                if liquidity < config.min_liquidity {
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Skipping low-liquidity market (M${:.0}): \"{}\"",
+                        liquidity, contract.question
+                    )));
+                    finish_skip(&ctx, trace, "Liquidity below min_liquidity");
+                    continue;
+                }
+                if let Some(reason) =
+                    seed_skip_reason(contract.initial_liquidity, liquidity, &config)
+                {
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Skipping market ({reason}): \"{}\"",
+                        contract.question
+                    )));
+                    finish_skip(&ctx, trace, format!("Seed liquidity: {reason}"));
+                    continue;
+                }
+
+                let urgent = config.urgent_close.as_ref().is_some_and(|u| {
+                    closes_within(contract.close_time, now_epoch_millis(), u.window_secs).is_some()
+                });
+                if config.urgent_close.is_some() && contract.close_time.is_some() {
+                    watched
+                        .entry(contract.id.clone())
+                        .or_insert_with(|| WatchedMarket {
+                            broadcast: broadcast.clone(),
+                            flagged: urgent,
+                        });
+                }
+
+                let age = market_age_secs(contract.created_time, now_epoch_millis());
+                if age < config.min_market_age_secs {
+                    let wait = config.min_market_age_secs - age;
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Deferring new market {wait}s (age {age}s < {}s min): \"{}\"",
+                        config.min_market_age_secs, contract.question
+                    )));
+                    let now = now_epoch_secs();
+                    pending.deferred.push(DeferredMarket {
+                        contract_id: contract.id.clone(),
+                        question: contract.question.clone(),
+                        queued_at: now,
+                        due_at: now + wait,
+                    });
+                    pending.save();
+                    let manifold = ctx.manifold.clone();
+                    let replay_tx = replay_tx.clone();
+                    let contract_id = contract.id.clone();
+                    tasks.spawn(async move {
+                        requeue_after(
+                            manifold.as_ref(),
+                            &replay_tx,
+                            contract_id,
+                            Some(*broadcast),
+                            wait,
+                        )
+                        .await;
+                    });
+                    continue;
+                }
+
+                let _ = log_tx.send(BotLogEntry::Info(format!(
+                    "New binary market (M${:.0} liq): \"{}\" by {}",
+                    liquidity, contract.question, creator.username
+                )));
+                // Claim it so bet events and replays don't re-trigger
+                if !ctx.analyzed.lock().unwrap().claim(&contract.id) {
+                    continue;
+                }
+                let job = AnalysisJob::NewMarket(broadcast);
+                if config.analysis_workers.is_some() {
+                    // Closing soon: ahead of any liquidity
+                    let priority = if urgent { f64::INFINITY } else { liquidity };
+                    queue_analysis(
+                        &mut analysis_queue,
+                        &analysis_slots,
+                        &log_tx,
+                        Some(priority),
+                        job,
+                        Instant::now(),
+                    );
+                } else {
+                    let delay = warm_up_delay(&mut warm_up, &log_tx, job.label(), &config);
+                    start_analysis(&ctx, &mut tasks, job, delay, None);
+                }
+            }
+            WsEvent::NewBet(bet, received_at) => {
+                if let Some(age) = stale_bet_event(received_at, Instant::now(), &config) {
+                    log_stale_bet(&log_tx, &bet, age);
+                    continue;
+                }
+                if let Some(confirmation) = &config.move_confirmation {
+                    if !confirm_move(&mut move_runs, &bet, now_epoch_secs(), confirmation) {
+                        continue;
+                    }
+                }
+                // Evict stale cache entries periodically
+                let now = now_epoch_secs();
+                let claimed = {
+                    let mut analyzed = ctx.analyzed.lock().unwrap();
+                    analyzed.evict_expired(now);
+                    analyzed.claim(&bet.contract_id)
+                };
+                if !claimed {
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Already analyzed market {} — skipping (prob {:.0}% → {:.0}%)",
+                        bet.contract_id,
+                        bet.prob_before * 100.0,
+                        bet.prob_after * 100.0,
+                    )));
+                    continue;
+                }
+                ctx.counters.lock().unwrap().markets_seen += 1;
+
+                let job = AnalysisJob::BetTriggered(*bet);
+                if config.analysis_workers.is_some() {
+                    // Liquidity is only known once the handler fetches the market
+                    queue_analysis(
+                        &mut analysis_queue,
+                        &analysis_slots,
+                        &log_tx,
+                        None,
+                        job,
+                        received_at,
+                    );
+                } else {
+                    let delay = warm_up_delay(&mut warm_up, &log_tx, job.label(), &config);
+                    start_analysis(&ctx, &mut tasks, job, delay, None);
+                }
+            }
+            WsEvent::Error(e) => {
+                let _ = log_tx.send(BotLogEntry::Error(e));
+            }
+            WsEvent::Debug(note) => {
+                if config.dev_tools {
+                    let _ = log_tx.send(BotLogEntry::Info(format!("[ws] {note}")));
+                }
+            }
+        }
+    }
+    ctx.xai_health.lock().unwrap().flush();
+}
+
+/// A market analysis waiting to start.
+#[derive(Clone)]
+enum AnalysisJob {
+    NewMarket(Box<NewContractBroadcast>),
+    BetTriggered(BetData),
+    /// A failed or held analysis run again (see `retry_analysis`).
+    Retry(Box<AnalysisJob>),
+}
+
+impl AnalysisJob {
+    /// Question, or contract id when the question isn't known yet.
+    fn label(&self) -> &str {
+        match self {
+            AnalysisJob::NewMarket(broadcast) => &broadcast.contract.question,
+            AnalysisJob::BetTriggered(bet) => &bet.contract_id,
+            AnalysisJob::Retry(job) => job.label(),
+        }
+    }
+
+    fn contract_id(&self) -> &str {
+        match self {
+            AnalysisJob::NewMarket(broadcast) => &broadcast.contract.id,
+            AnalysisJob::BetTriggered(bet) => &bet.contract_id,
+            AnalysisJob::Retry(job) => job.contract_id(),
+        }
+    }
+
+    async fn run(self, ctx: &BotContext) -> AnalysisEnd {
+        match self {
+            AnalysisJob::NewMarket(broadcast) => handle_new_market(ctx, &broadcast).await,
+            AnalysisJob::BetTriggered(bet) => handle_bet_triggered(ctx, &bet).await,
+            AnalysisJob::Retry(job) => retry_analysis(ctx, *job).await,
+        }
+    }
+}
+
+/// How an analysis ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AnalysisEnd {
+    /// A decision was reached (bet, skip or hard failure): the market counts
+    /// as analyzed.
+    Decided,
+    /// Set aside until analysis resumes; the market isn't analyzed yet.
+    Held,
+}
+
+/// Analyses waiting for a free worker: highest liquidity first, FIFO among
+/// equals. Unknown liquidity sorts last; urgent jobs are pushed as infinite.
+struct AnalysisQueue<T> {
+    /// Jobs with their liquidity and arrival sequence number.
+    jobs: Vec<(f64, u64, T)>,
+    next_seq: u64,
+}
+
+impl<T> Default for AnalysisQueue<T> {
+    fn default() -> Self {
+        Self {
+            jobs: Vec::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<T> AnalysisQueue<T> {
+    fn push(&mut self, liquidity: Option<f64>, job: T) {
+        let key = liquidity.unwrap_or(f64::NEG_INFINITY);
+        self.jobs.push((key, self.next_seq, job));
+        self.next_seq += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let best = self
+            .jobs
+            .iter()
+            .enumerate()
+            .max_by(|(_, (a, seq_a, _)), (_, (b, seq_b, _))| a.total_cmp(b).then(seq_b.cmp(seq_a)))
+            .map(|(i, _)| i)?;
+        Some(self.jobs.remove(best).2)
+    }
+
+    fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+/// Most failed markets kept for retry; the oldest are dropped.
+const MAX_FAILED: usize = 20;
+
+/// Add a failure, replacing any earlier one for the same market.
+fn push_failure<T>(failed: &mut Vec<(FailedMarket, T)>, market: FailedMarket, job: T) {
+    failed.retain(|(m, _)| m.contract_id != market.contract_id);
+    failed.push((market, job));
+    if failed.len() > MAX_FAILED {
+        failed.remove(0);
+    }
+}
+
+fn publish_failed(ctx: &BotContext) {
+    let snapshot = ctx
+        .failed
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(m, _)| m.clone())
+        .collect();
+    let _ = ctx.status_tx.send(BotStatus::Failed(snapshot));
+}
+
+/// Remember a failed market so it can be retried from the dashboard.
+fn record_failure(ctx: &BotContext, job: AnalysisJob, question: &str, reason: &str) {
+    let market = FailedMarket {
+        contract_id: job.contract_id().to_string(),
+        question: question.to_string(),
+        reason: reason.to_string(),
+    };
+    push_failure(&mut ctx.failed.lock().unwrap(), market, job);
+    publish_failed(ctx);
+}
+
+/// Most analyses held while `xai_pause` is active; the oldest are dropped.
+const MAX_HELD: usize = 500;
+
+/// Set `job` aside to rerun when analysis resumes, if its research failed
+/// because `xai_pause` is active (or just tripped on it): every market would
+/// fail until then, and the failed list only keeps the last few. False if
+/// analysis isn't paused.
+fn hold_if_paused(ctx: &BotContext, job: AnalysisJob) -> bool {
+    if replaying(ctx) || !ctx.analysis_paused.load(Ordering::SeqCst) {
+        return false;
+    }
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "Analysis paused — holding \"{}\" until it resumes",
+        job.label()
+    )));
+    let mut held = ctx.held.lock().unwrap();
+    held.retain(|j| j.contract_id() != job.contract_id());
+    held.push(job);
+    if held.len() > MAX_HELD {
+        held.remove(0);
+    }
+    true
+}
+
+/// Queue the analyses held while analysis was paused for the worker pool,
+/// skipping markets claimed or analyzed since.
+fn resume_held(ctx: &BotContext, queue: &mut AnalysisQueue<(AnalysisJob, Instant)>) {
+    let jobs = std::mem::take(&mut *ctx.held.lock().unwrap());
+    if jobs.is_empty() {
+        return;
+    }
+    let mut queued = 0;
+    for job in jobs {
+        if !ctx.analyzed.lock().unwrap().claim(job.contract_id()) {
+            continue;
+        }
+        queue.push(None, (AnalysisJob::Retry(Box::new(job)), Instant::now()));
+        queued += 1;
+    }
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "Rerunning {queued} analyses held while paused"
+    )));
+}
+
+/// Remove a market from the failed list, returning what to re-run.
+fn take_failure(ctx: &BotContext, contract_id: &str) -> Option<AnalysisJob> {
+    let job = {
+        let mut failed = ctx.failed.lock().unwrap();
+        let i = failed
+            .iter()
+            .position(|(m, _)| m.contract_id == contract_id)?;
+        failed.remove(i).1
+    };
+    publish_failed(ctx);
+    Some(job)
+}
+
+/// Queue a failed market for the worker pool to retry, ahead of new events
+/// since it was asked for. Refused while another analysis of it is running.
+fn retry_failed(
+    ctx: &BotContext,
+    contract_id: &str,
+    queue: &mut AnalysisQueue<(AnalysisJob, Instant)>,
+    slots: &tokio::sync::Semaphore,
+) {
+    // The failure was recorded as analyzed; only a running analysis blocks it
+    if !ctx.analyzed.lock().unwrap().reclaim(contract_id) {
+        let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+            "{contract_id} is being analyzed; not retried"
+        )));
+        return;
+    }
+    let Some(job) = take_failure(ctx, contract_id) else {
+        ctx.analyzed.lock().unwrap().release(contract_id);
+        return;
+    };
+    let job = AnalysisJob::Retry(Box::new(job));
+    queue_analysis(
+        queue,
+        slots,
+        &ctx.log_tx,
+        Some(f64::INFINITY),
+        job,
+        Instant::now(),
+    );
+}
+
+/// Re-run a failed or held market's pipeline. New markets are refreshed first so the
+/// decision uses the current price.
+async fn retry_analysis(ctx: &BotContext, job: AnalysisJob) -> AnalysisEnd {
+    let _ = ctx
+        .log_tx
+        .send(BotLogEntry::Info(format!("Retrying \"{}\"", job.label())));
+    match job {
+        AnalysisJob::NewMarket(original) => {
+            let broadcast = match ctx.manifold.get_market(&original.contract.id).await {
+                Ok(market) => NewContractBroadcast {
+                    creator: original.creator,
+                    ..broadcast_from_market(market)
+                },
+                Err(_) => *original,
+            };
+            handle_new_market(ctx, &broadcast).await
+        }
+        AnalysisJob::BetTriggered(bet) => handle_bet_triggered(ctx, &bet).await,
+        AnalysisJob::Retry(job) => Box::pin(retry_analysis(ctx, *job)).await,
+    }
+}
+
+/// How long a new-bet event has waited since `received_at`, if longer than
+/// `max_bet_event_age_secs`.
+fn stale_bet_event(received_at: Instant, now: Instant, config: &BotConfig) -> Option<Duration> {
+    let max = Duration::from_secs(config.max_bet_event_age_secs?);
+    let age = now.saturating_duration_since(received_at);
+    (age > max).then_some(age)
+}
+
+fn log_stale_bet(log_tx: &mpsc::UnboundedSender<BotLogEntry>, bet: &BetData, age: Duration) {
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "Dropping stale new-bet event on {} ({}s old, prob {:.0}% → {:.0}%)",
+        bet.contract_id,
+        age.as_secs(),
+        bet.prob_before * 100.0,
+        bet.prob_after * 100.0,
+    )));
+}
+
+/// `events` same-direction bets on one market within `window_secs` of the
+/// first of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoveConfirmation {
+    pub events: u32,
+    pub window_secs: u64,
+}
+
+/// Consecutive same-direction bets seen on one market.
+#[derive(Debug, Clone, PartialEq)]
+struct MoveRun {
+    up: bool,
+    count: u32,
+    /// Epoch seconds of the run's first bet.
+    started_at: u64,
+}
+
+/// Count `bet` towards its market's run and whether the run now confirms the
+/// move. A bet in the other direction, or past the window, starts a new run;
+/// a confirmed run is cleared so the next move needs confirming afresh.
+fn confirm_move(
+    runs: &mut HashMap<String, MoveRun>,
+    bet: &BetData,
+    now: u64,
+    confirmation: &MoveConfirmation,
+) -> bool {
+    runs.retain(|_, run| now.saturating_sub(run.started_at) <= confirmation.window_secs);
+    if bet.prob_after == bet.prob_before {
+        return false;
+    }
+    let up = bet.prob_after > bet.prob_before;
+    let run = runs
+        .entry(bet.contract_id.clone())
+        .and_modify(|run| {
+            if run.up == up {
+                run.count += 1;
+            } else {
+                *run = MoveRun {
+                    up,
+                    count: 1,
+                    started_at: now,
+                };
+            }
+        })
+        .or_insert(MoveRun {
+            up,
+            count: 1,
+            started_at: now,
+        });
+    if run.count < confirmation.events {
+        return false;
+    }
+    runs.remove(&bet.contract_id);
+    true
+}
+
+/// Queue an analysis for the worker pool with when its event was received,
+/// logging when it has to wait.
+fn queue_analysis(
+    queue: &mut AnalysisQueue<(AnalysisJob, Instant)>,
+    slots: &tokio::sync::Semaphore,
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    liquidity: Option<f64>,
+    job: AnalysisJob,
+    received_at: Instant,
+) {
+    if slots.available_permits() == 0 {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "All analysis workers busy — queued \"{}\" ({} waiting)",
+            job.label(),
+            queue.len() + 1,
+        )));
+    }
+    queue.push(liquidity, (job, received_at));
+}
+
+/// Start the best queued analysis on the worker slot `permit`, dropping bet
+/// events that went stale while they waited.
+fn dispatch_analysis(
+    ctx: &BotContext,
+    tasks: &mut JoinSet<()>,
+    queue: &mut AnalysisQueue<(AnalysisJob, Instant)>,
+    warm_up: &mut WarmUpThrottle,
+    permit: tokio::sync::OwnedSemaphorePermit,
+) {
+    let Some((job, received_at)) = queue.pop() else {
+        return;
+    };
+    if let AnalysisJob::BetTriggered(bet) = &job {
+        if let Some(age) = stale_bet_event(received_at, Instant::now(), &ctx.config) {
+            log_stale_bet(&ctx.log_tx, bet, age);
+            ctx.analyzed.lock().unwrap().release(&bet.contract_id);
+            return;
+        }
+    }
+    let delay = warm_up_delay(warm_up, &ctx.log_tx, job.label(), &ctx.config);
+    start_analysis(ctx, tasks, job, delay, Some(permit));
+}
+
+/// Start `job` in `tasks` after `delay`, holding `permit` (a worker slot)
+/// until it finishes.
+fn start_analysis(
+    ctx: &BotContext,
+    tasks: &mut JoinSet<()>,
+    job: AnalysisJob,
+    delay: Duration,
+    permit: Option<tokio::sync::OwnedSemaphorePermit>,
+) {
+    let ctx = ctx.clone();
+    tasks.spawn(async move {
+        tokio::time::sleep(delay).await;
+        let contract_id = job.contract_id().to_string();
+        let end = job.run(&ctx).await;
+        let mut analyzed = ctx.analyzed.lock().unwrap();
+        match end {
+            // Only now is the market persisted as analyzed
+            AnalysisEnd::Decided => analyzed.complete(&contract_id, now_epoch_secs()),
+            // Free for the rerun on resume (or a fresh event) to claim
+            AnalysisEnd::Held => analyzed.release(&contract_id),
+        }
+        drop(permit);
+    });
+}
+
+/// The warm-up wait for an analysis about to start, logged when non-zero.
+fn warm_up_delay(
+    throttle: &mut WarmUpThrottle,
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    label: &str,
+    config: &BotConfig,
+) -> Duration {
+    let Some(w) = &config.warm_up else {
+        return Duration::ZERO;
+    };
+    let delay = throttle.delay(Instant::now(), w);
+    if !delay.is_zero() {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Warm-up: delaying analysis {:.0}s: \"{label}\"",
+            delay.as_secs_f64()
+        )));
+    }
+    delay
+}
+
+/// Check each contract we hold bets on and report the ones that have resolved.
+async fn poll_resolutions(
+    manifold: &dyn ManifoldApi,
+    contract_ids: Vec<String>,
+    resolution_tx: &mpsc::UnboundedSender<(String, String)>,
+) {
+    for id in contract_ids {
+        if let Ok(market) = manifold.get_market(&id).await {
+            if let (true, Some(resolution)) = (market.is_resolved, market.resolution) {
+                let _ = resolution_tx.send((id, resolution));
+            }
+        }
+        // Stay well under the API rate limit
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Milliseconds-epoch `created_time` to age in whole seconds.
+fn market_age_secs(created_time_ms: u64, now_ms: u64) -> u64 {
+    now_ms.saturating_sub(created_time_ms) / 1000
+}
+
+/// Wait `delay_secs`, refresh the market so the replayed event carries current
+/// price and liquidity, then feed it back as a new-contract event. `original`
+/// is used if the refresh fails; without it the market is dropped.
+async fn requeue_after(
+    manifold: &dyn ManifoldApi,
+    replay_tx: &mpsc::UnboundedSender<WsEvent>,
+    contract_id: String,
+    original: Option<NewContractBroadcast>,
+    delay_secs: u64,
+) {
+    tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+    let broadcast = match (manifold.get_market(&contract_id).await, original) {
+        (Ok(market), Some(original)) => NewContractBroadcast {
+            creator: original.creator,
+            ..broadcast_from_market(market)
+        },
+        (Ok(market), None) => broadcast_from_market(market),
+        (Err(_), Some(original)) => original,
+        (Err(_), None) => return,
+    };
+    let _ = replay_tx.send(WsEvent::NewContract(Box::new(broadcast)));
+}
+
+/// Markets fetched per page when backfilling (Manifold's maximum).
+const MARKETS_PAGE: u32 = 1000;
+
+/// Fetch markets created since `since_ms` (capped to `backfill_max_secs` ago) and
+/// replay them through `replay_tx` as new-contract events.
+async fn backfill_markets(
+    manifold: &dyn ManifoldApi,
+    log_tx: &mpsc::UnboundedSender<BotLogEntry>,
+    replay_tx: &mpsc::UnboundedSender<WsEvent>,
+    since_ms: u64,
+    config: &BotConfig,
+) {
+    let now_ms = now_epoch_millis();
+    let since_ms = since_ms.max(now_ms.saturating_sub(config.backfill_max_secs * 1000));
+
+    // Page back until the outage began; markets closed since are skipped later
+    let missed = fetch_paginated(
+        MARKETS_PAGE,
+        config.backfill_limit as usize,
+        |m: &Market| m.created_time >= since_ms,
+        |before, limit| async move { manifold.get_markets(before.as_deref(), limit).await },
+    )
+    .await;
+    let missed = match missed {
+        Ok(m) => m,
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!("Backfill failed: {e}")));
+            return;
+        }
+    };
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "Backfilling {} market(s) created during {}s downtime",
+        missed.len(),
+        now_ms.saturating_sub(since_ms) / 1000,
+    )));
+
+    // Oldest first, matching live event order
+    for market in missed.into_iter().rev() {
+        let broadcast = broadcast_from_market(market);
+        let _ = replay_tx.send(WsEvent::NewContract(Box::new(broadcast)));
+    }
+}
+
+/// A new market's liquidity, `None` if it's missing and should be fetched
+/// (`fetch_missing_liquidity`); otherwise missing counts as zero.
+fn known_liquidity(total_liquidity: Option<f64>, config: &BotConfig) -> Option<f64> {
+    match total_liquidity {
+        Some(liquidity) => Some(liquidity),
+        None if config.fetch_missing_liquidity => None,
+        None => Some(0.0),
+    }
+}
+
+/// The fields of a market the listing filters read, from a WebSocket payload
+/// or a REST fetch.
+struct Listing<'a> {
+    question: &'a str,
+    close_time: Option<u64>,
+    probability: Option<f64>,
+    outcome_type: &'a str,
+    visibility: &'a str,
+    mechanism: &'a str,
+}
+
+impl<'a> From<&'a ContractData> for Listing<'a> {
+    fn from(contract: &'a ContractData) -> Self {
+        Self {
+            question: &contract.question,
+            close_time: contract.close_time,
+            probability: contract.probability,
+            outcome_type: &contract.outcome_type,
+            visibility: &contract.visibility,
+            mechanism: &contract.mechanism,
+        }
+    }
+}
+
+impl<'a> From<&'a Market> for Listing<'a> {
+    fn from(market: &'a Market) -> Self {
+        Self {
+            question: &market.question,
+            close_time: market.close_time,
+            probability: market.probability,
+            outcome_type: &market.outcome_type,
+            visibility: &market.visibility,
+            mechanism: &market.mechanism,
+        }
+    }
+}
+
+/// Why a market fails the filters that need nothing beyond its listing, as a
+/// log line and a DECISION reason; `None` if it passes them. `source` tags
+/// the log line, e.g. `bet-triggered`.
+fn listing_skip(
+    listing: Listing,
+    now_ms: u64,
+    config: &BotConfig,
+    source: Option<&str>,
+) -> Option<(String, String)> {
+    let question = listing.question;
+    let note = |detail: Option<String>| match (source, detail) {
+        (Some(source), Some(detail)) => format!(" ({source}, {detail})"),
+        (Some(source), None) => format!(" ({source})"),
+        (None, Some(detail)) => format!(" ({detail})"),
+        (None, None) => String::new(),
+    };
+    let (log, reason) = if closed_before_analysis(listing.close_time, now_ms, config) {
+        (
+            format!("Skipping closed market{}", note(None)),
+            "Market closed".to_string(),
+        )
+    } else if !price_tradable(listing.probability, config) {
+        let price = format!("{:.0}%", listing.probability.unwrap_or(0.5) * 100.0);
+        (
+            format!(
+                "Skipping market priced outside the tradable range{}",
+                note(Some(price))
+            ),
+            "Price outside tradable_price_range".to_string(),
+        )
+    } else if let Some(reason) = outcome_type_skip_reason(listing.outcome_type, config) {
+        (
+            format!(
+                "Skipping {} market{}",
+                listing.outcome_type,
+                note(Some(reason.to_string()))
+            ),
+            format!("{} market: {reason}", listing.outcome_type),
+        )
+    } else if !visibility_allowed(listing.visibility, config) {
+        (
+            format!("Skipping {} market{}", listing.visibility, note(None)),
+            format!("{} market", listing.visibility),
+        )
+    } else if !mechanism_allowed(listing.mechanism, config) {
+        (
+            format!(
+                "Skipping {} market{}",
+                listing.mechanism,
+                note(Some("unsupported mechanism".to_string()))
+            ),
+            format!("Unsupported mechanism {}", listing.mechanism),
+        )
+    } else if let Some(lang) = disallowed_language(question, config) {
+        (
+            format!(
+                "Skipping {} market{}",
+                lang.eng_name(),
+                note(Some(lang.code().to_string()))
+            ),
+            format!("Language not allowed: {}", lang.code()),
+        )
+    } else {
+        return None;
+    };
+    Some((format!("{log}: \"{question}\""), reason))
+}
+
+/// Why a market falls short of `min_initial_liquidity`, judged by its seed
+/// or, without one, its current `liquidity`.
+fn seed_skip_reason(initial: Option<f64>, liquidity: f64, config: &BotConfig) -> Option<String> {
+    let min = config.min_initial_liquidity?;
+    match initial {
+        Some(seed) if seed < min => Some(format!("seeded with M${seed:.0} < M${min:.0}")),
+        None if liquidity < min => Some(format!(
+            "no seed reported, pool M${liquidity:.0} < M${min:.0}"
+        )),
+        _ => None,
+    }
+}
+
+/// `original` refreshed from a REST fetch, keeping its creator. Liquidity is
+/// always set, to zero if the fetch failed or lacked it too, so the replayed
+/// event isn't fetched again.
+fn with_fetched_liquidity(
+    original: NewContractBroadcast,
+    fetched: Option<Market>,
+) -> NewContractBroadcast {
+    let mut broadcast = match fetched {
+        Some(market) => NewContractBroadcast {
+            creator: original.creator,
+            ..broadcast_from_market(market)
+        },
+        None => original,
+    };
+    broadcast.contract.total_liquidity.get_or_insert(0.0);
+    broadcast
+}
+
+fn broadcast_from_market(market: Market) -> NewContractBroadcast {
+    NewContractBroadcast {
+        contract: ContractData {
+            id: market.id,
+            slug: market.slug,
+            question: market.question,
+            outcome_type: market.outcome_type,
+            mechanism: market.mechanism,
+            visibility: market.visibility,
+            created_time: market.created_time,
+            close_time: market.close_time,
+            is_resolved: market.is_resolved,
+            volume: market.volume,
+            probability: market.probability,
+            p: None,
+            total_liquidity: market.total_liquidity,
+            initial_liquidity: market.initial_liquidity,
+            text_description: market.text_description,
+        },
+        creator: CreatorData {
+            id: market.creator_id,
+            username: market.creator_username,
+            name: market.creator_name,
+        },
+    }
+}
+
+/// Research a market with xAI (streaming if configured), reusing a prior
+/// analysis of the same question and description when `reuse_analysis_by_question` is on.
+async fn research(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+    research_with(ctx, question, description, true).await
+}
+
+/// `research` that never answers from the reuse-by-question cache, for
+/// re-asking a question already analyzed. Its answer still refreshes the cache.
+async fn research_fresh(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+    research_with(ctx, question, description, false).await
+}
+
+async fn research_with(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+    read_cache: bool,
+) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+    // Replays answer exactly what was recorded, cache and pause aside
+    let replaying = replaying(ctx);
+    let reuse = ctx.config.reuse_analysis_by_question && !replaying;
+    if reuse && read_cache {
+        let cached = ctx
+            .analyses
+            .lock()
+            .unwrap()
+            .get(question, description, now_epoch_secs());
+        if let Some(result) = cached {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "Reusing prior analysis of \"{question}\"",
+            )));
+            return Ok(result);
+        }
+    }
+
+    if !replaying && ctx.analysis_paused.load(Ordering::SeqCst) {
+        return Err("analysis paused after repeated xAI errors".into());
+    }
+
+    let result = ask_xai(ctx, question, description, XaiCall::Research).await?;
+    let result = retry_empty_response(ctx, question, description, result).await;
+    if reuse && xai::parse_prediction(&result.text).is_some() {
+        ctx.analyses
+            .lock()
+            .unwrap()
+            .insert(question, description, &result, now_epoch_secs());
+    }
+    Ok(result)
+}
+
+/// The xAI calls made while deciding on a market. All of them go through
+/// `ask_xai`, so replay, recording and the simulator cover each one.
+#[derive(Debug, Clone, Copy)]
+enum XaiCall<'a> {
+    Research,
+    /// Re-asked after a response with no answer text.
+    Nudged,
+    /// Re-asked after the model skipped, with its reason.
+    Clarified(&'a str),
+    /// A second opinion, optionally from another model.
+    Confirmation(Option<&'a str>),
+}
+
+impl XaiCall<'_> {
+    /// Name recordings are kept under.
+    fn name(&self) -> &'static str {
+        match self {
+            XaiCall::Research => "research",
+            XaiCall::Nudged => "nudged",
+            XaiCall::Clarified(_) => "clarified",
+            XaiCall::Confirmation(_) => "confirmation",
+        }
+    }
+}
+
+/// Whether `xai_replay` answers from recordings instead of xAI.
+fn replaying(ctx: &BotContext) -> bool {
+    ctx.replay
+        .as_ref()
+        .is_some_and(|r| r.lock().unwrap().mode() == ReplayMode::Replay)
+}
+
+/// Make one xAI call: from the recordings when replaying, from the simulator
+/// in simulation mode, otherwise live (and recorded, if recording).
+async fn ask_xai(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+    call: XaiCall<'_>,
+) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(replay) = &ctx.replay {
+        let replay = replay.lock().unwrap();
+        if replay.mode() == ReplayMode::Replay {
+            return replay
+                .get(call.name(), question, description)
+                .ok_or_else(|| {
+                    format!(
+                        "no recorded xAI {} response for \"{question}\"",
+                        call.name()
+                    )
+                    .into()
+                });
+        }
+    }
+
+    let result = match (&ctx.simulator, call) {
+        (Some(simulator), _) => {
+            count_xai_call(ctx);
+            simulator.research(question).await
+        }
+        (None, XaiCall::Research) => research_live(ctx, question, description).await,
+        (None, XaiCall::Nudged) => {
+            count_xai_call(ctx);
+            ctx.xai.research_market_nudged(question, description).await
+        }
+        (None, XaiCall::Clarified(reason)) => {
+            count_xai_call(ctx);
+            ctx.xai
+                .research_market_clarified(question, description, reason)
+                .await
+        }
+        (None, XaiCall::Confirmation(model)) => {
+            count_xai_call(ctx);
+            ctx.xai
+                .research_market_with_model(question, description, model)
+                .await
+        }
+    };
+    let result = record_xai_result(ctx, result)?;
+    if let Some(replay) = &ctx.replay {
+        let prompt = match call {
+            XaiCall::Clarified(reason) => {
+                ctx.xai
+                    .clarified_request_json(question, description, reason)
+            }
+            _ => ctx.xai.research_request_json(question, description),
+        };
+        replay.lock().unwrap().record(
+            call.name(),
+            question,
+            description,
+            prompt,
+            &result,
+            now_epoch_secs(),
+        );
+    }
+    Ok(result)
+}
+
+/// Ask xAI for research, streaming if configured and falling back to a plain
+/// request if the stream fails.
+async fn research_live(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+    // Streamed results carry no sources, so they can't pass a source gate
+    if ctx.config.xai_streaming && ctx.config.min_sources.is_none() {
+        count_xai_call(ctx);
+        match record_xai_result(
+            ctx,
+            ctx.xai
+                .research_market_streaming(question, description)
+                .await,
+        ) {
+            Ok(r) => return Ok(r),
+            Err(e) => {
+                let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                    "xAI stream failed for \"{question}\" ({e}), retrying without streaming"
+                )));
+            }
+        }
+    }
+    count_xai_call(ctx);
+    ctx.xai.research_market(question, description).await
+}
+
+/// If the response has no answer text, count it and, if `retry_empty_responses`
+/// is on, re-ask once with a nudge for the final answer. Keeps the original
+/// result if the retry fails.
+async fn retry_empty_response(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+    result: SearchResult,
+) -> SearchResult {
+    if !result.is_empty() {
+        return result;
+    }
+    ctx.counters.lock().unwrap().empty_responses += 1;
+    let retry = ctx.config.retry_empty_responses;
+    let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+        "xAI returned an empty response for \"{question}\"{}",
+        if retry { ", retrying" } else { "" }
+    )));
+    if !retry {
+        return result;
+    }
+    match ask_xai(ctx, question, description, XaiCall::Nudged).await {
+        Ok(retried) => {
+            if retried.is_empty() {
+                ctx.counters.lock().unwrap().empty_responses += 1;
+                let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                    "xAI returned an empty response again for \"{question}\", giving up"
+                )));
+            }
+            retried
+        }
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Empty-response retry failed for \"{question}\": {e}"
+            )));
+            result
+        }
+    }
+}
+
+/// The escalation settings, if a skip on a market this liquid should be escalated.
+fn skip_escalation(liquidity: f64, config: &BotConfig) -> Option<&SkipEscalation> {
+    config
+        .skip_escalation
+        .as_ref()
+        .filter(|e| liquidity >= e.min_liquidity)
+}
+
+/// If the model skipped and `skip_escalation` allows a retry, re-ask once with
+/// a clarifying prompt. Never retries the retry; keeps the original result if
+/// it fails.
+async fn retry_model_skip(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+    liquidity: f64,
+    result: SearchResult,
+    trace: &mut DecisionTrace,
+) -> SearchResult {
+    let Some(xai::PredictionResult::Skip(reason)) = xai::parse_prediction(&result.text) else {
+        return result;
+    };
+    if !skip_escalation(liquidity, &ctx.config).is_some_and(|e| e.retry) {
+        return result;
+    }
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "Model skipped \"{question}\" (M${liquidity:.0} liq), retrying with a clarifying prompt | {reason}"
+    )));
+    match ask_xai(ctx, question, description, XaiCall::Clarified(&reason)).await {
+        Ok(retried) => {
+            trace.xai_request = Some(ctx.xai.clarified_request_json(
+                question,
+                description,
+                &reason,
+            ));
+            retried
+        }
+        Err(e) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Clarifying retry failed for \"{question}\": {e}"
+            )));
+            result
+        }
+    }
+}
+
+/// Count a skip for the daily report and, if `skip_escalation` allows, queue
+/// the market for a human.
+fn record_skip(ctx: &BotContext, contract_id: &str, question: &str, liquidity: f64, reason: &str) {
+    ctx.counters.lock().unwrap().skips.push(SkippedMarket {
+        question: question.to_string(),
+        reason: reason.to_string(),
+    });
+    if skip_escalation(liquidity, &ctx.config).is_some_and(|e| e.needs_human) {
+        let _ = ctx.status_tx.send(BotStatus::NeedsHuman(NeedsHumanMarket {
+            contract_id: contract_id.to_string(),
+            question: question.to_string(),
+            reason: reason.to_string(),
+            liquidity,
+        }));
+    }
+}
+
+/// Analyses still in flight after this long are dropped from the dashboard;
+/// xAI requests time out well before.
+const IN_FLIGHT_TIMEOUT_SECS: u64 = 10 * 60;
+
+/// Marks an analysis as in progress until dropped, so every early return of a
+/// handler clears it.
+struct InFlightGuard {
+    in_flight: Arc<Mutex<Vec<InFlightAnalysis>>>,
+    status_tx: mpsc::UnboundedSender<BotStatus>,
+    contract_id: String,
+}
+
+impl InFlightGuard {
+    fn start(ctx: &BotContext, contract_id: &str, question: &str) -> Self {
+        ctx.in_flight.lock().unwrap().push(InFlightAnalysis {
+            contract_id: contract_id.to_string(),
+            question: question.to_string(),
+            started_at: now_epoch_secs(),
+        });
+        publish_in_flight(&ctx.in_flight, &ctx.status_tx);
+        Self {
+            in_flight: ctx.in_flight.clone(),
+            status_tx: ctx.status_tx.clone(),
+            contract_id: contract_id.to_string(),
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        // Only the first match: the same market may be analyzed twice at once
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(i) = in_flight
+            .iter()
+            .position(|a| a.contract_id == self.contract_id)
+        {
+            in_flight.remove(i);
+        }
+        drop(in_flight);
+        publish_in_flight(&self.in_flight, &self.status_tx);
+    }
+}
+
+fn publish_in_flight(
+    in_flight: &Mutex<Vec<InFlightAnalysis>>,
+    status_tx: &mpsc::UnboundedSender<BotStatus>,
+) {
+    let snapshot = in_flight.lock().unwrap().clone();
+    let _ = status_tx.send(BotStatus::Analyzing(snapshot));
+}
+
+/// How often `run_bot` evicts stale per-market bookkeeping.
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// A market from the feed watched for `BotConfig::urgent_close`.
+struct WatchedMarket {
+    broadcast: Box<NewContractBroadcast>,
+    /// Already flagged as closing soon.
+    flagged: bool,
+}
+
+/// Drop watched markets that have closed; returns how many. A market stays
+/// watched however long it is open, since that's when it becomes urgent.
+fn evict_closed(watched: &mut HashMap<String, WatchedMarket>, now_ms: u64) -> usize {
+    let before = watched.len();
+    watched.retain(|_, w| w.broadcast.contract.close_time.is_some_and(|t| t > now_ms));
+    before - watched.len()
+}
+
+/// Drop entries added more than `ttl` seconds before `now`; returns how many.
+fn evict_stale_entries<K, V>(
+    map: &mut HashMap<K, V>,
+    now: u64,
+    ttl: u64,
+    added_at: impl Fn(&V) -> u64,
+) -> usize {
+    let before = map.len();
+    map.retain(|_, v| now.saturating_sub(added_at(v)) <= ttl);
+    before - map.len()
+}
+
+/// `evict_stale_entries` for a list.
+fn evict_stale_items<T>(
+    items: &mut Vec<T>,
+    now: u64,
+    ttl: u64,
+    added_at: impl Fn(&T) -> u64,
+) -> usize {
+    let before = items.len();
+    items.retain(|item| now.saturating_sub(added_at(item)) <= ttl);
+    before - items.len()
+}
+
+/// Drop analyses older than `IN_FLIGHT_TIMEOUT_SECS`; true if any were.
+fn expire_in_flight(in_flight: &mut Vec<InFlightAnalysis>, now: u64) -> bool {
+    let before = in_flight.len();
+    in_flight.retain(|a| now.saturating_sub(a.started_at) < IN_FLIGHT_TIMEOUT_SECS);
+    in_flight.len() != before
+}
+
+async fn handle_new_market(ctx: &BotContext, broadcast: &NewContractBroadcast) -> AnalysisEnd {
+    let BotContext { log_tx, config, .. } = ctx;
+    let question = &broadcast.contract.question;
+    let contract_id = &broadcast.contract.id;
+    let _in_flight = InFlightGuard::start(ctx, contract_id, question);
+    let mut trace =
+        DecisionTrace::new(contract_id, question, Strategy::NewMarket, now_epoch_secs());
+
+    let creator = creator_profile(ctx, &broadcast.creator.username).await;
+    if let Some(reason) = creator_untrusted(ctx, creator.as_ref(), question) {
+        finish_skip(ctx, trace, reason);
+        return AnalysisEnd::Decided;
+    }
+
+    let creator_note = creator
+        .map(|p| format!(" (creator: {})", creator_hint(&p, now_epoch_millis())))
+        .unwrap_or_default();
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "Researching \"{question}\"{creator_note}...",
+    )));
+
+    let description = broadcast.contract.text_description.as_deref();
+    let liquidity = broadcast.contract.total_liquidity.unwrap_or(0.0);
+    trace.xai_request = Some(ctx.xai.research_request_json(question, description));
+    let result = match research(ctx, question, description).await {
+        Ok(r) => r,
+        Err(_) if hold_if_paused(ctx, AnalysisJob::NewMarket(Box::new(broadcast.clone()))) => {
+            // No DECISION yet: the rerun on resume makes it
+            return AnalysisEnd::Held;
+        }
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "xAI research failed for \"{question}\": {e}",
+            )));
+            record_failure(
+                ctx,
+                AnalysisJob::NewMarket(Box::new(broadcast.clone())),
+                question,
+                &format!("xAI research failed: {e}"),
+            );
+            finish_skip(ctx, trace, format!("xAI research failed: {e}"));
+            return AnalysisEnd::Decided;
+        }
+    };
+    let result = retry_model_skip(ctx, question, description, liquidity, result, &mut trace).await;
+    trace.xai_response = Some(result.text.clone());
+    trace.sources = result.sources.clone();
+
+    let parsed = require_sources(
+        xai::parse_prediction(&result.text),
+        result.sources.len(),
+        config,
+    );
+    let mut prediction = match require_reasoning(require_information(parsed, config), config) {
+        Some(xai::PredictionResult::Predict(p)) => p,
+        Some(xai::PredictionResult::Skip(reason)) => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "Skipping unevaluable market: \"{question}\" | {reason}",
+            )));
+            store_analysis(ctx, contract_id, question, None, &reason);
+            record_skip(ctx, contract_id, question, liquidity, &reason);
+            finish_skip(ctx, trace, format!("Skip: {reason}"));
+            return AnalysisEnd::Decided;
+        }
+        None if result.is_empty() => {
+            finish_skip(ctx, trace, "Empty xAI response");
+            return AnalysisEnd::Decided;
+        }
+        None => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "Could not parse prediction for \"{question}\"",
+            )));
+            let truncated = &result.text[..result.text.len().min(300)];
+            let _ = log_tx.send(BotLogEntry::Info(format!("xAI response: {truncated}")));
+            finish_skip(ctx, trace, "Unparseable xAI response");
+            return AnalysisEnd::Decided;
+        }
+    };
+
+    store_analysis(
+        ctx,
+        contract_id,
+        question,
+        Some(prediction.probability),
+        &prediction.reasoning,
+    );
+    ctx.counters.lock().unwrap().markets_analyzed += 1;
+    let Some(probability) = bound_prediction(log_tx, question, prediction.probability, config)
+    else {
+        trace.prediction = Some(prediction.probability);
+        finish_skip(ctx, trace, "Prediction outside prediction_bounds");
+        return AnalysisEnd::Decided;
+    };
+    prediction.probability = probability;
+
+    let market_prob = broadcast.contract.probability.unwrap_or(0.5);
+    let days_to_close = days_to_close(broadcast.contract.close_time, now_epoch_millis());
+    trace.prediction = Some(prediction.probability);
+    trace.reasoning = Some(prediction.reasoning.clone());
+    trace.market_prob = Some(market_prob);
+    trace.liquidity = Some(liquidity);
+
+    let reasoning = if prediction.reasoning.is_empty() {
+        "No reasoning provided".to_string()
+    } else {
+        prediction.reasoning
+    };
+
+    log_shadow_decisions(
+        ctx,
+        question,
+        prediction.probability,
+        market_prob,
+        liquidity,
+        days_to_close,
+        false,
+    );
+
+    if let Some(check) = config.sanity_check {
+        if !check(question, prediction.probability) {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] {:.0}% prediction vetoed by sanity check — skipping | {reasoning}",
+                prediction.probability * 100.0,
+            )));
+            finish_skip(ctx, trace, "Vetoed by sanity check");
+            return AnalysisEnd::Decided;
+        }
+    }
+
+    let price = bought_price(prediction.probability, market_prob);
+    let Some(amount) = stake_for(ctx, config.bet_amount, liquidity, price, question).await else {
+        finish_skip(ctx, trace, "Could not size stake");
+        return AnalysisEnd::Decided;
+    };
+    let amount = cap_stake_to_liquidity(log_tx, question, amount, liquidity, config);
+
+    let decision = decide_bet(
+        prediction.probability,
+        market_prob,
+        amount,
+        liquidity,
+        days_to_close,
+        config,
+    );
+    trace.amount = Some(amount);
+    trace.required_edge = Some(required_edge(days_to_close, config));
+    trace.record_decision(&decision);
+
+    let (outcome, limit_prob, net_edge) = match decision {
+        BetDecision::Skip {
+            net_edge,
+            guarded: true,
+            ..
+        } => {
+            log_extreme_price_guard(
+                log_tx,
+                question,
+                prediction.probability,
+                market_prob,
+                net_edge,
+                config,
+            );
+            finish_decision(ctx, &trace);
+            return AnalysisEnd::Decided;
+        }
+        BetDecision::Skip {
+            abs_edge, net_edge, ..
+        } => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] {:.0}% (market {:.0}%), edge {:.1}% ({:.1}% after costs) < {} — skipping | {reasoning}",
+                prediction.probability * 100.0,
+                market_prob * 100.0,
+                abs_edge * 100.0,
+                net_edge * 100.0,
+                min_edge_note(days_to_close, config),
+            )));
+            finish_decision(ctx, &trace);
+            return AnalysisEnd::Decided;
+        }
+        BetDecision::Bet {
+            outcome,
+            limit_prob,
+            net_edge,
+            distance_capped,
+            ..
+        } => {
+            if distance_capped {
+                log_distance_cap(
+                    log_tx,
+                    question,
+                    prediction.probability,
+                    limit_prob,
+                    market_prob,
+                    config,
+                );
+            }
+            (outcome, limit_prob, net_edge)
+        }
+    };
+
+    let Some(amount) = confirm_bet(
+        ctx,
+        question,
+        description,
+        prediction.probability,
+        market_prob,
+        amount,
+    )
+    .await
+    else {
+        finish_skip(ctx, trace, "Not confirmed by a second prediction");
+        return AnalysisEnd::Decided;
+    };
+    trace.amount = Some(amount);
+
+    // Only now is the stake final
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "[{question}] {:.0}% (market {:.0}%) -> {outcome} M${amount:.0} limit@{:.0}% | {reasoning}",
+        prediction.probability * 100.0,
+        market_prob * 100.0,
+        limit_prob * 100.0,
+    )));
+    log_evidence(log_tx, &prediction.evidence);
+
+    let bets = build_ladder(
+        contract_id,
+        amount,
+        outcome,
+        market_prob,
+        limit_prob,
+        config,
+    );
+    queue_bet(
+        ctx,
+        BetOrder {
+            bets,
+            question: question.to_string(),
+            prediction: prediction.probability,
+            market_prob,
+            strategy: Strategy::NewMarket,
+            net_edge,
+            trace,
+            origin: AnalysisJob::NewMarket(Box::new(broadcast.clone())),
+        },
+    );
+    AnalysisEnd::Decided
+}
+
+/// Turn a `Predict` backed by fewer than `min_sources` distinct sources into a skip.
+fn require_sources(
+    prediction: Option<xai::PredictionResult>,
+    sources: usize,
+    config: &BotConfig,
+) -> Option<xai::PredictionResult> {
+    match (prediction, config.min_sources) {
+        (Some(xai::PredictionResult::Predict(p)), Some(min)) if sources < min => {
+            Some(xai::PredictionResult::Skip(format!(
+                "only {sources} source(s) for a {:.0}% prediction (< {min} required): {}",
+                p.probability * 100.0,
+                p.reasoning
+            )))
+        }
+        (prediction, _) => prediction,
+    }
+}
+
+/// Turn a `Predict` the model flagged as low-information into a skip when
+/// `skip_low_information` is on.
+fn require_information(
+    prediction: Option<xai::PredictionResult>,
+    config: &BotConfig,
+) -> Option<xai::PredictionResult> {
+    match prediction {
+        Some(xai::PredictionResult::Predict(p))
+            if config.skip_low_information && p.low_information =>
+        {
+            Some(xai::PredictionResult::Skip(format!(
+                "{:.0}% prediction on little or no information: {}",
+                p.probability * 100.0,
+                p.reasoning
+            )))
+        }
+        prediction => prediction,
+    }
+}
+
+/// Turn a `Predict` whose reasoning is shorter than `min_reasoning_len` into a skip.
+fn require_reasoning(
+    prediction: Option<xai::PredictionResult>,
+    config: &BotConfig,
+) -> Option<xai::PredictionResult> {
+    match (prediction, config.min_reasoning_len) {
+        (Some(xai::PredictionResult::Predict(p)), Some(min))
+            if p.reasoning.trim().chars().count() < min =>
+        {
+            let reasoning = if p.reasoning.trim().is_empty() {
+                "No reasoning provided"
+            } else {
+                p.reasoning.trim()
+            };
+            Some(xai::PredictionResult::Skip(format!(
+                "reasoning too short for a {:.0}% prediction (< {min} chars): {reasoning}",
+                p.probability * 100.0,
+            )))
+        }
+        (prediction, _) => prediction,
+    }
+}
+
+/// After a recheck delay, a bet-triggered market is skipped if less than this
+/// fraction of the triggering move remains.
+const MIN_MOVE_RETAINED: f64 = 0.5;
+
+/// Fraction of the `prob_before -> prob_after` move still in the price `current`:
+/// 1.0 if it held, 0.0 if fully reverted, negative if it overshot back.
+fn move_retained(prob_before: f64, prob_after: f64, current: f64) -> f64 {
+    let moved = prob_after - prob_before;
+    if moved.abs() < 1e-9 {
+        return 1.0;
+    }
+    (current - prob_before) / moved
+}
+
+/// A move counts as continuing a trend when the prior prices already drifted
+/// its way by at least this share of the move itself...
+const TREND_DRIFT_SHARE: f64 = 0.5;
+/// ...with at least this share of the prior steps going that way.
+const TREND_CONSISTENCY: f64 = 0.6;
+
+/// Whether the `prob_before -> prob_after` move continues a steady trend in
+/// the prior prices (`history`, oldest first) rather than spiking. Too little
+/// history counts as a spike.
+fn is_trend(history: &[f64], prob_before: f64, prob_after: f64) -> bool {
+    let jump = prob_after - prob_before;
+    let (Some(first), Some(last)) = (history.first(), history.last()) else {
+        return false;
+    };
+    if history.len() < 3 || jump.abs() < 1e-9 {
+        return false;
+    }
+    let drift = last - first;
+    if drift * jump <= 0.0 || drift.abs() < TREND_DRIFT_SHARE * jump.abs() {
+        return false;
+    }
+    let steps: Vec<f64> = history
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| d.abs() > 1e-9)
+        .collect();
+    let with_move = steps.iter().filter(|d| **d * jump > 0.0).count();
+    !steps.is_empty() && with_move as f64 >= TREND_CONSISTENCY * steps.len() as f64
+}
+
+/// Whether a fetched price predates the bet that triggered the fetch: it is
+/// more than `tolerance` from the bet's `prob_after`, and the market's last bet
+/// is older than the trigger. Without bet times, a price nearer `prob_before`
+/// than `prob_after` counts as stale.
+fn fetched_prob_stale(
+    fetched: f64,
+    last_bet_time: Option<u64>,
+    bet: &BetData,
+    tolerance: f64,
+) -> bool {
+    if (fetched - bet.prob_after).abs() <= tolerance {
+        return false;
+    }
+    match last_bet_time {
+        Some(last) if bet.created_time > 0 => last < bet.created_time,
+        _ => (fetched - bet.prob_before).abs() < (fetched - bet.prob_after).abs(),
+    }
+}
+
+/// Replace a stale fetched price: with a fresh re-fetch if
+/// `stale_prob_refetch_ms` is set and it helps, otherwise with the triggering
+/// bet's `prob_after`. Logs which value is used.
+async fn reconcile_stale_prob(
+    ctx: &BotContext,
+    mut market: Market,
+    bet: &BetData,
+    tolerance: f64,
+) -> Market {
+    let discrepancy = format!(
+        "Stale price for \"{}\": fetched {:.0}%, but the triggering bet moved it to {:.0}%",
+        market.question,
+        market.probability.unwrap_or_default() * 100.0,
+        bet.prob_after * 100.0
+    );
+    if let Some(delay) = ctx.config.stale_prob_refetch_ms {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        if let Ok(refetched) = ctx.manifold.get_market(&market.id).await {
+            if let Some(p) = refetched
+                .probability
+                .filter(|p| !fetched_prob_stale(*p, refetched.last_bet_time, bet, tolerance))
+            {
+                let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                    "{discrepancy}; using the re-fetched {:.0}%",
+                    p * 100.0
+                )));
+                return refetched;
+            }
+        }
+    }
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "{discrepancy}; using the bet's {:.0}%",
+        bet.prob_after * 100.0
+    )));
+    market.probability = Some(bet.prob_after);
+    market
+}
+
+async fn handle_bet_triggered(ctx: &BotContext, bet_data: &BetData) -> AnalysisEnd {
+    let BotContext {
+        manifold,
+        log_tx,
+        config,
+        ..
+    } = ctx;
+    if let Some(delay) = config.reversion_recheck_secs {
+        tokio::time::sleep(Duration::from_secs(delay)).await;
+    }
+    // The question is filled in once the market is fetched
+    let mut trace = DecisionTrace::new(
+        &bet_data.contract_id,
+        "",
+        config.bet_triggered_mode.strategy(),
+        now_epoch_secs(),
+    );
+    let mut market = match manifold.get_market(&bet_data.contract_id).await {
+        Ok(m) => m,
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "Failed to fetch market {}: {e}",
+                bet_data.contract_id
+            )));
+            finish_skip(ctx, trace, format!("Failed to fetch market: {e}"));
+            return AnalysisEnd::Decided;
+        }
+    };
+    trace.question = market.question.clone();
+    if let (Some(tolerance), Some(fetched)) = (config.stale_prob_tolerance, market.probability) {
+        if fetched_prob_stale(fetched, market.last_bet_time, bet_data, tolerance) {
+            market = reconcile_stale_prob(ctx, market, bet_data, tolerance).await;
+        }
+    }
+
+    if market.is_resolved {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Skipping resolved market (bet-triggered): \"{}\"",
+            market.question
+        )));
+        finish_skip(ctx, trace, "Market resolved");
+        return AnalysisEnd::Decided;
+    }
+
+    let listing = Listing::from(&market);
+    if let Some((log, reason)) =
+        listing_skip(listing, now_epoch_millis(), config, Some("bet-triggered"))
+    {
+        let _ = log_tx.send(BotLogEntry::Info(log));
+        finish_skip(ctx, trace, reason);
+        return AnalysisEnd::Decided;
+    }
+
+    if config.creator_filter.is_some() {
+        let creator = creator_profile(ctx, &market.creator_username).await;
+        if let Some(reason) = creator_untrusted(ctx, creator.as_ref(), &market.question) {
+            finish_skip(ctx, trace, reason);
+            return AnalysisEnd::Decided;
+        }
+    }
+
+    if let (Some(_), Some(current)) = (config.reversion_recheck_secs, market.probability) {
+        let retained = move_retained(bet_data.prob_before, bet_data.prob_after, current);
+        if retained < MIN_MOVE_RETAINED {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "Skipping reverted move (bet-triggered): \"{}\" {:.0}% -> {:.0}%, now {:.0}%",
+                market.question,
+                bet_data.prob_before * 100.0,
+                bet_data.prob_after * 100.0,
+                current * 100.0,
+            )));
+            finish_skip(ctx, trace, "Move reverted before analysis");
+            return AnalysisEnd::Decided;
+        }
+    }
+
+    if let Some(lookback) = config.reversion_trend_lookback {
+        // Newest bet is the trigger (or later); the rest are the run-up
+        match manifold.get_bets(&bet_data.contract_id, lookback + 1).await {
+            Ok(bets) => {
+                let history: Vec<f64> = bets.iter().skip(1).rev().map(|b| b.prob_after).collect();
+                if is_trend(&history, bet_data.prob_before, bet_data.prob_after) {
+                    let _ = log_tx.send(BotLogEntry::Info(format!(
+                        "Skipping trending market (bet-triggered): \"{}\" {:.0}% -> {:.0}% continues a {:.0}% -> {:.0}% run",
+                        market.question,
+                        bet_data.prob_before * 100.0,
+                        bet_data.prob_after * 100.0,
+                        history.first().copied().unwrap_or_default() * 100.0,
+                        history.last().copied().unwrap_or_default() * 100.0,
+                    )));
+                    finish_skip(ctx, trace, "Move continues a trend");
+                    return AnalysisEnd::Decided;
+                }
+            }
+            Err(e) => {
+                let _ = log_tx.send(BotLogEntry::Error(format!(
+                    "Failed to fetch bet history for \"{}\", treating the move as a spike: {e}",
+                    market.question
+                )));
+            }
+        }
+    }
+
+    let liquidity = market.total_liquidity.unwrap_or(0.0);
+    if liquidity < config.min_liquidity {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Skipping low-liquidity market (bet-triggered, M${liquidity:.0}): \"{}\"",
+            market.question
+        )));
+        finish_skip(ctx, trace, "Liquidity below min_liquidity");
+        return AnalysisEnd::Decided;
+    }
+    if let Some(reason) = seed_skip_reason(market.initial_liquidity, liquidity, config) {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "Skipping market (bet-triggered, {reason}): \"{}\"",
+            market.question
+        )));
+        finish_skip(ctx, trace, format!("Seed liquidity: {reason}"));
+        return AnalysisEnd::Decided;
+    }
+
+    if let (Some(max_age), true) = (config.max_market_age_secs, market.created_time > 0) {
+        let age = market_age_secs(market.created_time, now_epoch_millis());
+        if age > max_age {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "Skipping stale market (bet-triggered, {}d old): \"{}\"",
+                age / 86_400,
+                market.question
+            )));
+            finish_skip(ctx, trace, "Market older than max_market_age_secs");
+            return AnalysisEnd::Decided;
+        }
+    }
+
+    let question = &market.question;
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "Analyzing market (bet-triggered, M${liquidity:.0} liq): \"{question}\""
+    )));
+    let _in_flight = InFlightGuard::start(ctx, &bet_data.contract_id, question);
+
+    let description = market.text_description.as_deref();
+    trace.xai_request = Some(ctx.xai.research_request_json(question, description));
+    let result = match research(ctx, question, description).await {
+        Ok(r) => r,
+        Err(_) if hold_if_paused(ctx, AnalysisJob::BetTriggered(bet_data.clone())) => {
+            // No DECISION yet: the rerun on resume makes it
+            return AnalysisEnd::Held;
+        }
+        Err(e) => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "xAI research failed for \"{question}\": {e}"
+            )));
+            record_failure(
+                ctx,
+                AnalysisJob::BetTriggered(bet_data.clone()),
+                question,
+                &format!("xAI research failed: {e}"),
+            );
+            finish_skip(ctx, trace, format!("xAI research failed: {e}"));
+            return AnalysisEnd::Decided;
+        }
+    };
+    let result = retry_model_skip(ctx, question, description, liquidity, result, &mut trace).await;
+    trace.xai_response = Some(result.text.clone());
+    trace.sources = result.sources.clone();
+
+    let parsed = require_sources(
+        xai::parse_prediction(&result.text),
+        result.sources.len(),
+        config,
+    );
+    let mut prediction = match require_reasoning(require_information(parsed, config), config) {
+        Some(xai::PredictionResult::Predict(p)) => p,
+        Some(xai::PredictionResult::Skip(reason)) => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "Skipping unevaluable market: \"{question}\" | {reason}",
+            )));
+            store_analysis(ctx, &bet_data.contract_id, question, None, &reason);
+            record_skip(ctx, &bet_data.contract_id, question, liquidity, &reason);
+            finish_skip(ctx, trace, format!("Skip: {reason}"));
+            return AnalysisEnd::Decided;
+        }
+        None if result.is_empty() => {
+            finish_skip(ctx, trace, "Empty xAI response");
+            return AnalysisEnd::Decided;
+        }
+        None => {
+            let _ = log_tx.send(BotLogEntry::Error(format!(
+                "Could not parse prediction for \"{question}\""
+            )));
+            finish_skip(ctx, trace, "Unparseable xAI response");
+            return AnalysisEnd::Decided;
+        }
+    };
+
+    store_analysis(
+        ctx,
+        &bet_data.contract_id,
+        question,
+        Some(prediction.probability),
+        &prediction.reasoning,
+    );
+    ctx.counters.lock().unwrap().markets_analyzed += 1;
+    let Some(probability) = bound_prediction(log_tx, question, prediction.probability, config)
+    else {
+        trace.prediction = Some(prediction.probability);
+        finish_skip(ctx, trace, "Prediction outside prediction_bounds");
+        return AnalysisEnd::Decided;
+    };
+    prediction.probability = probability;
+
+    let market_prob = market.probability.unwrap_or(0.5);
+    let days_to_close = days_to_close(market.close_time, now_epoch_millis());
+    trace.prediction = Some(prediction.probability);
+    trace.reasoning = Some(prediction.reasoning.clone());
+    trace.market_prob = Some(market_prob);
+    trace.liquidity = Some(liquidity);
+
+    let reasoning = if prediction.reasoning.is_empty() {
+        "No reasoning provided".to_string()
+    } else {
+        prediction.reasoning
+    };
+
+    log_shadow_decisions(
+        ctx,
+        question,
+        prediction.probability,
+        market_prob,
+        liquidity,
+        days_to_close,
+        true,
+    );
+
+    if let Some(check) = config.sanity_check {
+        if !check(question, prediction.probability) {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[bet-triggered] [{question}] {:.0}% prediction vetoed by sanity check — skipping | {reasoning}",
+                prediction.probability * 100.0,
+            )));
+            finish_skip(ctx, trace, "Vetoed by sanity check");
+            return AnalysisEnd::Decided;
+        }
+    }
+
+    let mode = config.bet_triggered_mode;
+    if !mode.allows(bet_data, prediction.probability, market_prob) {
+        let _ = log_tx.send(BotLogEntry::Info(format!(
+            "[bet-triggered, {}] [{question}] {:.0}% (market {:.0}%) doesn't follow the {:.0}% -> {:.0}% move — skipping | {reasoning}",
+            mode.strategy(),
+            prediction.probability * 100.0,
+            market_prob * 100.0,
+            bet_data.prob_before * 100.0,
+            bet_data.prob_after * 100.0,
+        )));
+        finish_skip(
+            ctx,
+            trace,
+            format!("Against the move ({} mode)", mode.strategy()),
+        );
+        return AnalysisEnd::Decided;
+    }
+
+    let price = bought_price(prediction.probability, market_prob);
+    let Some(amount) = stake_for(ctx, config.reversion_amount, liquidity, price, question).await
+    else {
+        finish_skip(ctx, trace, "Could not size stake");
+        return AnalysisEnd::Decided;
+    };
+    let amount = reversion_stake(
+        amount,
+        (prediction.probability - market_prob).abs(),
+        liquidity,
+        config,
+    );
+    let amount = cap_stake_to_liquidity(log_tx, question, amount, liquidity, config);
+
+    let decision = decide_bet(
+        prediction.probability,
+        market_prob,
+        amount,
+        liquidity,
+        days_to_close,
+        config,
+    );
+    trace.amount = Some(amount);
+    trace.required_edge = Some(required_edge(days_to_close, config));
+    trace.record_decision(&decision);
+
+    let (outcome, limit_prob, net_edge) = match decision {
+        BetDecision::Skip {
+            net_edge,
+            guarded: true,
+            ..
+        } => {
+            log_extreme_price_guard(
+                log_tx,
+                question,
+                prediction.probability,
+                market_prob,
+                net_edge,
+                config,
+            );
+            finish_decision(ctx, &trace);
+            return AnalysisEnd::Decided;
+        }
+        BetDecision::Skip {
+            abs_edge, net_edge, ..
+        } => {
+            let _ = log_tx.send(BotLogEntry::Info(format!(
+                "[bet-triggered] [{question}] {:.0}% (market {:.0}%), edge {:.1}% ({:.1}% after costs) < {} — skipping | {reasoning}",
+                prediction.probability * 100.0,
+                market_prob * 100.0,
+                abs_edge * 100.0,
+                net_edge * 100.0,
+                min_edge_note(days_to_close, config),
+            )));
+            finish_decision(ctx, &trace);
+            return AnalysisEnd::Decided;
+        }
+        BetDecision::Bet {
+            outcome,
+            limit_prob,
+            net_edge,
+            distance_capped,
+            ..
+        } => {
+            if distance_capped {
+                log_distance_cap(
+                    log_tx,
+                    question,
+                    prediction.probability,
+                    limit_prob,
+                    market_prob,
+                    config,
+                );
+            }
+            (outcome, limit_prob, net_edge)
+        }
+    };
+
+    let Some(amount) = confirm_bet(
+        ctx,
+        question,
+        description,
+        prediction.probability,
+        market_prob,
+        amount,
+    )
+    .await
+    else {
+        finish_skip(ctx, trace, "Not confirmed by a second prediction");
+        return AnalysisEnd::Decided;
+    };
+    trace.amount = Some(amount);
+
+    // Only now is the stake final
+    let _ = log_tx.send(BotLogEntry::Info(format!(
+        "[bet-triggered, {}] [{question}] {:.0}% (market {:.0}%) -> {outcome} M${amount:.0} limit@{:.0}% | {reasoning}",
+        mode.strategy(),
+        prediction.probability * 100.0,
+        market_prob * 100.0,
+        limit_prob * 100.0,
+    )));
+    log_evidence(log_tx, &prediction.evidence);
+
+    let bets = build_ladder(
+        &bet_data.contract_id,
+        amount,
+        outcome,
+        market_prob,
+        limit_prob,
+        config,
+    );
+    queue_bet(
+        ctx,
+        BetOrder {
+            bets,
+            question: question.to_string(),
+            prediction: prediction.probability,
+            market_prob,
+            strategy: mode.strategy(),
+            net_edge,
+            trace,
+            origin: AnalysisJob::BetTriggered(bet_data.clone()),
+        },
+    );
+    AnalysisEnd::Decided
+}
+
+/// For stakes of at least `confirmation.min_stake`, re-research the market
+/// independently and check the second prediction agrees. Returns the stake to
+/// bet (possibly reduced), or `None` to skip.
+async fn confirm_bet(
+    ctx: &BotContext,
+    question: &str,
+    description: Option<&str>,
+    prediction: f64,
+    market_prob: f64,
+    amount: f64,
+) -> Option<f64> {
+    let Some(confirmation) = &ctx.config.confirmation else {
+        return Some(amount);
+    };
+    if amount < confirmation.min_stake {
+        return Some(amount);
+    }
+    let call = XaiCall::Confirmation(confirmation.model.as_deref());
+    let confirming = match ask_xai(ctx, question, description, call)
+        .await
+        .ok()
+        .and_then(|r| require_information(xai::parse_prediction(&r.text), &ctx.config))
+    {
+        Some(xai::PredictionResult::Predict(p)) => Some(p.probability),
+        _ => None,
+    };
+    let agrees = confirming
+        .is_some_and(|c| confirmation_agrees(prediction, c, market_prob, confirmation.tolerance));
+    let confirming_note = match confirming {
+        Some(c) => format!("{:.0}%", c * 100.0),
+        None => "no prediction".to_string(),
+    };
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "[{question}] confirmation for M${amount:.0}: first {:.0}%, second {confirming_note} (market {:.0}%) — {}",
+        prediction * 100.0,
+        market_prob * 100.0,
+        if agrees { "agree" } else { "disagree" },
+    )));
+    if agrees {
+        return Some(amount);
+    }
+    match confirmation.reduce_to {
+        Some(fraction) => {
+            let reduced = amount * fraction;
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] unconfirmed — reducing stake to M${reduced:.0}"
+            )));
+            Some(reduced)
+        }
+        None => {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "[{question}] unconfirmed — skipping"
+            )));
+            None
+        }
+    }
+}
+
+fn count_xai_call(ctx: &BotContext) {
+    let mut counters = ctx.counters.lock().unwrap();
+    counters.xai_calls += 1;
+    counters.xai_cost += ctx.config.xai_cost_per_call;
+}
+
+/// Pause analysis once at least `min_calls` recent xAI calls are in the window
+/// and `max_error_rate` or more of them failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XaiPause {
+    pub max_error_rate: f64,
+    pub min_calls: usize,
+}
+
+impl XaiPause {
+    fn trips(&self, stats: &XaiHealthStats) -> bool {
+        stats.calls >= self.min_calls && stats.error_rate() >= self.max_error_rate
+    }
+}
+
+/// Count an xAI call's outcome towards the error rate, pausing analysis if
+/// `xai_pause` trips. Passes the result through.
+fn record_xai_result<T>(
+    ctx: &BotContext,
+    result: Result<T, Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    let stats = {
+        let mut health = ctx.xai_health.lock().unwrap();
+        match &result {
+            Ok(_) => health.record_success(),
+            Err(e) => health.record_error(XaiErrorKind::classify(e.as_ref()), &e.to_string()),
+        }
+        health.stats()
+    };
+    if let Some(pause) = &ctx.config.xai_pause {
+        if pause.trips(&stats) && !ctx.analysis_paused.swap(true, Ordering::SeqCst) {
+            let cause = stats
+                .dominant_error()
+                .map_or(String::new(), |kind| format!(", mostly {kind}"));
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "ANALYSIS PAUSED: {stats}{cause} — check the xAI key and credits, then resume manually"
+            )));
+        }
+    }
+    publish_xai_health(ctx);
+    result
+}
+
+fn publish_xai_health(ctx: &BotContext) {
+    let _ = ctx.status_tx.send(BotStatus::Xai {
+        stats: ctx.xai_health.lock().unwrap().stats(),
+        paused: ctx.analysis_paused.load(Ordering::SeqCst),
+    });
+    publish_state(ctx);
+}
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Winners/losers listed in the daily report.
+const REPORT_TOP_N: usize = 3;
+
+/// Activity during one UTC day, for the daily report.
+#[derive(Debug, Clone, Default)]
+pub struct CounterState {
+    /// Days since the epoch.
+    pub day: u64,
+    /// Markets considered after dedup (new and bet-triggered).
+    pub markets_seen: u64,
+    /// Markets xAI returned a prediction for.
+    pub markets_analyzed: u64,
+    pub xai_calls: u64,
+    /// Estimated USD, from `xai_cost_per_call`.
+    pub xai_cost: f64,
+    /// xAI responses with no answer text, retries included.
+    pub empty_responses: u64,
+    /// Ledger entries resolved during the day.
+    pub resolved: Vec<LedgerEntry>,
+    /// Markets skipped after analysis, with the reason.
+    pub skips: Vec<SkippedMarket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkippedMarket {
+    pub question: String,
+    pub reason: String,
+}
+
+impl CounterState {
+    pub fn new(day: u64) -> Self {
+        Self {
+            day,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportBet {
+    pub question: String,
+    pub outcome: String,
+    pub resolution: String,
+    pub pnl: f64,
+}
+
+/// End-of-day summary, written to `reports/<date>.json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    /// UTC date, `YYYY-MM-DD`.
+    pub date: String,
+    pub markets_seen: u64,
+    pub markets_analyzed: u64,
+    pub bets_placed: usize,
+    /// Filled mana on bets placed during the day.
+    pub total_staked: f64,
+    pub resolved: usize,
+    /// P&L of bets resolved during the day.
+    pub realized_pnl: f64,
+    /// Wins over YES/NO-resolved bets; `None` if none resolved that way.
+    pub win_rate: Option<f64>,
+    pub xai_calls: u64,
+    pub xai_cost: f64,
+    pub empty_responses: u64,
+    pub top_winners: Vec<ReportBet>,
+    pub top_losers: Vec<ReportBet>,
+    pub skipped: Vec<SkippedMarket>,
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Daily report {}: {} markets seen, {} analyzed, {} bets (M${:.0} staked), {} resolved (M${:+.0}",
+            self.date,
+            self.markets_seen,
+            self.markets_analyzed,
+            self.bets_placed,
+            self.total_staked,
+            self.resolved,
+            self.realized_pnl,
+        )?;
+        if let Some(rate) = self.win_rate {
+            write!(f, ", {:.0}% won", rate * 100.0)?;
+        }
+        write!(
+            f,
+            "), {} skipped, {} xAI calls (${:.2})",
+            self.skipped.len(),
+            self.xai_calls,
+            self.xai_cost
+        )?;
+        if self.empty_responses > 0 {
+            write!(f, ", {} empty xAI responses", self.empty_responses)?;
+        }
+        Ok(())
+    }
+}
+
+/// Summarize the day counted in `counters`, with bets placed that day taken
+/// from the ledger.
+pub fn generate_daily_report(pnl: &PnlTracker, counters: &CounterState) -> Report {
+    let start = counters.day * SECS_PER_DAY;
+    let placed: Vec<&LedgerEntry> = pnl
+        .entries()
+        .iter()
+        .filter(|e| (start..start + SECS_PER_DAY).contains(&e.placed_at))
+        .collect();
+
+    let mut resolved: Vec<ReportBet> = counters
+        .resolved
+        .iter()
+        .map(|e| ReportBet {
+            question: e.question.clone(),
+            outcome: e.outcome.clone(),
+            resolution: e.resolution.clone().unwrap_or_default(),
+            pnl: e.realized_pnl().unwrap_or(0.0),
+        })
+        .collect();
+    resolved.sort_by(|a, b| b.pnl.total_cmp(&a.pnl));
+
+    let wins = counters
+        .resolved
+        .iter()
+        .filter(|e| e.won() == Some(true))
+        .count();
+    let decided = counters
+        .resolved
+        .iter()
+        .filter(|e| e.won().is_some())
+        .count();
+    let (year, month, day) = crate::sanity::civil_from_days(counters.day as i64);
+
+    Report {
+        date: format!("{year:04}-{month:02}-{day:02}"),
+        markets_seen: counters.markets_seen,
+        markets_analyzed: counters.markets_analyzed,
+        bets_placed: placed.len(),
+        total_staked: placed.iter().map(|e| e.filled).sum(),
+        resolved: resolved.len(),
+        realized_pnl: resolved.iter().map(|b| b.pnl).sum(),
+        win_rate: (decided > 0).then_some(wins as f64 / decided as f64),
+        xai_calls: counters.xai_calls,
+        xai_cost: counters.xai_cost,
+        empty_responses: counters.empty_responses,
+        top_winners: resolved
+            .iter()
+            .filter(|b| b.pnl > 0.0)
+            .take(REPORT_TOP_N)
+            .cloned()
+            .collect(),
+        top_losers: resolved
+            .iter()
+            .rev()
+            .filter(|b| b.pnl < 0.0)
+            .take(REPORT_TOP_N)
+            .cloned()
+            .collect(),
+        skipped: counters.skips.clone(),
+    }
+}
+
+fn until_next_utc_day() -> Duration {
+    let now = now_epoch_secs();
+    Duration::from_secs(SECS_PER_DAY - now % SECS_PER_DAY)
+}
+
+/// Log the report, write it to `reports/<date>.json` and post it to the webhook.
+async fn publish_report(ctx: &BotContext, report: &Report) {
+    let _ = ctx.log_tx.send(BotLogEntry::Info(report.to_string()));
+
+    let path = data_dir()
+        .join("reports")
+        .join(format!("{}.json", report.date));
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let json = serde_json::to_string_pretty(report).unwrap_or_default();
+    if let Err(e) = std::fs::write(&path, json) {
+        let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+            "Failed to write report {}: {e}",
+            path.display()
+        )));
+    }
+
+    if let Some(url) = &ctx.config.report_webhook_url {
+        let result = reqwest::Client::new()
+            .post(url)
+            .header(reqwest::header::USER_AGENT, &ctx.config.user_agent)
+            .json(report)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        if let Err(e) = result {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "Failed to post daily report to webhook: {e}"
+            )));
+        }
+    }
+}
+
+/// Config and live state as JSON, for attaching to a bug report. Both API
+/// keys are redacted wherever they appear.
+fn diagnostics_snapshot(ctx: &BotContext) -> serde_json::Value {
+    let counters = ctx.counters.lock().unwrap();
+    let snapshot = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "generated_at": now_epoch_secs(),
+        "state": ctx.state.lock().unwrap().to_string(),
+        "connected": ctx.connected.load(Ordering::SeqCst),
+        "trading_paused": ctx.paused.load(Ordering::SeqCst),
+        "analysis_paused": ctx.analysis_paused.load(Ordering::SeqCst),
+        "short_balance": *ctx.short_balance.lock().unwrap(),
+        "config": config_json(&ctx.config),
+        "counters": {
+            "day": counters.day,
+            "markets_seen": counters.markets_seen,
+            "markets_analyzed": counters.markets_analyzed,
+            "xai_calls": counters.xai_calls,
+            "xai_cost": counters.xai_cost,
+            "empty_responses": counters.empty_responses,
+            "resolved": counters.resolved.len(),
+            "skips": counters.skips.len(),
+        },
+        "cache": ctx.analyzed.lock().unwrap().stats(),
+        "in_flight": ctx.in_flight.lock().unwrap().len(),
+        "xai": ctx.xai_health.lock().unwrap().stats(),
+        "recent_failures": ctx
+            .failed
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(market, _)| market.clone())
+            .collect::<Vec<_>>(),
+    });
+    let text = crate::trace::redact(
+        &snapshot.to_string(),
+        &[ctx.manifold.api_key(), ctx.xai.api_key()],
+    );
+    serde_json::from_str(&text).unwrap_or(snapshot)
+}
+
+/// `config` as JSON. The report webhook URL often embeds a token, so only
+/// whether it is set is shown; the sanity check is a function, likewise.
+fn config_json(config: &BotConfig) -> serde_json::Value {
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    if config.report_webhook_url.is_some() {
+        value["report_webhook_url"] = "[REDACTED]".into();
+    }
+    value["sanity_check"] = config.sanity_check.is_some().into();
+    value
+}
+
+/// Settings read once when the bot starts (clients, timers, the worker pool
+/// and the executor task), which a live config update can't change.
+const STARTUP_ONLY_SETTINGS: &[&str] = &[
+    "user_agent",
+    "ws_topics",
+    "ws",
+    "restore_positions",
+    "research",
+    "resolution_poll_secs",
+    "xai_error_window",
+    "paper_ledger",
+    "profit_sweep",
+    "reevaluation",
+    "reprice",
+    "urgent_close",
+    "analysis_workers",
+    "min_bet_interval_secs",
+    "prioritize_bets_by_edge",
+    "bet_batch",
+    "execution",
+    "record_executions",
+    "simulation",
+    "xai_replay",
+];
+
+/// Top-level settings that differ between `old` and `new`, as `name: old -> new`.
+fn config_changes(old: &BotConfig, new: &BotConfig) -> Vec<String> {
+    let (old, new) = (config_json(old), config_json(new));
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Vec::new();
+    };
+    new.iter()
+        .filter(|(name, value)| old.get(*name) != Some(value))
+        .map(|(name, value)| {
+            let before = old.get(name).unwrap_or(&serde_json::Value::Null);
+            format!("{name}: {before} -> {value}")
+        })
+        .collect()
+}
+
+/// Validate `new` and give it `current`'s startup-only settings. Returns the
+/// config to run with and the startup-only settings whose change was ignored.
+fn prepare_config_update(
+    current: &BotConfig,
+    new: BotConfig,
+) -> Result<(BotConfig, Vec<&'static str>), String> {
+    new.validate()?;
+    let (before, after) = (config_json(current), config_json(&new));
+    let ignored = STARTUP_ONLY_SETTINGS
+        .iter()
+        .copied()
+        .filter(|name| before.get(name) != after.get(name))
+        .collect();
+    Ok((
+        BotConfig {
+            user_agent: current.user_agent.clone(),
+            ws_topics: current.ws_topics.clone(),
+            ws: current.ws,
+            restore_positions: current.restore_positions,
+            research: current.research.clone(),
+            resolution_poll_secs: current.resolution_poll_secs,
+            xai_error_window: current.xai_error_window,
+            paper_ledger: current.paper_ledger,
+            profit_sweep: current.profit_sweep.clone(),
+            reevaluation: current.reevaluation.clone(),
+            reprice: current.reprice.clone(),
+            urgent_close: current.urgent_close.clone(),
+            analysis_workers: current.analysis_workers,
+            min_bet_interval_secs: current.min_bet_interval_secs,
+            prioritize_bets_by_edge: current.prioritize_bets_by_edge,
+            bet_batch: current.bet_batch,
+            execution: current.execution.clone(),
+            record_executions: current.record_executions,
+            simulation: current.simulation.clone(),
+            xai_replay: current.xai_replay,
+            ..new
+        },
+        ignored,
+    ))
+}
+
+/// Save `trace` if decision traces are on, with both API keys redacted.
+fn save_trace(ctx: &BotContext, trace: &DecisionTrace) {
+    if ctx.config.decision_traces {
+        trace.save(&[ctx.manifold.api_key(), ctx.xai.api_key()]);
+    }
+}
+
+/// End a decision: save its trace and log its DECISION line. Every market
+/// that gets a `DecisionTrace` finishes here exactly once, placed or not.
+fn finish_decision(ctx: &BotContext, trace: &DecisionTrace) {
+    save_trace(ctx, trace);
+    log_decision_summary(ctx, trace);
+}
+
+/// `finish_decision` for a market dropped before any order, recording why.
+fn finish_skip(ctx: &BotContext, mut trace: DecisionTrace, reason: impl Into<String>) {
+    trace.decision = Some(reason.into());
+    finish_decision(ctx, &trace);
+}
+
+/// Log the structured `DECISION {json}` line for a finished decision, and
+/// mirror it to the paper ledger if that's on.
+fn log_decision_summary(ctx: &BotContext, trace: &DecisionTrace) {
+    let summary = trace.summary();
+    let json = serde_json::to_string(&summary).unwrap_or_default();
+    let _ = ctx
+        .log_tx
+        .send(BotLogEntry::Info(format!("DECISION {json}")));
+    if let Some(paper) = &ctx.paper {
+        paper
+            .lock()
+            .unwrap()
+            .record(PaperEntry::from_summary(&summary, now_epoch_secs()));
+    }
+}
+
+/// Write an analysis (`prediction` is `None` for a skip) through to the SQLite store.
+#[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+fn store_analysis(
+    ctx: &BotContext,
+    contract_id: &str,
+    question: &str,
+    prediction: Option<f64>,
+    reasoning: &str,
+) {
+    #[cfg(feature = "sqlite")]
+    if let Some(store) = &ctx.store {
+        let result = store.lock().unwrap().record_analysis(
+            contract_id,
+            question,
+            prediction,
+            reasoning,
+            now_epoch_secs(),
+        );
+        if let Err(e) = result {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "SQLite: failed to record analysis of \"{question}\": {e}"
+            )));
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+fn store_bet(ctx: &BotContext, entry: &LedgerEntry, response: &BetResponse) {
+    #[cfg(feature = "sqlite")]
+    if let Some(store) = &ctx.store {
+        let response = serde_json::to_string(response).unwrap_or_default();
+        if let Err(e) = store.lock().unwrap().record_bet(entry, &response) {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "SQLite: failed to record bet on \"{}\": {e}",
+                entry.question
+            )));
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+fn store_resolution(ctx: &BotContext, contract_id: &str, resolution: &str) {
+    #[cfg(feature = "sqlite")]
+    if let Some(store) = &ctx.store {
+        let result =
+            store
+                .lock()
+                .unwrap()
+                .record_resolution(contract_id, resolution, now_epoch_secs());
+        if let Err(e) = result {
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "SQLite: failed to record resolution of {contract_id}: {e}"
+            )));
+        }
+    }
+}
+
+/// A decided bet waiting for the executor.
+struct BetOrder {
+    /// One limit order, or the rungs of a ladder.
+    bets: Vec<BetRequest>,
+    question: String,
+    prediction: f64,
+    market_prob: f64,
+    strategy: Strategy,
+    /// Post-cost edge, for `prioritize_bets_by_edge`.
+    net_edge: f64,
+    trace: DecisionTrace,
+    /// What to re-run if placement fails.
+    origin: AnalysisJob,
+}
+
+/// Orders waiting to be placed: FIFO, or largest edge first (FIFO among ties).
+struct BetQueue {
+    by_edge: bool,
+    /// Orders with their arrival sequence number.
+    orders: Vec<(u64, BetOrder)>,
+    next_seq: u64,
+}
+
+impl BetQueue {
+    fn new(by_edge: bool) -> Self {
+        Self {
+            by_edge,
+            orders: Vec::new(),
+            next_seq: 0,
+        }
+    }
+
+    fn push(&mut self, order: BetOrder) {
+        self.orders.push((self.next_seq, order));
+        self.next_seq += 1;
+    }
+
+    fn pop(&mut self) -> Option<BetOrder> {
+        let best = if self.by_edge {
+            self.orders
+                .iter()
+                .enumerate()
+                .max_by(|(_, (seq_a, a)), (_, (seq_b, b))| {
+                    a.net_edge.total_cmp(&b.net_edge).then(seq_b.cmp(seq_a))
+                })
+                .map(|(i, _)| i)?
+        } else if self.orders.is_empty() {
+            return None;
+        } else {
+            0
+        };
+        Some(self.orders.remove(best).1)
+    }
+
+    fn len(&self) -> usize {
+        self.orders.len()
+    }
+}
+
+impl BetOrder {
+    /// Total across all rungs.
+    fn amount(&self) -> f64 {
+        self.bets.iter().map(|b| b.amount).sum()
+    }
+}
+
+/// Hand a decided bet to the executor.
+fn queue_bet(ctx: &BotContext, order: BetOrder) {
+    let sent = match ctx.orders.upgrade() {
+        Some(tx) => tx.send(order).map_err(|e| e.0),
+        None => Err(order),
+    };
+    if let Err(mut order) = sent {
+        let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+            "Bot stopped — not placing M${:.0} on \"{}\"",
+            order.amount(),
+            order.question,
+        )));
+        order.trace.bet_error = Some("bot stopped".to_string());
+        finish_decision(ctx, &order.trace);
+    }
+}
+
+/// Single consumer of decided bets, so placements never race each other for
+/// balance or rate limit. Orders arriving during a placement are queued and
+/// ordered per `prioritize_bets_by_edge`, or batched per `bet_batch`.
+async fn run_executor(
+    mut ctx: BotContext,
+    mut orders: mpsc::UnboundedReceiver<BetOrder>,
+    mut configs: tokio::sync::watch::Receiver<BotConfig>,
+) {
+    if let Some(batch) = ctx.config.bet_batch {
+        run_batch_executor(ctx, orders, configs, batch).await;
+        return;
+    }
+    let mut queue = BetQueue::new(ctx.config.prioritize_bets_by_edge);
+    while let Some(order) = orders.recv().await {
+        queue.push(order);
+        loop {
+            while let Ok(order) = orders.try_recv() {
+                queue.push(order);
+            }
+            let Some(order) = queue.pop() else {
+                break;
+            };
+            let waiting = queue.len();
+            if waiting > 0 {
+                let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                    "Bet queue: {waiting} waiting behind \"{}\"",
+                    order.question
+                )));
+            }
+            refresh_config(&mut ctx, &mut configs);
+            execute_order(&ctx, order).await;
+        }
+    }
+}
+
+/// Take up the latest config from `run_bot`, so limits checked at placement
+/// follow live updates.
+fn refresh_config(ctx: &mut BotContext, configs: &mut tokio::sync::watch::Receiver<BotConfig>) {
+    if configs.has_changed().unwrap_or(false) {
+        ctx.config = configs.borrow_and_update().clone();
+    }
+}
+
+/// How long to hold a bet so it's at least `min_interval` after the previous one.
+fn pacing_delay(last: Option<Instant>, now: Instant, min_interval: Duration) -> Duration {
+    last.map_or(Duration::ZERO, |last| {
+        min_interval.saturating_sub(now.saturating_duration_since(last))
+    })
+}
+
+/// Place one order if allowed, recording the outcome in its trace.
+async fn execute_order(ctx: &BotContext, mut order: BetOrder) {
+    let mut trace = std::mem::take(&mut order.trace);
+    let contract_id = order.bets.first().map_or("", |b| b.contract_id.as_str());
+    let allowed = match check_can_place(ctx, order.amount(), contract_id, &order.question).await {
+        Ok(()) => fit_short_balance(ctx, &mut order).await,
+        Err(reason) => Err(reason),
+    };
+    match allowed {
+        Ok(()) => {
+            let results = place_order(ctx, &order).await;
+            if results
+                .iter()
+                .any(|r| matches!(r, Err(BotError::InsufficientBalance)))
+            {
+                let balance = ctx.manifold.get_me().await.map_or(0.0, |u| u.balance);
+                ctx.balance.lock().unwrap().set(balance, Instant::now());
+                set_short_balance(ctx, Some(balance));
+            }
+            let first_error = results
+                .iter()
+                .find_map(|r| r.as_ref().err().map(|e| e.to_string()));
+            if results.iter().any(|r| r.is_ok()) {
+                if take_failure(ctx, order.origin.contract_id()).is_some() {
+                    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                        "Recovered \"{}\" — removed from failed list",
+                        order.question
+                    )));
+                }
+            } else if let Some(error) = first_error {
+                record_failure(ctx, order.origin.clone(), &order.question, &error);
+            }
+            for (bet, result) in order.bets.iter().zip(results) {
+                trace.record_bet(bet, &result.map_err(|e| e.to_string()));
+            }
+        }
+        Err(reason) => trace.bet_error = Some(reason),
+    }
+    finish_decision(ctx, &trace);
+}
+
+/// Enter, update or leave the insufficient-balance state, logging transitions
+/// and telling the dashboard.
+fn set_short_balance(ctx: &BotContext, balance: Option<f64>) {
+    let previous = std::mem::replace(&mut *ctx.short_balance.lock().unwrap(), balance);
+    match (previous, balance) {
+        (None, Some(balance)) => {
+            let action = if ctx.config.shrink_bets_to_balance {
+                "shrinking bets to fit"
+            } else {
+                "holding bets"
+            };
+            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                "INSUFFICIENT BALANCE (M${balance:.0}): {action} until it recovers"
+            )));
+        }
+        (Some(_), None) => {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(
+                "Balance recovered — normal bet sizing resumed".to_string(),
+            ));
+        }
+        _ => {}
+    }
+    let _ = ctx.status_tx.send(BotStatus::InsufficientBalance(balance));
+    publish_state(ctx);
+}
+
+/// While the balance is short, re-check it before each order: leave the state
+/// if the order now fits, otherwise shrink the order (if configured) or hold it.
+async fn fit_short_balance(ctx: &BotContext, order: &mut BetOrder) -> Result<(), String> {
+    if ctx.short_balance.lock().unwrap().is_none() {
+        return Ok(());
+    }
+    let balance = match ctx.manifold.get_me().await {
+        Ok(user) => user.balance,
+        Err(e) => return Err(format!("insufficient balance, recheck failed: {e}")),
+    };
+    let amount = order.amount();
+    if balance >= amount {
+        set_short_balance(ctx, None);
+        return Ok(());
+    }
+    set_short_balance(ctx, Some(balance));
+    if ctx.config.shrink_bets_to_balance
+        && scale_bets(&mut order.bets, balance, ctx.config.min_bet_amount)
+    {
+        let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+            "Balance M${balance:.0}: reducing M${amount:.0} on \"{}\" to M${:.0}",
+            order.question,
+            order.amount(),
+        )));
+        return Ok(());
+    }
+    let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+        "Insufficient balance (M${balance:.0}): not placing M${amount:.0} on \"{}\"",
+        order.question,
+    )));
+    Err(format!("insufficient balance (M${balance:.0})"))
+}
+
+/// Scale `bets` proportionally so they total at most `balance`, rounded down
+/// to whole mana. False (and untouched) if any bet would drop below `min_bet`.
+fn scale_bets(bets: &mut [BetRequest], balance: f64, min_bet: f64) -> bool {
+    let total: f64 = bets.iter().map(|b| b.amount).sum();
+    if total <= balance {
+        return true;
+    }
+    let factor = balance / total;
+    let scaled: Vec<f64> = bets.iter().map(|b| (b.amount * factor).floor()).collect();
+    if scaled.iter().any(|&a| a < min_bet) {
+        return false;
+    }
+    for (bet, amount) in bets.iter_mut().zip(scaled) {
+        bet.amount = amount;
+    }
+    true
+}
+
+/// `run_executor` in `bet_batch` mode: gather a window of orders, then place
+/// the selected ones best edge first.
+async fn run_batch_executor(
+    mut ctx: BotContext,
+    mut orders: mpsc::UnboundedReceiver<BetOrder>,
+    mut configs: tokio::sync::watch::Receiver<BotConfig>,
+    batch: BetBatch,
+) {
+    while let Some(first) = orders.recv().await {
+        let mut pending = vec![first];
+        let window = tokio::time::sleep(Duration::from_secs(batch.window_secs));
+        tokio::pin!(window);
+        loop {
+            tokio::select! {
+                _ = &mut window => break,
+                order = orders.recv() => match order {
+                    Some(order) => pending.push(order),
+                    None => break,
+                },
+            }
+        }
+
+        let ranked = select_batch(pending, &batch);
+        let placing = ranked.iter().filter(|(_, place)| *place).count();
+        let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+            "Batch: {} bet(s) ranked by edge, placing {placing}, deferring {}",
+            ranked.len(),
+            ranked.len() - placing,
+        )));
+        for (i, (order, place)) in ranked.iter().enumerate() {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "  #{} {} {:+.1}% M${:.0} \"{}\"",
+                i + 1,
+                if *place { "PLACE" } else { "DEFER" },
+                order.net_edge * 100.0,
+                order.amount(),
+                order.question,
+            )));
+        }
+        refresh_config(&mut ctx, &mut configs);
+        for (mut order, place) in ranked {
+            if place {
+                execute_order(&ctx, order).await;
+            } else {
+                order.trace.bet_error = Some("deferred by batch selection".to_string());
+                finish_decision(&ctx, &order.trace);
+            }
+        }
+    }
+}
+
+/// Rank `orders` by post-cost edge (arrival order among ties) and mark which
+/// to place: the best `top_k` that fit in `budget`, skipping any that would
+/// overrun it.
+fn select_batch(mut orders: Vec<BetOrder>, batch: &BetBatch) -> Vec<(BetOrder, bool)> {
+    orders.sort_by(|a, b| b.net_edge.total_cmp(&a.net_edge));
+    let mut placed = 0;
+    let mut staked = 0.0;
+    orders
+        .into_iter()
+        .map(|order| {
+            let fits = batch
+                .budget
+                .is_none_or(|budget| staked + order.amount() <= budget);
+            let place = placed < batch.top_k && fits;
+            if place {
+                placed += 1;
+                staked += order.amount();
+            }
+            (order, place)
+        })
+        .collect()
+}
+
+/// Whether `amount` more may be bet on `contract_id` now: trading isn't paused
+/// and the position and exposure caps allow it. Logs and returns the reason if not.
+async fn check_can_place(
+    ctx: &BotContext,
+    amount: f64,
+    contract_id: &str,
+    question: &str,
+) -> Result<(), String> {
+    if ctx.paused.load(Ordering::SeqCst) {
+        let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+            "Trading paused — not placing M${amount:.0} on \"{question}\"",
+        )));
+        return Err("trading paused".to_string());
+    }
+
+    if let Some(blackout) = active_blackout(&ctx.config.blackouts, now_epoch_secs()) {
+        let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+            "Blackout active ({blackout}) — not placing M${amount:.0} on \"{question}\"",
+        )));
+        return Err(format!("blackout ({blackout})"));
+    }
+
+    if let Some(max) = ctx.config.max_open_positions {
+        let open = ctx.pnl.lock().unwrap().open_contract_ids();
+        if !positions_allow(&open, contract_id, max) {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "Position cap: not opening M${amount:.0} on \"{question}\" ({} of {max} markets held)",
+                open.len(),
+            )));
+            return Err(format!(
+                "position cap ({} of {max} markets held)",
+                open.len()
+            ));
+        }
+    }
+
+    if let Some(fraction) = ctx.config.max_total_exposure_fraction {
+        let balance = match current_balance(ctx).await {
+            Ok(balance) => balance,
+            Err(e) => {
+                let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                    "Exposure check failed (could not fetch balance), not betting on \"{question}\": {e}"
+                )));
+                return Err(format!("exposure check failed: {e}"));
+            }
+        };
+        let open = ctx.pnl.lock().unwrap().open_exposure();
+        if !exposure_allows(open, amount, balance, fraction) {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "Exposure cap: not placing M${amount:.0} on \"{question}\" (open M${open:.0}, balance M${balance:.0}, cap {:.0}% of bankroll)",
+                fraction * 100.0,
+            )));
+            return Err(format!(
+                "exposure cap (open M${open:.0}, balance M${balance:.0})"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// How long a fetched balance stands in for `/me` in the exposure check.
+const BALANCE_TTL: Duration = Duration::from_secs(60);
+
+/// The last fetched balance, less the bets placed since.
+#[derive(Debug, Default)]
+struct BalanceCache {
+    balance: f64,
+    fetched_at: Option<Instant>,
+}
+
+impl BalanceCache {
+    fn get(&self, now: Instant) -> Option<f64> {
+        let fetched_at = self.fetched_at?;
+        (now.saturating_duration_since(fetched_at) < BALANCE_TTL).then_some(self.balance)
+    }
+
+    fn set(&mut self, balance: f64, now: Instant) {
+        self.balance = balance;
+        self.fetched_at = Some(now);
+    }
+
+    /// Deduct mana a placed order took or locked.
+    fn spend(&mut self, amount: f64) {
+        self.balance -= amount;
+    }
+}
+
+/// The account balance, from the cache while it's fresh.
+async fn current_balance(ctx: &BotContext) -> Result<f64, ApiError> {
+    if let Some(balance) = ctx.balance.lock().unwrap().get(Instant::now()) {
+        return Ok(balance);
+    }
+    let balance = ctx.manifold.get_me().await?.balance;
+    ctx.balance.lock().unwrap().set(balance, Instant::now());
+    Ok(balance)
+}
+
+/// Place each order of `order` in turn, stopping early once the market has
+/// closed or mana has run out. Ladders get a summary line of the combined fill.
+async fn place_order(ctx: &BotContext, order: &BetOrder) -> Vec<Result<BetResponse, BotError>> {
+    let mut results = Vec::new();
+    for bet in &order.bets {
+        let result = place_and_record(
+            ctx,
+            bet,
+            &order.question,
+            order.prediction,
+            order.market_prob,
+            order.strategy,
+        )
+        .await;
+        let stop = matches!(
+            result,
+            Err(BotError::MarketClosed | BotError::InsufficientBalance)
+        );
+        results.push(result);
+        if stop {
+            break;
+        }
+    }
+
+    if order.bets.len() > 1 {
+        let placed: Vec<(&BetRequest, &BetResponse)> = order
+            .bets
+            .iter()
+            .zip(&results)
+            .filter_map(|(bet, r)| r.as_ref().ok().map(|resp| (bet, resp)))
+            .collect();
+        let staked: f64 = placed.iter().map(|(bet, _)| bet.amount).sum();
+        let filled: f64 = placed.iter().filter_map(|(_, r)| r.amount).sum();
+        let prices: Vec<f64> = order.bets.iter().filter_map(|b| b.limit_prob).collect();
+        let lo = prices.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = prices.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let line = format!(
+            "[{}] LADDER: {}/{} orders placed on \"{}\", M${staked:.0} of M${:.0} at {:.0}–{:.0}% (filled M${filled:.0})",
+            order.strategy,
+            placed.len(),
+            order.bets.len(),
+            order.question,
+            order.amount(),
+            lo * 100.0,
+            hi * 100.0,
+        );
+        let _ = ctx.log_tx.send(if placed.len() < order.bets.len() {
+            BotLogEntry::Error(line)
+        } else {
+            BotLogEntry::Trade(line)
+        });
+    }
+    results
+}
+
+/// Place one limit order, then log it and record it in the ledger.
+async fn place_and_record(
+    ctx: &BotContext,
+    bet: &BetRequest,
+    question: &str,
+    prediction: f64,
+    market_prob: f64,
+    strategy: Strategy,
+) -> Result<BetResponse, BotError> {
+    let outcome = &bet.outcome;
+    let limit_prob = bet.limit_prob.unwrap_or(market_prob);
+
+    if let Some(secs) = ctx.config.min_bet_interval_secs {
+        let last = *ctx.last_bet_at.lock().unwrap();
+        let wait = pacing_delay(last, Instant::now(), Duration::from_secs(secs));
+        if !wait.is_zero() {
+            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                "Pacing: holding bet on \"{question}\" {:.1}s",
+                wait.as_secs_f64()
+            )));
+            tokio::time::sleep(wait).await;
+        }
+    }
+    let result = ctx.executor.execute(bet).await;
+    *ctx.last_bet_at.lock().unwrap() = Some(Instant::now());
+
+    match result {
+        Ok(resp) if !ctx.executor.is_live() => {
+            let _ = ctx.log_tx.send(BotLogEntry::Trade(format!(
+                "[{strategy}] BET NOT PLACED ({}): {outcome} M${:.0} on \"{question}\" limit@{:.0}%",
+                ctx.executor.name(),
+                bet.amount,
+                limit_prob * 100.0,
+            )));
+            Ok(resp)
+        }
+        Ok(resp) => {
+            let filled = resp.amount.unwrap_or(0.0);
+            let _ = ctx.log_tx.send(BotLogEntry::Trade(format!(
+                "[{strategy}] BET PLACED: {outcome} M${:.0} on \"{question}\" limit@{:.0}% (filled M${filled:.0})",
+                bet.amount,
+                limit_prob * 100.0,
+            )));
+
+            // What the order holds: the full amount while it rests on the book
+            let mut amount = bet.amount;
+            let tif = ctx.config.order_tif;
+            if tif != OrderTif::Gtc && resp.is_filled != Some(true) {
+                let mut cancelled = false;
+                if let Some(bet_id) = &resp.bet_id {
+                    match ctx.executor.cancel(bet_id).await {
+                        Ok(()) => {
+                            cancelled = true;
+                            amount = filled;
+                            let _ = ctx.log_tx.send(BotLogEntry::Info(format!(
+                                "{tif:?}: cancelled unfilled remainder on \"{question}\" (filled M${filled:.0} of M${:.0})",
+                                bet.amount,
+                            )));
+                        }
+                        Err(e) => {
+                            let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                                "{tif:?}: failed to cancel remainder on \"{question}\": {e}"
+                            )));
+                        }
+                    }
+                }
+                if tif == OrderTif::Fok && filled > 0.0 {
+                    let _ = ctx.log_tx.send(BotLogEntry::Error(format!(
+                        "FOK partially filled M${filled:.0} of M${:.0} on \"{question}\" — Manifold cannot unwind fills",
+                        bet.amount,
+                    )));
+                }
+                // An order that couldn't be cancelled may still rest on the book
+                // until its expiry, so it's tracked at full size like a GTC one
+                if cancelled && filled <= 0.0 {
+                    return Ok(resp);
+                }
+            }
+
+            let entry = LedgerEntry {
+                contract_id: bet.contract_id.clone(),
+                question: question.to_string(),
+                outcome: outcome.clone(),
+                amount,
+                filled,
+                shares: resp.shares.unwrap_or(0.0),
+                limit_prob,
+                prediction,
+                market_prob,
+                placed_at: now_epoch_secs(),
+                resolution: None,
+                strategy,
+                sold_for: None,
+                order_id: resp.bet_id.clone(),
+            };
+            store_bet(ctx, &entry, &resp);
+            ctx.balance.lock().unwrap().spend(amount);
+            ctx.pnl.lock().unwrap().record(entry);
+            Ok(resp)
+        }
+        Err(err) => {
+            let msg = match &err {
+                BotError::MarketClosed => format!("Market closed before bet on \"{question}\""),
+                BotError::InsufficientBalance => format!(
+                    "Insufficient balance for {outcome} M${:.0} on \"{question}\"",
+                    bet.amount,
+                ),
+                e => format!("Failed to place bet on \"{question}\": {e}"),
+            };
+            let _ = ctx.log_tx.send(BotLogEntry::Error(msg));
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulate::{SimulatedExecutor, SimulatedManifold, Simulation, Simulator};
+    use futures_util::future::BoxFuture;
+
+    /// Channels kept alive for a `test_context`.
+    struct TestChannels {
+        log_rx: mpsc::UnboundedReceiver<BotLogEntry>,
+        _status_rx: mpsc::UnboundedReceiver<BotStatus>,
+        /// The strong sender behind `BotContext::orders`.
+        _order_tx: mpsc::UnboundedSender<BetOrder>,
+        order_rx: mpsc::UnboundedReceiver<BetOrder>,
+    }
+
+    impl TestChannels {
+        fn logs(&mut self) -> Vec<String> {
+            std::iter::from_fn(|| self.log_rx.try_recv().ok())
+                .map(|entry| format!("{entry:?}"))
+                .collect()
+        }
+    }
+
+    /// A context with in-memory state whose research and bets go to
+    /// `simulation` instead of xAI and Manifold.
+    fn test_context(config: BotConfig, simulation: Simulation) -> (BotContext, TestChannels) {
+        let (log_tx, log_rx) = mpsc::unbounded_channel();
+        let (status_tx, status_rx) = mpsc::unbounded_channel();
+        let (order_tx, order_rx) = mpsc::unbounded_channel();
+        let simulator = Arc::new(Simulator::new(simulation));
+        let ctx = BotContext {
+            manifold: Arc::new(SimulatedManifold::new(simulator.clone())),
+            xai: XaiClient::new(String::new(), DEFAULT_USER_AGENT),
+            log_tx,
+            status_tx,
+            pnl: Arc::new(Mutex::new(PnlTracker::default())),
+            analyzed: Arc::new(Mutex::new(AnalyzedCache::default())),
+            paper: None,
+            paused: Arc::new(AtomicBool::new(false)),
+            xai_health: Arc::new(Mutex::new(XaiHealth::in_memory(config.xai_error_window))),
+            analysis_paused: Arc::new(AtomicBool::new(false)),
+            connected: Arc::new(AtomicBool::new(true)),
+            state: Arc::new(Mutex::new(BotState::NotStarted)),
+            analyses: Arc::new(Mutex::new(AnalysisCache::default())),
+            counters: Arc::new(Mutex::new(CounterState::new(0))),
+            in_flight: Arc::new(Mutex::new(Vec::new())),
+            failed: Arc::new(Mutex::new(Vec::new())),
+            held: Arc::new(Mutex::new(Vec::new())),
+            short_balance: Arc::new(Mutex::new(None)),
+            balance: Arc::new(Mutex::new(BalanceCache::default())),
+            repriced: Arc::new(Mutex::new(HashMap::new())),
+            reevaluated: Arc::new(Mutex::new(HashMap::new())),
+            last_bet_at: Arc::new(Mutex::new(None)),
+            creators: Arc::new(Mutex::new(HashMap::from([(
+                "alice".to_string(),
+                (UserProfile::default(), now_epoch_secs()),
+            )]))),
+            executor: Arc::new(SimulatedExecutor::new(simulator.clone())),
+            simulator: Some(simulator),
+            replay: None,
+            orders: order_tx.downgrade(),
+            #[cfg(feature = "sqlite")]
+            store: None,
+            config,
+        };
+        let channels = TestChannels {
+            log_rx,
+            _status_rx: status_rx,
+            _order_tx: order_tx,
+            order_rx,
+        };
+        (ctx, channels)
+    }
+
+    /// Instant, error-free simulation.
+    fn instant_simulation() -> Simulation {
+        Simulation {
+            min_latency_ms: 0,
+            max_latency_ms: 0,
+            error_rate: 0.0,
+            seed: 1,
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// A new BINARY market from `alice`, whose profile `test_context` has
+    /// cached so no request goes out.
+    fn new_contract(question: &str, probability: f64) -> NewContractBroadcast {
+        serde_json::from_value(serde_json::json!({
+            "contract": {
+                "id": "abc",
+                "slug": "will-x-happen",
+                "question": question,
+                "outcomeType": "BINARY",
+                "mechanism": "cpmm-1",
+                "visibility": "public",
+                "createdTime": 1_700_000_000_000u64,
+                "isResolved": false,
+                "probability": probability,
+                "totalLiquidity": 100.0,
+            },
+            "creator": {"id": "u1", "username": "alice", "name": "Alice"},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decide_bet() {
+        let config = BotConfig::default();
+
+        match decide_bet(0.70, 0.50, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet {
+                outcome,
+                limit_prob,
+                ..
+            } => {
+                assert_eq!(outcome, "YES");
+                assert_eq!(limit_prob, 0.70);
+            }
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+
+        match decide_bet(0.0, 0.30, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet {
+                outcome,
+                limit_prob,
+                ..
+            } => {
+                assert_eq!(outcome, "NO");
+                assert_eq!(limit_prob, 0.01);
+            }
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+
+        assert!(matches!(
+            decide_bet(0.55, 0.50, 10.0, 1000.0, None, &config),
+            BetDecision::Skip { .. }
+        ));
+    }
+
+    #[test]
+    fn test_max_limit_distance() {
+        let config = BotConfig {
+            max_limit_distance: Some(0.15),
+            ..BotConfig::default()
+        };
+
+        // 98% prediction on a 40% market is pulled in to 55%
+        match decide_bet(0.98, 0.40, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet {
+                limit_prob,
+                distance_capped,
+                ..
+            } => {
+                assert!((limit_prob - 0.55).abs() < 1e-9);
+                assert!(distance_capped);
+            }
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+
+        // Within the cap: untouched
+        match decide_bet(0.54, 0.40, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet {
+                limit_prob,
+                distance_capped,
+                ..
+            } => {
+                assert_eq!(limit_prob, 0.54);
+                assert!(!distance_capped);
+            }
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+
+        // Distance cap first, then the 1-99% clamp still applies
+        match decide_bet(0.0, 0.12, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet {
+                outcome,
+                limit_prob,
+                distance_capped,
+                ..
+            } => {
+                assert_eq!(outcome, "NO");
+                assert_eq!(limit_prob, 0.01);
+                assert!(!distance_capped);
+            }
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+        match decide_bet(1.0, 0.88, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet { limit_prob, .. } => assert_eq!(limit_prob, 0.99),
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+        match decide_bet(0.0, 0.30, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet {
+                limit_prob,
+                distance_capped,
+                ..
+            } => {
+                assert!((limit_prob - 0.15).abs() < 1e-9);
+                assert!(distance_capped);
+            }
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(0.6349, 0.01), 0.63);
+        assert_eq!(round_to_tick(0.635, 0.01), 0.64);
+        assert_eq!(round_to_tick(0.7, 0.01), 0.70);
+        // Boundaries stay on a tick inside (0, 1)
+        assert_eq!(round_to_tick(0.01, 0.01), 0.01);
+        assert_eq!(round_to_tick(0.99, 0.01), 0.99);
+        assert_eq!(round_to_tick(0.01, 0.05), 0.05);
+        assert_eq!(round_to_tick(0.99, 0.05), 0.95);
+        assert_eq!(round_to_tick(0.62, 0.05), 0.6);
+
+        let config = BotConfig::default();
+        match decide_bet(0.6349, 0.40, 10.0, 1000.0, None, &config) {
+            BetDecision::Bet { limit_prob, .. } => assert_eq!(limit_prob, 0.63),
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+        let unrounded = BotConfig {
+            limit_tick: None,
+            ..BotConfig::default()
+        };
+        match decide_bet(0.6349, 0.40, 10.0, 1000.0, None, &unrounded) {
+            BetDecision::Bet { limit_prob, .. } => assert_eq!(limit_prob, 0.6349),
+            BetDecision::Skip { .. } => panic!("expected Bet"),
+        }
+    }
+
+    #[test]
+    fn test_prediction_bounds() {
+        let clamp = PredictionBounds::default();
+        assert_eq!(apply_prediction_bounds(0.0, &clamp), Some(0.05));
+        assert_eq!(apply_prediction_bounds(1.0, &clamp), Some(0.95));
+        assert_eq!(apply_prediction_bounds(0.5, &clamp), Some(0.5));
+        // The band is inclusive
+        assert_eq!(apply_prediction_bounds(0.05, &clamp), Some(0.05));
+        assert_eq!(apply_prediction_bounds(0.95, &clamp), Some(0.95));
+
+        let skip = PredictionBounds {
+            skip: true,
+            ..clamp
+        };
+        assert_eq!(apply_prediction_bounds(0.04, &skip), None);
+        assert_eq!(apply_prediction_bounds(0.96, &skip), None);
+        assert_eq!(apply_prediction_bounds(0.05, &skip), Some(0.05));
+        assert_eq!(apply_prediction_bounds(0.95, &skip), Some(0.95));
+    }
+
+    #[test]
+    fn test_skip_escalation() {
+        let mut config = BotConfig::default();
+        assert!(skip_escalation(5000.0, &config).is_none());
+
+        config.skip_escalation = Some(SkipEscalation::default());
+        assert!(skip_escalation(999.0, &config).is_none());
+        assert_eq!(
+            skip_escalation(1000.0, &config),
+            Some(&SkipEscalation::default())
+        );
+    }
+
+    #[test]
+    fn test_warm_up_throttle() {
+        let warm_up = WarmUp {
+            secs: 100,
+            starts_per_min: 6.0,
+        };
+        let t0 = Instant::now();
+        let mut throttle = WarmUpThrottle::default();
+        // Not connected yet: no throttle
+        assert_eq!(throttle.delay(t0, &warm_up), Duration::ZERO);
+
+        throttle.connected(t0);
+        // A burst at connect is spaced 10s apart
+        assert_eq!(throttle.delay(t0, &warm_up), Duration::ZERO);
+        assert_eq!(throttle.delay(t0, &warm_up), Duration::from_secs(10));
+        assert_eq!(throttle.delay(t0, &warm_up), Duration::from_secs(20));
+
+        // Halfway through, the gap has halved
+        let mut throttle = WarmUpThrottle::default();
+        throttle.connected(t0);
+        let mid = t0 + Duration::from_secs(50);
+        assert_eq!(throttle.delay(mid, &warm_up), Duration::ZERO);
+        assert_eq!(throttle.delay(mid, &warm_up), Duration::from_secs(5));
+
+        // After the warm-up: unthrottled
+        let end = t0 + Duration::from_secs(100);
+        assert_eq!(throttle.delay(end, &warm_up), Duration::ZERO);
+        assert_eq!(throttle.delay(end, &warm_up), Duration::ZERO);
+
+        // Reconnecting restarts the warm-up
+        throttle.connected(end);
+        throttle.delay(end, &warm_up);
+        assert_eq!(throttle.delay(end, &warm_up), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_effective_edge() {
+        // 50% pool with M$100 liquidity: 100 YES / 100 NO shares, k = 10,000.
+        // M$10 of YES buys 110 - 10,000 / 110 = 19.09 shares.
+        assert!((cpmm_buy_shares(0.5, 10.0, 100.0) - 19.0909).abs() < 1e-3);
+        // Unknown liquidity: no price impact
+        assert_eq!(cpmm_buy_shares(0.5, 10.0, 0.0), 20.0);
+
+        // avg price 0.5238 + fee 0.07 * 0.5238 * 0.4762 = 0.5412
+        let net = effective_edge(0.70, 0.50, 10.0, 100.0);
+        assert!((net - 0.1587).abs() < 1e-3);
+        // NO side is symmetric
+        assert!((effective_edge(0.30, 0.50, 10.0, 100.0) - net).abs() < 1e-9);
+
+        // Thinner pools and bigger bets cost more
+        assert!(effective_edge(0.70, 0.50, 10.0, 1000.0) > net);
+        assert!(effective_edge(0.70, 0.50, 25.0, 100.0) < net);
+
+        // A raw 12% edge on a M$100 pool nets under the 10% minimum
+        let config = BotConfig::default();
+        assert!(matches!(
+            decide_bet(0.62, 0.50, 10.0, 100.0, None, &config),
+            BetDecision::Skip { .. }
+        ));
+        assert!(matches!(
+            decide_bet(0.62, 0.50, 10.0, 10_000.0, None, &config),
+            BetDecision::Bet { .. }
+        ));
+    }
+
+    #[test]
+    fn test_bot_error_from_api_error() {
+        let classify = |body: &str| BotError::from(&ManifoldApiError::from_body(403, body));
+        assert_eq!(
+            classify(r#"{"message":"Insufficient balance."}"#),
+            BotError::InsufficientBalance
+        );
+        assert_eq!(
+            classify(r#"{"message":"Trading is closed."}"#),
+            BotError::MarketClosed
+        );
+        assert_eq!(
+            classify(r#"{"message":"Limit order not allowed at this price"}"#),
+            BotError::LimitOrderRejected("Limit order not allowed at this price".to_string())
+        );
+        assert_eq!(
+            classify("upstream timeout"),
+            BotError::Api {
+                status: 403,
+                message: "upstream timeout".to_string()
+            }
+        );
+
+        let boxed: Box<dyn std::error::Error + Send + Sync> = "connection reset".into();
+        assert_eq!(
+            BotError::from(boxed),
+            BotError::Request("connection reset".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extreme_price_guard() {
+        let config = BotConfig {
+            extreme_price_guard: Some(ExtremePriceGuard::default()),
+            ..BotConfig::default()
+        };
+        let guarded =
+            |decision: BetDecision| matches!(decision, BetDecision::Skip { guarded: true, .. });
+
+        // Fading a 2% market with a 20% prediction is blocked...
+        assert!(guarded(decide_bet(0.20, 0.02, 10.0, 1000.0, None, &config)));
+        // ...unless the edge clears the override
+        assert!(matches!(
+            decide_bet(0.60, 0.02, 10.0, 1000.0, None, &config),
+            BetDecision::Bet { outcome: "YES", .. }
+        ));
+        // Same on the high side
+        assert!(guarded(decide_bet(0.80, 0.97, 10.0, 1000.0, None, &config)));
+        assert!(matches!(
+            decide_bet(0.30, 0.97, 10.0, 1000.0, None, &config),
+            BetDecision::Bet { outcome: "NO", .. }
+        ));
+
+        assert!(guarded(decide_bet(0.80, 0.96, 10.0, 1000.0, None, &config)));
+
+        // Inside the band: untouched
+        assert!(matches!(
+            decide_bet(0.30, 0.06, 10.0, 1000.0, None, &config),
+            BetDecision::Bet { outcome: "YES", .. }
+        ));
+        assert!(matches!(
+            decide_bet(0.01, 0.20, 10.0, 1000.0, None, &config),
+            BetDecision::Bet { outcome: "NO", .. }
+        ));
+        // Disabled: the fade goes through
+        assert!(matches!(
+            decide_bet(0.20, 0.02, 10.0, 1000.0, None, &BotConfig::default()),
+            BetDecision::Bet { outcome: "YES", .. }
+        ));
+    }
+
+    #[test]
+    fn test_xai_pause_trips() {
+        let pause = XaiPause {
+            max_error_rate: 0.8,
+            min_calls: 10,
+        };
+        let stats = |calls: usize, errors: usize| XaiHealthStats {
+            calls,
+            errors,
+            auth: errors,
+            ..XaiHealthStats::default()
+        };
+        // A dead key right after start: every call fails, but too few to judge
+        assert!(!pause.trips(&stats(3, 3)));
+        assert!(!pause.trips(&stats(20, 15)));
+        assert!(pause.trips(&stats(10, 8)));
+        assert!(pause.trips(&stats(20, 20)));
+    }
+
+    #[test]
+    fn test_position_flipped() {
+        // Holding YES at a 60% market
+        assert!(!position_flipped("YES", 0.70, 0.60, 0.05));
+        assert!(!position_flipped("YES", 0.57, 0.60, 0.05));
+        assert!(position_flipped("YES", 0.54, 0.60, 0.05));
+        assert!(position_flipped("YES", 0.30, 0.60, 0.05));
+        // Holding NO at a 40% market
+        assert!(!position_flipped("NO", 0.30, 0.40, 0.05));
+        assert!(!position_flipped("NO", 0.42, 0.40, 0.05));
+        assert!(position_flipped("NO", 0.46, 0.40, 0.05));
+    }
+
+    #[test]
+    fn test_derive_state() {
+        let healthy = XaiHealthStats {
+            calls: 10,
+            errors: 1,
+            ..XaiHealthStats::default()
+        };
+        let failing = XaiHealthStats {
+            calls: 10,
+            errors: 7,
+            ..XaiHealthStats::default()
+        };
+        let blackout = Blackout::Daily {
+            start: 0,
+            end: 3600,
+        };
+
+        assert_eq!(
+            derive_state(true, false, false, None, None, &healthy),
+            BotState::Running
+        );
+        assert_eq!(
+            derive_state(false, false, false, None, None, &healthy),
+            BotState::Connecting
+        );
+        assert_eq!(
+            derive_state(true, false, false, None, None, &failing),
+            BotState::Degraded("70% of recent xAI calls failing".to_string())
+        );
+        assert_eq!(
+            derive_state(true, false, false, None, Some(&blackout), &failing),
+            BotState::Paused("blackout daily 00:00-01:00 UTC".to_string())
+        );
+        assert_eq!(
+            derive_state(true, false, false, Some(3.4), Some(&blackout), &healthy),
+            BotState::Paused("insufficient balance (M$3)".to_string())
+        );
+        // Breakers need a human, so they outrank everything
+        assert!(matches!(
+            derive_state(false, false, true, Some(3.4), None, &failing),
+            BotState::Halted(_)
+        ));
+        assert_eq!(
+            derive_state(true, true, true, None, None, &healthy).to_string(),
+            "Halted: loss circuit breaker tripped"
+        );
+    }
+
+    #[test]
+    fn test_config_json() {
+        let config = BotConfig {
+            report_webhook_url: Some("https://hooks.example/T0KEN".to_string()),
+            sanity_check: Some(crate::sanity::base_rate_check),
+            sizing: SizingStrategy::BalanceFraction(0.02),
+            ..BotConfig::default()
+        };
+        let json = config_json(&config);
+        assert!(!json.to_string().contains("T0KEN"));
+        assert_eq!(json["report_webhook_url"], "[REDACTED]");
+        assert_eq!(json["sanity_check"], true);
+        assert_eq!(json["min_edge"], 0.10);
+        assert_eq!(json["sizing"]["BalanceFraction"], 0.02);
+        assert_eq!(json["xai_pause"]["min_calls"], 10);
+
+        // Unset stays null rather than looking redacted
+        let json = config_json(&BotConfig::default());
+        assert!(json["report_webhook_url"].is_null());
+        assert_eq!(json["sanity_check"], false);
+    }
+
+    #[test]
+    fn test_calibration_trips() {
+        let breaker = CalibrationBreaker {
+            min_samples: 10,
+            z_threshold: 2.0,
+            window: 50,
+        };
+        // Bets predicted at 70% each
+        let record = |wins: usize, samples: usize| Calibration {
+            samples,
+            wins,
+            expected_wins: 0.7 * samples as f64,
+            variance: 0.21 * samples as f64,
+            brier: 0.0,
+        };
+        // Three straight losses: a streak, but too few samples to judge
+        assert!(!calibration_trips(&record(0, 3), &breaker));
+        // Within variance of the predictions
+        assert!(!calibration_trips(&record(12, 20), &breaker));
+        assert!(!calibration_trips(&record(14, 20), &breaker));
+        // 6 of 20 is ~3.9 sd short
+        assert!(calibration_trips(&record(6, 20), &breaker));
+    }
+
+    #[test]
+    fn test_creator_rejection() {
+        let filter = CreatorFilter {
+            min_account_age_days: 30.0,
+            min_balance: 100.0,
+        };
+        let day_ms = SECS_PER_DAY * 1000;
+        let now = 1000 * day_ms;
+        let profile = |age_days: u64, balance: f64| UserProfile {
+            username: "alice".to_string(),
+            created_time: now - age_days * day_ms,
+            balance,
+            ..UserProfile::default()
+        };
+        assert_eq!(creator_rejection(&profile(365, 500.0), &filter, now), None);
+        assert_eq!(creator_rejection(&profile(30, 100.0), &filter, now), None);
+        assert_eq!(
+            creator_rejection(&profile(2, 500.0), &filter, now).as_deref(),
+            Some("account 2d old < 30d")
+        );
+        assert_eq!(
+            creator_rejection(&profile(365, 20.0), &filter, now).as_deref(),
+            Some("balance M$20 < M$100")
+        );
+        let banned = UserProfile {
+            is_banned_from_posting: true,
+            ..profile(365, 500.0)
+        };
+        assert_eq!(
+            creator_rejection(&banned, &filter, now).as_deref(),
+            Some("banned from posting")
+        );
+    }
+
+    #[test]
+    fn test_creator_hint() {
+        let day_ms = SECS_PER_DAY * 1000;
+        let now = 1000 * day_ms;
+        let mut profile = UserProfile {
+            created_time: now - 800 * day_ms,
+            balance: 1523.4,
+            ..UserProfile::default()
+        };
+        assert_eq!(creator_hint(&profile, now), "2y old, M$1523 balance");
+        profile.created_time = now - 3 * day_ms;
+        profile.is_bot = true;
+        profile.creator_traders = Some(crate::api::PeriodCounts { all_time: 12 });
+        assert_eq!(
+            creator_hint(&profile, now),
+            "3d old, M$1523 balance, 12 traders, bot"
+        );
+        profile.created_time = now - 95 * day_ms;
+        assert!(creator_hint(&profile, now).starts_with("3mo old"));
+    }
+
+    #[test]
+    fn test_is_trend() {
+        // Steady climb, then another step up: don't fight it
+        let climbing = [0.30, 0.34, 0.37, 0.41, 0.45];
+        assert!(is_trend(&climbing, 0.45, 0.55));
+        // Flat, then a jump: a spike to fade
+        let flat = [0.50, 0.51, 0.50, 0.49, 0.50];
+        assert!(!is_trend(&flat, 0.50, 0.65));
+        // Against the prior drift: a spike
+        assert!(!is_trend(&climbing, 0.45, 0.30));
+        // Net drift the right way but mostly choppy steps
+        let choppy = [0.40, 0.55, 0.45, 0.60, 0.50, 0.48, 0.58];
+        assert!(!is_trend(&choppy, 0.58, 0.70));
+        // Drift too small next to the move
+        let creeping = [0.48, 0.49, 0.50, 0.51];
+        assert!(!is_trend(&creeping, 0.51, 0.80));
+        // Falling trend
+        let falling = [0.80, 0.74, 0.70, 0.66, 0.61];
+        assert!(is_trend(&falling, 0.61, 0.52));
+        // Too little history
+        assert!(!is_trend(&[0.3, 0.4], 0.4, 0.5));
+        assert!(!is_trend(&[], 0.4, 0.5));
+    }
+
+    #[test]
+    fn test_move_retained() {
+        // Spike 40% -> 60% that held
+        assert_eq!(move_retained(0.40, 0.60, 0.60), 1.0);
+        // Simulated revert: back to 42% after the delay
+        let retained = move_retained(0.40, 0.60, 0.42);
+        assert!((retained - 0.1).abs() < 1e-9);
+        assert!(retained < MIN_MOVE_RETAINED);
+        // Overshoot back past the starting price
+        assert!(move_retained(0.40, 0.60, 0.35) < 0.0);
+        // Downward moves work the same way
+        assert!((move_retained(0.70, 0.50, 0.55) - 0.75).abs() < 1e-9);
+        // No move: nothing to revert
+        assert_eq!(move_retained(0.50, 0.50, 0.30), 1.0);
+    }
+
+    #[test]
+    fn test_bet_queue_ordering() {
+        let order = |question: &str, net_edge: f64| BetOrder {
+            bets: vec![build_bet("c", 10.0, "YES", 0.6, &BotConfig::default())],
+            question: question.to_string(),
+            prediction: 0.6,
+            market_prob: 0.4,
+            strategy: Strategy::NewMarket,
+            net_edge,
+            trace: DecisionTrace::default(),
+            origin: AnalysisJob::BetTriggered(BetData {
+                contract_id: "c".to_string(),
+                prob_before: 0.5,
+                prob_after: 0.4,
+                created_time: 0,
+            }),
+        };
+        let burst = [("a", 0.12), ("b", 0.30), ("c", 0.12), ("d", 0.20)];
+        let drain = |mut queue: BetQueue| {
+            let mut out = Vec::new();
+            while let Some(o) = queue.pop() {
+                out.push(o.question);
+            }
+            out
+        };
+
+        let mut fifo = BetQueue::new(false);
+        let mut by_edge = BetQueue::new(true);
+        for (q, edge) in burst {
+            fifo.push(order(q, edge));
+            by_edge.push(order(q, edge));
+        }
+        assert_eq!(by_edge.len(), 4);
+        assert_eq!(drain(fifo), ["a", "b", "c", "d"]);
+        // Largest edge first; equal edges keep arrival order
+        assert_eq!(drain(by_edge), ["b", "d", "a", "c"]);
+
+        // Late arrivals slot in by edge among what's still waiting
+        let mut queue = BetQueue::new(true);
+        queue.push(order("a", 0.15));
+        queue.push(order("b", 0.11));
+        assert_eq!(queue.pop().unwrap().question, "a");
+        queue.push(order("c", 0.25));
+        assert_eq!(drain(queue), ["c", "b"]);
+    }
+
+    #[test]
+    fn test_scale_bets() {
+        let bets = |amounts: &[f64]| -> Vec<BetRequest> {
+            amounts
+                .iter()
+                .map(|&a| build_bet("c", a, "YES", 0.6, &BotConfig::default()))
+                .collect()
+        };
+        let amounts = |bets: &[BetRequest]| bets.iter().map(|b| b.amount).collect::<Vec<_>>();
+
+        // Already fits
+        let mut single = bets(&[10.0]);
+        assert!(scale_bets(&mut single, 25.0, 1.0));
+        assert_eq!(amounts(&single), [10.0]);
+
+        // Shrunk to the balance, whole mana
+        assert!(scale_bets(&mut single, 7.5, 1.0));
+        assert_eq!(amounts(&single), [7.0]);
+
+        // Ladder rungs scale together
+        let mut ladder = bets(&[10.0, 10.0, 10.0]);
+        assert!(scale_bets(&mut ladder, 15.0, 1.0));
+        assert_eq!(amounts(&ladder), [5.0, 5.0, 5.0]);
+
+        // Too little left: unchanged
+        let mut ladder = bets(&[10.0, 10.0]);
+        assert!(!scale_bets(&mut ladder, 1.5, 1.0));
+        assert_eq!(amounts(&ladder), [10.0, 10.0]);
+    }
+
+    #[test]
+    fn test_push_failure() {
+        let market = |id: &str, reason: &str| FailedMarket {
+            contract_id: id.to_string(),
+            question: id.to_string(),
+            reason: reason.to_string(),
+        };
+        let mut failed = Vec::new();
+        push_failure(&mut failed, market("a", "timeout"), ());
+        push_failure(&mut failed, market("b", "timeout"), ());
+        // Failing again replaces the entry and moves it to the end
+        push_failure(&mut failed, market("a", "503"), ());
+        let ids: Vec<_> = failed.iter().map(|(m, _)| m.contract_id.as_str()).collect();
+        assert_eq!(ids, ["b", "a"]);
+        assert_eq!(failed[1].0.reason, "503");
+
+        // Bounded: the oldest are dropped
+        for i in 0..MAX_FAILED {
+            push_failure(&mut failed, market(&format!("m{i}"), "x"), ());
+        }
+        assert_eq!(failed.len(), MAX_FAILED);
+        assert_eq!(failed[0].0.contract_id, "m0");
+    }
+
+    #[test]
+    fn test_pacing_burst() {
+        let min = Duration::from_secs(5);
+        let start = Instant::now();
+        assert_eq!(pacing_delay(None, start, min), Duration::ZERO);
+
+        // Four bets ready at once go out 5s apart
+        let mut now = start;
+        let mut last = None;
+        let mut placed_at = Vec::new();
+        for _ in 0..4 {
+            now += pacing_delay(last, now, min);
+            placed_at.push(now.duration_since(start).as_secs());
+            last = Some(now);
+        }
+        assert_eq!(placed_at, [0, 5, 10, 15]);
+
+        // A bet ready after the interval has passed isn't held
+        let later = now + Duration::from_secs(8);
+        assert_eq!(pacing_delay(last, later, min), Duration::ZERO);
+        assert_eq!(
+            pacing_delay(last, now + Duration::from_secs(2), min),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn test_fetched_prob_stale() {
+        let bet = BetData {
+            contract_id: "c".to_string(),
+            prob_before: 0.50,
+            prob_after: 0.30,
+            created_time: 2_000,
+        };
+        // Agrees with the bet
+        assert!(!fetched_prob_stale(0.31, Some(1_000), &bet, 0.02));
+        // Still the pre-bet price, and the market hasn't seen the bet
+        assert!(fetched_prob_stale(0.50, Some(1_000), &bet, 0.02));
+        // Moved on since the bet (e.g. reverted): fresher, not stale
+        assert!(!fetched_prob_stale(0.48, Some(3_000), &bet, 0.02));
+
+        // No bet times: judged by which side of the move the price is on
+        let untimed = BetData {
+            created_time: 0,
+            ..bet.clone()
+        };
+        assert!(fetched_prob_stale(0.49, Some(3_000), &untimed, 0.02));
+        assert!(fetched_prob_stale(0.45, None, &untimed, 0.02));
+        assert!(!fetched_prob_stale(0.35, None, &untimed, 0.02));
+        assert!(!fetched_prob_stale(0.20, None, &untimed, 0.02));
+    }
+
+    #[test]
+    fn test_seed_skip_reason() {
+        let contract = |extra: serde_json::Value| -> ContractData {
+            let mut json = serde_json::json!({
+                "id": "abc",
+                "slug": "will-x-happen",
+                "question": "Will X happen?",
+                "outcomeType": "BINARY",
+                "mechanism": "cpmm-1",
+                "visibility": "public",
+                "createdTime": 1_700_000_000_000u64,
+                "isResolved": false,
+                "probability": 0.5,
+                "totalLiquidity": 400.0,
+            });
+            json.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            serde_json::from_value(json).unwrap()
+        };
+        let seeded = contract(serde_json::json!({"initialLiquidity": 50.0}));
+        assert_eq!(seeded.initial_liquidity, Some(50.0));
+        let unseeded = contract(serde_json::json!({}));
+        assert_eq!(unseeded.initial_liquidity, None);
+
+        let mut config = BotConfig::default();
+        // Off by default
+        assert_eq!(
+            seed_skip_reason(seeded.initial_liquidity, 400.0, &config),
+            None
+        );
+
+        config.min_initial_liquidity = Some(100.0);
+        // A small seed since topped up by others is still a small seed
+        assert_eq!(
+            seed_skip_reason(seeded.initial_liquidity, 400.0, &config).as_deref(),
+            Some("seeded with M$50 < M$100")
+        );
+        assert_eq!(seed_skip_reason(Some(250.0), 400.0, &config), None);
+        // No seed field: fall back to the pool
+        assert_eq!(
+            seed_skip_reason(unseeded.initial_liquidity, 400.0, &config),
+            None
+        );
+        assert_eq!(
+            seed_skip_reason(None, 60.0, &config).as_deref(),
+            Some("no seed reported, pool M$60 < M$100")
+        );
+    }
+
+    #[test]
+    fn test_reprice_action() {
+        let tick = Some(0.01);
+        // YES resting at 60% (predicted 75%): not reached while the market is above
+        assert_eq!(
+            reprice_action("YES", 0.60, 0.64, 0.75, 0.10, tick),
+            RepriceAction::Keep
+        );
+        // Market came down past the limit without filling it: YES rounds up,
+        // so the new limit isn't left below the market
+        assert_eq!(
+            reprice_action("YES", 0.60, 0.581, 0.75, 0.10, tick),
+            RepriceAction::Replace(0.59)
+        );
+        assert_eq!(
+            reprice_action("YES", 0.60, 0.58, 0.75, 0.10, tick),
+            RepriceAction::Replace(0.58)
+        );
+        // ...but the edge at that price is gone
+        assert_eq!(
+            reprice_action("YES", 0.60, 0.60, 0.65, 0.10, tick),
+            RepriceAction::Cancel
+        );
+        // 11 points clear 10 only before the taker fee
+        assert_eq!(
+            reprice_action("YES", 0.60, 0.58, 0.69, 0.10, tick),
+            RepriceAction::Cancel
+        );
+
+        // NO resting at 40% (predicted 25%) fills as the price rises
+        assert_eq!(
+            reprice_action("NO", 0.40, 0.36, 0.25, 0.10, tick),
+            RepriceAction::Keep
+        );
+        assert_eq!(
+            reprice_action("NO", 0.40, 0.42, 0.25, 0.10, None),
+            RepriceAction::Replace(0.42)
+        );
+        // NO rounds down
+        assert_eq!(
+            reprice_action("NO", 0.40, 0.429, 0.25, 0.10, tick),
+            RepriceAction::Replace(0.42)
+        );
+        assert_eq!(
+            reprice_action("NO", 0.40, 0.41, 0.35, 0.10, tick),
+            RepriceAction::Cancel
+        );
+    }
+
+    #[test]
+    fn test_evict_stale() {
+        let mut map: HashMap<&str, u64> = [("old", 100), ("edge", 400), ("new", 900)].into();
+        assert_eq!(evict_stale_entries(&mut map, 1000, 600, |at| *at), 1);
+        assert!(!map.contains_key("old"));
+        assert!(map.contains_key("edge") && map.contains_key("new"));
+        // Nothing left past the TTL
+        assert_eq!(evict_stale_entries(&mut map, 1000, 600, |at| *at), 0);
+
+        // Watched markets go when they close, however long ago they were added
+        let watch = |close_time: Option<u64>| {
+            let mut broadcast = new_contract("q", 0.5);
+            broadcast.contract.close_time = close_time;
+            WatchedMarket {
+                broadcast: Box::new(broadcast),
+                flagged: false,
+            }
+        };
+        let mut watched: HashMap<String, WatchedMarket> = [
+            ("open".to_string(), watch(Some(10 * SECS_PER_DAY * 1000))),
+            ("closed".to_string(), watch(Some(500))),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(evict_closed(&mut watched, 1000), 1);
+        assert!(watched.contains_key("open"));
+
+        let deferred = |contract_id: &str, queued_at: u64| DeferredMarket {
+            contract_id: contract_id.to_string(),
+            question: String::new(),
+            queued_at,
+            due_at: queued_at + 60,
+        };
+        let mut items = vec![deferred("a", 0), deferred("b", 950), deferred("c", 10)];
+        assert_eq!(evict_stale_items(&mut items, 1000, 600, |d| d.queued_at), 2);
+        assert_eq!(items, vec![deferred("b", 950)]);
+    }
+
+    #[test]
+    fn test_confirm_move() {
+        let confirmation = MoveConfirmation {
+            events: 3,
+            window_secs: 60,
+        };
+        let bet = |contract_id: &str, prob_before: f64, prob_after: f64| BetData {
+            contract_id: contract_id.to_string(),
+            prob_before,
+            prob_after,
+            created_time: 0,
+        };
+        let mut runs = HashMap::new();
+
+        // A single spike doesn't trigger
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("a", 0.50, 0.70),
+            0,
+            &confirmation
+        ));
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("a", 0.70, 0.72),
+            10,
+            &confirmation
+        ));
+        // Other markets are counted separately
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("b", 0.40, 0.30),
+            15,
+            &confirmation
+        ));
+        assert!(confirm_move(
+            &mut runs,
+            &bet("a", 0.72, 0.75),
+            20,
+            &confirmation
+        ));
+        // Confirmed runs start over
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("a", 0.75, 0.78),
+            25,
+            &confirmation
+        ));
+
+        // A bet the other way resets the count
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("b", 0.30, 0.28),
+            20,
+            &confirmation
+        ));
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("b", 0.28, 0.35),
+            25,
+            &confirmation
+        ));
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("b", 0.35, 0.33),
+            30,
+            &confirmation
+        ));
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("b", 0.33, 0.31),
+            35,
+            &confirmation
+        ));
+        assert!(confirm_move(
+            &mut runs,
+            &bet("b", 0.31, 0.29),
+            40,
+            &confirmation
+        ));
+
+        // Bets that don't move the price don't count
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("c", 0.50, 0.50),
+            0,
+            &confirmation
+        ));
+        assert!(!runs.contains_key("c"));
+
+        // Too slow: the window expires and the run starts over
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("d", 0.50, 0.60),
+            100,
+            &confirmation
+        ));
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("d", 0.60, 0.65),
+            150,
+            &confirmation
+        ));
+        assert!(!confirm_move(
+            &mut runs,
+            &bet("d", 0.65, 0.70),
+            170,
+            &confirmation
+        ));
+        assert_eq!(runs["d"].count, 1);
+    }
+
+    #[test]
+    fn test_stale_bet_event() {
+        let received_at = Instant::now();
+        let mut config = BotConfig::default();
+        // Off by default: any delay is fine
+        assert_eq!(
+            stale_bet_event(
+                received_at,
+                received_at + Duration::from_secs(3600),
+                &config
+            ),
+            None
+        );
+
+        config.max_bet_event_age_secs = Some(30);
+        assert_eq!(stale_bet_event(received_at, received_at, &config), None);
+        assert_eq!(
+            stale_bet_event(received_at, received_at + Duration::from_secs(30), &config),
+            None
+        );
+        assert_eq!(
+            stale_bet_event(received_at, received_at + Duration::from_secs(90), &config),
+            Some(Duration::from_secs(90))
+        );
+    }
+
+    #[test]
+    fn test_analysis_queue_priority() {
+        // Saturated pool: everything queues, then drains one slot at a time
+        let mut queue = AnalysisQueue::default();
+        queue.push(Some(100.0), "thin");
+        queue.push(None, "bet-triggered");
+        queue.push(Some(5000.0), "deep");
+        queue.push(Some(800.0), "mid");
+        queue.push(Some(5000.0), "deep, later");
+        assert_eq!(queue.len(), 5);
+
+        let mut order = Vec::new();
+        while let Some(job) = queue.pop() {
+            order.push(job);
+        }
+        assert_eq!(
+            order,
+            ["deep", "deep, later", "mid", "thin", "bet-triggered"]
+        );
+        assert!(queue.is_empty());
+
+        // A deep market arriving later still jumps the line
+        queue.push(Some(200.0), "a");
+        queue.push(Some(300.0), "b");
+        assert_eq!(queue.pop(), Some("b"));
+        queue.push(Some(10_000.0), "c");
+        assert_eq!(queue.pop(), Some("c"));
+        assert_eq!(queue.pop(), Some("a"));
+    }
+
+    #[test]
+    fn test_simulated_load() {
+        use std::sync::atomic::AtomicUsize;
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .unwrap();
+        let workers = 3;
+        let window = 50;
+        let markets = 200;
+        let config = BotConfig {
+            analysis_workers: Some(workers),
+            xai_error_window: window,
+            // Confirmations are simulated too, never sent to xAI
+            confirmation: Some(Confirmation {
+                min_stake: 0.0,
+                ..Confirmation::default()
+            }),
+            ..BotConfig::default()
+        };
+        let simulation = Simulation {
+            min_latency_ms: 1,
+            max_latency_ms: 5,
+            error_rate: 0.5,
+            seed: 1,
+        };
+        let (ctx, mut channels) = test_context(config, simulation);
+
+        let finished = runtime.block_on(async {
+            tokio::time::timeout(Duration::from_secs(20), async {
+                // A burst of new markets far faster than the workers can
+                // analyze, through the same queue and dispatch as `run_bot`
+                let slots = Arc::new(tokio::sync::Semaphore::new(workers));
+                let mut queue = AnalysisQueue::default();
+                for i in 0..markets {
+                    let mut broadcast = new_contract(&format!("Simulated market {i}?"), 0.5);
+                    broadcast.contract.id = format!("sim-{i}");
+                    queue_analysis(
+                        &mut queue,
+                        &slots,
+                        &ctx.log_tx,
+                        Some((i % 7) as f64 * 100.0),
+                        AnalysisJob::NewMarket(Box::new(broadcast)),
+                        Instant::now(),
+                    );
+                }
+                let peak = Arc::new(AtomicUsize::new(0));
+                let sampler = {
+                    let (in_flight, peak) = (ctx.in_flight.clone(), peak.clone());
+                    tokio::spawn(async move {
+                        loop {
+                            peak.fetch_max(in_flight.lock().unwrap().len(), Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_micros(200)).await;
+                        }
+                    })
+                };
+                let mut warm_up = WarmUpThrottle::default();
+                let mut tasks = JoinSet::new();
+                while !queue.is_empty() {
+                    let permit = slots.clone().acquire_owned().await.unwrap();
+                    dispatch_analysis(&ctx, &mut tasks, &mut queue, &mut warm_up, permit);
+                }
+                // Every slot back means every analysis has finished
+                let _all = slots.acquire_many(workers as u32).await.unwrap();
+                sampler.abort();
+                let peak = peak.load(Ordering::SeqCst);
+                assert!((1..=workers).contains(&peak), "peak {peak}");
+                assert!(ctx.in_flight.lock().unwrap().is_empty());
+
+                // Each market got one research call (and a confirmation, if it
+                // came to a bet), then either a decision or a bet for the executor
+                let logs = channels.logs();
+                let count = |text: &str| logs.iter().filter(|line| line.contains(text)).count();
+                let (decisions, confirmations) = (count("DECISION "), count("confirmation for M$"));
+                assert!(confirmations > 0);
+                assert_eq!(
+                    ctx.counters.lock().unwrap().xai_calls,
+                    markets + confirmations as u64
+                );
+                let bets = std::iter::from_fn(|| channels.order_rx.try_recv().ok()).count();
+                assert!(bets > 0);
+                assert_eq!((decisions + bets) as u64, markets);
+                assert!(ctx.failed.lock().unwrap().len() <= MAX_FAILED);
+
+                // The window stays bounded and the breaker trips at this error rate
+                let stats = ctx.xai_health.lock().unwrap().stats();
+                assert_eq!(stats.calls, window);
+                assert_eq!(stats.errors, stats.rate_limit);
+                let pause = XaiPause {
+                    max_error_rate: 0.3,
+                    min_calls: 20,
+                };
+                assert!(pause.trips(&stats));
+            })
+            .await
+        });
+        assert!(finished.is_ok(), "simulated load test deadlocked");
+    }
+
+    /// The far ends of a `run_bot` spawned on the current runtime.
+    struct RunningBot {
+        ws_tx: mpsc::UnboundedSender<WsEvent>,
+        cmd_tx: mpsc::UnboundedSender<BotCommand>,
+        log_rx: mpsc::UnboundedReceiver<BotLogEntry>,
+        status_rx: mpsc::UnboundedReceiver<BotStatus>,
+        task: JoinHandle<()>,
+    }
+
+    /// Start `run_bot` with `config`, which should set `simulation` so
+    /// nothing reaches xAI or Manifold.
+    fn spawn_bot(config: BotConfig) -> RunningBot {
+        let (ws_tx, ws_rx) = mpsc::unbounded_channel();
+        let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
+        let (log_tx, log_rx) = mpsc::unbounded_channel();
+        let (status_tx, status_rx) = mpsc::unbounded_channel();
+        let channels = BotChannels {
+            ws_rx,
+            cmd_rx,
+            log_tx,
+            status_tx,
+        };
+        let task = tokio::spawn(run_bot(
+            Arc::new(ManifoldClient::new(String::new())),
+            XaiClient::new(String::new(), DEFAULT_USER_AGENT),
+            channels,
+            config,
+        ));
+        RunningBot {
+            ws_tx,
+            cmd_tx,
+            log_rx,
+            status_rx,
+            task,
+        }
+    }
+
+    #[test]
+    fn test_run_bot_under_synthetic_load() {
+        use crate::simulate::{synthetic_bets, SimRng};
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .unwrap();
+        let (workers, window, markets) = (3, 50, 40);
+        let config = BotConfig {
+            analysis_workers: Some(workers),
+            xai_error_window: window,
+            simulation: Some(Simulation {
+                min_latency_ms: 1,
+                max_latency_ms: 5,
+                error_rate: 0.2,
+                seed: 5,
+            }),
+            ..BotConfig::default()
+        };
+        let mut bets = synthetic_bets(&mut SimRng::new(11), 500, markets);
+        for bet in &mut bets {
+            bet.contract_id = bet.contract_id.replace("sim-", "load-");
+        }
+        let distinct: HashSet<String> = bets.iter().map(|b| b.contract_id.clone()).collect();
+
+        let finished = runtime.block_on(async {
+            tokio::time::timeout(Duration::from_secs(30), async {
+                let mut bot = spawn_bot(config);
+                bot.ws_tx.send(WsEvent::Connected).unwrap();
+                for bet in &bets {
+                    let event = WsEvent::NewBet(Box::new(bet.clone()), Instant::now());
+                    bot.ws_tx.send(event).unwrap();
+                }
+
+                // Poll until every claimed market has finished its analysis,
+                // watching the in-flight snapshots along the way
+                let mut peak = 0;
+                let snapshot = loop {
+                    bot.cmd_tx.send(BotCommand::Diagnostics).unwrap();
+                    let snapshot = loop {
+                        match bot.status_rx.recv().await.unwrap() {
+                            BotStatus::Analyzing(in_flight) => peak = peak.max(in_flight.len()),
+                            BotStatus::Diagnostics(snapshot) => break snapshot,
+                            _ => {}
+                        }
+                    };
+                    let cache = &snapshot["cache"];
+                    if cache["in_progress"] == 0 && cache["markets"] == distinct.len() {
+                        break snapshot;
+                    }
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                };
+                assert!((1..=workers).contains(&peak), "peak {peak}");
+                assert_eq!(snapshot["in_flight"], 0);
+                assert_eq!(snapshot["counters"]["markets_seen"], distinct.len());
+                // The error window stays bounded however many calls were made
+                assert!(snapshot["xai"]["calls"].as_u64().unwrap() <= window as u64);
+
+                // One analysis per market; every later bet on it is skipped
+                let logs: Vec<String> = std::iter::from_fn(|| bot.log_rx.try_recv().ok())
+                    .map(|entry| format!("{entry:?}"))
+                    .collect();
+                let skipped = logs
+                    .iter()
+                    .filter(|l| l.contains("Already analyzed market load-"))
+                    .count();
+                assert_eq!(skipped, bets.len() - distinct.len());
+                assert!(logs.iter().any(|l| l.contains("SIMULATION MODE")));
+
+                // Closing the feed stops the bot
+                drop(bot.ws_tx);
+                bot.task.await.unwrap();
+            })
+            .await
+        });
+        assert!(finished.is_ok(), "run_bot deadlocked under load");
+    }
+
+    /// Simulated Manifold that logs which calls reach it.
+    struct CountingManifold {
+        inner: SimulatedManifold,
+        calls: Mutex<Vec<&'static str>>,
+    }
+
+    impl CountingManifold {
+        fn new() -> Self {
+            let simulator = Arc::new(Simulator::new(instant_simulation()));
+            Self {
+                inner: SimulatedManifold::new(simulator),
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn called(&self, call: &'static str) -> &SimulatedManifold {
+            self.calls.lock().unwrap().push(call);
+            &self.inner
+        }
+    }
+
+    impl ManifoldApi for CountingManifold {
+        fn api_key(&self) -> &str {
+            ""
+        }
+
+        fn get_me(&self) -> BoxFuture<'_, Result<crate::api::User, ApiError>> {
+            self.called("get_me").get_me()
+        }
+
+        fn get_market<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Market, ApiError>> {
+            self.called("get_market").get_market(id)
+        }
+
+        fn get_user<'a>(
+            &'a self,
+            username: &'a str,
+        ) -> BoxFuture<'a, Result<UserProfile, ApiError>> {
+            self.called("get_user").get_user(username)
+        }
+
+        fn get_bets<'a>(
+            &'a self,
+            contract_id: &'a str,
+            limit: u32,
+        ) -> BoxFuture<'a, Result<Vec<crate::api::MarketBet>, ApiError>> {
+            self.called("get_bets").get_bets(contract_id, limit)
+        }
+
+        fn get_user_bets<'a>(
+            &'a self,
+            user_id: &'a str,
+            before: Option<&'a str>,
+            limit: u32,
+        ) -> BoxFuture<'a, Result<Vec<UserBet>, ApiError>> {
+            self.called("get_user_bets")
+                .get_user_bets(user_id, before, limit)
+        }
+
+        fn get_open_orders<'a>(
+            &'a self,
+            user_id: &'a str,
+        ) -> BoxFuture<'a, Result<Vec<LimitOrder>, ApiError>> {
+            self.called("get_open_orders").get_open_orders(user_id)
+        }
+
+        fn get_markets<'a>(
+            &'a self,
+            before: Option<&'a str>,
+            limit: u32,
+        ) -> BoxFuture<'a, Result<Vec<Market>, ApiError>> {
+            self.called("get_markets").get_markets(before, limit)
+        }
+
+        fn place_bet<'a>(
+            &'a self,
+            request: &'a BetRequest,
+        ) -> BoxFuture<'a, Result<BetResponse, ApiError>> {
+            self.called("place_bet").place_bet(request)
+        }
+
+        fn cancel_bet<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), ApiError>> {
+            self.called("cancel_bet").cancel_bet(bet_id)
+        }
+
+        fn send_managram<'a>(
+            &'a self,
+            to_user_id: &'a str,
+            amount: f64,
+            message: &'a str,
+        ) -> BoxFuture<'a, Result<(), ApiError>> {
+            self.called("send_managram")
+                .send_managram(to_user_id, amount, message)
+        }
+
+        fn sell_shares<'a>(
+            &'a self,
+            contract_id: &'a str,
+            request: &'a SellRequest,
+        ) -> BoxFuture<'a, Result<BetResponse, ApiError>> {
+            self.called("sell_shares").sell_shares(contract_id, request)
+        }
+    }
+
+    #[test]
+    fn test_dry_run_leaves_positions_alone() {
+        let config = BotConfig {
+            execution: ExecutionMode::DryRun,
+            ..BotConfig::default()
+        };
+        let (mut ctx, mut channels) = test_context(config, instant_simulation());
+        let manifold = Arc::new(CountingManifold::new());
+        ctx.manifold = manifold.clone();
+        ctx.executor = execution::backend(&ExecutionMode::DryRun, false, ctx.manifold.clone());
+        // A position from an earlier live run, with an order still resting
+        ctx.pnl.lock().unwrap().record(LedgerEntry {
+            contract_id: "held".to_string(),
+            question: "Held?".to_string(),
+            outcome: "YES".to_string(),
+            amount: 20.0,
+            filled: 10.0,
+            shares: 25.0,
+            limit_prob: 0.4,
+            prediction: 0.9,
+            market_prob: 0.4,
+            placed_at: 0,
+            resolution: None,
+            strategy: Strategy::NewMarket,
+            sold_for: None,
+            order_id: Some("order-1".to_string()),
+        });
+        let reevaluation = Reevaluation {
+            interval_secs: 60,
+            max_per_round: 10,
+            // Any prediction counts as flipped, so a live run would sell
+            exit_edge: -1.0,
+        };
+        let reprice = Reprice {
+            interval_secs: 60,
+            max_attempts: 3,
+        };
+        let sweep = ProfitSweep {
+            to_user_id: "vault".to_string(),
+            ceiling: 0.0,
+            interval_secs: 60,
+        };
+        block_on(async {
+            reevaluate_positions(&ctx, &reevaluation).await;
+            reprice_orders(&ctx, &reprice).await;
+            sweep_profits(&ctx, &sweep).await;
+        });
+        assert!(manifold.calls.lock().unwrap().is_empty());
+        assert_eq!(ctx.pnl.lock().unwrap().positions().len(), 1);
+        assert!(channels.logs().is_empty());
+
+        // The same rounds with live bets do reach Manifold
+        ctx.executor = execution::backend(&ExecutionMode::Manifold, false, ctx.manifold.clone());
+        block_on(async {
+            reevaluate_positions(&ctx, &reevaluation).await;
+            reprice_orders(&ctx, &reprice).await;
+            sweep_profits(&ctx, &sweep).await;
+        });
+        let calls = manifold.calls.lock().unwrap();
+        assert!(calls.contains(&"sell_shares"), "{calls:?}");
+        assert!(calls.contains(&"send_managram"), "{calls:?}");
+    }
+
+    #[test]
+    fn test_aborting_run_bot_stops_its_tasks() {
+        let config = BotConfig {
+            // Research outlasts the test unless the analysis is cancelled
+            simulation: Some(Simulation {
+                min_latency_ms: 5_000,
+                max_latency_ms: 5_000,
+                ..instant_simulation()
+            }),
+            ..BotConfig::default()
+        };
+        block_on(async {
+            let mut bot = spawn_bot(config);
+            let mut broadcast = new_contract("Will stopping work?", 0.5);
+            broadcast.contract.id = "stop-1".to_string();
+            bot.ws_tx.send(WsEvent::Connected).unwrap();
+            bot.ws_tx
+                .send(WsEvent::NewContract(Box::new(broadcast)))
+                .unwrap();
+            loop {
+                if let BotStatus::Analyzing(in_flight) = bot.status_rx.recv().await.unwrap() {
+                    if !in_flight.is_empty() {
+                        break;
+                    }
+                }
+            }
+
+            bot.task.abort();
+            // The log closes once every task holding a sender is gone
+            let drained = tokio::time::timeout(Duration::from_secs(1), async {
+                let mut logs = Vec::new();
+                while let Some(entry) = bot.log_rx.recv().await {
+                    logs.push(format!("{entry:?}"));
+                }
+                logs
+            })
+            .await
+            .expect("tasks outlived run_bot");
+            assert!(!drained.iter().any(|l| l.contains("DECISION ")));
+        });
+    }
+
+    /// The first log line containing `needle`, skipping the rest.
+    async fn next_log(log_rx: &mut mpsc::UnboundedReceiver<BotLogEntry>, needle: &str) -> String {
+        loop {
+            let line = format!("{:?}", log_rx.recv().await.unwrap());
+            if line.contains(needle) {
+                return line;
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_config_applies_to_next_event() {
+        let config = BotConfig {
+            min_liquidity: 500.0,
+            simulation: Some(instant_simulation()),
+            ..BotConfig::default()
+        };
+        let market = |id: &str| {
+            let mut broadcast = new_contract("Will the threshold move?", 0.5);
+            broadcast.contract.id = id.to_string();
+            WsEvent::NewContract(Box::new(broadcast))
+        };
+        block_on(async {
+            let mut bot = spawn_bot(config.clone());
+            bot.ws_tx.send(WsEvent::Connected).unwrap();
+            bot.ws_tx.send(market("update-1")).unwrap();
+            next_log(&mut bot.log_rx, "Skipping low-liquidity").await;
+
+            let update = BotConfig {
+                min_liquidity: 50.0,
+                ..config
+            };
+            bot.cmd_tx
+                .send(BotCommand::UpdateConfig(Box::new(update)))
+                .unwrap();
+            assert!(next_log(&mut bot.log_rx, "Config updated")
+                .await
+                .contains("min_liquidity"));
+            bot.ws_tx.send(market("update-2")).unwrap();
+            next_log(&mut bot.log_rx, "New binary market").await;
+        });
+    }
+
+    #[test]
+    fn test_new_market_filters_log_a_decision() {
+        let config = BotConfig {
+            tradable_price_range: (0.2, 0.8),
+            min_liquidity: 500.0,
+            simulation: Some(instant_simulation()),
+            ..BotConfig::default()
+        };
+        block_on(async {
+            let mut bot = spawn_bot(config);
+            bot.ws_tx.send(WsEvent::Connected).unwrap();
+            for (id, prob) in [("filter-1", 0.9), ("filter-2", 0.5)] {
+                let mut broadcast = new_contract("Will the filter log?", prob);
+                broadcast.contract.id = id.to_string();
+                bot.ws_tx
+                    .send(WsEvent::NewContract(Box::new(broadcast)))
+                    .unwrap();
+            }
+            let priced_out = next_log(&mut bot.log_rx, "DECISION ").await;
+            assert!(priced_out.contains("filter-1"), "{priced_out}");
+            assert!(priced_out.contains("Price outside tradable_price_range"));
+            let thin = next_log(&mut bot.log_rx, "DECISION ").await;
+            assert!(thin.contains("filter-2"), "{thin}");
+            assert!(thin.contains("Liquidity below min_liquidity"));
+        });
+    }
+
+    #[test]
+    fn test_config_file_overrides_defaults() {
+        let dir = std::env::temp_dir().join(format!("bot-config-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        let missing = BotConfig::load_from(&path).unwrap();
+        assert_eq!(missing.min_liquidity, BotConfig::default().min_liquidity);
+
+        std::fs::write(
+            &path,
+            r#"{"min_liquidity": 250, "order_tif": "Ioc", "execution": "DryRun"}"#,
+        )
+        .unwrap();
+        let loaded = BotConfig::load_from(&path).unwrap();
+        assert_eq!(loaded.min_liquidity, 250.0);
+        assert_eq!(loaded.order_tif, OrderTif::Ioc);
+        assert_eq!(loaded.execution, ExecutionMode::DryRun);
+        // Unset fields keep their defaults
+        assert_eq!(loaded.bet_amount, BotConfig::default().bet_amount);
+
+        std::fs::write(&path, r#"{"min_liquidity": "lots"}"#).unwrap();
+        assert!(BotConfig::load_from(&path).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_place_and_record_paces_bets() {
+        let config = BotConfig {
+            min_bet_interval_secs: Some(1),
+            ..BotConfig::default()
+        };
+        let (ctx, mut channels) = test_context(config, instant_simulation());
+        let bet = BetRequest {
+            contract_id: "sim-0".to_string(),
+            amount: 10.0,
+            outcome: "YES".to_string(),
+            limit_prob: Some(0.5),
+            expires_at: None,
+            expires_millis_after: None,
+        };
+        let place = || place_and_record(&ctx, &bet, "Paced?", 0.7, 0.5, Strategy::NewMarket);
+        let placed = block_on(async {
+            let mut placed = Vec::new();
+            for _ in 0..2 {
+                place().await.unwrap();
+                placed.push(Instant::now());
+            }
+            placed
+        });
+        // The first bet goes straight out; the second waits out the interval
+        assert!(placed[1].duration_since(placed[0]) >= Duration::from_secs(1));
+        let logs = channels.logs();
+        assert_eq!(
+            logs.iter()
+                .filter(|l| l.contains("Pacing: holding bet"))
+                .count(),
+            1
+        );
+        assert_eq!(
+            logs.iter().filter(|l| l.contains("BET NOT PLACED")).count(),
+            2
+        );
+    }
+
+    /// Live backend whose limit orders fill only `filled` mana and then rest,
+    /// refusing cancels if `cancel_fails`.
+    struct PartialFillExecutor {
+        filled: f64,
+        cancel_fails: bool,
+        cancelled: Mutex<Vec<String>>,
+    }
+
+    impl ExecutionBackend for PartialFillExecutor {
+        fn execute<'a>(
+            &'a self,
+            request: &'a BetRequest,
+        ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+            Box::pin(async move {
+                Ok(BetResponse {
+                    bet_id: Some("order-1".to_string()),
+                    amount: Some(self.filled),
+                    shares: Some(self.filled * 2.0),
+                    outcome: Some(request.outcome.clone()),
+                    contract_id: Some(request.contract_id.clone()),
+                    is_filled: Some(false),
+                })
+            })
+        }
+
+        fn cancel<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+            self.cancelled.lock().unwrap().push(bet_id.to_string());
+            let result = if self.cancel_fails {
+                Err(BotError::Request("cancel timed out".to_string()))
+            } else {
+                Ok(())
+            };
+            Box::pin(async move { result })
+        }
+
+        fn sell<'a>(
+            &'a self,
+            _contract_id: &'a str,
+            _request: &'a SellRequest,
+        ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+            Box::pin(async { Ok(BetResponse::default()) })
+        }
+
+        fn send_managram<'a>(
+            &'a self,
+            _to_user_id: &'a str,
+            _amount: f64,
+            _message: &'a str,
+        ) -> BoxFuture<'a, Result<(), BotError>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn name(&self) -> &'static str {
+            "partial fill"
+        }
+    }
+
+    #[test]
+    fn test_ioc_and_fok_cancel_the_remainder() {
+        let bet = BetRequest {
+            contract_id: "sim-0".to_string(),
+            amount: 10.0,
+            outcome: "YES".to_string(),
+            limit_prob: Some(0.5),
+            expires_at: None,
+            expires_millis_after: None,
+        };
+        for (tif, filled, cancel_fails) in [
+            (OrderTif::Ioc, 4.0, false),
+            (OrderTif::Fok, 4.0, false),
+            (OrderTif::Ioc, 0.0, false),
+            (OrderTif::Ioc, 0.0, true),
+        ] {
+            let config = BotConfig {
+                order_tif: tif,
+                ..BotConfig::default()
+            };
+            let (mut ctx, mut channels) = test_context(config, instant_simulation());
+            let executor = Arc::new(PartialFillExecutor {
+                filled,
+                cancel_fails,
+                cancelled: Mutex::new(Vec::new()),
+            });
+            ctx.executor = executor.clone();
+            block_on(place_and_record(
+                &ctx,
+                &bet,
+                "Partial?",
+                0.7,
+                0.5,
+                Strategy::NewMarket,
+            ))
+            .unwrap();
+
+            assert_eq!(*executor.cancelled.lock().unwrap(), ["order-1"], "{tif:?}");
+            let ledger: Vec<f64> = ctx
+                .pnl
+                .lock()
+                .unwrap()
+                .entries()
+                .iter()
+                .map(|e| e.amount)
+                .collect();
+            // Only the filled part is held; nothing is recorded for no fill.
+            // An order that wasn't cancelled may still rest, so it's held in full.
+            if cancel_fails {
+                assert_eq!(ledger, [bet.amount]);
+            } else if filled > 0.0 {
+                assert_eq!(ledger, [filled], "{tif:?}");
+            } else {
+                assert!(ledger.is_empty());
+            }
+            let logs = channels.logs();
+            let note = if cancel_fails {
+                "failed to cancel remainder"
+            } else {
+                "cancelled unfilled remainder"
+            };
+            assert!(logs.iter().any(|l| l.contains(note)), "{logs:?}");
+            let flagged = logs.iter().any(|l| l.contains("FOK partially filled"));
+            assert_eq!(flagged, tif == OrderTif::Fok, "{tif:?}");
+        }
+    }
+
+    #[test]
+    fn test_analyzed_cache_claim_is_atomic() {
+        let cache = Arc::new(Mutex::new(AnalyzedCache::default()));
+        // Duplicate events for one market racing on separate threads
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                let cache = cache.clone();
+                std::thread::spawn(move || cache.lock().unwrap().claim("c"))
+            })
+            .collect();
+        let claimed = handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .filter(|&won| won)
+            .count();
+        assert_eq!(claimed, 1);
+
+        let mut cache = cache.lock().unwrap();
+        assert!(!cache.claim("c"));
+        cache.complete("c", 1_000);
+        assert!(!cache.claim("c"));
+        // Claimable again once the entry expires
+        cache.evict_expired(1_000 + CACHE_TTL_SECS);
+        assert!(!cache.contains("c"));
+        assert!(cache.claim("c"));
+    }
+
+    #[test]
+    fn test_analyzed_cache_clear() {
+        let mut cache = AnalyzedCache::default();
+        cache.claim("a");
+        cache.complete("a", 100);
+        cache.claim("b");
+        cache.complete("b", 300);
+        cache.claim("running");
+        cache.claim("lost");
+        // A claim nothing released expires after the TTL
+        assert_eq!(cache.evict_stale_claims(now_epoch_secs(), 3600), 0);
+        assert_eq!(cache.evict_stale_claims(now_epoch_secs() + 7200, 3600), 2);
+        assert!(cache.claim("lost"));
+        cache.release("lost");
+        cache.claim("running");
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                markets: 2,
+                in_progress: 1,
+                oldest: Some(100),
+                newest: Some(300),
+            }
+        );
+
+        assert!(cache.forget("a"));
+        assert!(!cache.forget("a"));
+        assert!(!cache.forget("running"));
+        assert!(cache.claim("a"));
+
+        // Clearing leaves running analyses claimed
+        assert_eq!(cache.clear(), 1);
+        assert!(cache.claim("b"));
+        assert!(!cache.claim("running"));
+        assert_eq!(cache.stats().markets, 0);
+        assert_eq!(cache.stats().oldest, None);
+    }
+
+    #[test]
+    fn test_analyzed_cache_crash_recovery() {
+        let path =
+            std::env::temp_dir().join(format!("analyzed-cache-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let now = now_epoch_secs();
+
+        let mut cache = AnalyzedCache::load_from(path.clone());
+        assert!(cache.claim("finished"));
+        assert!(cache.claim("interrupted"));
+        cache.complete("finished", now);
+        // Crash mid-analysis of "interrupted"
+        drop(cache);
+
+        let mut cache = AnalyzedCache::load_from(path.clone());
+        assert!(cache.contains("finished"));
+        assert!(!cache.contains("interrupted"));
+        assert!(cache.claim("interrupted"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_closes_within() {
+        let now = 1_000_000_000;
+        let hour = 3_600;
+        assert_eq!(
+            closes_within(Some(now + hour * 1000), now, 2 * hour),
+            Some(hour)
+        );
+        assert_eq!(
+            closes_within(Some(now + 2 * hour * 1000), now, 2 * hour),
+            Some(2 * hour)
+        );
+        // Outside the window, already closed, or no close time
+        assert_eq!(
+            closes_within(Some(now + 3 * hour * 1000), now, 2 * hour),
+            None
+        );
+        assert_eq!(closes_within(Some(now - 1000), now, 2 * hour), None);
+        assert_eq!(closes_within(Some(now + 500), now, 2 * hour), None);
+        assert_eq!(closes_within(None, now, 2 * hour), None);
+    }
+
+    #[test]
+    fn test_sweep_amount() {
+        assert_eq!(sweep_amount(1234.7, 1000.0), Some(234.0));
+        assert_eq!(sweep_amount(1010.0, 1000.0), Some(10.0));
+        // Too little to send, or below the ceiling
+        assert_eq!(sweep_amount(1009.9, 1000.0), None);
+        assert_eq!(sweep_amount(500.0, 1000.0), None);
+    }
+
+    #[test]
+    fn test_blackout_windows() {
+        let day = 20_000 * SECS_PER_DAY;
+        let at = |h: u64, m: u64| day + h * 3600 + m * 60;
+
+        // 13:30-14:30 UTC
+        let daily = Blackout::Daily {
+            start: 13 * 3600 + 30 * 60,
+            end: 14 * 3600 + 30 * 60,
+        };
+        assert!(!daily.contains(at(13, 29)));
+        assert!(daily.contains(at(13, 30)));
+        assert!(daily.contains(at(14, 29)));
+        assert!(!daily.contains(at(14, 30)));
+        // Recurs the next day
+        assert!(daily.contains(at(13, 45) + SECS_PER_DAY));
+
+        // 23:00-01:00 wraps past midnight
+        let overnight = Blackout::Daily {
+            start: 23 * 3600,
+            end: 3600,
+        };
+        assert!(overnight.contains(at(23, 0)));
+        assert!(overnight.contains(at(23, 59)));
+        assert!(overnight.contains(at(0, 0)));
+        assert!(overnight.contains(at(0, 59)));
+        assert!(!overnight.contains(at(1, 0)));
+        assert!(!overnight.contains(at(22, 59)));
+        assert_eq!(overnight.to_string(), "daily 23:00-01:00 UTC");
+
+        // A one-off window spanning midnight
+        let once = Blackout::Between {
+            start: at(22, 0),
+            end: at(2, 0) + SECS_PER_DAY,
+        };
+        assert!(once.contains(at(23, 0)));
+        assert!(once.contains(at(1, 0) + SECS_PER_DAY));
+        assert!(!once.contains(at(23, 0) + SECS_PER_DAY));
+
+        let blackouts = [daily, overnight];
+        assert_eq!(active_blackout(&blackouts, at(0, 30)), Some(&overnight));
+        assert_eq!(active_blackout(&blackouts, at(12, 0)), None);
+        assert_eq!(active_blackout(&[], at(0, 30)), None);
+    }
+
+    #[test]
+    fn test_confirmation_agrees() {
+        // Same side, within tolerance
+        assert!(confirmation_agrees(0.70, 0.60, 0.40, 0.15));
+        assert!(confirmation_agrees(0.70, 0.85, 0.40, 0.15));
+        // Same side, too far apart
+        assert!(!confirmation_agrees(0.70, 0.50, 0.40, 0.15));
+        // Close, but on the other side of the market
+        assert!(!confirmation_agrees(0.45, 0.35, 0.40, 0.15));
+        // NO bets
+        assert!(confirmation_agrees(0.10, 0.20, 0.60, 0.15));
+        assert!(!confirmation_agrees(0.10, 0.65, 0.60, 0.15));
+    }
+
+    #[test]
+    fn test_expire_in_flight() {
+        let analysis = |id: &str, started_at| InFlightAnalysis {
+            contract_id: id.to_string(),
+            question: id.to_string(),
+            started_at,
+        };
+        let now = 10_000;
+        let mut in_flight = vec![
+            analysis("fresh", now - 30),
+            analysis("stuck", now - IN_FLIGHT_TIMEOUT_SECS),
+        ];
+        assert!(expire_in_flight(&mut in_flight, now));
+        assert_eq!(in_flight, [analysis("fresh", now - 30)]);
+        assert!(!expire_in_flight(&mut in_flight, now));
+    }
+
+    #[test]
+    fn test_select_batch() {
+        let order = |question: &str, amount: f64, net_edge: f64| BetOrder {
+            bets: vec![build_bet("c", amount, "YES", 0.6, &BotConfig::default())],
+            question: question.to_string(),
+            prediction: 0.6,
+            market_prob: 0.4,
+            strategy: Strategy::NewMarket,
+            net_edge,
+            trace: DecisionTrace::default(),
+            origin: AnalysisJob::BetTriggered(BetData {
+                contract_id: "c".to_string(),
+                prob_before: 0.5,
+                prob_after: 0.4,
+                created_time: 0,
+            }),
+        };
+        let burst = || {
+            vec![
+                order("a", 10.0, 0.12),
+                order("b", 30.0, 0.30),
+                order("c", 10.0, 0.12),
+                order("d", 20.0, 0.20),
+                order("e", 10.0, 0.15),
+            ]
+        };
+        let summary = |ranked: Vec<(BetOrder, bool)>| {
+            ranked
+                .into_iter()
+                .map(|(o, place)| (o.question, place))
+                .collect::<Vec<_>>()
+        };
+
+        // Top 2 by edge, no budget; ties keep arrival order
+        let batch = BetBatch {
+            window_secs: 30,
+            top_k: 2,
+            budget: None,
+        };
+        assert_eq!(
+            summary(select_batch(burst(), &batch)),
+            [
+                ("b".to_string(), true),
+                ("d".to_string(), true),
+                ("e".to_string(), false),
+                ("a".to_string(), false),
+                ("c".to_string(), false),
+            ]
+        );
+
+        // M$45 budget: b (30) fits, d (20) would overrun, e (10) fits, a would overrun
+        let batch = BetBatch {
+            top_k: 3,
+            budget: Some(45.0),
+            ..batch
+        };
+        assert_eq!(
+            summary(select_batch(burst(), &batch)),
+            [
+                ("b".to_string(), true),
+                ("d".to_string(), false),
+                ("e".to_string(), true),
+                ("a".to_string(), false),
+                ("c".to_string(), false),
+            ]
+        );
+
+        // Budget smaller than every bet: nothing placed
+        let batch = BetBatch {
+            budget: Some(5.0),
+            ..batch
+        };
+        assert!(select_batch(burst(), &batch)
+            .iter()
+            .all(|(_, place)| !place));
+    }
+
+    #[test]
+    fn test_market_age_secs() {
+        let created = 1_700_000_000_000;
+        assert_eq!(market_age_secs(created, created), 0);
+        assert_eq!(market_age_secs(created, created + 999), 0);
+        assert_eq!(market_age_secs(created, created + 90_500), 90);
+        // Clock skew: created "in the future" counts as brand new
+        assert_eq!(market_age_secs(created, created - 5_000), 0);
+    }
+
+    #[test]
+    fn test_pending_state_parse() {
+        let now = 1_700_000_000;
+        let state = PendingState {
+            version: PENDING_STATE_VERSION,
+            deferred: vec![
+                DeferredMarket {
+                    contract_id: "fresh".to_string(),
+                    question: "Q1".to_string(),
+                    queued_at: now - 60,
+                    due_at: now + 60,
+                },
+                DeferredMarket {
+                    contract_id: "stale".to_string(),
+                    question: "Q2".to_string(),
+                    queued_at: now - PENDING_TTL_SECS,
+                    due_at: now - PENDING_TTL_SECS + 60,
+                },
+            ],
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed = PendingState::parse(&json, now);
+        assert_eq!(parsed.deferred.len(), 1);
+        assert_eq!(parsed.deferred[0].contract_id, "fresh");
+
+        let future = json.replace("\"version\":1", "\"version\":99");
+        assert_eq!(PendingState::parse(&future, now), PendingState::default());
+        assert_eq!(PendingState::parse("garbage", now), PendingState::default());
+    }
+
+    #[test]
+    fn test_ladder_rungs() {
+        let close = |rungs: Vec<(f64, f64)>, expected: &[(f64, f64)]| {
+            assert_eq!(rungs.len(), expected.len(), "{rungs:?}");
+            for ((a, p), (ea, ep)) in rungs.iter().zip(expected) {
+                assert!((a - ea).abs() < 1e-9 && (p - ep).abs() < 1e-9, "{rungs:?}");
+            }
+        };
+
+        let cent = Some(0.01);
+        // Single level: the plain order, price untouched
+        close(ladder_rungs(40.0, 0.40, 0.613, 1, cent), &[(40.0, 0.613)]);
+        // YES: stepping up from the market to the limit
+        close(
+            ladder_rungs(40.0, 0.40, 0.60, 4, cent),
+            &[(10.0, 0.45), (10.0, 0.50), (10.0, 0.55), (10.0, 0.60)],
+        );
+        // NO: stepping down
+        close(
+            ladder_rungs(30.0, 0.70, 0.40, 3, cent),
+            &[(10.0, 0.60), (10.0, 0.50), (10.0, 0.40)],
+        );
+        // Too little mana for M$1 rungs: fewer levels
+        close(
+            ladder_rungs(2.5, 0.40, 0.60, 4, cent),
+            &[(1.25, 0.50), (1.25, 0.60)],
+        );
+        close(ladder_rungs(0.5, 0.40, 0.60, 4, cent), &[(0.5, 0.60)]);
+        // Rungs rounding to the same percent merge
+        close(
+            ladder_rungs(30.0, 0.50, 0.51, 3, cent),
+            &[(10.0, 0.50), (20.0, 0.51)],
+        );
+        // Other ticks: 0.4667 and 0.5333 land on 0.45 and 0.55
+        close(
+            ladder_rungs(30.0, 0.40, 0.60, 3, Some(0.05)),
+            &[(10.0, 0.45), (10.0, 0.55), (10.0, 0.60)],
+        );
+        // ... and rungs on the same tick merge
+        close(
+            ladder_rungs(30.0, 0.50, 0.55, 3, Some(0.05)),
+            &[(10.0, 0.50), (20.0, 0.55)],
+        );
+        // No tick leaves prices unrounded
+        close(
+            ladder_rungs(30.0, 0.40, 0.50, 3, None),
+            &[
+                (10.0, 0.4 + 0.1 / 3.0),
+                (10.0, 0.4 + 0.2 / 3.0),
+                (10.0, 0.50),
+            ],
+        );
+        // Amounts always add up
+        let total: f64 = ladder_rungs(25.0, 0.20, 0.80, 7, cent)
+            .iter()
+            .map(|r| r.0)
+            .sum();
+        assert!((total - 25.0).abs() < 1e-9);
+
+        let config = BotConfig {
+            ladder_levels: 2,
+            ..BotConfig::default()
+        };
+        let bets = build_ladder("c", 20.0, "YES", 0.40, 0.60, &config);
+        assert_eq!(bets.len(), 2);
+        assert_eq!(bets[0].limit_prob, Some(0.50));
+        assert!(bets.iter().all(|b| b.amount == 10.0 && b.outcome == "YES"));
+    }
+
+    #[test]
+    fn test_build_bet_tif_serialization() {
+        let gtc = build_bet("abc", 10.0, "YES", 0.6, &BotConfig::default());
+        let json = serde_json::to_value(&gtc).unwrap();
+        assert_eq!(json["contractId"], "abc");
+        assert_eq!(json["limitProb"], 0.6);
+        assert!(json.get("expiresMillisAfter").is_none());
+        assert!(json.get("expiresAt").is_none());
+
+        let config = BotConfig {
+            limit_expiry_ms: Some(3_600_000),
+            ..BotConfig::default()
+        };
+        let expiring = build_bet("abc", 10.0, "YES", 0.6, &config);
+        assert_eq!(expiring.expires_millis_after, Some(3_600_000));
+
+        let config = BotConfig {
+            order_tif: OrderTif::Ioc,
+            ..BotConfig::default()
+        };
+        let ioc = build_bet("abc", 10.0, "NO", 0.4, &config);
+        let json = serde_json::to_value(&ioc).unwrap();
+        assert_eq!(json["outcome"], "NO");
+        assert_eq!(json["expiresMillisAfter"], IMMEDIATE_EXPIRY_MS);
+    }
+
+    #[test]
+    fn test_exposure_allows() {
+        // Bankroll M$1000 (all free), 50% cap
+        assert!(exposure_allows(0.0, 100.0, 1000.0, 0.5));
+        assert!(exposure_allows(400.0, 100.0, 600.0, 0.5));
+        assert!(!exposure_allows(450.0, 100.0, 550.0, 0.5));
+        assert!(!exposure_allows(0.0, 10.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_restore_positions() {
+        // Newest first, as `/v0/bets?userId=` returns them
+        let payload = r#"[
+            {"id": "b5", "contractId": "m1", "outcome": "YES", "amount": -12, "shares": -20, "createdTime": 5000},
+            {"id": "b4", "contractId": "m2", "outcome": "NO", "amount": -30, "shares": -50, "createdTime": 4000},
+            {"id": "b3", "contractId": "m1", "outcome": "YES", "amount": 20, "shares": 40, "createdTime": 3000},
+            {"id": "b2", "contractId": "m2", "outcome": "NO", "amount": 25, "shares": 50, "createdTime": 2000},
+            {"id": "b1", "contractId": "m3", "outcome": "YES", "amount": 10, "shares": 25, "createdTime": 1000},
+            {"id": "b0", "contractId": "m4", "outcome": "NO", "amount": 5, "createdTime": 500}
+        ]"#;
+        let bets: Vec<UserBet> = serde_json::from_str(payload).unwrap();
+        let holdings = net_holdings(&bets);
+        // m2 was sold out and m4 never filled
+        assert_eq!(holdings.len(), 2);
+        assert_eq!(
+            holdings[0],
+            Holding {
+                contract_id: "m1".to_string(),
+                outcome: "YES".to_string(),
+                shares: 20.0,
+                cost: 8.0,
+                last_bet_at: 5000,
+            }
+        );
+        assert_eq!(holdings[1].contract_id, "m3");
+
+        let mut pnl = PnlTracker::default();
+        pnl.record(LedgerEntry {
+            resolution: Some("YES".to_string()),
+            ..imported_entry(&holdings[1], "Already settled", 1.0)
+        });
+        let entries = holdings
+            .iter()
+            .map(|h| imported_entry(h, &h.contract_id, 0.5))
+            .collect();
+        // m3 is already in the ledger, so only m1 is added
+        assert_eq!(pnl.seed(entries), 1);
+        assert_eq!(pnl.open_contract_ids(), ["m1"]);
+        assert_eq!(pnl.open_exposure(), 8.0);
+        assert_eq!(pnl.entries()[1].placed_at, 5);
+        assert_eq!(pnl.entries()[1].strategy, Strategy::Imported);
+        // Imported prices aren't predictions
+        assert_eq!(pnl.calibration(usize::MAX).samples, 0);
+        // ... and they're the user's to manage
+        assert!(pnl.positions().is_empty());
+    }
+
+    #[test]
+    fn test_decision_line_on_skips() {
+        let broadcast = new_contract("Will X happen?", 0.5);
+        // Each way an analysis can end short of an order, with its reason
+        let cached = |text: &str| Some(text.to_string());
+        let cases = [
+            (BotConfig::default(), 1.0, None, "xAI research failed"),
+            (
+                BotConfig::default(),
+                0.0,
+                cached(r#"{"action":"skip","reasoning":"Vague"}"#),
+                "Skip: Vague",
+            ),
+            (
+                BotConfig::default(),
+                0.0,
+                cached("I cannot say."),
+                "Unparseable xAI response",
+            ),
+            (
+                BotConfig {
+                    prediction_bounds: Some(PredictionBounds {
+                        lower: 0.0,
+                        upper: 0.01,
+                        skip: true,
+                    }),
+                    ..BotConfig::default()
+                },
+                0.0,
+                None,
+                "Prediction outside prediction_bounds",
+            ),
+        ];
+        for (config, error_rate, cached, reason) in cases {
+            let config = BotConfig {
+                reuse_analysis_by_question: true,
+                ..config
+            };
+            let simulation = Simulation {
+                error_rate,
+                ..instant_simulation()
+            };
+            let (ctx, mut channels) = test_context(config, simulation);
+            if let Some(text) = cached {
+                let result = SearchResult {
+                    text,
+                    sources: Vec::new(),
+                };
+                ctx.analyses.lock().unwrap().insert(
+                    "Will X happen?",
+                    None,
+                    &result,
+                    now_epoch_secs(),
+                );
+            }
+            block_on(handle_new_market(&ctx, &broadcast));
+            let decisions: Vec<String> = channels
+                .logs()
+                .into_iter()
+                .filter(|line| line.contains("DECISION "))
+                .collect();
+            assert_eq!(decisions.len(), 1, "{reason}");
+            assert!(decisions[0].contains("skipped"), "{}", decisions[0]);
+            assert!(decisions[0].contains(reason), "{}", decisions[0]);
+        }
+    }
+
+    #[test]
+    fn test_filtered_markets_log_a_decision() {
+        let decisions = |channels: &mut TestChannels| -> Vec<String> {
+            channels
+                .logs()
+                .into_iter()
+                .filter(|line| line.contains("DECISION "))
+                .collect()
+        };
+        let bet = BetData {
+            contract_id: "filtered".to_string(),
+            prob_before: 0.5,
+            prob_after: 0.3,
+            created_time: 0,
+        };
+        let cases = [
+            (
+                BotConfig {
+                    min_liquidity: 1e9,
+                    ..BotConfig::default()
+                },
+                0.0,
+                "Liquidity below min_liquidity",
+            ),
+            (
+                BotConfig {
+                    tradable_outcome_types: vec!["MULTIPLE_CHOICE".to_string()],
+                    ..BotConfig::default()
+                },
+                0.0,
+                "BINARY market",
+            ),
+            (BotConfig::default(), 1.0, "Failed to fetch market"),
+        ];
+        for (config, error_rate, reason) in cases {
+            let simulation = Simulation {
+                error_rate,
+                ..instant_simulation()
+            };
+            let (ctx, mut channels) = test_context(config, simulation);
+            block_on(handle_bet_triggered(&ctx, &bet));
+            let decisions = decisions(&mut channels);
+            assert_eq!(decisions.len(), 1, "{reason}");
+            assert!(decisions[0].contains(reason), "{}", decisions[0]);
+        }
+
+        let config = BotConfig {
+            creator_filter: Some(CreatorFilter {
+                min_account_age_days: 0.0,
+                min_balance: 100.0,
+            }),
+            ..BotConfig::default()
+        };
+        let (ctx, mut channels) = test_context(config, instant_simulation());
+        block_on(handle_new_market(
+            &ctx,
+            &new_contract("Will X happen?", 0.5),
+        ));
+        let decisions = decisions(&mut channels);
+        assert_eq!(decisions.len(), 1);
+        assert!(
+            decisions[0].contains("Untrusted creator"),
+            "{}",
+            decisions[0]
+        );
+    }
+
+    #[test]
+    fn test_exposure_check_caches_balance() {
+        let config = BotConfig {
+            max_total_exposure_fraction: Some(0.5),
+            ..BotConfig::default()
+        };
+        let (ctx, _channels) = test_context(config, instant_simulation());
+        let start = Instant::now();
+        ctx.balance.lock().unwrap().set(100.0, start);
+
+        // Answered from the cache: no `/me` call (which would fail here)
+        assert!(block_on(check_can_place(&ctx, 50.0, "a", "Q")).is_ok());
+        assert!(block_on(check_can_place(&ctx, 60.0, "a", "Q")).is_err());
+
+        // Bets placed since the fetch come off the cached balance
+        let mut cache = ctx.balance.lock().unwrap();
+        cache.spend(30.0);
+        assert_eq!(cache.get(start), Some(70.0));
+        // ... until it's stale
+        assert_eq!(cache.get(start + BALANCE_TTL), None);
+    }
+
+    #[test]
+    fn test_paused_analysis_is_held() {
+        let (ctx, mut channels) = test_context(BotConfig::default(), instant_simulation());
+        ctx.analysis_paused.store(true, Ordering::SeqCst);
+        // Far more markets than the failed list keeps, some seen twice
+        for i in 0..MAX_FAILED * 2 {
+            let mut broadcast = new_contract("Will X happen?", 0.5);
+            broadcast.contract.id = format!("m{}", i % (MAX_FAILED + 5));
+            block_on(handle_new_market(&ctx, &broadcast));
+        }
+
+        // None lost as failures, none reached xAI, each held once
+        assert!(ctx.failed.lock().unwrap().is_empty());
+        assert_eq!(ctx.counters.lock().unwrap().xai_calls, 0);
+        assert_eq!(ctx.held.lock().unwrap().len(), MAX_FAILED + 5);
+        assert!(!channels
+            .logs()
+            .iter()
+            .any(|line| line.contains("DECISION ")));
+
+        // A failure while analysis runs is still a failure
+        ctx.analysis_paused.store(false, Ordering::SeqCst);
+        assert!(!hold_if_paused(
+            &ctx,
+            AnalysisJob::NewMarket(Box::new(new_contract("Q", 0.5)))
+        ));
+    }
+
+    #[test]
+    fn test_held_analysis_releases_its_claim() {
+        let (ctx, _channels) = test_context(BotConfig::default(), instant_simulation());
+        let run = |id: &str| {
+            let mut broadcast = new_contract("Will X happen?", 0.5);
+            broadcast.contract.id = id.to_string();
+            assert!(ctx.analyzed.lock().unwrap().claim(id));
+            let job = AnalysisJob::NewMarket(Box::new(broadcast));
+            block_on(async {
+                let mut tasks = JoinSet::new();
+                start_analysis(&ctx, &mut tasks, job, Duration::ZERO, None);
+                while tasks.join_next().await.is_some() {}
+            });
+        };
+
+        ctx.analysis_paused.store(true, Ordering::SeqCst);
+        run("held-1");
+        // Not analyzed, so resuming (or the next event) can claim it again
+        assert!(!ctx.analyzed.lock().unwrap().contains("held-1"));
+        assert_eq!(ctx.held.lock().unwrap().len(), 1);
+
+        ctx.analysis_paused.store(false, Ordering::SeqCst);
+        run("decided-1");
+        let stats = ctx.analyzed.lock().unwrap().stats();
+        assert_eq!((stats.markets, stats.in_progress), (1, 0));
+    }
+
+    #[test]
+    fn test_resumed_analyses_wait_for_a_worker() {
+        let (ctx, mut channels) = test_context(BotConfig::default(), instant_simulation());
+        ctx.analysis_paused.store(true, Ordering::SeqCst);
+        for id in ["r1", "r2", "r3"] {
+            let mut broadcast = new_contract("Will X happen?", 0.5);
+            broadcast.contract.id = id.to_string();
+            block_on(handle_new_market(&ctx, &broadcast));
+        }
+        // Analyzed by a fresh event while it was held
+        ctx.analyzed
+            .lock()
+            .unwrap()
+            .complete("r3", now_epoch_secs());
+        ctx.analysis_paused.store(false, Ordering::SeqCst);
+
+        let mut queue = AnalysisQueue::default();
+        resume_held(&ctx, &mut queue);
+        // Queued (and claimed) rather than started, so the worker limit holds
+        assert_eq!(queue.len(), 2);
+        assert!(ctx.held.lock().unwrap().is_empty());
+        assert_eq!(ctx.analyzed.lock().unwrap().stats().in_progress, 2);
+
+        let slots = Arc::new(tokio::sync::Semaphore::new(1));
+        let mut warm_up = WarmUpThrottle::default();
+        block_on(async {
+            let mut tasks = JoinSet::new();
+            while !queue.is_empty() {
+                let permit = slots.clone().acquire_owned().await.unwrap();
+                dispatch_analysis(&ctx, &mut tasks, &mut queue, &mut warm_up, permit);
+            }
+            while tasks.join_next().await.is_some() {}
+        });
+        let stats = ctx.analyzed.lock().unwrap().stats();
+        assert_eq!((stats.markets, stats.in_progress), (3, 0));
+        // Each rerun reached its decision: a bet for the executor
+        let orders = std::iter::from_fn(|| channels.order_rx.try_recv().ok()).count();
+        assert_eq!(orders, 2);
+    }
+
+    #[test]
+    fn test_retry_claims_the_market() {
+        let (ctx, mut channels) = test_context(BotConfig::default(), instant_simulation());
+        let slots = tokio::sync::Semaphore::new(1);
+        let mut queue = AnalysisQueue::default();
+        let job = AnalysisJob::NewMarket(Box::new(new_contract("Will X happen?", 0.5)));
+        record_failure(&ctx, job.clone(), "Will X happen?", "xAI research failed");
+        ctx.analyzed
+            .lock()
+            .unwrap()
+            .complete("abc", now_epoch_secs());
+
+        // Running already: refused, and still listed for a later retry
+        assert!(ctx.analyzed.lock().unwrap().reclaim("abc"));
+        retry_failed(&ctx, "abc", &mut queue, &slots);
+        assert!(queue.is_empty());
+        assert_eq!(ctx.failed.lock().unwrap().len(), 1);
+        assert!(channels.logs().iter().any(|l| l.contains("not retried")));
+        ctx.analyzed.lock().unwrap().release("abc");
+
+        // Otherwise claimed and queued for a worker, not started
+        retry_failed(&ctx, "abc", &mut queue, &slots);
+        assert_eq!(queue.len(), 1);
+        assert!(ctx.failed.lock().unwrap().is_empty());
+        assert_eq!(ctx.analyzed.lock().unwrap().stats().in_progress, 1);
+
+        let mut warm_up = WarmUpThrottle::default();
+        block_on(async {
+            let permit = Arc::new(slots).acquire_owned().await.unwrap();
+            let mut tasks = JoinSet::new();
+            dispatch_analysis(&ctx, &mut tasks, &mut queue, &mut warm_up, permit);
+            while tasks.join_next().await.is_some() {}
+        });
+        let stats = ctx.analyzed.lock().unwrap().stats();
+        assert_eq!((stats.markets, stats.in_progress), (1, 0));
+        assert!(channels.logs().iter().any(|l| l.contains("Retrying")));
+    }
+
+    #[test]
+    fn test_replay_covers_every_xai_call() {
+        let path =
+            std::env::temp_dir().join(format!("xai-replay-bot-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = BotConfig {
+            confirmation: Some(Confirmation {
+                min_stake: 0.0,
+                ..Confirmation::default()
+            }),
+            ..BotConfig::default()
+        };
+        let session = |mode, simulated: bool| {
+            let (mut ctx, channels) = test_context(config.clone(), instant_simulation());
+            if !simulated {
+                ctx.simulator = None;
+            }
+            ctx.replay = Some(Arc::new(Mutex::new(XaiReplay::load_from(
+                mode,
+                path.clone(),
+            ))));
+            (ctx, channels)
+        };
+        let question = "Will X ship by June?";
+
+        // Record a decision, priced well away from the (simulated) prediction
+        let (ctx, mut recorded) = session(ReplayMode::Record, true);
+        let text = block_on(research(&ctx, question, None)).unwrap().text;
+        let Some(xai::PredictionResult::Predict(p)) = xai::parse_prediction(&text) else {
+            panic!("expected Predict: {text}");
+        };
+        let market_prob = if p.probability > 0.5 { 0.2 } else { 0.8 };
+        let broadcast = new_contract(question, market_prob);
+        block_on(handle_new_market(&ctx, &broadcast));
+        let original = recorded.order_rx.try_recv().unwrap();
+
+        // Replayed with no simulator and no live xAI: research and the
+        // confirmation both come from the recordings, and so does the bet
+        let (ctx, mut replayed) = session(ReplayMode::Replay, false);
+        block_on(handle_new_market(&ctx, &broadcast));
+        let order = replayed.order_rx.try_recv().unwrap();
+        let bet_json = |order: &BetOrder| serde_json::to_value(&order.bets).unwrap();
+        assert_eq!(bet_json(&order), bet_json(&original));
+        assert_eq!(ctx.counters.lock().unwrap().xai_calls, 0);
+        // The bet line follows the confirmation, with the final stake
+        let logs = replayed.logs();
+        let position = |text: &str| logs.iter().position(|line| line.contains(text));
+        let confirmed = position("confirmation for M$").unwrap();
+        let bet = position(" limit@").unwrap();
+        assert!(confirmed < bet);
+        let stake: f64 = order.bets.iter().map(|b| b.amount).sum();
+        assert!(logs[bet].contains(&format!("M${stake:.0} limit@")));
+
+        // Calls never recorded fail instead of reaching xAI
+        let missing = block_on(ask_xai(&ctx, question, None, XaiCall::Clarified("Vague")));
+        let _ = std::fs::remove_file(&path);
+        let Err(e) = missing else {
+            panic!("expected no recording");
+        };
+        assert!(e.to_string().contains("no recorded xAI clarified response"));
+    }
+
+    #[test]
+    fn test_research_fresh_bypasses_reuse() {
+        let config = BotConfig {
+            reuse_analysis_by_question: true,
+            ..BotConfig::default()
+        };
+        let (ctx, _channels) = test_context(config, instant_simulation());
+        let question = "Will X ship by June?";
+        let cached = SearchResult {
+            text: r#"{"action":"predict","probability":10,"reasoning":"Old news"}"#.to_string(),
+            sources: Vec::new(),
+        };
+        ctx.analyses
+            .lock()
+            .unwrap()
+            .insert(question, None, &cached, now_epoch_secs());
+
+        let reused = block_on(research(&ctx, question, None)).unwrap();
+        assert_eq!(reused.text, cached.text);
+        // Re-evaluation asks again, and the new answer replaces the old one
+        let fresh = block_on(research_fresh(&ctx, question, None)).unwrap();
+        assert_ne!(fresh.text, cached.text);
+        assert_eq!(
+            block_on(research(&ctx, question, None)).unwrap().text,
+            fresh.text
+        );
+    }
+
+    #[test]
+    fn test_imported_resolutions() {
+        let holding = Holding {
+            contract_id: "m".to_string(),
+            outcome: "YES".to_string(),
+            shares: 10.0,
+            cost: 5.0,
+            last_bet_at: 0,
+        };
+        let imported = |won: bool| LedgerEntry {
+            resolution: Some(if won { "YES" } else { "NO" }.to_string()),
+            ..imported_entry(&holding, "Q", 0.5)
+        };
+        let placed = |won| LedgerEntry {
+            strategy: Strategy::NewMarket,
+            prediction: 0.7,
+            ..imported(won)
+        };
+
+        // Manual results neither extend nor reset the bot's loss streak
+        assert_eq!(next_loss_streak(2, &imported(false)), 2);
+        assert_eq!(next_loss_streak(2, &imported(true)), 2);
+        assert_eq!(next_loss_streak(2, &placed(false)), 3);
+        assert_eq!(next_loss_streak(2, &placed(true)), 0);
+
+        // No made-up prediction in the log
+        assert_eq!(
+            resolution_line("NO", &imported(false)),
+            "Resolved NO: \"Q\" (imported YES position)"
+        );
+        assert!(resolution_line("NO", &placed(false)).contains("predicted 70%"));
+    }
+
+    #[test]
+    fn test_open_positions() {
+        let entry = |contract_id: &str| LedgerEntry {
+            contract_id: contract_id.to_string(),
+            question: contract_id.to_string(),
+            outcome: "YES".to_string(),
+            amount: 10.0,
+            filled: 10.0,
+            shares: 25.0,
+            limit_prob: 0.4,
+            prediction: 0.6,
+            market_prob: 0.4,
+            placed_at: 0,
+            resolution: None,
+            strategy: Strategy::NewMarket,
+            sold_for: None,
+            order_id: None,
+        };
+        let mut pnl = PnlTracker::default();
+        pnl.record(entry("a"));
+        pnl.record(entry("a"));
+        pnl.record(entry("b"));
+        // Two bets on one market are one position
+        let open = pnl.open_contract_ids();
+        assert_eq!(open, ["a", "b"]);
+
+        // At the cap: no new markets, but held ones can still be bet
+        assert!(!positions_allow(&open, "c", 2));
+        assert!(positions_allow(&open, "a", 2));
+        assert!(positions_allow(&open, "c", 3));
+
+        // A resolution or sale frees a slot
+        pnl.resolve("a", "YES");
+        assert!(positions_allow(&pnl.open_contract_ids(), "c", 2));
+        pnl.record(entry("c"));
+        assert!(!positions_allow(&pnl.open_contract_ids(), "d", 2));
+        pnl.close("b", "YES", 12.0);
+        assert_eq!(pnl.open_contract_ids(), ["c"]);
+        assert!(positions_allow(&pnl.open_contract_ids(), "d", 2));
+    }
+
+    #[test]
+    fn test_generate_daily_report() {
+        // 2026-03-01
+        let day = 20_513;
+        let start = day * SECS_PER_DAY;
+        let entry =
+            |question: &str, outcome: &str, placed_at, resolution: Option<&str>| LedgerEntry {
+                contract_id: question.to_string(),
+                question: question.to_string(),
+                outcome: outcome.to_string(),
+                amount: 10.0,
+                filled: 10.0,
+                shares: 25.0,
+                limit_prob: 0.4,
+                prediction: 0.6,
+                market_prob: 0.4,
+                placed_at,
+                resolution: resolution.map(str::to_string),
+                strategy: Strategy::NewMarket,
+                sold_for: None,
+                order_id: None,
+            };
+
+        let mut pnl = PnlTracker::default();
+        pnl.record(entry("yesterday", "YES", start - 1, None));
+        pnl.record(entry("a", "YES", start + 10, None));
+        pnl.record(entry("b", "NO", start + 20, None));
+
+        let mut counters = CounterState::new(day);
+        counters.markets_seen = 12;
+        counters.markets_analyzed = 4;
+        counters.xai_calls = 5;
+        counters.xai_cost = 0.25;
+        counters.resolved = vec![
+            entry("won", "YES", 0, Some("YES")),
+            entry("lost", "NO", 0, Some("YES")),
+            entry("cancelled", "NO", 0, Some("CANCEL")),
+        ];
+        counters.skips.push(SkippedMarket {
+            question: "Will I like it?".to_string(),
+            reason: "Subjective".to_string(),
+        });
+
+        let report = generate_daily_report(&pnl, &counters);
+        assert_eq!(report.date, "2026-03-01");
+        assert_eq!(report.bets_placed, 2);
+        assert_eq!(report.total_staked, 20.0);
+        assert_eq!(report.resolved, 3);
+        assert_eq!(report.realized_pnl, 5.0);
+        assert_eq!(report.win_rate, Some(0.5));
+        assert_eq!(report.top_winners.len(), 1);
+        assert_eq!(report.top_winners[0].question, "won");
+        assert_eq!(report.top_winners[0].pnl, 15.0);
+        assert_eq!(report.top_losers.len(), 1);
+        assert_eq!(report.top_losers[0].question, "lost");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["markets_seen"], 12);
+        assert_eq!(json["xai_cost"], 0.25);
+        assert_eq!(json["skipped"][0]["reason"], "Subjective");
+        assert!(report.to_string().contains("1 skipped"));
+        assert!(!report.to_string().contains("empty"));
+        counters.empty_responses = 2;
+        let report = generate_daily_report(&pnl, &counters);
+        assert!(report.to_string().ends_with(", 2 empty xAI responses"));
+
+        // Quiet day
+        let empty = generate_daily_report(&PnlTracker::default(), &CounterState::new(day));
+        assert_eq!(empty.win_rate, None);
+        assert!(empty.top_winners.is_empty());
+    }
+
+    #[test]
+    fn test_require_sources() {
+        let predict =
+            || xai::parse_prediction(r#"{"action":"predict","probability":80,"reasoning":"x"}"#);
+        let is_predict =
+            |r: Option<xai::PredictionResult>| matches!(r, Some(xai::PredictionResult::Predict(_)));
+
+        let mut config = BotConfig::default();
+        assert!(is_predict(require_sources(predict(), 0, &config)));
+
+        config.min_sources = Some(2);
+        assert!(!is_predict(require_sources(predict(), 0, &config)));
+        assert!(!is_predict(require_sources(predict(), 1, &config)));
+        assert!(is_predict(require_sources(predict(), 2, &config)));
+        assert!(is_predict(require_sources(predict(), 5, &config)));
+
+        match require_sources(predict(), 1, &config) {
+            Some(xai::PredictionResult::Skip(reason)) => {
+                assert!(reason.starts_with("only 1 source(s) for a 80% prediction"))
+            }
+            _ => panic!("expected Skip"),
+        }
+        // Skips and parse failures pass through
+        let skip = xai::parse_prediction(r#"{"action":"skip","reasoning":"Subjective"}"#);
+        assert!(matches!(
+            require_sources(skip, 0, &config),
+            Some(xai::PredictionResult::Skip(_))
+        ));
+        assert!(require_sources(None, 0, &config).is_none());
+    }
+
+    #[test]
+    fn test_require_reasoning() {
+        let parse = |reasoning: &str| {
+            xai::parse_prediction(&format!(
+                r#"{{"action":"predict","probability":80,"reasoning":"{reasoning}"}}"#
+            ))
+        };
+        let is_predict =
+            |r: Option<xai::PredictionResult>| matches!(r, Some(xai::PredictionResult::Predict(_)));
+        let mut config = BotConfig::default();
+        // Off by default
+        assert!(is_predict(require_reasoning(parse(""), &config)));
+
+        config.min_reasoning_len = Some(20);
+        match require_reasoning(parse("  "), &config) {
+            Some(xai::PredictionResult::Skip(reason)) => assert_eq!(
+                reason,
+                "reasoning too short for a 80% prediction (< 20 chars): No reasoning provided"
+            ),
+            _ => panic!("expected Skip"),
+        }
+        assert!(!is_predict(require_reasoning(parse("Likely."), &config)));
+        // Surrounding whitespace doesn't count towards the length
+        assert!(!is_predict(require_reasoning(
+            parse("   Likely yes.      "),
+            &config
+        )));
+        assert!(is_predict(require_reasoning(
+            parse("CEO confirmed the launch date"),
+            &config
+        )));
+        assert!(is_predict(require_reasoning(
+            parse("Exactly twenty chars"),
+            &config
+        )));
+
+        // Skips pass through untouched
+        let skip = xai::parse_prediction(r#"{"action":"skip","reasoning":""}"#);
+        assert!(matches!(
+            require_reasoning(skip, &config),
+            Some(xai::PredictionResult::Skip(r)) if r.is_empty()
+        ));
+        assert!(require_reasoning(None, &config).is_none());
+    }
+
+    #[test]
+    fn test_require_information() {
+        let parse = |probability: u32, reasoning: &str| {
+            xai::parse_prediction(&format!(
+                r#"{{"action":"predict","probability":{probability},"reasoning":"{reasoning}"}}"#
+            ))
+        };
+        let is_predict =
+            |r: Option<xai::PredictionResult>| matches!(r, Some(xai::PredictionResult::Predict(_)));
+        let mut config = BotConfig::default();
+
+        // Extremes on a no-info market are skipped even though the nominal edge
+        // against a defaulted 50% market is 50 points
+        for probability in [0, 100, 50] {
+            match require_information(parse(probability, "Little info found on X"), &config) {
+                Some(xai::PredictionResult::Skip(reason)) => {
+                    assert!(reason.starts_with(&format!("{probability}% prediction on little")))
+                }
+                _ => panic!("expected Skip for {probability}%"),
+            }
+        }
+        let flagged = xai::parse_prediction(
+            r#"{"action":"predict","probability":100,"reasoning":"x","low_information":true}"#,
+        );
+        assert!(!is_predict(require_information(flagged, &config)));
+
+        // Informed extremes still predict
+        assert!(is_predict(require_information(
+            parse(100, "Officially announced"),
+            &config
+        )));
+        assert!(is_predict(require_information(
+            parse(0, "Cancelled"),
+            &config
+        )));
+
+        // Skips and parse failures pass through; the check can be turned off
+        let skip = xai::parse_prediction(r#"{"action":"skip","reasoning":"No info"}"#);
+        assert!(matches!(
+            require_information(skip, &config),
+            Some(xai::PredictionResult::Skip(_))
+        ));
+        assert!(require_information(None, &config).is_none());
+        config.skip_low_information = false;
+        assert!(is_predict(require_information(
+            parse(100, "Little info found on X"),
+            &config
+        )));
+    }
+
+    #[test]
+    fn test_closed_before_analysis() {
+        let now = 1_700_000_000_000;
+        let mut config = BotConfig::default();
+        assert!(closed_before_analysis(Some(now - 60_000), now, &config));
+        assert!(closed_before_analysis(Some(now), now, &config));
+        assert!(!closed_before_analysis(Some(now + 60_000), now, &config));
+        // No close time: never closes
+        assert!(!closed_before_analysis(None, now, &config));
+
+        config.skip_closed_markets = false;
+        assert!(!closed_before_analysis(Some(now - 60_000), now, &config));
+    }
+
+    #[test]
+    fn test_config_update() {
+        let current = BotConfig::default();
+        assert_eq!(current.validate(), Ok(()));
+        // Research params are validated like every other setting
+        let hot = BotConfig {
+            research: ResearchParams {
+                temperature: Some(3.0),
+                ..ResearchParams::default()
+            },
+            ..BotConfig::default()
+        };
+        assert!(hot.validate().unwrap_err().contains("temperature"));
+        let contract = |prob: f64| -> ContractData {
+            serde_json::from_value(serde_json::json!({
+                "id": "b",
+                "slug": "will-x-happen",
+                "question": "Will X happen?",
+                "outcomeType": "BINARY",
+                "mechanism": "cpmm-1",
+                "visibility": "public",
+                "createdTime": 1_700_000_000_000u64,
+                "isResolved": false,
+                "probability": prob,
+                "totalLiquidity": 500.0,
+            }))
+            .unwrap()
+        };
+        let now_ms = 1_700_000_000_000;
+        assert_eq!(
+            listing_skip((&contract(0.9)).into(), now_ms, &current, None),
+            None
+        );
+
+        let new = BotConfig {
+            min_liquidity: 200.0,
+            tradable_price_range: (0.2, 0.8),
+            max_open_positions: Some(1),
+            analysis_workers: Some(8),
+            ..BotConfig::default()
+        };
+        let (updated, ignored) = prepare_config_update(&current, new).unwrap();
+        // The next event is judged by the new thresholds
+        assert!(
+            listing_skip((&contract(0.9)).into(), now_ms, &updated, None)
+                .unwrap()
+                .0
+                .contains("outside the tradable range")
+        );
+        assert_eq!(
+            listing_skip((&contract(0.5)).into(), now_ms, &updated, None),
+            None
+        );
+        // Startup-only settings keep their running values
+        assert_eq!(ignored, ["analysis_workers"]);
+        assert_eq!(updated.analysis_workers, current.analysis_workers);
+
+        // The executor, started with the old config, picks up the new one
+        let (mut ctx, _channels) = test_context(current.clone(), instant_simulation());
+        ctx.pnl.lock().unwrap().record(LedgerEntry {
+            contract_id: "a".to_string(),
+            question: "a".to_string(),
+            outcome: "YES".to_string(),
+            amount: 10.0,
+            filled: 10.0,
+            shares: 25.0,
+            limit_prob: 0.4,
+            prediction: 0.6,
+            market_prob: 0.4,
+            placed_at: 0,
+            resolution: None,
+            strategy: Strategy::NewMarket,
+            sold_for: None,
+            order_id: None,
+        });
+        let (config_tx, mut configs) = tokio::sync::watch::channel(current.clone());
+        refresh_config(&mut ctx, &mut configs);
+        assert!(block_on(check_can_place(&ctx, 10.0, "b", "b")).is_ok());
+        config_tx.send(updated.clone()).unwrap();
+        refresh_config(&mut ctx, &mut configs);
+        assert!(block_on(check_can_place(&ctx, 10.0, "b", "b"))
+            .unwrap_err()
+            .starts_with("position cap"));
+
+        let changes = config_changes(&current, &updated);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.contains(&format!(
+            "min_liquidity: {} -> 200.0",
+            serde_json::json!(current.min_liquidity)
+        )));
+        assert!(changes
+            .iter()
+            .any(|c| c.starts_with("tradable_price_range: ")));
+        assert!(config_changes(&updated, &updated).is_empty());
+
+        // Invalid configs are rejected whole
+        for invalid in [
+            BotConfig {
+                bet_amount: -5.0,
+                ..BotConfig::default()
+            },
+            BotConfig {
+                min_edge: 1.5,
+                ..BotConfig::default()
+            },
+            BotConfig {
+                tradable_price_range: (0.8, 0.2),
+                ..BotConfig::default()
+            },
+            BotConfig {
+                max_stake_liquidity_fraction: Some(0.0),
+                ..BotConfig::default()
+            },
+            BotConfig {
+                min_bet_amount: 10.0,
+                max_bet_amount: Some(5.0),
+                ..BotConfig::default()
+            },
+            BotConfig {
+                ws: ws::WsConfig {
+                    ping_interval_secs: 30,
+                    stale_timeout_secs: 30,
+                },
+                ..BotConfig::default()
+            },
+            BotConfig {
+                resolution_poll_secs: 0,
+                ..BotConfig::default()
+            },
+            BotConfig {
+                calibration_breaker: Some(CalibrationBreaker {
+                    min_samples: 30,
+                    z_threshold: 2.0,
+                    window: 20,
+                }),
+                ..BotConfig::default()
+            },
+            BotConfig {
+                ws: ws::WsConfig {
+                    ping_interval_secs: 0,
+                    stale_timeout_secs: 60,
+                },
+                ..BotConfig::default()
+            },
+        ] {
+            assert!(prepare_config_update(&current, invalid.clone()).is_err());
+            // ... and refused at startup, before anything is spawned
+            let mut orchestrator = Orchestrator::new(String::new(), String::new(), invalid);
+            assert!(orchestrator.start().is_err());
+        }
+    }
+
+    #[test]
+    fn test_price_tradable() {
+        let mut config = BotConfig::default();
+        for prob in [Some(0.01), Some(0.5), Some(0.99), None] {
+            assert!(price_tradable(prob, &config));
+        }
+
+        config.tradable_price_range = (0.2, 0.8);
+        assert!(price_tradable(Some(0.2), &config));
+        assert!(price_tradable(Some(0.55), &config));
+        assert!(price_tradable(Some(0.8), &config));
+        assert!(!price_tradable(Some(0.1), &config));
+        assert!(!price_tradable(Some(0.95), &config));
+        // Unknown prices are treated as 50%
+        assert!(price_tradable(None, &config));
+        config.tradable_price_range = (0.6, 0.9);
+        assert!(!price_tradable(None, &config));
+    }
+
+    #[test]
+    fn test_required_edge_scales_with_close_time() {
+        let mut config = BotConfig::default();
+        // Flat by default
+        assert_eq!(required_edge(Some(3650.0), &config), 0.10);
+
+        config.min_edge_per_year = 0.05;
+        let close = |days: f64| required_edge(Some(days), &config);
+        assert_eq!(required_edge(None, &config), 0.10);
+        assert_eq!(close(0.0), 0.10);
+        assert!((close(1.0) - 0.10).abs() < 0.001);
+        assert!((close(182.5) - 0.125).abs() < 1e-9);
+        assert!((close(365.0) - 0.15).abs() < 1e-9);
+        assert!((close(730.0) - 0.20).abs() < 1e-9);
+        // Already past close: no discount below min_edge
+        assert_eq!(close(-10.0), 0.10);
+
+        // A 16% edge clears a market closing tomorrow but not one two years out
+        let bets = |days| {
+            matches!(
+                decide_bet(0.66, 0.50, 0.0, 0.0, Some(days), &config),
+                BetDecision::Bet { .. }
+            )
+        };
+        assert!(bets(1.0));
+        assert!(bets(365.0));
+        assert!(!bets(730.0));
+
+        assert_eq!(
+            min_edge_note(Some(365.0), &config),
+            "15.0% min (365d to close)"
+        );
+        assert_eq!(min_edge_note(None, &config), "10% min");
+
+        let day_ms = 86_400_000;
+        assert_eq!(days_to_close(Some(3 * day_ms), day_ms), Some(2.0));
+        assert_eq!(days_to_close(Some(0), day_ms), Some(0.0));
+        assert_eq!(days_to_close(None, day_ms), None);
+    }
+
+    #[test]
+    fn test_liquidity_cap() {
+        let tiers = LiquidityTier::defaults();
+        assert_eq!(liquidity_cap(0.0, &tiers), Some(5.0));
+        assert_eq!(liquidity_cap(199.9, &tiers), Some(5.0));
+        // Thresholds are exclusive
+        assert_eq!(liquidity_cap(200.0, &tiers), Some(25.0));
+        assert_eq!(liquidity_cap(999.0, &tiers), Some(25.0));
+        assert_eq!(liquidity_cap(1000.0, &tiers), Some(50.0));
+        assert_eq!(liquidity_cap(1e9, &tiers), Some(50.0));
+
+        // Order in the config doesn't matter
+        let mut reversed = tiers.clone();
+        reversed.reverse();
+        assert_eq!(liquidity_cap(150.0, &reversed), Some(5.0));
+
+        // Above the last threshold (no catch-all tier) or no tiers: uncapped
+        assert_eq!(liquidity_cap(5000.0, &tiers[..2]), None);
+        assert_eq!(liquidity_cap(100.0, &[]), None);
+    }
+
+    #[test]
+    fn test_cap_stake_to_liquidity() {
+        let (log_tx, mut log_rx) = mpsc::unbounded_channel();
+        let mut config = BotConfig::default();
+        assert_eq!(liquidity_fraction_cap(100.0, &config), None);
+        assert_eq!(
+            cap_stake_to_liquidity(&log_tx, "q", 50.0, 100.0, &config),
+            50.0
+        );
+
+        config.max_stake_liquidity_fraction = Some(0.05);
+        for (amount, liquidity, expected) in [
+            // Small pool: the cap binds
+            (25.0, 100.0, 5.0),
+            (25.0, 400.0, 20.0),
+            // Deep pool or small stake: untouched
+            (25.0, 500.0, 25.0),
+            (25.0, 10_000.0, 25.0),
+            (2.0, 100.0, 2.0),
+            // Unknown liquidity isn't capped
+            (25.0, 0.0, 25.0),
+        ] {
+            let capped = cap_stake_to_liquidity(&log_tx, "q", amount, liquidity, &config);
+            assert!(
+                (capped - expected).abs() < 1e-9,
+                "M${amount} into M${liquidity}: {capped}"
+            );
+        }
+
+        // Only binding caps are logged
+        let logs: Vec<_> = std::iter::from_fn(|| log_rx.try_recv().ok()).collect();
+        assert_eq!(logs.len(), 2);
+        assert!(
+            matches!(&logs[0], BotLogEntry::Info(m) if m == "[q] stake M$25 capped to M$5 (5% of M$100 liquidity)")
+        );
+    }
+
+    #[test]
+    fn test_bet_triggered_mode() {
+        let config = BotConfig::default();
+        // A bet pushed the market from 40% to 60%
+        let bet = BetData {
+            contract_id: "c".to_string(),
+            prob_before: 0.40,
+            prob_after: 0.60,
+            created_time: 0,
+        };
+        let outcome = |prediction: f64| match decide_bet(prediction, 0.60, 10.0, 0.0, None, &config)
+        {
+            BetDecision::Bet { outcome, .. } => Some(outcome),
+            BetDecision::Skip { .. } => None,
+        };
+        let (reversion, momentum) = (BetTriggeredMode::Reversion, BetTriggeredMode::Momentum);
+
+        // Model thinks it overshot: reversion fades it, momentum stays out
+        assert_eq!(outcome(0.35), Some("NO"));
+        assert!(reversion.allows(&bet, 0.35, 0.60));
+        assert!(!momentum.allows(&bet, 0.35, 0.60));
+
+        // Model thinks it has further to go: both bet with it
+        assert_eq!(outcome(0.85), Some("YES"));
+        assert!(reversion.allows(&bet, 0.85, 0.60));
+        assert!(momentum.allows(&bet, 0.85, 0.60));
+
+        // Same on a downward move, and a flat "move" gives momentum nothing
+        let down = BetData {
+            prob_before: 0.60,
+            prob_after: 0.40,
+            ..bet.clone()
+        };
+        assert!(momentum.allows(&down, 0.15, 0.40));
+        assert!(!momentum.allows(&down, 0.70, 0.40));
+        let flat = BetData {
+            prob_before: 0.60,
+            ..bet.clone()
+        };
+        assert!(!momentum.allows(&flat, 0.85, 0.60));
+        assert!(reversion.allows(&flat, 0.85, 0.60));
+
+        assert_eq!(reversion.strategy(), Strategy::Reversion);
+        assert_eq!(momentum.strategy(), Strategy::Momentum);
+        assert_eq!(momentum.strategy().to_string(), "momentum");
+    }
+
+    #[test]
+    fn test_reversion_stake() {
+        let mut config = BotConfig::default();
+        // Flat without scaling
+        assert_eq!(reversion_stake(25.0, 0.40, 5000.0, &config), 25.0);
+
+        config.reversion_scaling = Some(EdgeScaling {
+            reference_edge: 0.10,
+            max_amount: 80.0,
+        });
+        // Proportional to how far the market overshot
+        assert!((reversion_stake(25.0, 0.10, 5000.0, &config) - 25.0).abs() < 1e-9);
+        assert!((reversion_stake(25.0, 0.20, 5000.0, &config) - 50.0).abs() < 1e-9);
+        assert!((reversion_stake(25.0, 0.05, 5000.0, &config) - 12.5).abs() < 1e-9);
+        // Clamped to the max, and still to the liquidity tier
+        assert_eq!(reversion_stake(25.0, 0.50, 5000.0, &config), 80.0);
+        config.liquidity_tiers = LiquidityTier::defaults();
+        assert_eq!(reversion_stake(25.0, 0.50, 500.0, &config), 25.0);
+        assert_eq!(reversion_stake(25.0, 0.50, 5000.0, &config), 50.0);
+
+        // ... and to the bet size limits
+        config.liquidity_tiers = Vec::new();
+        config.max_bet_amount = Some(40.0);
+        assert_eq!(reversion_stake(25.0, 0.50, 5000.0, &config), 40.0);
+        config.min_bet_amount = 20.0;
+        assert_eq!(reversion_stake(25.0, 0.05, 5000.0, &config), 20.0);
+
+        // A nonsensical reference edge leaves the stake alone
+        config.reversion_scaling = Some(EdgeScaling {
+            reference_edge: 0.0,
+            max_amount: 80.0,
+        });
+        assert_eq!(reversion_stake(25.0, 0.50, 5000.0, &config), 25.0);
+    }
+
+    #[test]
+    fn test_bet_size() {
+        let mut config = BotConfig::default();
+        assert_eq!(bet_size(10.0, 1000.0, &config), 10.0);
+
+        config.sizing = SizingStrategy::BalanceFraction(0.02);
+        assert!((bet_size(10.0, 1000.0, &config) - 20.0).abs() < 1e-9);
+        // Floor
+        config.min_bet_amount = 5.0;
+        assert_eq!(bet_size(10.0, 100.0, &config), 5.0);
+        // Ceiling
+        config.max_bet_amount = Some(50.0);
+        assert_eq!(bet_size(10.0, 10_000.0, &config), 50.0);
+
+        // The sized stake still goes through the exposure cap
+        let stake = bet_size(10.0, 1000.0, &config);
+        assert!(exposure_allows(0.0, stake, 1000.0, 0.05));
+        assert!(!exposure_allows(40.0, stake, 1000.0, 0.05));
+    }
+
+    #[test]
+    fn test_next_order_ceiling() {
+        let fixed = BotConfig {
+            bet_amount: 10.0,
+            reversion_amount: 25.0,
+            ..BotConfig::default()
+        };
+        assert_eq!(next_order_ceiling(5.0, &fixed), 25.0);
+        // Scales with the balance it's judged against, so a small refill
+        // recovers rather than waiting for the flat amounts
+        let fraction = BotConfig {
+            sizing: SizingStrategy::BalanceFraction(0.1),
+            min_bet_amount: 1.0,
+            ..fixed.clone()
+        };
+        assert_eq!(next_order_ceiling(20.0, &fraction), 2.0);
+        assert_eq!(next_order_ceiling(5.0, &fraction), 1.0);
+        let impact = BotConfig {
+            sizing: SizingStrategy::TargetImpact(0.02),
+            max_bet_amount: Some(40.0),
+            ..fixed.clone()
+        };
+        assert_eq!(next_order_ceiling(5.0, &impact), 40.0);
+        let uncapped = BotConfig {
+            max_bet_amount: None,
+            ..impact
+        };
+        assert_eq!(next_order_ceiling(1e6, &uncapped), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_size_for_impact() {
+        // Price of the bought side after `amount` goes into the pool
+        let price_after = |price: f64, amount: f64, liquidity: f64| {
+            let y = liquidity * ((1.0 - price) / price).sqrt();
+            let n = liquidity * (price / (1.0 - price)).sqrt();
+            let shares = cpmm_buy_shares(price, amount, liquidity);
+            let (y, n) = (y + amount - shares, n + amount);
+            n / (y + n)
+        };
+
+        // 50% -> 60% on M$100 liquidity: 100 * (sqrt(1.5) - 1)
+        let amount = size_for_impact(0.10, 100.0, 0.5);
+        assert!((amount - 100.0 * (1.5f64.sqrt() - 1.0)).abs() < 1e-9);
+        for (impact, liquidity, price) in
+            [(0.02, 500.0, 0.3), (0.05, 50.0, 0.8), (0.01, 5000.0, 0.1)]
+        {
+            let amount = size_for_impact(impact, liquidity, price);
+            assert!((price_after(price, amount, liquidity) - (price + impact)).abs() < 1e-9);
+        }
+        // Deeper pools take proportionally more for the same move
+        assert!(
+            (size_for_impact(0.02, 1000.0, 0.4) / size_for_impact(0.02, 100.0, 0.4) - 10.0).abs()
+                < 1e-9
+        );
+
+        assert_eq!(size_for_impact(0.02, 0.0, 0.5), 0.0);
+        // Unreachable targets are sized to the highest price instead
+        let capped = size_for_impact(0.10, 100.0, 0.95);
+        assert!(capped.is_finite());
+        assert!((price_after(0.95, capped, 100.0) - MAX_IMPACT_PRICE).abs() < 1e-9);
+        assert_eq!(size_for_impact(0.05, 100.0, 0.99), 0.0);
+
+        let config = BotConfig {
+            min_bet_amount: 5.0,
+            max_bet_amount: Some(50.0),
+            ..BotConfig::default()
+        };
+        assert_eq!(impact_size(0.001, 100.0, 0.5, &config), 5.0);
+        assert_eq!(impact_size(0.10, 100.0, 0.95, &config), 50.0);
+
+        // Without a stake cap the config is rejected, and a runaway stake is
+        // never bet
+        let uncapped = BotConfig {
+            sizing: SizingStrategy::TargetImpact(0.05),
+            ..BotConfig::default()
+        };
+        assert!(uncapped.validate().is_err());
+        assert!(BotConfig {
+            max_stake_liquidity_fraction: Some(0.1),
+            ..uncapped.clone()
+        }
+        .validate()
+        .is_ok());
+        for amount in [f64::INFINITY, f64::NAN] {
+            assert!(matches!(
+                decide_bet(0.9, 0.5, amount, 100.0, None, &BotConfig::default()),
+                BetDecision::Skip { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_visibility_filter() {
+        let contract = |visibility: &str| -> ContractData {
+            serde_json::from_value(serde_json::json!({
+                "id": "abc",
+                "slug": "will-x-happen",
+                "question": "Will X happen?",
+                "outcomeType": "BINARY",
+                "mechanism": "cpmm-1",
+                "visibility": visibility,
+                "createdTime": 1_700_000_000_000u64,
+                "isResolved": false,
+                "probability": 0.5,
+                "totalLiquidity": 100.0,
+            }))
+            .unwrap()
+        };
+
+        let config = BotConfig::default();
+        assert!(visibility_allowed(&contract("public").visibility, &config));
+        assert!(!visibility_allowed(
+            &contract("unlisted").visibility,
+            &config
+        ));
+        assert!(!visibility_allowed(
+            &contract("private").visibility,
+            &config
+        ));
+        assert!(visibility_allowed("", &config));
+
+        let config = BotConfig {
+            trade_visibility: vec!["public".to_string(), "unlisted".to_string()],
+            ..BotConfig::default()
+        };
+        assert!(visibility_allowed(
+            &contract("unlisted").visibility,
+            &config
+        ));
+        assert!(!visibility_allowed(
+            &contract("private").visibility,
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_missing_liquidity() {
+        let original: NewContractBroadcast = serde_json::from_value(serde_json::json!({
+            "contract": {
+                "id": "abc",
+                "slug": "will-x-happen",
+                "question": "Will X happen?",
+                "outcomeType": "BINARY",
+                "mechanism": "cpmm-1",
+                "visibility": "public",
+                "createdTime": 1_700_000_000_000u64,
+                "isResolved": false,
+                "probability": 0.5,
+            },
+            "creator": {"id": "u1", "username": "alice", "name": "Alice"},
+        }))
+        .unwrap();
+        assert_eq!(original.contract.total_liquidity, None);
+
+        // Assume zero (default)
+        let config = BotConfig::default();
+        assert_eq!(known_liquidity(None, &config), Some(0.0));
+        assert_eq!(known_liquidity(Some(250.0), &config), Some(250.0));
+
+        // Fetch fallback
+        let config = BotConfig {
+            fetch_missing_liquidity: true,
+            ..BotConfig::default()
+        };
+        assert_eq!(known_liquidity(None, &config), None);
+        assert_eq!(known_liquidity(Some(250.0), &config), Some(250.0));
+
+        let market: Market = serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "question": "Will X happen?",
+            "url": "https://manifold.markets/bob/will-x-happen",
+            "probability": 0.5,
+            "outcomeType": "BINARY",
+            "mechanism": "cpmm-1",
+            "isResolved": false,
+            "creatorUsername": "bob",
+            "totalLiquidity": 400.0,
+        }))
+        .unwrap();
+        let fetched = with_fetched_liquidity(original.clone(), Some(market));
+        assert_eq!(fetched.contract.total_liquidity, Some(400.0));
+        assert_eq!(fetched.creator.username, "alice");
+        assert_eq!(
+            known_liquidity(fetched.contract.total_liquidity, &config),
+            Some(400.0)
+        );
+
+        // A failed fetch falls back to zero rather than fetching forever
+        let failed = with_fetched_liquidity(original, None);
+        assert_eq!(
+            known_liquidity(failed.contract.total_liquidity, &config),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_disallowed_language() {
+        let mut config = BotConfig::default();
+        let spanish =
+            "¿Ganará el Real Madrid la Liga de Campeones de la UEFA al final de esta temporada?";
+        // No filter by default
+        assert_eq!(disallowed_language(spanish, &config), None);
+
+        config.allowed_languages = vec!["eng".to_string()];
+        assert_eq!(
+            disallowed_language(
+                "Will the Federal Reserve cut interest rates before the end of the year?",
+                &config
+            ),
+            None
+        );
+        assert_eq!(
+            disallowed_language(spanish, &config),
+            Some(whatlang::Lang::Spa)
+        );
+        assert_eq!(
+            disallowed_language(
+                "Превысит ли годовая инфляция в России восемь процентов к концу этого года, по данным Росстата?",
+                &config
+            ),
+            Some(whatlang::Lang::Rus)
+        );
+        assert_eq!(
+            disallowed_language("中国经济今年的增长率会超过百分之五吗？", &config),
+            Some(whatlang::Lang::Cmn)
+        );
+        // Nothing to detect
+        assert_eq!(disallowed_language("2025?", &config), None);
+
+        config.allowed_languages.push("spa".to_string());
+        assert_eq!(disallowed_language(spanish, &config), None);
+    }
+
+    #[test]
+    fn test_mechanism_filter() {
+        let contract = |mechanism: &str| -> ContractData {
+            serde_json::from_value(serde_json::json!({
+                "id": "abc",
+                "slug": "will-x-happen",
+                "question": "Will X happen?",
+                "outcomeType": "BINARY",
+                "mechanism": mechanism,
+                "visibility": "public",
+                "createdTime": 1_700_000_000_000u64,
+                "isResolved": false,
+                "probability": 0.5,
+                "totalLiquidity": 100.0,
+            }))
+            .unwrap()
+        };
+
+        let config = BotConfig::default();
+        assert!(mechanism_allowed(&contract("cpmm-1").mechanism, &config));
+        assert!(!mechanism_allowed(&contract("dpm-2").mechanism, &config));
+        assert!(!mechanism_allowed(
+            &contract("cpmm-multi-1").mechanism,
+            &config
+        ));
+        assert!(!mechanism_allowed(&contract("none").mechanism, &config));
+
+        let config = BotConfig {
+            trade_mechanisms: vec!["cpmm-1".to_string(), "dpm-2".to_string()],
+            ..BotConfig::default()
+        };
+        assert!(mechanism_allowed(&contract("dpm-2").mechanism, &config));
+    }
+
+    #[test]
+    fn test_outcome_type_filter() {
+        let config = BotConfig::default();
+        assert_eq!(outcome_type_skip_reason("BINARY", &config), None);
+        assert_eq!(
+            outcome_type_skip_reason("MULTIPLE_CHOICE", &config),
+            Some("one probability per answer, no single YES/NO price")
+        );
+        assert_eq!(
+            outcome_type_skip_reason("SOMETHING_NEW", &config),
+            Some("unrecognized outcome type")
+        );
+
+        // Opting in to another type
+        let config = BotConfig {
+            tradable_outcome_types: vec!["BINARY".to_string(), "PSEUDO_NUMERIC".to_string()],
+            ..BotConfig::default()
+        };
+        assert_eq!(outcome_type_skip_reason("PSEUDO_NUMERIC", &config), None);
+        assert!(outcome_type_skip_reason("MULTIPLE_CHOICE", &config).is_some());
+
+        // Opting out of binary
+        let config = BotConfig {
+            tradable_outcome_types: vec!["PSEUDO_NUMERIC".to_string()],
+            ..BotConfig::default()
+        };
+        assert_eq!(
+            outcome_type_skip_reason("BINARY", &config),
+            Some("not in tradable outcome types")
+        );
+    }
+}
@@ -0,0 +1,643 @@
+//! Where decided bets go: placed on Manifold, simulated, or emitted as JSON
+//! instructions for external execution (`BotConfig::execution`), optionally
+//! recorded to a file along the way. Cancels, sales and managrams go the same
+//! way, so no mode but `Manifold` touches the account.
+
+use crate::api::{BetRequest, BetResponse, ManagramRequest, ManifoldApi, SellRequest};
+use crate::bot::BotError;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Carries out the trades the bot decided on.
+pub trait ExecutionBackend: Send + Sync {
+    fn execute<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>>;
+
+    /// Cancel the unfilled remainder of a limit order.
+    fn cancel<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>>;
+
+    /// Sell shares back to the pool. The sale comes back as a bet of negative
+    /// `amount`.
+    fn sell<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>>;
+
+    /// Send `amount` mana to another user.
+    fn send_managram<'a>(
+        &'a self,
+        to_user_id: &'a str,
+        amount: f64,
+        message: &'a str,
+    ) -> BoxFuture<'a, Result<(), BotError>>;
+
+    /// Whether bets really reach Manifold, so their fills belong in the ledger.
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    /// Shown in the log for bets that aren't live.
+    fn name(&self) -> &'static str;
+}
+
+/// Which backend to use.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum ExecutionMode {
+    /// Place bets through the Manifold API.
+    #[default]
+    Manifold,
+    /// Don't place bets; pretend each filled in full at its limit price.
+    DryRun,
+    /// Don't place bets; write each as a JSON instruction instead.
+    Emit(EmitTarget),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EmitTarget {
+    /// `--headless` moves its log to stderr so stdout stays JSONL.
+    Stdout,
+    /// Appended to, so this may be a named pipe.
+    File(PathBuf),
+}
+
+pub struct ManifoldExecutor {
+    manifold: Arc<dyn ManifoldApi>,
+}
+
+impl ManifoldExecutor {
+    pub fn new(manifold: Arc<dyn ManifoldApi>) -> Self {
+        Self { manifold }
+    }
+}
+
+impl ExecutionBackend for ManifoldExecutor {
+    fn execute<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move { Ok(self.manifold.place_bet(request).await?) })
+    }
+
+    fn cancel<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async move { Ok(self.manifold.cancel_bet(bet_id).await?) })
+    }
+
+    fn sell<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move { Ok(self.manifold.sell_shares(contract_id, request).await?) })
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        to_user_id: &'a str,
+        amount: f64,
+        message: &'a str,
+    ) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async move {
+            Ok(self
+                .manifold
+                .send_managram(to_user_id, amount, message)
+                .await?)
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "live"
+    }
+}
+
+/// Fills every bet in full at its limit price without calling Manifold.
+#[derive(Default)]
+pub struct DryRunExecutor {
+    placed: AtomicU64,
+}
+
+/// The response Manifold would give if `request` filled completely at its
+/// limit price.
+fn simulated_fill(request: &BetRequest, id: u64) -> BetResponse {
+    let price = request
+        .limit_prob
+        .map(|limit| match request.outcome.as_str() {
+            "NO" => 1.0 - limit,
+            _ => limit,
+        });
+    BetResponse {
+        bet_id: Some(format!("dry-run-{id}")),
+        amount: Some(request.amount),
+        shares: price.filter(|p| *p > 0.0).map(|p| request.amount / p),
+        outcome: Some(request.outcome.clone()),
+        contract_id: Some(request.contract_id.clone()),
+        is_filled: Some(true),
+    }
+}
+
+impl ExecutionBackend for DryRunExecutor {
+    fn execute<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        let id = self.placed.fetch_add(1, Ordering::SeqCst) + 1;
+        Box::pin(async move { Ok(simulated_fill(request, id)) })
+    }
+
+    /// Dry-run bets fill at once, so there is never a remainder to cancel.
+    fn cancel<'a>(&'a self, _bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Proceeds are unknown without the pool, so the sale reports none.
+    fn sell<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move {
+            Ok(BetResponse {
+                shares: request.shares.map(|s| -s),
+                outcome: Some(request.outcome.clone()),
+                contract_id: Some(contract_id.to_string()),
+                ..BetResponse::default()
+            })
+        })
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        _to_user_id: &'a str,
+        _amount: f64,
+        _message: &'a str,
+    ) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "dry run"
+    }
+}
+
+/// Writes one JSON line per bet: the `POST /v0/bet` body exactly as the bot
+/// would send it, so an external executor can forward it unchanged.
+pub struct EmitExecutor {
+    target: EmitTarget,
+}
+
+impl EmitExecutor {
+    pub fn new(target: EmitTarget) -> Self {
+        Self { target }
+    }
+}
+
+/// Only bets are emitted; anything else is refused rather than sent to Manifold.
+fn not_emitted(action: &str) -> BotError {
+    BotError::Request(format!("{action} not sent: only bets are emitted"))
+}
+
+fn instruction_line(request: &BetRequest) -> Result<String, serde_json::Error> {
+    Ok(format!("{}\n", serde_json::to_string(request)?))
+}
+
+impl ExecutionBackend for EmitExecutor {
+    fn execute<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move {
+            let failed = |e: &dyn std::fmt::Display| BotError::Request(format!("emit failed: {e}"));
+            let line = instruction_line(request).map_err(|e| failed(&e))?;
+            match &self.target {
+                EmitTarget::Stdout => {
+                    let mut stdout = std::io::stdout().lock();
+                    stdout
+                        .write_all(line.as_bytes())
+                        .and_then(|()| stdout.flush())
+                        .map_err(|e| failed(&e))?;
+                }
+                EmitTarget::File(path) => {
+                    // Opening a pipe blocks until a reader attaches
+                    let path = path.clone();
+                    tokio::task::spawn_blocking(move || {
+                        std::fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(path)?
+                            .write_all(line.as_bytes())
+                    })
+                    .await
+                    .map_err(|e| failed(&e))?
+                    .map_err(|e| failed(&e))?;
+                }
+            }
+            // Handed off: whether and how much fills is unknown
+            Ok(BetResponse::default())
+        })
+    }
+
+    fn cancel<'a>(&'a self, _bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async { Err(not_emitted("cancel")) })
+    }
+
+    fn sell<'a>(
+        &'a self,
+        _contract_id: &'a str,
+        _request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async { Err(not_emitted("sale")) })
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        _to_user_id: &'a str,
+        _amount: f64,
+        _message: &'a str,
+    ) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async { Err(not_emitted("managram")) })
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "emitted"
+    }
+}
+
+/// Appends every request (bet, cancel, sale or managram) and its result to
+/// `executions.jsonl`, then passes the result on. Wraps any other backend.
+pub struct RecordingExecutor {
+    inner: Arc<dyn ExecutionBackend>,
+    path: PathBuf,
+}
+
+impl RecordingExecutor {
+    pub fn new(inner: Arc<dyn ExecutionBackend>) -> Self {
+        Self::with_path(inner, crate::bot::data_dir().join("executions.jsonl"))
+    }
+
+    fn with_path(inner: Arc<dyn ExecutionBackend>, path: PathBuf) -> Self {
+        Self { inner, path }
+    }
+
+    fn record<T: Serialize>(
+        &self,
+        action: &str,
+        request: &impl Serialize,
+        result: &Result<T, BotError>,
+    ) {
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(mut file) = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            let line = record_line(action, request, result, self.inner.name());
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+fn record_line<T: Serialize>(
+    action: &str,
+    request: &impl Serialize,
+    result: &Result<T, BotError>,
+    backend: &str,
+) -> String {
+    let (response, error) = match result {
+        Ok(resp) => (Some(resp), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    let record = serde_json::json!({
+        "at": crate::bot::now_epoch_secs(),
+        "action": action,
+        "backend": backend,
+        "request": request,
+        "response": response,
+        "error": error,
+    });
+    format!("{record}\n")
+}
+
+impl ExecutionBackend for RecordingExecutor {
+    fn execute<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move {
+            let result = self.inner.execute(request).await;
+            self.record("bet", request, &result);
+            result
+        })
+    }
+
+    fn cancel<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async move {
+            let result = self.inner.cancel(bet_id).await;
+            self.record("cancel", &serde_json::json!({ "betId": bet_id }), &result);
+            result
+        })
+    }
+
+    fn sell<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move {
+            let result = self.inner.sell(contract_id, request).await;
+            let recorded = serde_json::json!({
+                "contractId": contract_id,
+                "outcome": request.outcome,
+                "shares": request.shares,
+            });
+            self.record("sell", &recorded, &result);
+            result
+        })
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        to_user_id: &'a str,
+        amount: f64,
+        message: &'a str,
+    ) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(async move {
+            let result = self.inner.send_managram(to_user_id, amount, message).await;
+            let recorded = ManagramRequest {
+                amount,
+                to_ids: vec![to_user_id.to_string()],
+                message: message.to_string(),
+            };
+            self.record("managram", &recorded, &result);
+            result
+        })
+    }
+
+    fn is_live(&self) -> bool {
+        self.inner.is_live()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+}
+
+/// The backend for `mode`, recording every bet if `record` is set.
+pub fn backend(
+    mode: &ExecutionMode,
+    record: bool,
+    manifold: Arc<dyn ManifoldApi>,
+) -> Arc<dyn ExecutionBackend> {
+    let backend: Arc<dyn ExecutionBackend> = match mode {
+        ExecutionMode::Manifold => Arc::new(ManifoldExecutor::new(manifold)),
+        ExecutionMode::DryRun => Arc::new(DryRunExecutor::default()),
+        ExecutionMode::Emit(target) => Arc::new(EmitExecutor::new(target.clone())),
+    };
+    if record {
+        Arc::new(RecordingExecutor::new(backend))
+    } else {
+        backend
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+    use std::sync::Mutex;
+
+    fn request() -> BetRequest {
+        BetRequest {
+            contract_id: "abc123".to_string(),
+            amount: 25.0,
+            outcome: "NO".to_string(),
+            limit_prob: Some(0.6),
+            expires_at: None,
+            expires_millis_after: Some(1000),
+        }
+    }
+
+    /// Answers from a script and remembers what it was asked.
+    struct MockExecutor {
+        results: Mutex<Vec<Result<BetResponse, BotError>>>,
+        seen: Mutex<Vec<String>>,
+    }
+
+    impl ExecutionBackend for MockExecutor {
+        fn execute<'a>(
+            &'a self,
+            request: &'a BetRequest,
+        ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+            self.seen.lock().unwrap().push(request.contract_id.clone());
+            let result = self.results.lock().unwrap().remove(0);
+            Box::pin(async move { result })
+        }
+
+        fn cancel<'a>(&'a self, bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+            self.seen.lock().unwrap().push(format!("cancel {bet_id}"));
+            Box::pin(async { Ok(()) })
+        }
+
+        fn sell<'a>(
+            &'a self,
+            contract_id: &'a str,
+            _request: &'a SellRequest,
+        ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(format!("sell {contract_id}"));
+            Box::pin(async { Ok(BetResponse::default()) })
+        }
+
+        fn send_managram<'a>(
+            &'a self,
+            to_user_id: &'a str,
+            _amount: f64,
+            _message: &'a str,
+        ) -> BoxFuture<'a, Result<(), BotError>> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(format!("managram {to_user_id}"));
+            Box::pin(async { Ok(()) })
+        }
+
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+    }
+
+    #[test]
+    fn test_backend_selection() {
+        let manifold: Arc<dyn ManifoldApi> =
+            Arc::new(crate::api::ManifoldClient::new(String::new()));
+        assert!(backend(&ExecutionMode::Manifold, false, manifold.clone()).is_live());
+        assert!(!backend(&ExecutionMode::DryRun, false, manifold.clone()).is_live());
+        let emit = backend(&ExecutionMode::Emit(EmitTarget::Stdout), true, manifold);
+        assert!(!emit.is_live());
+        assert_eq!(emit.name(), "emitted");
+    }
+
+    #[test]
+    fn test_dry_run_fills_at_limit() {
+        let executor = DryRunExecutor::default();
+        let resp = executor
+            .execute(&request())
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(resp.bet_id.as_deref(), Some("dry-run-1"));
+        assert_eq!(resp.amount, Some(25.0));
+        assert_eq!(resp.is_filled, Some(true));
+        // NO at a 60% limit costs 40 per share
+        assert!((resp.shares.unwrap() - 62.5).abs() < 1e-9);
+
+        let yes = BetRequest {
+            outcome: "YES".to_string(),
+            ..request()
+        };
+        let resp = executor.execute(&yes).now_or_never().unwrap().unwrap();
+        assert_eq!(resp.bet_id.as_deref(), Some("dry-run-2"));
+        assert!((resp.shares.unwrap() - 25.0 / 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_instruction_matches_request() {
+        let line = instruction_line(&request()).unwrap();
+        assert!(line.ends_with('\n'));
+        let emitted: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(emitted, serde_json::to_value(request()).unwrap());
+        assert_eq!(
+            emitted,
+            serde_json::json!({
+                "contractId": "abc123",
+                "amount": 25.0,
+                "outcome": "NO",
+                "limitProb": 0.6,
+                "expiresMillisAfter": 1000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_recording_passes_results_through() {
+        let path =
+            std::env::temp_dir().join(format!("executions_test_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mock = Arc::new(MockExecutor {
+            results: Mutex::new(vec![
+                Ok(BetResponse {
+                    bet_id: Some("b1".to_string()),
+                    ..BetResponse::default()
+                }),
+                Err(BotError::MarketClosed),
+            ]),
+            seen: Mutex::new(Vec::new()),
+        });
+        let executor = RecordingExecutor::with_path(mock.clone(), path.clone());
+        assert!(executor.is_live());
+
+        let resp = executor.execute(&request()).now_or_never().unwrap();
+        assert_eq!(resp.unwrap().bet_id.as_deref(), Some("b1"));
+        let err = executor.execute(&request()).now_or_never().unwrap();
+        assert_eq!(err.unwrap_err(), BotError::MarketClosed);
+        assert_eq!(*mock.seen.lock().unwrap(), vec!["abc123", "abc123"]);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let records: Vec<serde_json::Value> = written
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["backend"], "mock");
+        assert_eq!(
+            records[0]["request"],
+            serde_json::to_value(request()).unwrap()
+        );
+        assert_eq!(records[0]["response"]["betId"], "b1");
+        assert!(records[0]["error"].is_null());
+        assert!(records[1]["response"].is_null());
+        assert_eq!(records[1]["error"], "market is closed");
+        assert_eq!(records[0]["action"], "bet");
+    }
+
+    #[test]
+    fn test_recording_covers_every_action() {
+        let path = std::env::temp_dir().join(format!(
+            "executions_actions_test_{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mock = Arc::new(MockExecutor {
+            results: Mutex::new(Vec::new()),
+            seen: Mutex::new(Vec::new()),
+        });
+        let executor = RecordingExecutor::with_path(mock.clone(), path.clone());
+        let sale = SellRequest {
+            outcome: "YES".to_string(),
+            shares: Some(12.0),
+        };
+        executor.cancel("b1").now_or_never().unwrap().unwrap();
+        executor.sell("c1", &sale).now_or_never().unwrap().unwrap();
+        executor
+            .send_managram("vault", 50.0, "Profit sweep")
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            *mock.seen.lock().unwrap(),
+            vec!["cancel b1", "sell c1", "managram vault"]
+        );
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let records: Vec<serde_json::Value> = written
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let actions: Vec<_> = records.iter().map(|r| r["action"].clone()).collect();
+        assert_eq!(actions, ["cancel", "sell", "managram"]);
+        assert_eq!(records[0]["request"]["betId"], "b1");
+        assert_eq!(records[1]["request"]["shares"], 12.0);
+        assert_eq!(records[2]["request"]["toIds"][0], "vault");
+    }
+
+    #[test]
+    fn test_non_live_backends_keep_off_the_account() {
+        let sale = SellRequest {
+            outcome: "NO".to_string(),
+            shares: Some(3.0),
+        };
+        let dry_run = DryRunExecutor::default();
+        assert!(dry_run.cancel("b1").now_or_never().unwrap().is_ok());
+        let sold = dry_run.sell("c1", &sale).now_or_never().unwrap().unwrap();
+        assert_eq!((sold.amount, sold.shares), (None, Some(-3.0)));
+
+        // Emitting only hands off bets; anything else is refused
+        let emit = EmitExecutor::new(EmitTarget::Stdout);
+        let err = emit.cancel("b1").now_or_never().unwrap().unwrap_err();
+        assert!(err.to_string().contains("cancel not sent"));
+        assert!(emit
+            .send_managram("vault", 1.0, "")
+            .now_or_never()
+            .unwrap()
+            .is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Display-less entry point (`--headless`): runs the bot from env keys and
+//! prints its log to stdout, or to stderr when bet instructions are emitted
+//! on stdout.
+
+use crate::api::{is_auth_error, ManifoldClient};
+use crate::bot::{self, BotLogEntry, BotStatus};
+use crate::execution::{EmitTarget, ExecutionMode};
+
+/// Where the log goes. With `Emit(Stdout)` stdout carries only the JSON bet
+/// instructions, so an executor can read it as JSONL.
+#[derive(Clone, Copy)]
+struct Console {
+    stderr: bool,
+}
+
+impl Console {
+    fn for_config(config: &bot::BotConfig) -> Self {
+        Self {
+            stderr: config.execution == ExecutionMode::Emit(EmitTarget::Stdout),
+        }
+    }
+
+    fn line(self, text: std::fmt::Arguments) {
+        if self.stderr {
+            eprintln!("{text}");
+        } else {
+            println!("{text}");
+        }
+    }
+}
+
+pub fn run() {
+    let manifold_key = std::env::var("MANIFOLD_API_KEY").unwrap_or_default();
+    let xai_key = std::env::var("XAI_API_KEY").unwrap_or_default();
+    if manifold_key.is_empty() || xai_key.is_empty() {
+        eprintln!("--headless requires MANIFOLD_API_KEY and XAI_API_KEY");
+        std::process::exit(1);
+    }
+    let config = match bot::BotConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid bot config: {e}");
+            std::process::exit(1);
+        }
+    };
+    let console = Console::for_config(&config);
+
+    let runtime = tokio::runtime::Runtime::new().expect("failed to start tokio runtime");
+    runtime.block_on(async move {
+        match ManifoldClient::new(manifold_key.clone())
+            .get_me_with_retry()
+            .await
+        {
+            Ok(user) => console.line(format_args!(
+                "Logged in as {} (M${:.0})",
+                user.name, user.balance
+            )),
+            Err(e) if is_auth_error(&e) => {
+                eprintln!("Invalid Manifold API key: {e}");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Could not reach Manifold: {e}");
+                std::process::exit(1);
+            }
+        }
+
+        let mut orchestrator = bot::Orchestrator::new(manifold_key, xai_key, config);
+        let mut handles = match orchestrator.start() {
+            Ok(handles) => handles,
+            Err(e) => {
+                eprintln!("Invalid bot config: {e}");
+                std::process::exit(1);
+            }
+        };
+
+        let shutdown = tokio::signal::ctrl_c();
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                Some(entry) = handles.logs.recv() => print_entry(console, &entry),
+                Some(status) = handles.status.recv() => match status {
+                    BotStatus::LossStreak { losses, paused } => {
+                        if losses > 0 || paused {
+                            console.line(format_args!("[STATUS] loss streak {losses}{}", if paused { ", PAUSED" } else { "" }));
+                        }
+                    }
+                    // Failures are already in the log; there's no retry button here
+                    // Closing-soon markets are logged as they're flagged
+                    BotStatus::Heartbeat
+                    | BotStatus::Analyzing(_)
+                    | BotStatus::Failed(_)
+                    | BotStatus::ClosingSoon(_)
+                    | BotStatus::Cache(_) => {}
+                    BotStatus::InsufficientBalance(Some(balance)) => {
+                        console.line(format_args!("[STATUS] insufficient balance (M${balance:.0})"));
+                    }
+                    BotStatus::InsufficientBalance(None) => console.line(format_args!("[STATUS] balance recovered")),
+                    // The pause itself is logged; the rate is only for the dashboard
+                    BotStatus::Xai { .. } => {}
+                    BotStatus::State(state) => console.line(format_args!("[STATUS] {state}")),
+                    BotStatus::Diagnostics(snapshot) => console.line(format_args!("[DIAGNOSTICS] {snapshot}")),
+                    BotStatus::NeedsHuman(m) => {
+                        console.line(format_args!(
+                            "[NEEDS HUMAN] \"{}\" ({}, M${:.0} liq): {}",
+                            m.question, m.contract_id, m.liquidity, m.reason
+                        ));
+                    }
+                },
+                // The bot logs everything it acts on; raw feed events are dropped
+                Some(_) = handles.ws_events.recv() => {}
+                _ = &mut shutdown => {
+                    console.line(format_args!("Interrupted, shutting down"));
+                    orchestrator.stop();
+                    break;
+                }
+            }
+        }
+    });
+}
+
+fn print_entry(console: Console, entry: &BotLogEntry) {
+    match entry {
+        BotLogEntry::Info(s) => console.line(format_args!("[INFO] {s}")),
+        BotLogEntry::Trade(s) => console.line(format_args!("[TRADE] {s}")),
+        BotLogEntry::Error(s) => eprintln!("[ERROR] {s}"),
+        BotLogEntry::Shadow(s) => console.line(format_args!("[SHADOW] {s}")),
+    }
+}
@@ -1,16 +1,36 @@
+mod analysis_cache;
 mod api;
 mod bot;
+mod execution;
+mod headless;
+mod paper;
+mod pnl;
+mod questions;
+mod sanity;
+mod selftest;
+mod simulate;
+#[cfg(feature = "sqlite")]
+mod store;
+mod trace;
 #[allow(dead_code)]
 mod ws;
 mod xai;
+mod xai_health;
+mod xai_replay;
 
-use bot::BotLogEntry;
+use bot::{BotCommand, BotLogEntry, BotStatus};
 use dioxus::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 const MAIN_CSS: Asset = asset!("/assets/main.css");
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
+/// Oldest needs-human entries are dropped past this many.
+const MAX_NEEDS_HUMAN: usize = 50;
 
 #[derive(Clone, PartialEq)]
 struct ManifoldKey(String);
@@ -18,15 +38,23 @@ struct ManifoldKey(String);
 #[derive(Clone, PartialEq)]
 struct XaiKey(String);
 
-#[derive(Clone, PartialEq)]
-enum ConnectionStatus {
-    Disconnected,
-    Connecting,
-    Connected,
+/// Consecutive resolved losses and whether the bot auto-paused trading.
+#[derive(Clone, Copy, PartialEq, Default)]
+struct LossStreak {
+    losses: u32,
+    paused: bool,
 }
 
 fn main() {
     dotenvy::dotenv().ok();
+    if std::env::args().any(|a| a == "--headless") {
+        headless::run();
+        return;
+    }
+    if std::env::args().any(|a| a == "--self-test") {
+        selftest::run();
+        return;
+    }
     dioxus::LaunchBuilder::new()
         .with_cfg(desktop! {
             dioxus::desktop::Config::new().with_menu(None)
@@ -42,28 +70,40 @@ fn App() -> Element {
     let api_key = use_signal(|| ManifoldKey(env_manifold.clone()));
     let xai_key = use_signal(|| XaiKey(env_xai.clone()));
     let mut user_info = use_signal(|| None::<api::User>);
-    let connection_status = use_signal(|| ConnectionStatus::Disconnected);
+    let bot_state = use_signal(bot::BotState::default);
     let log_entries = use_signal(Vec::<BotLogEntry>::new);
     let ws_events = use_signal(Vec::<String>::new);
+    let loss_streak = use_signal(LossStreak::default);
 
     use_context_provider(|| api_key);
     use_context_provider(|| xai_key);
     use_context_provider(|| user_info);
-    use_context_provider(|| connection_status);
+    use_context_provider(|| bot_state);
     use_context_provider(|| log_entries);
     use_context_provider(|| ws_events);
+    use_context_provider(|| loss_streak);
 
-    // Auto-validate if keys came from .env
-    let mut auto_started = use_signal(|| false);
-    if !auto_started() && !env_manifold.is_empty() && !env_xai.is_empty() {
-        auto_started.set(true);
-        let mkey = env_manifold.clone();
+    // Auto-validate if keys came from .env; transient failures are retried,
+    // then shown with a retry button: (message, retryable)
+    let mut startup_error = use_signal(|| None::<(String, bool)>);
+    let mut connecting = use_signal(|| false);
+    let env_key = env_manifold.clone();
+    let mut connect_from_env = move || {
+        let client = api::ManifoldClient::new(env_key.clone());
+        connecting.set(true);
+        startup_error.set(None);
         spawn(async move {
-            let client = api::ManifoldClient::new(mkey);
-            if let Ok(user) = client.get_me().await {
-                user_info.set(Some(user));
+            match client.get_me_with_retry().await {
+                Ok(user) => user_info.set(Some(user)),
+                Err(e) => startup_error.set(Some((login_error(&e), !api::is_auth_error(&e)))),
             }
+            connecting.set(false);
         });
+    };
+    let mut auto_started = use_signal(|| false);
+    if !auto_started() && !env_manifold.is_empty() && !env_xai.is_empty() {
+        auto_started.set(true);
+        connect_from_env();
     }
 
     rsx! {
@@ -77,12 +117,38 @@ fn App() -> Element {
             if user_info.read().is_some() {
                 BotDashboard {}
             } else {
+                if connecting() {
+                    div { class: "bg-gray-800 rounded-lg p-4 mb-4 text-gray-300",
+                        "Connecting to Manifold..."
+                    }
+                }
+                if let Some((err, retryable)) = startup_error() {
+                    div { class: "bg-red-900 border border-red-500 rounded-lg p-4 mb-4 flex justify-between items-center",
+                        span { class: "text-red-200 font-medium", "{err}" }
+                        if retryable {
+                            button {
+                                class: "bg-red-600 hover:bg-red-700 px-4 py-1 rounded font-medium",
+                                onclick: move |_| connect_from_env(),
+                                "Retry"
+                            }
+                        }
+                    }
+                }
                 ApiKeyInput {}
             }
         }
     }
 }
 
+/// User-facing message for a failed `get_me`: a rejected key or an unreachable API.
+fn login_error(err: &reqwest::Error) -> String {
+    if api::is_auth_error(err) {
+        format!("Invalid Manifold API key: {err}")
+    } else {
+        format!("Could not reach Manifold: {err}")
+    }
+}
+
 #[component]
 fn ApiKeyInput() -> Element {
     let mut api_key = use_context::<Signal<ManifoldKey>>();
@@ -108,14 +174,14 @@ fn ApiKeyInput() -> Element {
         error.set(None);
         spawn(async move {
             let client = api::ManifoldClient::new(mkey.clone());
-            match client.get_me().await {
+            match client.get_me_with_retry().await {
                 Ok(user) => {
                     api_key.set(ManifoldKey(mkey));
                     xai_key.set(XaiKey(xkey));
                     user_info.set(Some(user));
                 }
                 Err(e) => {
-                    error.set(Some(format!("Invalid Manifold API key: {e}")));
+                    error.set(Some(login_error(&e)));
                 }
             }
             loading.set(false);
@@ -188,50 +254,123 @@ fn BotDashboard() -> Element {
     let api_key = use_context::<Signal<ManifoldKey>>();
     let xai_key = use_context::<Signal<XaiKey>>();
     let user_info = use_context::<Signal<Option<api::User>>>();
-    let mut connection_status = use_context::<Signal<ConnectionStatus>>();
+    let mut bot_state = use_context::<Signal<bot::BotState>>();
     let mut log_entries = use_context::<Signal<Vec<BotLogEntry>>>();
     let mut ws_events = use_context::<Signal<Vec<String>>>();
+    let mut loss_streak = use_context::<Signal<LossStreak>>();
+    let mut cmd_tx = use_signal(|| None::<mpsc::UnboundedSender<BotCommand>>);
+    let mut topic_tx = use_signal(|| None::<mpsc::UnboundedSender<ws::TopicCommand>>);
+    let mut last_heartbeat = use_signal(|| None::<Instant>);
+    let mut needs_human = use_signal(Vec::<bot::NeedsHumanMarket>::new);
+    let mut analyzing = use_signal(Vec::<bot::InFlightAnalysis>::new);
+    let mut failed = use_signal(Vec::<bot::FailedMarket>::new);
+    let mut short_balance = use_signal(|| None::<f64>);
+    let mut closing_soon = use_signal(Vec::<bot::ClosingMarket>::new);
+    let mut cache_stats = use_signal(bot::CacheStats::default);
+    let mut xai_health = use_signal(|| (xai_health::XaiHealthStats::default(), false));
+    // Re-render every second so "last activity" stays current
+    let mut now = use_signal(Instant::now);
+    use_hook(|| {
+        spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                now.set(Instant::now());
+            }
+        })
+    });
+    let loaded_config = use_hook(|| {
+        bot::BotConfig::load().map(|config| bot::BotConfig {
+            dev_tools: config.dev_tools || std::env::var("DEV_TOOLS").is_ok_and(|v| v == "1"),
+            ..config
+        })
+    });
+    let config = loaded_config.clone().unwrap_or_default();
+
+    // Shared with the unmount hook, so leaving the dashboard always stops the
+    // bot rather than relying on the UI task being dropped
+    let orchestrator = use_hook(|| Rc::new(RefCell::new(None::<bot::Orchestrator>)));
+    {
+        let orchestrator = orchestrator.clone();
+        use_drop(move || {
+            if let Some(mut orchestrator) = orchestrator.borrow_mut().take() {
+                orchestrator.stop();
+            }
+            // A remounted dashboard starts from a clean slate
+            bot_state.set(bot::BotState::NotStarted);
+        });
+    }
 
     let mut started = use_signal(|| false);
     if !started() {
         started.set(true);
         let mkey = api_key.read().0.clone();
         let xkey = xai_key.read().0.clone();
+        let config = config.clone();
+        let orchestrator_slot = orchestrator.clone();
         spawn(async move {
-            connection_status.set(ConnectionStatus::Connecting);
-
-            let manifold = api::ManifoldClient::new(mkey);
-            let xai = xai::XaiClient::new(xkey);
-
-            let (ws_internal_tx, mut ws_internal_rx) = mpsc::unbounded_channel::<ws::WsEvent>();
-            let (ws_to_bot_tx, ws_to_bot_rx) = mpsc::unbounded_channel::<ws::WsEvent>();
-            let (bot_log_tx, mut bot_log_rx) = mpsc::unbounded_channel::<BotLogEntry>();
+            if let Err(e) = loaded_config {
+                bot_state.set(bot::BotState::Halted(format!("invalid config: {e}")));
+                return;
+            }
+            bot_state.set(bot::BotState::Connecting);
 
-            tokio::spawn(ws::run_ws(ws_internal_tx));
+            let manifold = api::ManifoldClient::new(mkey.clone());
+            let mut orchestrator = bot::Orchestrator::new(mkey, xkey, config);
+            let mut handles = match orchestrator.start() {
+                Ok(handles) => handles,
+                Err(e) => {
+                    bot_state.set(bot::BotState::Halted(format!("invalid config: {e}")));
+                    return;
+                }
+            };
+            // Replacing a previous run's orchestrator drops (and stops) it
+            *orchestrator_slot.borrow_mut() = Some(orchestrator);
+            cmd_tx.set(Some(handles.commands.clone()));
+            topic_tx.set(Some(handles.topics.clone()));
 
-            let config = bot::BotConfig::default();
-            tokio::spawn(bot::run_bot(manifold, xai, ws_to_bot_rx, bot_log_tx, config));
+            // Questions of recently seen markets, so bet lines are readable. Unknown
+            // ids are fetched lazily, at most one request per second.
+            let mut questions = questions::QuestionCache::new(500);
+            let (fetched_tx, mut fetched_rx) =
+                mpsc::unbounded_channel::<(String, Option<String>)>();
+            let mut fetching: HashSet<String> = HashSet::new();
+            let mut last_fetch: Option<Instant> = None;
 
             loop {
                 tokio::select! {
-                    Some(event) = ws_internal_rx.recv() => {
+                    Some(event) = handles.ws_events.recv() => {
                         match &event {
-                            ws::WsEvent::Connected => {
-                                connection_status.set(ConnectionStatus::Connected);
-                            }
-                            ws::WsEvent::Disconnected => {
-                                connection_status.set(ConnectionStatus::Connecting);
-                            }
+                            // The bot folds the connection into `BotStatus::State`
+                            ws::WsEvent::Connected | ws::WsEvent::Disconnected => {}
                             ws::WsEvent::NewContract(b) => {
+                                questions.insert(b.contract.id.clone(), b.contract.question.clone());
                                 ws_events.write().push(format!(
                                     "New market: \"{}\" by {} [{}]",
                                     b.contract.question, b.creator.username, b.contract.outcome_type
                                 ));
                             }
-                            ws::WsEvent::NewBet(b) => {
+                            ws::WsEvent::NewBet(b, _) => {
+                                let market = match questions.get(&b.contract_id) {
+                                    Some(q) => format!("\"{q}\""),
+                                    None => {
+                                        let due = last_fetch
+                                            .is_none_or(|t| t.elapsed() >= Duration::from_secs(1));
+                                        if due && fetching.insert(b.contract_id.clone()) {
+                                            last_fetch = Some(Instant::now());
+                                            let manifold = manifold.clone();
+                                            let fetched_tx = fetched_tx.clone();
+                                            let id = b.contract_id.clone();
+                                            tokio::spawn(async move {
+                                                let question =
+                                                    manifold.get_market(&id).await.ok().map(|m| m.question);
+                                                let _ = fetched_tx.send((id, question));
+                                            });
+                                        }
+                                        format!("market {}", b.contract_id)
+                                    }
+                                };
                                 ws_events.write().push(format!(
-                                    "New bet: market {} (prob {:.0}% → {:.0}%)",
-                                    &b.contract_id[..8.min(b.contract_id.len())],
+                                    "New bet: {market} (prob {:.0}% → {:.0}%)",
                                     b.prob_before * 100.0,
                                     b.prob_after * 100.0,
                                 ));
@@ -239,38 +378,118 @@ fn BotDashboard() -> Element {
                             ws::WsEvent::Error(e) => {
                                 ws_events.write().push(format!("Error: {e}"));
                             }
+                            // Logged by the bot when dev tools are on
+                            ws::WsEvent::Debug(_) => {}
                         };
                         let len = ws_events.read().len();
                         if len > 200 {
                             ws_events.write().drain(0..len - 200);
                         }
-                        let _ = ws_to_bot_tx.send(event);
                     }
-                    Some(entry) = bot_log_rx.recv() => {
+                    Some((id, question)) = fetched_rx.recv() => {
+                        fetching.remove(&id);
+                        if let Some(question) = question {
+                            questions.insert(id, question);
+                        }
+                    }
+                    Some(entry) = handles.logs.recv() => {
                         log_entries.write().push(entry);
                         let len = log_entries.read().len();
                         if len > 200 {
                             log_entries.write().drain(0..len - 200);
                         }
                     }
+                    Some(status) = handles.status.recv() => match status {
+                        BotStatus::LossStreak { losses, paused } => {
+                            loss_streak.set(LossStreak { losses, paused });
+                        }
+                        BotStatus::Heartbeat => last_heartbeat.set(Some(Instant::now())),
+                        BotStatus::Analyzing(in_flight) => analyzing.set(in_flight),
+                        BotStatus::Failed(markets) => failed.set(markets),
+                        BotStatus::InsufficientBalance(balance) => short_balance.set(balance),
+                        BotStatus::ClosingSoon(markets) => closing_soon.set(markets),
+                        BotStatus::Cache(stats) => cache_stats.set(stats),
+                        BotStatus::Xai { stats, paused } => xai_health.set((stats, paused)),
+                        BotStatus::State(state) => bot_state.set(state),
+                        BotStatus::Diagnostics(snapshot) => {
+                            let text = serde_json::to_string_pretty(&snapshot).unwrap_or_default();
+                            let js = format!(
+                                "navigator.clipboard.writeText({})",
+                                serde_json::Value::String(text)
+                            );
+                            document::eval(&js);
+                            log_entries.write().push(BotLogEntry::Info(
+                                "Diagnostics copied to the clipboard".to_string(),
+                            ));
+                        }
+                        BotStatus::NeedsHuman(market) => {
+                            let mut queue = needs_human.write();
+                            queue.retain(|m| m.contract_id != market.contract_id);
+                            queue.push(market);
+                            if queue.len() > MAX_NEEDS_HUMAN {
+                                queue.remove(0);
+                            }
+                        }
+                    },
                     else => break,
                 }
             }
+            bot_state.set(bot::BotState::Stopped);
         });
     }
 
     let user = user_info.read();
     let user = user.as_ref().unwrap();
-    let status_text = match connection_status() {
-        ConnectionStatus::Disconnected => "Disconnected",
-        ConnectionStatus::Connecting => "Connecting...",
-        ConnectionStatus::Connected => "Connected",
+    let state = bot_state();
+    let status_color = match state {
+        bot::BotState::Running => "text-green-400",
+        bot::BotState::Connecting | bot::BotState::Paused(_) | bot::BotState::Degraded(_) => {
+            "text-yellow-400"
+        }
+        bot::BotState::Halted(_) => "text-red-400",
+        bot::BotState::NotStarted | bot::BotState::Stopped => "text-gray-400",
+    };
+    let streak = loss_streak();
+    // Re-rendered every second via `now`, read below for last activity
+    let epoch_now = bot::now_epoch_secs();
+    // Stale after missing a few heartbeats: the bot loop is stuck or gone
+    let (activity_text, activity_color) = match last_heartbeat() {
+        Some(at) => {
+            let secs = now().saturating_duration_since(at).as_secs();
+            let stale = secs > 3 * bot::HEARTBEAT_INTERVAL.as_secs();
+            (
+                format!("{secs}s ago"),
+                if stale {
+                    "text-red-400"
+                } else {
+                    "text-gray-300"
+                },
+            )
+        }
+        None => ("waiting...".to_string(), "text-yellow-400"),
     };
-    let status_color = match connection_status() {
-        ConnectionStatus::Disconnected => "text-red-400",
-        ConnectionStatus::Connecting => "text-yellow-400",
-        ConnectionStatus::Connected => "text-green-400",
+    let (xai_stats, analysis_paused) = xai_health();
+    let xai_warning = analysis_paused || xai_stats.is_spiking();
+    let xai_text = if xai_stats.calls == 0 {
+        "no calls yet".to_string()
+    } else {
+        format!(
+            "{:.0}% errors ({} calls)",
+            xai_stats.error_rate() * 100.0,
+            xai_stats.calls
+        )
     };
+    let xai_color = if xai_warning {
+        "text-red-400"
+    } else if xai_stats.errors > 0 {
+        "text-yellow-400"
+    } else {
+        "text-gray-300"
+    };
+    let xai_cause = xai_stats
+        .dominant_error()
+        .map(|kind| format!(", mostly {kind}"))
+        .unwrap_or_default();
 
     rsx! {
         div { class: "bg-gray-800 rounded-lg p-4 mb-4 flex justify-between items-center",
@@ -281,8 +500,175 @@ fn BotDashboard() -> Element {
                 span { class: "font-medium text-green-400", "M${user.balance:.0}" }
             }
             div {
+                span { class: "text-gray-400", "Loss streak: " }
+                span { class: "font-medium mr-4", "{streak.losses}" }
                 span { class: "text-gray-400", "Status: " }
-                span { class: "{status_color} font-medium", "{status_text}" }
+                span { class: "{status_color} font-medium", "{state}" }
+                span { class: "text-gray-400 ml-4", "Last activity: " }
+                span { class: "{activity_color} font-medium", "{activity_text}" }
+                span { class: "text-gray-400 ml-4", "xAI: " }
+                span { class: "{xai_color} font-medium", "{xai_text}" }
+                if config.daily_report {
+                    button {
+                        class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-sm ml-4",
+                        onclick: move |_| {
+                            if let Some(tx) = cmd_tx.read().as_ref() {
+                                let _ = tx.send(BotCommand::Report);
+                            }
+                        },
+                        "Report"
+                    }
+                }
+                button {
+                    class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-sm ml-4",
+                    onclick: move |_| {
+                        if let Some(tx) = cmd_tx.read().as_ref() {
+                            let _ = tx.send(BotCommand::Diagnostics);
+                        }
+                    },
+                    "Copy diagnostics"
+                }
+                button {
+                    class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-sm ml-4",
+                    onclick: move |_| match bot::BotConfig::load() {
+                        Ok(new) => {
+                            if let Some(tx) = cmd_tx.read().as_ref() {
+                                let _ = tx.send(BotCommand::UpdateConfig(Box::new(new)));
+                            }
+                        }
+                        Err(e) => log_entries
+                            .write()
+                            .push(BotLogEntry::Error(format!("Config reload failed: {e}"))),
+                    },
+                    "Reload config"
+                }
+            }
+        }
+
+        if streak.paused {
+            div { class: "bg-red-900 border border-red-500 rounded-lg p-4 mb-4 flex justify-between items-center",
+                span { class: "text-red-200 font-medium",
+                    "Trading paused after {streak.losses} consecutive losses"
+                }
+                button {
+                    class: "bg-red-600 hover:bg-red-700 px-4 py-1 rounded font-medium",
+                    onclick: move |_| {
+                        if let Some(tx) = cmd_tx.read().as_ref() {
+                            let _ = tx.send(BotCommand::Resume);
+                        }
+                    },
+                    "Resume trading"
+                }
+            }
+        }
+
+        if xai_warning {
+            div { class: "bg-red-900 border border-red-500 rounded-lg p-4 mb-4 flex justify-between items-center",
+                div {
+                    div { class: "text-red-200 font-medium",
+                        if analysis_paused {
+                            "Analysis paused: {xai_stats}{xai_cause}. Check the xAI key and credits."
+                        } else {
+                            "xAI errors spiking: {xai_stats}{xai_cause}"
+                        }
+                    }
+                    if let Some(error) = &xai_stats.last_error {
+                        div { class: "text-red-300 text-sm mt-1 truncate", "Last error: {error}" }
+                    }
+                }
+                if analysis_paused {
+                    button {
+                        class: "bg-red-600 hover:bg-red-700 px-4 py-1 rounded font-medium ml-4",
+                        onclick: move |_| {
+                            if let Some(tx) = cmd_tx.read().as_ref() {
+                                let _ = tx.send(BotCommand::ResumeAnalysis);
+                            }
+                        },
+                        "Resume analysis"
+                    }
+                }
+            }
+        }
+
+        if !analyzing.read().is_empty() {
+            AnalyzingPanel { analyses: analyzing(), now: epoch_now }
+        }
+
+        if !closing_soon.read().is_empty() {
+            div { class: "bg-gray-800 border border-yellow-600 rounded-lg p-4 mb-4",
+                h3 { class: "text-lg font-semibold mb-2 text-yellow-400",
+                    "Closing soon ({closing_soon.read().len()})"
+                }
+                for market in closing_soon.read().iter() {
+                    div {
+                        key: "{market.contract_id}",
+                        class: "flex justify-between text-sm py-0.5 border-b border-gray-700",
+                        span { "{market.question}" }
+                        span { class: "text-yellow-300 ml-4",
+                            "{market.close_time.saturating_sub(epoch_now) / 60}m left"
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(balance) = short_balance() {
+            div { class: "bg-orange-900 border border-orange-500 rounded-lg p-4 mb-4",
+                span { class: "text-orange-200 font-medium",
+                    if config.shrink_bets_to_balance {
+                        "Insufficient balance (M${balance:.0}): bets are reduced to fit until it recovers"
+                    } else {
+                        "Insufficient balance (M${balance:.0}): bets are held until it recovers"
+                    }
+                }
+            }
+        }
+
+        if !failed.read().is_empty() {
+            div { class: "bg-gray-800 border border-red-700 rounded-lg p-4 mb-4",
+                h3 { class: "text-lg font-semibold mb-2 text-red-400", "Failed ({failed.read().len()})" }
+                for (id, market) in failed.read().iter().rev().map(|m| (m.contract_id.clone(), m.clone())) {
+                    div {
+                        key: "{market.contract_id}",
+                        class: "flex justify-between items-center py-1 border-b border-gray-700 text-sm",
+                        div {
+                            span { class: "font-medium", "{market.question}" }
+                            span { class: "text-gray-400 ml-2", "{market.reason}" }
+                        }
+                        button {
+                            class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-xs ml-4",
+                            onclick: move |_| {
+                                if let Some(tx) = cmd_tx.read().as_ref() {
+                                    let _ = tx.send(BotCommand::Retry(id.clone()));
+                                }
+                            },
+                            "Retry"
+                        }
+                    }
+                }
+            }
+        }
+
+        if !needs_human.read().is_empty() {
+            div { class: "bg-gray-800 border border-yellow-600 rounded-lg p-4 mb-4",
+                h3 { class: "text-lg font-semibold mb-2 text-yellow-400", "Needs human review" }
+                for (id, market) in needs_human.read().iter().rev().map(|m| (m.contract_id.clone(), m.clone())) {
+                    div {
+                        key: "{market.contract_id}",
+                        class: "flex justify-between items-center py-1 border-b border-gray-700 text-sm",
+                        div {
+                            span { class: "font-medium", "{market.question}" }
+                            span { class: "text-gray-400 ml-2", "(M${market.liquidity:.0} liq) {market.reason}" }
+                        }
+                        button {
+                            class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-xs ml-4",
+                            onclick: move |_| {
+                                needs_human.write().retain(|m| m.contract_id != id);
+                            },
+                            "Dismiss"
+                        }
+                    }
+                }
             }
         }
 
@@ -290,6 +676,255 @@ fn BotDashboard() -> Element {
             EventFeed {}
             TradeLog {}
         }
+
+        CachePanel { stats: cache_stats(), now: epoch_now, cmd_tx }
+        TopicsPanel { initial: config.ws_topics.clone(), topic_tx }
+
+        if config.dev_tools {
+            BetPanel {}
+        }
+    }
+}
+
+/// Live WebSocket subscriptions, starting from `initial`.
+#[component]
+fn TopicsPanel(
+    initial: Vec<String>,
+    topic_tx: Signal<Option<mpsc::UnboundedSender<ws::TopicCommand>>>,
+) -> Element {
+    let mut topics = use_signal(|| initial);
+    let mut new_topic = use_signal(String::new);
+    let send = move |cmd: ws::TopicCommand| {
+        if let Some(tx) = topic_tx.read().as_ref() {
+            let _ = tx.send(cmd);
+        }
+    };
+
+    rsx! {
+        div { class: "bg-gray-800 rounded-lg p-4 mb-4 text-sm",
+            div { class: "flex flex-wrap items-center gap-2",
+                span { class: "text-gray-400", "Subscriptions: " }
+                for (topic, removed) in topics.read().iter().map(|t| (t.clone(), t.clone())) {
+                    span {
+                        key: "{topic}",
+                        class: "bg-gray-700 rounded px-2 py-1 font-mono text-xs",
+                        "{topic}"
+                        button {
+                            class: "text-gray-400 hover:text-red-400 ml-2",
+                            onclick: move |_| {
+                                topics.write().retain(|t| *t != removed);
+                                send(ws::TopicCommand::Unsubscribe(removed.clone()));
+                            },
+                            "×"
+                        }
+                    }
+                }
+                input {
+                    class: "bg-gray-700 rounded px-2 py-1 text-xs font-mono",
+                    placeholder: "global/new-comment",
+                    value: "{new_topic}",
+                    oninput: move |e| new_topic.set(e.value()),
+                }
+                button {
+                    class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-xs",
+                    disabled: new_topic.read().trim().is_empty(),
+                    onclick: move |_| {
+                        let topic = new_topic.read().trim().to_string();
+                        if !topics.read().contains(&topic) {
+                            topics.write().push(topic.clone());
+                            send(ws::TopicCommand::Subscribe(topic));
+                        }
+                        new_topic.set(String::new());
+                    },
+                    "Subscribe"
+                }
+            }
+        }
+    }
+}
+
+/// "3h 12m ago"-style age of an epoch-seconds timestamp.
+fn format_age(at: u64, now: u64) -> String {
+    let secs = now.saturating_sub(at);
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        _ => format!("{}h {}m ago", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Analyzed-cache size with controls to force re-analysis.
+#[component]
+fn CachePanel(
+    stats: bot::CacheStats,
+    now: u64,
+    cmd_tx: Signal<Option<mpsc::UnboundedSender<BotCommand>>>,
+) -> Element {
+    let mut forget_id = use_signal(String::new);
+    let send = move |cmd: BotCommand| {
+        if let Some(tx) = cmd_tx.read().as_ref() {
+            let _ = tx.send(cmd);
+        }
+    };
+    let range = match (stats.oldest, stats.newest) {
+        (Some(oldest), Some(newest)) => format!(
+            "oldest {}, newest {}",
+            format_age(oldest, now),
+            format_age(newest, now)
+        ),
+        _ => "empty".to_string(),
+    };
+
+    rsx! {
+        div { class: "bg-gray-800 rounded-lg p-4 mb-4 flex justify-between items-center text-sm",
+            div {
+                span { class: "text-gray-400", "Analyzed cache: " }
+                span { class: "font-medium", "{stats.markets} markets" }
+                span { class: "text-gray-400 ml-2", "({range}; {stats.in_progress} in progress)" }
+            }
+            div { class: "flex items-center gap-2",
+                input {
+                    class: "bg-gray-700 rounded px-2 py-1 text-xs",
+                    placeholder: "Market id",
+                    value: "{forget_id}",
+                    oninput: move |e| forget_id.set(e.value()),
+                }
+                button {
+                    class: "bg-gray-700 hover:bg-gray-600 px-3 py-1 rounded text-xs",
+                    disabled: forget_id.read().trim().is_empty(),
+                    onclick: move |_| {
+                        send(BotCommand::Forget(forget_id.read().trim().to_string()));
+                        forget_id.set(String::new());
+                    },
+                    "Forget"
+                }
+                button {
+                    class: "bg-red-800 hover:bg-red-700 px-3 py-1 rounded text-xs",
+                    onclick: move |_| send(BotCommand::ClearCache),
+                    "Clear all"
+                }
+            }
+        }
+    }
+}
+
+/// Markets with an xAI analysis in progress; `now` is epoch seconds.
+#[component]
+fn AnalyzingPanel(analyses: Vec<bot::InFlightAnalysis>, now: u64) -> Element {
+    rsx! {
+        div { class: "bg-gray-800 rounded-lg p-4 mb-4",
+            h3 { class: "text-lg font-semibold mb-2", "Analyzing now ({analyses.len()})" }
+            for (i, analysis) in analyses.iter().enumerate() {
+                div {
+                    key: "{i}",
+                    class: "flex justify-between text-sm py-0.5 border-b border-gray-700",
+                    span { "{analysis.question}" }
+                    span { class: "text-gray-400 ml-4",
+                        "{now.saturating_sub(analysis.started_at)}s"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Dev tool: place an arbitrary bet through `place_bet` and show the raw response.
+#[component]
+fn BetPanel() -> Element {
+    let api_key = use_context::<Signal<ManifoldKey>>();
+    let mut contract_id = use_signal(String::new);
+    let mut amount = use_signal(|| "1".to_string());
+    let mut outcome = use_signal(|| "YES".to_string());
+    let mut limit = use_signal(String::new);
+    let mut result = use_signal(|| None::<Result<String, String>>);
+    let mut loading = use_signal(|| false);
+
+    let submit = move |_| {
+        let amount = match amount.read().trim().parse::<f64>() {
+            Ok(a) if a > 0.0 => a,
+            _ => {
+                result.set(Some(Err("Amount must be a positive number".to_string())));
+                return;
+            }
+        };
+        let limit_prob = match limit.read().trim() {
+            "" => None,
+            l => match l.parse::<f64>() {
+                Ok(pct) if (1.0..=99.0).contains(&pct) => Some(pct / 100.0),
+                _ => {
+                    result.set(Some(Err(
+                        "Limit must be a percentage from 1 to 99".to_string()
+                    )));
+                    return;
+                }
+            },
+        };
+        let request = api::BetRequest {
+            contract_id: contract_id.read().trim().to_string(),
+            amount,
+            outcome: outcome(),
+            limit_prob,
+            expires_at: None,
+            expires_millis_after: None,
+        };
+        let client = api::ManifoldClient::new(api_key.read().0.clone());
+        loading.set(true);
+        spawn(async move {
+            let response = client
+                .place_bet_raw(&request)
+                .await
+                .map(|json| serde_json::to_string_pretty(&json).unwrap_or_default())
+                .map_err(|e| e.to_string());
+            result.set(Some(response));
+            loading.set(false);
+        });
+    };
+
+    let input_class = "bg-gray-700 text-white px-3 py-1 rounded border border-gray-600 focus:border-blue-500 focus:outline-none";
+
+    rsx! {
+        div { class: "bg-gray-800 rounded-lg p-4 mt-4 space-y-3",
+            h3 { class: "text-lg font-semibold", "Manual Bet (dev)" }
+            div { class: "flex gap-2",
+                input {
+                    class: "{input_class} flex-1",
+                    placeholder: "Contract id",
+                    value: "{contract_id}",
+                    oninput: move |e| contract_id.set(e.value()),
+                }
+                input {
+                    class: "{input_class} w-24",
+                    placeholder: "Amount",
+                    value: "{amount}",
+                    oninput: move |e| amount.set(e.value()),
+                }
+                select {
+                    class: input_class,
+                    value: "{outcome}",
+                    onchange: move |e| outcome.set(e.value()),
+                    option { value: "YES", "YES" }
+                    option { value: "NO", "NO" }
+                }
+                input {
+                    class: "{input_class} w-28",
+                    placeholder: "Limit % (opt)",
+                    value: "{limit}",
+                    oninput: move |e| limit.set(e.value()),
+                }
+                button {
+                    class: "bg-blue-600 hover:bg-blue-700 px-4 py-1 rounded font-medium disabled:opacity-50",
+                    disabled: loading(),
+                    onclick: submit,
+                    if loading() { "Placing..." } else { "Place bet" }
+                }
+            }
+            if let Some(Ok(json)) = result.read().as_ref() {
+                pre { class: "text-green-400 font-mono text-xs overflow-x-auto", "{json}" }
+            }
+            if let Some(Err(e)) = result.read().as_ref() {
+                pre { class: "text-red-400 font-mono text-xs whitespace-pre-wrap", "{e}" }
+            }
+        }
     }
 }
 
@@ -376,11 +1011,13 @@ fn TradeLog() -> Element {
                             BotLogEntry::Info(_) => "text-gray-300 py-0.5 border-b border-gray-700",
                             BotLogEntry::Trade(_) => "text-green-400 py-0.5 border-b border-gray-700",
                             BotLogEntry::Error(_) => "text-red-400 py-0.5 border-b border-gray-700",
+                            BotLogEntry::Shadow(_) => "text-purple-400 py-0.5 border-b border-gray-700",
                         },
                         {render_log_text(match entry {
                             BotLogEntry::Info(s) => s.as_str(),
                             BotLogEntry::Trade(s) => s.as_str(),
                             BotLogEntry::Error(s) => s.as_str(),
+                            BotLogEntry::Shadow(s) => s.as_str(),
                         })}
                     }
                 }
@@ -0,0 +1,386 @@
+//! Paper ledger, opt-in via `BotConfig::paper_ledger`: every decision the
+//! strategy made, placed or not, so its unconstrained record can be compared
+//! against what actually executed under the pause, exposure and balance limits.
+
+use crate::pnl::Strategy;
+use crate::trace::DecisionSummary;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One would-be decision. Never a real bet: `executed` says what the live
+/// bot actually did with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperEntry {
+    pub contract_id: String,
+    pub question: String,
+    pub strategy: Strategy,
+    pub prediction: Option<f64>,
+    pub market_prob: Option<f64>,
+    /// Side the strategy wanted; `None` if it chose not to bet.
+    pub outcome: Option<String>,
+    /// Stake the strategy wanted (mana), before any guardrail reduced it.
+    pub amount: f64,
+    pub limit_prob: Option<f64>,
+    /// The live result: `placed`, `partial`, `failed`, `blocked` or `skipped`.
+    pub executed: String,
+    pub reason: Option<String>,
+    /// Epoch seconds.
+    pub decided_at: u64,
+    /// `YES`, `NO`, `MKT` or `CANCEL` once the market resolves.
+    pub resolution: Option<String>,
+}
+
+impl PaperEntry {
+    pub fn from_summary(summary: &DecisionSummary, decided_at: u64) -> Self {
+        Self {
+            contract_id: summary.contract_id.clone(),
+            question: summary.question.clone(),
+            strategy: summary.strategy,
+            prediction: summary.prediction,
+            market_prob: summary.market_prob,
+            outcome: summary.outcome.clone(),
+            amount: summary.amount.unwrap_or(0.0),
+            limit_prob: summary.limit,
+            executed: summary.result.to_string(),
+            reason: summary.reason.clone(),
+            decided_at,
+            resolution: None,
+        }
+    }
+
+    /// Whether the strategy wanted a bet here.
+    pub fn is_bet(&self) -> bool {
+        self.outcome.is_some() && self.limit_prob.is_some() && self.amount > 0.0
+    }
+
+    /// Paper P&L once resolved, assuming the whole stake filled at the limit
+    /// price and MKT resolutions break even. `None` for skips and open bets.
+    pub fn realized_pnl(&self) -> Option<f64> {
+        if !self.is_bet() {
+            return None;
+        }
+        let (outcome, limit) = (self.outcome.as_deref()?, self.limit_prob?);
+        let price = if outcome == "YES" { limit } else { 1.0 - limit };
+        match self.resolution.as_deref()? {
+            r @ ("YES" | "NO") if r == outcome => Some(self.amount / price - self.amount),
+            "YES" | "NO" => Some(-self.amount),
+            _ => Some(0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaperSummary {
+    pub decisions: usize,
+    pub bets: usize,
+    /// Paper bets the live bot placed at least in part.
+    pub executed: usize,
+    pub open: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub staked: f64,
+    pub realized: f64,
+}
+
+impl std::fmt::Display for PaperSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} decisions, {} paper bets ({} executed live, {} open), {}W/{}L, staked M${:.0}, realized M${:+.0}",
+            self.decisions,
+            self.bets,
+            self.executed,
+            self.open,
+            self.wins,
+            self.losses,
+            self.staked,
+            self.realized
+        )
+    }
+}
+
+/// Decisions kept (and summarized); older ones drop off.
+const MAX_PAPER_ENTRIES: usize = 10_000;
+
+/// One line of `paper_ledger.jsonl`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PaperRecord {
+    Decision(PaperEntry),
+    Resolution {
+        contract_id: String,
+        resolution: String,
+    },
+}
+
+/// Ledger of the last `MAX_PAPER_ENTRIES` would-be decisions, appended to
+/// `paper_ledger.jsonl` separately from the real `ledger.json`. The file is
+/// only rewritten, down to the kept entries, once it holds twice that many
+/// lines.
+#[derive(Debug)]
+pub struct PaperLedger {
+    entries: VecDeque<PaperEntry>,
+    cap: usize,
+    /// Where the ledger is persisted; `None` keeps it in memory only.
+    path: Option<PathBuf>,
+    /// Lines in the file, to know when to compact it.
+    lines: usize,
+}
+
+impl Default for PaperLedger {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            cap: MAX_PAPER_ENTRIES,
+            path: None,
+            lines: 0,
+        }
+    }
+}
+
+fn paper_ledger_file_path() -> PathBuf {
+    crate::bot::data_dir().join("paper_ledger.jsonl")
+}
+
+impl PaperLedger {
+    pub fn load() -> Self {
+        Self::open(paper_ledger_file_path(), MAX_PAPER_ENTRIES)
+    }
+
+    /// Replay the records at `path`, keeping the last `cap` decisions.
+    fn open(path: PathBuf, cap: usize) -> Self {
+        let mut ledger = Self {
+            cap,
+            ..Self::default()
+        };
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        for line in text.lines() {
+            ledger.lines += 1;
+            match serde_json::from_str(line) {
+                Ok(PaperRecord::Decision(entry)) => ledger.push(entry),
+                Ok(PaperRecord::Resolution {
+                    contract_id,
+                    resolution,
+                }) => {
+                    ledger.mark_resolved(&contract_id, &resolution);
+                }
+                // A line cut short by a crash
+                Err(_) => {}
+            }
+        }
+        ledger.path = Some(path);
+        ledger
+    }
+
+    fn push(&mut self, entry: PaperEntry) {
+        self.entries.push_back(entry);
+        while self.entries.len() > self.cap {
+            self.entries.pop_front();
+        }
+    }
+
+    fn append(&mut self, record: &PaperRecord) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if self.lines >= 2 * self.cap {
+            self.compact();
+            return;
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let Ok(line) = serde_json::to_string(record) else {
+            return;
+        };
+        if let Ok(mut file) = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+        {
+            let _ = writeln!(file, "{line}");
+            self.lines += 1;
+        }
+    }
+
+    /// Rewrite the file as just the kept entries, resolutions folded in.
+    fn compact(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let text: String = self
+            .entries
+            .iter()
+            .filter_map(|e| serde_json::to_string(&PaperRecord::Decision(e.clone())).ok())
+            .map(|line| line + "\n")
+            .collect();
+        let tmp = path.with_extension("jsonl.tmp");
+        if std::fs::write(&tmp, text).is_ok() && std::fs::rename(&tmp, path).is_ok() {
+            self.lines = self.entries.len();
+        }
+    }
+
+    pub fn record(&mut self, entry: PaperEntry) {
+        self.push(entry.clone());
+        self.append(&PaperRecord::Decision(entry));
+    }
+
+    /// Contract ids with at least one unresolved paper bet.
+    pub fn open_contract_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.is_bet() && e.resolution.is_none())
+            .map(|e| e.contract_id.clone())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Mark every unresolved entry on `contract_id` resolved; true if any were.
+    pub fn resolve(&mut self, contract_id: &str, resolution: &str) -> bool {
+        let any = self.mark_resolved(contract_id, resolution);
+        if any {
+            self.append(&PaperRecord::Resolution {
+                contract_id: contract_id.to_string(),
+                resolution: resolution.to_string(),
+            });
+        }
+        any
+    }
+
+    fn mark_resolved(&mut self, contract_id: &str, resolution: &str) -> bool {
+        let mut any = false;
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|e| e.contract_id == contract_id && e.resolution.is_none())
+        {
+            entry.resolution = Some(resolution.to_string());
+            any = true;
+        }
+        any
+    }
+
+    pub fn summary(&self) -> PaperSummary {
+        let mut s = PaperSummary {
+            decisions: self.entries.len(),
+            ..PaperSummary::default()
+        };
+        for entry in self.entries.iter().filter(|e| e.is_bet()) {
+            s.bets += 1;
+            s.staked += entry.amount;
+            if matches!(entry.executed.as_str(), "placed" | "partial") {
+                s.executed += 1;
+            }
+            match entry.realized_pnl() {
+                Some(pnl) => {
+                    s.realized += pnl;
+                    if pnl > 0.0 {
+                        s.wins += 1;
+                    } else if pnl < 0.0 {
+                        s.losses += 1;
+                    }
+                }
+                None => s.open += 1,
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(contract_id: &str, outcome: Option<&str>, executed: &str) -> PaperEntry {
+        PaperEntry {
+            contract_id: contract_id.to_string(),
+            question: "Q".to_string(),
+            strategy: Strategy::NewMarket,
+            prediction: Some(0.7),
+            market_prob: Some(0.5),
+            outcome: outcome.map(str::to_string),
+            amount: if outcome.is_some() { 10.0 } else { 0.0 },
+            limit_prob: outcome.map(|_| 0.5),
+            executed: executed.to_string(),
+            reason: None,
+            decided_at: 0,
+            resolution: None,
+        }
+    }
+
+    #[test]
+    fn test_paper_summary() {
+        let mut ledger = PaperLedger::default();
+        ledger.record(entry("a", Some("YES"), "placed"));
+        ledger.record(entry("b", Some("NO"), "blocked"));
+        ledger.record(entry("c", None, "skipped"));
+        ledger.record(entry("d", Some("YES"), "blocked"));
+        assert_eq!(ledger.open_contract_ids(), vec!["a", "b", "d"]);
+
+        assert!(ledger.resolve("a", "YES"));
+        assert!(ledger.resolve("b", "YES"));
+        assert!(ledger.resolve("c", "NO"));
+        assert!(!ledger.resolve("c", "NO"));
+        assert_eq!(ledger.open_contract_ids(), vec!["d"]);
+
+        let s = ledger.summary();
+        assert_eq!((s.decisions, s.bets, s.executed, s.open), (4, 3, 1, 1));
+        assert_eq!((s.wins, s.losses), (1, 1));
+        assert_eq!(s.staked, 30.0);
+        // Won 10 at 50% (+10), lost 10 on the blocked bet
+        assert_eq!(s.realized, 0.0);
+    }
+
+    #[test]
+    fn test_paper_ledger_file() {
+        let path =
+            std::env::temp_dir().join(format!("paper-ledger-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let lines = || std::fs::read_to_string(&path).unwrap().lines().count();
+
+        let mut ledger = PaperLedger::open(path.clone(), 3);
+        ledger.record(entry("a", Some("YES"), "placed"));
+        ledger.record(entry("b", None, "skipped"));
+        assert!(ledger.resolve("a", "NO"));
+        // Each change is one appended line
+        assert_eq!(lines(), 3);
+        let reloaded = PaperLedger::open(path.clone(), 3);
+        assert_eq!(reloaded.summary(), ledger.summary());
+        assert_eq!(reloaded.summary().losses, 1);
+
+        // Only the last `cap` decisions are kept...
+        for id in ["c", "d", "e"] {
+            ledger.record(entry(id, Some("YES"), "blocked"));
+        }
+        assert_eq!(ledger.summary().decisions, 3);
+        assert_eq!(ledger.open_contract_ids(), vec!["c", "d", "e"]);
+        // ...and the file is cut back to them once it doubles
+        assert_eq!(lines(), 6);
+        ledger.record(entry("f", None, "skipped"));
+        assert_eq!(lines(), 3);
+        let reloaded = PaperLedger::open(path.clone(), 3);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(reloaded.summary(), ledger.summary());
+        assert_eq!(reloaded.open_contract_ids(), vec!["d", "e"]);
+    }
+
+    #[test]
+    fn test_paper_pnl_uses_limit_price() {
+        let mut e = PaperEntry {
+            limit_prob: Some(0.8),
+            resolution: Some("NO".to_string()),
+            ..entry("a", Some("NO"), "placed")
+        };
+        // NO at 20%: 10 mana buys 50 shares
+        assert!((e.realized_pnl().unwrap() - 40.0).abs() < 1e-9);
+        e.resolution = Some("MKT".to_string());
+        assert_eq!(e.realized_pnl(), Some(0.0));
+        e.resolution = None;
+        assert_eq!(e.realized_pnl(), None);
+    }
+}
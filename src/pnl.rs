@@ -0,0 +1,764 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Which part of the bot placed a bet, for per-strategy P&L attribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    /// Analysis of a newly created market.
+    #[default]
+    NewMarket,
+    /// Analysis of an existing market after a new-bet event moved it.
+    Reversion,
+    /// A bet-triggered bet placed with the move (`BetTriggeredMode::Momentum`).
+    Momentum,
+    /// A position already held at startup (`BotConfig::restore_positions`).
+    /// Its prediction is just the market price at import.
+    Imported,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 4] = [
+        Strategy::NewMarket,
+        Strategy::Reversion,
+        Strategy::Momentum,
+        Strategy::Imported,
+    ];
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strategy::NewMarket => write!(f, "new-market"),
+            Strategy::Reversion => write!(f, "reversion"),
+            Strategy::Momentum => write!(f, "momentum"),
+            Strategy::Imported => write!(f, "imported"),
+        }
+    }
+}
+
+/// A bet the bot placed, with the prediction that justified it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub contract_id: String,
+    pub question: String,
+    pub outcome: String,
+    /// Order size (mana).
+    pub amount: f64,
+    /// Amount filled at placement time (mana).
+    pub filled: f64,
+    pub shares: f64,
+    pub limit_prob: f64,
+    pub prediction: f64,
+    pub market_prob: f64,
+    /// Epoch seconds.
+    pub placed_at: u64,
+    /// `YES`, `NO`, `MKT` or `CANCEL` once the market resolves, or `SOLD`
+    /// once the bot sold the position.
+    pub resolution: Option<String>,
+    /// Entries recorded before strategies were tagged count as new-market.
+    #[serde(default)]
+    pub strategy: Strategy,
+    /// This bet's share of the mana received when the position was sold.
+    #[serde(default)]
+    pub sold_for: Option<f64>,
+    /// Manifold's id for the order, so the bot only ever touches its own.
+    #[serde(default)]
+    pub order_id: Option<String>,
+}
+
+impl LedgerEntry {
+    /// `Some(true)` if the bet's outcome matched a YES/NO resolution, `None` if
+    /// unresolved or resolved to MKT/CANCEL.
+    pub fn won(&self) -> Option<bool> {
+        match self.resolution.as_deref() {
+            Some(r @ ("YES" | "NO")) => Some(r == self.outcome),
+            _ => None,
+        }
+    }
+
+    /// Unfilled part of the order still resting on the book. An order's
+    /// `amount` is cut to what filled once its remainder is cancelled.
+    pub fn resting(&self) -> f64 {
+        (self.amount - self.filled).max(0.0)
+    }
+
+    /// Held before the bot tracked it rather than placed by the bot.
+    pub fn is_imported(&self) -> bool {
+        self.strategy == Strategy::Imported
+    }
+
+    /// The prediction's chance that this bet's outcome wins.
+    pub fn predicted_win_prob(&self) -> f64 {
+        if self.outcome == "YES" {
+            self.prediction
+        } else {
+            1.0 - self.prediction
+        }
+    }
+
+    /// Realized profit, approximating MKT resolutions as break-even.
+    pub fn realized_pnl(&self) -> Option<f64> {
+        if let Some(proceeds) = self.sold_for {
+            return Some(proceeds - self.filled);
+        }
+        match self.won() {
+            Some(true) => Some(self.shares - self.filled),
+            Some(false) => Some(-self.filled),
+            None if self.resolution.is_some() => Some(0.0),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PnlSummary {
+    pub bets: usize,
+    pub open: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub staked: f64,
+    pub realized: f64,
+}
+
+impl std::fmt::Display for PnlSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} bets ({} open), {}W/{}L, staked M${:.0}, realized M${:+.0}",
+            self.bets, self.open, self.wins, self.losses, self.staked, self.realized
+        )
+    }
+}
+
+fn summarize<'a>(entries: impl Iterator<Item = &'a LedgerEntry>) -> PnlSummary {
+    let mut s = PnlSummary::default();
+    for entry in entries {
+        s.bets += 1;
+        s.staked += entry.filled;
+        match entry.won() {
+            Some(true) => s.wins += 1,
+            Some(false) => s.losses += 1,
+            None => {}
+        }
+        match entry.realized_pnl() {
+            Some(pnl) => s.realized += pnl,
+            None => s.open += 1,
+        }
+    }
+    s
+}
+
+/// Open bets on one outcome of one market, summed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub contract_id: String,
+    pub question: String,
+    pub outcome: String,
+    pub shares: f64,
+    /// Mana paid for those shares.
+    pub filled: f64,
+}
+
+/// Resolved YES/NO markets measured against the predictions behind the
+/// bets on them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Calibration {
+    /// Markets, not bets.
+    pub samples: usize,
+    pub wins: usize,
+    /// Sum of each market's predicted chance of winning.
+    pub expected_wins: f64,
+    /// Binomial variance of the win count under the predictions.
+    pub variance: f64,
+    /// Mean squared error of the predicted win chances.
+    pub brier: f64,
+}
+
+impl Calibration {
+    /// How many standard deviations the win count sits from what the
+    /// predictions imply (normal approximation); negative means fewer wins.
+    pub fn z_score(&self) -> Option<f64> {
+        (self.variance > 0.0)
+            .then(|| (self.wins as f64 - self.expected_wins) / self.variance.sqrt())
+    }
+}
+
+/// One market's resolved bets as a single prediction: the side with the
+/// larger stake, its stake-weighted chance of winning, and whether it won.
+fn market_sample(bets: &[&LedgerEntry]) -> (f64, bool) {
+    let stake_on = |outcome: &str| -> f64 {
+        bets.iter()
+            .filter(|b| b.outcome == outcome)
+            .map(|b| b.amount)
+            .sum()
+    };
+    let side = if stake_on("YES") >= stake_on("NO") {
+        "YES"
+    } else {
+        "NO"
+    };
+    let side_bets: Vec<&&LedgerEntry> = bets.iter().filter(|b| b.outcome == side).collect();
+    let stake: f64 = side_bets.iter().map(|b| b.amount).sum();
+    let p = if stake > 0.0 {
+        side_bets
+            .iter()
+            .map(|b| b.amount * b.predicted_win_prob())
+            .sum::<f64>()
+            / stake
+    } else {
+        side_bets
+            .iter()
+            .map(|b| b.predicted_win_prob())
+            .sum::<f64>()
+            / side_bets.len() as f64
+    };
+    (p, side_bets[0].won() == Some(true))
+}
+
+/// Ledger of placed bets, persisted to disk so resolutions can be matched
+/// against predictions across restarts.
+#[derive(Debug, Default)]
+pub struct PnlTracker {
+    entries: Vec<LedgerEntry>,
+    /// Where the ledger is persisted; `None` keeps it in memory only.
+    path: Option<PathBuf>,
+}
+
+fn ledger_file_path() -> PathBuf {
+    crate::bot::data_dir().join("ledger.json")
+}
+
+impl PnlTracker {
+    pub fn load() -> Self {
+        let path = ledger_file_path();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(
+            path,
+            serde_json::to_string(&self.entries).unwrap_or_default(),
+        );
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn record(&mut self, entry: LedgerEntry) {
+        self.entries.push(entry);
+        self.save();
+    }
+
+    /// Add `entries` for markets the ledger has no record of; returns how
+    /// many were added. Markets already tracked, open or settled, are left
+    /// alone so their history isn't counted twice.
+    pub fn seed(&mut self, entries: Vec<LedgerEntry>) -> usize {
+        let known: std::collections::HashSet<String> =
+            self.entries.iter().map(|e| e.contract_id.clone()).collect();
+        let before = self.entries.len();
+        self.entries.extend(
+            entries
+                .into_iter()
+                .filter(|e| !known.contains(&e.contract_id)),
+        );
+        let added = self.entries.len() - before;
+        if added > 0 {
+            self.save();
+        }
+        added
+    }
+
+    /// Ids of the bot's open orders on `outcome` of `contract_id`.
+    pub fn order_ids(&self, contract_id: &str, outcome: &str) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| {
+                e.contract_id == contract_id && e.outcome == outcome && e.resolution.is_none()
+            })
+            .filter_map(|e| e.order_id.clone())
+            .collect()
+    }
+
+    /// Cut the open entry for `order_id` down to what filled before the order
+    /// was cancelled, dropping it if nothing did. Returns whether the ledger
+    /// tracked the order.
+    pub fn cancel_order(&mut self, order_id: &str, filled: f64, shares: f64) -> bool {
+        let Some(i) = self
+            .entries
+            .iter()
+            .position(|e| e.order_id.as_deref() == Some(order_id) && e.resolution.is_none())
+        else {
+            return false;
+        };
+        if filled > 0.0 {
+            let entry = &mut self.entries[i];
+            entry.amount = filled;
+            entry.filled = filled;
+            entry.shares = shares;
+        } else {
+            self.entries.remove(i);
+        }
+        self.save();
+        true
+    }
+
+    /// Contract ids with at least one unresolved bet.
+    pub fn open_contract_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|e| e.resolution.is_none())
+            .map(|e| e.contract_id.clone())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Open positions the bot placed, ordered by market and outcome. Imported
+    /// holdings are the user's to manage.
+    pub fn positions(&self) -> Vec<Position> {
+        let mut positions: Vec<Position> = Vec::new();
+        let open = |e: &&LedgerEntry| e.resolution.is_none() && !e.is_imported();
+        for entry in self.entries.iter().filter(open) {
+            match positions
+                .iter_mut()
+                .find(|p| p.contract_id == entry.contract_id && p.outcome == entry.outcome)
+            {
+                Some(p) => {
+                    p.shares += entry.shares;
+                    p.filled += entry.filled;
+                }
+                None => positions.push(Position {
+                    contract_id: entry.contract_id.clone(),
+                    question: entry.question.clone(),
+                    outcome: entry.outcome.clone(),
+                    shares: entry.shares,
+                    filled: entry.filled,
+                }),
+            }
+        }
+        positions.sort_by(|a, b| (&a.contract_id, &a.outcome).cmp(&(&b.contract_id, &b.outcome)));
+        positions
+    }
+
+    /// Mark the open `outcome` bets on `contract_id` sold for `proceeds` in
+    /// total, split by shares; returns the closed entries.
+    pub fn close(&mut self, contract_id: &str, outcome: &str, proceeds: f64) -> Vec<LedgerEntry> {
+        let is_open = |e: &LedgerEntry| {
+            e.contract_id == contract_id && e.outcome == outcome && e.resolution.is_none()
+        };
+        let shares: f64 = self
+            .entries
+            .iter()
+            .filter(|e| is_open(e))
+            .map(|e| e.shares)
+            .sum();
+        let mut closed = Vec::new();
+        for entry in self.entries.iter_mut().filter(|e| is_open(e)) {
+            let share = if shares > 0.0 {
+                entry.shares / shares
+            } else {
+                0.0
+            };
+            entry.resolution = Some("SOLD".to_string());
+            entry.sold_for = Some(proceeds * share);
+            closed.push(entry.clone());
+        }
+        if !closed.is_empty() {
+            self.save();
+        }
+        closed
+    }
+
+    /// Mana committed to unresolved bets: what filled, plus what still rests
+    /// on the book, since resting limit orders lock their unfilled amount too.
+    pub fn open_exposure(&self) -> f64 {
+        self.entries
+            .iter()
+            .filter(|e| e.resolution.is_none())
+            .map(|e| e.filled + e.resting())
+            .sum()
+    }
+
+    /// Mark all open bets on `contract_id` resolved; returns the newly resolved entries.
+    pub fn resolve(&mut self, contract_id: &str, resolution: &str) -> Vec<LedgerEntry> {
+        let mut resolved = Vec::new();
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|e| e.contract_id == contract_id && e.resolution.is_none())
+        {
+            entry.resolution = Some(resolution.to_string());
+            resolved.push(entry.clone());
+        }
+        if !resolved.is_empty() {
+            self.save();
+        }
+        resolved
+    }
+
+    /// P&L of the bot's own bets; imported holdings only count as exposure.
+    pub fn summary(&self) -> PnlSummary {
+        summarize(self.entries.iter().filter(|e| !e.is_imported()))
+    }
+
+    /// Calibration over the `window` most recently bet resolved markets, one
+    /// sample per market: the rungs and partial fills of a position are one
+    /// prediction, not several.
+    pub fn calibration(&self, window: usize) -> Calibration {
+        // Imported positions carry no real prediction
+        let mut markets: HashMap<&str, (usize, Vec<&LedgerEntry>)> = HashMap::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.is_imported() || entry.won().is_none() {
+                continue;
+            }
+            let (last, bets) = markets.entry(&entry.contract_id).or_default();
+            *last = i;
+            bets.push(entry);
+        }
+        let mut samples: Vec<(usize, f64, bool)> = markets
+            .into_values()
+            .map(|(last, bets)| {
+                let (p, won) = market_sample(&bets);
+                (last, p, won)
+            })
+            .collect();
+        samples.sort_by_key(|&(last, ..)| std::cmp::Reverse(last));
+
+        let mut c = Calibration::default();
+        let mut squared_error = 0.0;
+        for (_, p, won) in samples.into_iter().take(window) {
+            c.samples += 1;
+            c.wins += usize::from(won);
+            c.expected_wins += p;
+            c.variance += p * (1.0 - p);
+            squared_error += (f64::from(u8::from(won)) - p).powi(2);
+        }
+        if c.samples > 0 {
+            c.brier = squared_error / c.samples as f64;
+        }
+        c
+    }
+
+    /// Per-strategy summaries, omitting strategies with no bets and imported
+    /// holdings.
+    pub fn summary_by_strategy(&self) -> Vec<(Strategy, PnlSummary)> {
+        Strategy::ALL
+            .into_iter()
+            .filter(|strategy| *strategy != Strategy::Imported)
+            .map(|strategy| {
+                let entries = self.entries.iter().filter(|e| e.strategy == strategy);
+                (strategy, summarize(entries))
+            })
+            .filter(|(_, s)| s.bets > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(contract_id: &str, outcome: &str) -> LedgerEntry {
+        LedgerEntry {
+            contract_id: contract_id.to_string(),
+            question: "Q".to_string(),
+            outcome: outcome.to_string(),
+            amount: 10.0,
+            filled: 10.0,
+            shares: 20.0,
+            limit_prob: 0.5,
+            prediction: 0.7,
+            market_prob: 0.5,
+            placed_at: 0,
+            resolution: None,
+            strategy: Strategy::NewMarket,
+            sold_for: None,
+            order_id: None,
+        }
+    }
+
+    #[test]
+    fn test_positions_and_close() {
+        let mut tracker = PnlTracker::default();
+        tracker.record(entry("a", "YES"));
+        tracker.record(LedgerEntry {
+            shares: 60.0,
+            filled: 30.0,
+            ..entry("a", "YES")
+        });
+        tracker.record(entry("b", "NO"));
+        let positions = tracker.positions();
+        assert_eq!(positions.len(), 2);
+        assert_eq!(
+            (
+                positions[0].contract_id.as_str(),
+                positions[0].shares,
+                positions[0].filled
+            ),
+            ("a", 80.0, 40.0)
+        );
+
+        // 80 shares sold for M$60, split 20:60
+        let closed = tracker.close("a", "YES", 60.0);
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].sold_for, Some(15.0));
+        assert_eq!(closed[1].realized_pnl(), Some(15.0));
+        assert!(tracker.close("a", "YES", 60.0).is_empty());
+        assert_eq!(tracker.open_contract_ids(), vec!["b"]);
+        assert_eq!(tracker.positions().len(), 1);
+
+        let s = tracker.summary();
+        assert_eq!((s.open, s.wins, s.losses), (1, 0, 0));
+        assert_eq!(s.realized, 20.0);
+        // Sold bets aren't calibration samples
+        assert_eq!(tracker.calibration(usize::MAX).samples, 0);
+    }
+
+    /// `wins` won and `losses` lost YES bets, each predicted at 70%.
+    fn resolved(wins: usize, losses: usize) -> PnlTracker {
+        let mut tracker = PnlTracker::default();
+        for i in 0..wins + losses {
+            let id = i.to_string();
+            tracker.record(entry(&id, "YES"));
+            tracker.resolve(&id, if i < wins { "YES" } else { "NO" });
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_calibration() {
+        assert_eq!(
+            PnlTracker::default().calibration(usize::MAX).z_score(),
+            None
+        );
+
+        // Winning as often as predicted
+        let c = resolved(14, 6).calibration(usize::MAX);
+        assert_eq!((c.samples, c.wins), (20, 14));
+        assert!((c.expected_wins - 14.0).abs() < 1e-9);
+        assert!(c.z_score().unwrap().abs() < 1e-9);
+        assert!((c.brier - 0.21).abs() < 1e-9);
+
+        // 6 of 20 against 14 expected: about 3.9 sd short
+        let z = resolved(6, 14).calibration(usize::MAX).z_score().unwrap();
+        assert!((z - (-8.0 / 4.2f64.sqrt())).abs() < 1e-9);
+
+        // NO bets win with the complement; MKT/CANCEL aren't samples
+        let mut tracker = PnlTracker::default();
+        tracker.record(entry("a", "NO"));
+        tracker.record(entry("b", "YES"));
+        tracker.resolve("a", "NO");
+        tracker.resolve("b", "MKT");
+        let c = tracker.calibration(usize::MAX);
+        assert_eq!((c.samples, c.wins), (1, 1));
+        assert!((c.expected_wins - 0.3).abs() < 1e-9);
+
+        // A ladder's rungs on one market are a single sample, weighted by stake
+        let mut tracker = PnlTracker::default();
+        for (amount, prediction) in [(10.0, 0.6), (30.0, 0.8)] {
+            tracker.record(LedgerEntry {
+                amount,
+                prediction,
+                ..entry("a", "YES")
+            });
+        }
+        tracker.record(entry("a", "NO"));
+        tracker.resolve("a", "NO");
+        let c = tracker.calibration(usize::MAX);
+        assert_eq!((c.samples, c.wins), (1, 0));
+        assert!((c.expected_wins - 0.75).abs() < 1e-9);
+
+        // Only the most recently bet markets count: 6 early losses drop out
+        let mut tracker = resolved(0, 6);
+        for i in 6..26 {
+            let id = i.to_string();
+            tracker.record(entry(&id, "YES"));
+            tracker.resolve(&id, if i < 20 { "YES" } else { "NO" });
+        }
+        assert_eq!(tracker.calibration(usize::MAX).wins, 14);
+        let c = tracker.calibration(20);
+        assert_eq!((c.samples, c.wins), (20, 14));
+        assert!(c.z_score().unwrap().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_open_exposure() {
+        let mut tracker = PnlTracker::default();
+        assert_eq!(tracker.open_exposure(), 0.0);
+        tracker.record(entry("a", "YES"));
+        tracker.record(entry("a", "YES"));
+        tracker.record(entry("b", "NO"));
+        assert_eq!(tracker.open_exposure(), 30.0);
+        tracker.resolve("a", "NO");
+        assert_eq!(tracker.open_exposure(), 10.0);
+        tracker.resolve("b", "CANCEL");
+        assert_eq!(tracker.open_exposure(), 0.0);
+
+        // A partly filled order counts its fill and what still rests, and
+        // only the fill once the rest is cancelled
+        tracker.record(LedgerEntry {
+            amount: 40.0,
+            filled: 10.0,
+            order_id: Some("o1".to_string()),
+            ..entry("c", "YES")
+        });
+        assert_eq!(tracker.open_exposure(), 40.0);
+        assert!(tracker.cancel_order("o1", 10.0, 20.0));
+        assert_eq!(tracker.open_exposure(), 10.0);
+    }
+
+    #[test]
+    fn test_resolve_and_summary() {
+        let mut tracker = PnlTracker {
+            entries: vec![entry("a", "YES"), entry("b", "NO"), entry("c", "YES")],
+            path: None,
+        };
+
+        let resolved = tracker.resolve("a", "YES");
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].won(), Some(true));
+        assert!(tracker.resolve("a", "YES").is_empty());
+
+        assert_eq!(tracker.resolve("b", "YES")[0].won(), Some(false));
+        assert_eq!(tracker.open_contract_ids(), vec!["c".to_string()]);
+        assert_eq!(tracker.open_exposure(), 10.0);
+
+        let s = tracker.summary();
+        assert_eq!(s.bets, 3);
+        assert_eq!(s.open, 1);
+        assert_eq!((s.wins, s.losses), (1, 1));
+        assert_eq!(s.realized, 0.0);
+    }
+
+    #[test]
+    fn test_summary_by_strategy() {
+        let mut tracker = PnlTracker::default();
+        tracker.record(entry("a", "YES"));
+        tracker.record(LedgerEntry {
+            strategy: Strategy::Reversion,
+            ..entry("b", "NO")
+        });
+        tracker.record(LedgerEntry {
+            strategy: Strategy::Reversion,
+            ..entry("c", "NO")
+        });
+        tracker.resolve("a", "YES");
+        tracker.resolve("b", "YES");
+
+        let by_strategy = tracker.summary_by_strategy();
+        assert_eq!(by_strategy.len(), 2);
+        let (strategy, s) = &by_strategy[0];
+        assert_eq!(*strategy, Strategy::NewMarket);
+        assert_eq!((s.bets, s.wins, s.losses, s.realized), (1, 1, 0, 10.0));
+        let (strategy, s) = &by_strategy[1];
+        assert_eq!(*strategy, Strategy::Reversion);
+        assert_eq!((s.bets, s.open, s.wins, s.losses), (2, 1, 0, 1));
+        assert_eq!(s.realized, -10.0);
+        assert_eq!(tracker.summary().realized, 0.0);
+
+        // Strategies without bets are omitted
+        let tracker = PnlTracker::default();
+        assert!(tracker.summary_by_strategy().is_empty());
+    }
+
+    #[test]
+    fn test_order_ids() {
+        let mut tracker = PnlTracker::default();
+        let order = |contract_id, outcome, id: Option<&str>| LedgerEntry {
+            order_id: id.map(str::to_string),
+            ..entry(contract_id, outcome)
+        };
+        tracker.record(order("a", "YES", Some("o1")));
+        tracker.record(order("a", "YES", None));
+        tracker.record(order("a", "NO", Some("o2")));
+        tracker.record(order("b", "YES", Some("o3")));
+        tracker.record(order("a", "YES", Some("o4")));
+        assert_eq!(tracker.order_ids("a", "YES"), ["o1", "o4"]);
+        tracker.resolve("a", "YES");
+        assert!(tracker.order_ids("a", "YES").is_empty());
+    }
+
+    #[test]
+    fn test_cancel_order() {
+        let mut tracker = PnlTracker::default();
+        let order = |contract_id, id: &str| LedgerEntry {
+            amount: 30.0,
+            order_id: Some(id.to_string()),
+            ..entry(contract_id, "YES")
+        };
+        tracker.record(order("a", "o1"));
+        tracker.record(order("b", "o2"));
+        assert_eq!(tracker.open_exposure(), 60.0);
+
+        // Part filled: the entry keeps only the fill
+        assert!(tracker.cancel_order("o1", 12.0, 25.0));
+        let a = &tracker.entries()[0];
+        assert_eq!((a.amount, a.filled, a.shares), (12.0, 12.0, 25.0));
+        // Nothing filled: no bet left behind
+        assert!(tracker.cancel_order("o2", 0.0, 0.0));
+        assert_eq!(tracker.open_contract_ids(), ["a"]);
+        assert_eq!(tracker.open_exposure(), 12.0);
+        // Orders the ledger doesn't know are left alone
+        assert!(!tracker.cancel_order("o9", 5.0, 10.0));
+    }
+
+    #[test]
+    fn test_imported_holdings_excluded() {
+        let mut tracker = PnlTracker::default();
+        tracker.record(entry("a", "YES"));
+        let imported = |contract_id| LedgerEntry {
+            strategy: Strategy::Imported,
+            ..entry(contract_id, "NO")
+        };
+        assert_eq!(tracker.seed(vec![imported("b"), imported("c")]), 2);
+        tracker.resolve("b", "YES");
+
+        // Exposure counts them, but only the bot's bets are positions to manage
+        assert_eq!(tracker.open_exposure(), 20.0);
+        let positions = tracker.positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].contract_id, "a");
+
+        // The imported loss isn't the bot's P&L
+        let s = tracker.summary();
+        assert_eq!((s.bets, s.losses, s.realized), (1, 0, 0.0));
+        let by_strategy = tracker.summary_by_strategy();
+        assert_eq!(by_strategy.len(), 1);
+        assert_eq!(by_strategy[0].0, Strategy::NewMarket);
+        assert_eq!(tracker.calibration(usize::MAX).samples, 0);
+    }
+
+    #[test]
+    fn test_strategy_persistence() {
+        let json = serde_json::to_value(LedgerEntry {
+            strategy: Strategy::Reversion,
+            ..entry("a", "YES")
+        })
+        .unwrap();
+        assert_eq!(json["strategy"], "reversion");
+
+        // Ledgers written before strategy tagging still load
+        let mut json = json;
+        json.as_object_mut().unwrap().remove("strategy");
+        let entry: LedgerEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(entry.strategy, Strategy::NewMarket);
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+/// Bounded least-recently-used map of contract id -> market question, used to
+/// label feed events that only carry an id.
+pub struct QuestionCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (String, u64)>,
+}
+
+impl QuestionCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, contract_id: String, question: String) {
+        self.tick += 1;
+        self.entries.insert(contract_id, (question, self.tick));
+        if self.entries.len() > self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(id, _)| id.clone());
+            if let Some(id) = oldest {
+                self.entries.remove(&id);
+            }
+        }
+    }
+
+    /// Look up a question, marking it as recently used.
+    pub fn get(&mut self, contract_id: &str) -> Option<&str> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(contract_id).map(|(question, used)| {
+            *used = tick;
+            question.as_str()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = QuestionCache::new(2);
+        cache.insert("a".to_string(), "Question A".to_string());
+        cache.insert("b".to_string(), "Question B".to_string());
+
+        // Touch "a" so "b" becomes least recently used
+        assert_eq!(cache.get("a"), Some("Question A"));
+        cache.insert("c".to_string(), "Question C".to_string());
+
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some("Question A"));
+        assert_eq!(cache.get("c"), Some("Question C"));
+
+        // Re-inserting an existing id doesn't evict anything
+        cache.insert("c".to_string(), "Question C v2".to_string());
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get("c"), Some("Question C v2"));
+    }
+}
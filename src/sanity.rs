@@ -0,0 +1,238 @@
+//! Cheap base-rate heuristics that veto predictions wildly at odds with how
+//! often "will X happen by <date>" questions resolve YES.
+
+/// Pluggable check: `false` vetoes betting on `prediction` for `question`.
+pub type SanityCheck = fn(question: &str, prediction: f64) -> bool;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Horizons longer than this are too uncertain for near-0/near-1 predictions.
+const LONG_HORIZON_DAYS: f64 = 365.0;
+
+/// Built-in `SanityCheck` using the current time.
+#[allow(dead_code)] // opt-in via `BotConfig::sanity_check`
+pub fn base_rate_check(question: &str, prediction: f64) -> bool {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    base_rate_check_at(question, prediction, now)
+}
+
+/// For questions with a parsable deadline:
+/// - a YES prediction may exceed a crude time-decay base rate (something new
+///   happening within `days` is unlikely when `days` is small) by at most 0.5;
+/// - near-certain predictions (either way) are rejected for horizons over a year.
+///
+/// Questions without a recognizable deadline always pass.
+fn base_rate_check_at(question: &str, prediction: f64, now_secs: u64) -> bool {
+    let Some(deadline) = parse_deadline(question, now_secs) else {
+        return true;
+    };
+    if deadline <= now_secs {
+        return true;
+    }
+    let days = (deadline - now_secs) as f64 / SECS_PER_DAY as f64;
+
+    let base_rate = (days / LONG_HORIZON_DAYS).min(1.0) * 0.5;
+    if prediction > base_rate + 0.5 {
+        return false;
+    }
+    if days > LONG_HORIZON_DAYS && !(0.02..=0.98).contains(&prediction) {
+        return false;
+    }
+    true
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+fn parse_month(word: &str) -> Option<u32> {
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| m.starts_with(word))
+        .map(|i| i as u32 + 1)
+}
+
+fn parse_year(word: &str) -> Option<i64> {
+    word.parse::<i64>()
+        .ok()
+        .filter(|y| (2000..=2200).contains(y))
+}
+
+/// End (epoch seconds) of the deadline in phrases like "by March 15, 2026",
+/// "before July", "by end of 2027" or "in 2026". A month without a year means
+/// its next occurrence.
+fn parse_deadline(question: &str, now_secs: u64) -> Option<u64> {
+    let lower = question.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let (now_year, now_month, _) = civil_from_days((now_secs / SECS_PER_DAY) as i64);
+
+    (0..words.len())
+        .filter(|&i| matches!(words[i], "by" | "before" | "in" | "until"))
+        .find_map(|i| parse_date_phrase(&words[i + 1..], now_year, now_month))
+}
+
+/// Parse the date following a preposition, returning the end of that day,
+/// month or year in epoch seconds.
+fn parse_date_phrase(words: &[&str], now_year: i64, now_month: u32) -> Option<u64> {
+    let mut rest = words
+        .iter()
+        .copied()
+        .skip_while(|w| matches!(*w, "the" | "end" | "of"));
+
+    let first = rest.next()?;
+    if let Some(year) = parse_year(first) {
+        return Some(days_from_civil(year + 1, 1, 1) as u64 * SECS_PER_DAY);
+    }
+    let month = parse_month(first)?;
+    let mut day = None;
+    let mut year = None;
+    for w in rest.take(2) {
+        if let Some(y) = parse_year(w) {
+            year = Some(y);
+        } else if let Some(d) = w
+            .trim_end_matches(|c: char| c.is_alphabetic())
+            .parse::<u32>()
+            .ok()
+            .filter(|d| (1..=31).contains(d))
+        {
+            day = Some(d);
+        }
+    }
+    let year = year.unwrap_or(if month < now_month {
+        now_year + 1
+    } else {
+        now_year
+    });
+    let end_days = match day {
+        Some(d) => days_from_civil(year, month, d) + 1,
+        None if month == 12 => days_from_civil(year + 1, 1, 1),
+        None => days_from_civil(year, month + 1, 1),
+    };
+    Some(end_days as u64 * SECS_PER_DAY)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`: (year, month, day).
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-01 00:00 UTC
+    const NOW: u64 = 1_772_323_200;
+
+    #[test]
+    fn test_civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2026, 3, 1) as u64 * SECS_PER_DAY, NOW);
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        let day = |y, m, d| days_from_civil(y, m, d) as u64 * SECS_PER_DAY;
+        assert_eq!(
+            parse_deadline("Will X happen by March 15, 2026?", NOW),
+            Some(day(2026, 3, 16))
+        );
+        assert_eq!(
+            parse_deadline("Will X happen before July?", NOW),
+            Some(day(2026, 8, 1))
+        );
+        // Month already past this year: next year's
+        assert_eq!(
+            parse_deadline("Will X happen by Jan 10th?", NOW),
+            Some(day(2027, 1, 11))
+        );
+        assert_eq!(
+            parse_deadline("Will X happen by the end of 2027?", NOW),
+            Some(day(2028, 1, 1))
+        );
+        assert_eq!(parse_deadline("Will X win the election?", NOW), None);
+        assert_eq!(
+            parse_deadline("Will X be elected in a landslide?", NOW),
+            None
+        );
+        // A non-date "in" doesn't hide a later deadline
+        assert_eq!(
+            parse_deadline("Will a team in the NBA win 70 games by April 2026?", NOW),
+            Some(day(2026, 5, 1))
+        );
+    }
+
+    #[test]
+    fn test_base_rate_check() {
+        // Two weeks out: a 95% YES contradicts the near-term base rate
+        assert!(!base_rate_check_at(
+            "Will X launch by March 14, 2026?",
+            0.95,
+            NOW
+        ));
+        assert!(base_rate_check_at(
+            "Will X launch by March 14, 2026?",
+            0.40,
+            NOW
+        ));
+        // Six months out allows more confidence
+        assert!(base_rate_check_at(
+            "Will X launch by September 1, 2026?",
+            0.70,
+            NOW
+        ));
+        // Over a year out: no near-certainty either way
+        assert!(!base_rate_check_at(
+            "Will X launch by end of 2028?",
+            0.01,
+            NOW
+        ));
+        assert!(base_rate_check_at(
+            "Will X launch by end of 2028?",
+            0.90,
+            NOW
+        ));
+        // No deadline: always passes
+        assert!(base_rate_check_at("Will X win?", 0.99, NOW));
+    }
+}
@@ -0,0 +1,106 @@
+//! Parser self-test (`--self-test [corpus.json]`): runs `parse_prediction`
+//! over recorded xAI output texts and checks each against its expected result,
+//! so format drift is caught before it silently stops all trading.
+
+use crate::xai::{self, PredictionResult};
+use serde::Deserialize;
+
+/// Built-in corpus, `fixtures/xai_outputs.json`.
+const CORPUS: &str = include_str!("../fixtures/xai_outputs.json");
+
+#[derive(Deserialize)]
+struct Fixture {
+    name: String,
+    /// Output text exactly as xAI returned it.
+    text: String,
+    expected: Expected,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "result", rename_all = "lowercase")]
+enum Expected {
+    Predict { probability: f64 },
+    Skip,
+    Unparseable,
+}
+
+fn actual(text: &str) -> Expected {
+    match xai::parse_prediction(text) {
+        Some(PredictionResult::Predict(p)) => Expected::Predict {
+            probability: p.probability,
+        },
+        Some(PredictionResult::Skip(_)) => Expected::Skip,
+        None => Expected::Unparseable,
+    }
+}
+
+fn matches(expected: &Expected, actual: &Expected) -> bool {
+    match (expected, actual) {
+        (Expected::Predict { probability: e }, Expected::Predict { probability: a }) => {
+            (e - a).abs() < 1e-9
+        }
+        _ => expected == actual,
+    }
+}
+
+/// One line per fixture that didn't parse as expected.
+fn check_corpus(json: &str) -> Result<Vec<String>, serde_json::Error> {
+    let fixtures: Vec<Fixture> = serde_json::from_str(json)?;
+    Ok(fixtures
+        .iter()
+        .filter_map(|f| {
+            let actual = actual(&f.text);
+            (!matches(&f.expected, &actual))
+                .then(|| format!("{}: expected {:?}, got {actual:?}", f.name, f.expected))
+        })
+        .collect())
+}
+
+/// Check the corpus at the path after `--self-test`, or the built-in one.
+/// Exits non-zero on any failure.
+pub fn run() {
+    let path = std::env::args().skip_while(|a| a != "--self-test").nth(1);
+    let json = match &path {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("Could not read {path}: {e}");
+                std::process::exit(1);
+            }
+        },
+        None => CORPUS.to_string(),
+    };
+    let failures = match check_corpus(&json) {
+        Ok(failures) => failures,
+        Err(e) => {
+            eprintln!("Invalid corpus: {e}");
+            std::process::exit(1);
+        }
+    };
+    for failure in &failures {
+        eprintln!("[FAIL] {failure}");
+    }
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+    println!("parse_prediction self-test passed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_outputs() {
+        assert_eq!(check_corpus(CORPUS).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_check_corpus_reports_mismatch() {
+        let json = r#"[{"name": "wrong", "text": "{\"action\":\"skip\",\"reasoning\":\"x\"}",
+                        "expected": {"result": "predict", "probability": 0.5}}]"#;
+        let failures = check_corpus(json).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].starts_with("wrong: expected Predict"));
+    }
+}
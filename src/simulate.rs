@@ -0,0 +1,461 @@
+//! Simulated xAI research, Manifold API and bet execution with configurable
+//! latency and error rates (`BotConfig::simulation`), for load-testing the
+//! analysis pipeline without spending credits or mana or touching the account.
+
+use crate::api::{
+    ApiError, BetRequest, BetResponse, LimitOrder, ManifoldApi, ManifoldApiError, Market,
+    MarketBet, SellRequest, User, UserBet, UserProfile,
+};
+use crate::bot::BotError;
+use crate::execution::ExecutionBackend;
+#[cfg(test)]
+use crate::ws::BetData;
+use crate::xai::SearchResult;
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Simulation {
+    /// Each simulated call takes between these many milliseconds.
+    pub min_latency_ms: u64,
+    pub max_latency_ms: u64,
+    /// Fraction of calls that fail (0–1).
+    pub error_rate: f64,
+    /// Same seed, same sequence of latencies and failures.
+    pub seed: u64,
+}
+
+/// Small deterministic generator (xorshift64*), so runs are reproducible
+/// without another dependency.
+#[derive(Debug, Clone)]
+pub struct SimRng(u64);
+
+impl SimRng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is xorshift's fixed point
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[lo, hi]`.
+    pub fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        if hi <= lo {
+            lo
+        } else {
+            lo + self.next_u64() % (hi - lo + 1)
+        }
+    }
+}
+
+/// Draws latencies and failures for simulated calls.
+pub struct Simulator {
+    config: Simulation,
+    rng: Mutex<SimRng>,
+}
+
+impl Simulator {
+    pub fn new(config: Simulation) -> Self {
+        let rng = Mutex::new(SimRng::new(config.seed));
+        Self { config, rng }
+    }
+
+    /// How long the next call takes and whether it fails.
+    fn next_call(&self) -> (Duration, bool) {
+        let mut rng = self.rng.lock().unwrap();
+        let latency = rng.range(self.config.min_latency_ms, self.config.max_latency_ms);
+        let fails = rng.next_f64() < self.config.error_rate;
+        (Duration::from_millis(latency), fails)
+    }
+
+    /// A research result for `question` after a simulated delay. Failures look
+    /// like xAI rate limiting, so they count towards the xAI error rate.
+    pub async fn research(&self, question: &str) -> Result<SearchResult, BoxError> {
+        let (latency, fails) = self.next_call();
+        tokio::time::sleep(latency).await;
+        if fails {
+            return Err("xAI API error 429 Too Many Requests: simulated failure".into());
+        }
+        Ok(SearchResult {
+            text: simulated_prediction(question),
+            sources: Vec::new(),
+        })
+    }
+}
+
+/// FNV-1a, so simulated answers are stable for the same input.
+fn stable_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+/// A prediction that is stable for a question, spread over 5–95%.
+fn simulated_prediction(question: &str) -> String {
+    format!(
+        r#"{{"action":"predict","probability":{},"reasoning":"Simulated research."}}"#,
+        5 + stable_hash(question) % 91
+    )
+}
+
+/// Balance of the simulated account.
+const SIMULATED_BALANCE: f64 = 10_000.0;
+
+/// Liquidity of every simulated market.
+const SIMULATED_LIQUIDITY: f64 = 1_000.0;
+
+const DAY_MS: u64 = 86_400_000;
+
+/// Stands in for the Manifold API: every market exists, is open and binary,
+/// with a price that is stable for its id; the account holds no bets or
+/// orders; writes succeed without going anywhere. Each call takes a simulated
+/// delay and may fail like a Manifold outage.
+pub struct SimulatedManifold {
+    simulator: Arc<Simulator>,
+}
+
+impl SimulatedManifold {
+    pub fn new(simulator: Arc<Simulator>) -> Self {
+        Self { simulator }
+    }
+
+    async fn call(&self) -> Result<(), ApiError> {
+        let (latency, fails) = self.simulator.next_call();
+        tokio::time::sleep(latency).await;
+        if fails {
+            return Err(ManifoldApiError {
+                status: 503,
+                message: "simulated failure".to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// The YES price of simulated market `id`, spread over 5–95%.
+fn simulated_price(id: &str) -> f64 {
+    (5 + stable_hash(id) % 91) as f64 / 100.0
+}
+
+fn simulated_market(id: &str) -> Market {
+    let now_ms = crate::bot::now_epoch_millis();
+    Market {
+        id: id.to_string(),
+        slug: id.to_string(),
+        question: format!("Simulated market {id}"),
+        url: String::new(),
+        probability: Some(simulated_price(id)),
+        outcome_type: "BINARY".to_string(),
+        mechanism: "cpmm-1".to_string(),
+        is_resolved: false,
+        resolution: None,
+        close_time: Some(now_ms + 30 * DAY_MS),
+        creator_username: "simulated".to_string(),
+        creator_id: "simulated".to_string(),
+        creator_name: "Simulated".to_string(),
+        created_time: now_ms.saturating_sub(DAY_MS),
+        visibility: "public".to_string(),
+        volume: Some(SIMULATED_LIQUIDITY),
+        total_liquidity: Some(SIMULATED_LIQUIDITY),
+        initial_liquidity: Some(SIMULATED_LIQUIDITY),
+        text_description: None,
+        last_bet_time: None,
+    }
+}
+
+/// The response to `request` filling in full at its limit (or even odds).
+fn simulated_fill(request: &BetRequest) -> BetResponse {
+    let price = request
+        .limit_prob
+        .map(|limit| match request.outcome.as_str() {
+            "NO" => 1.0 - limit,
+            _ => limit,
+        });
+    BetResponse {
+        amount: Some(request.amount),
+        shares: price.filter(|p| *p > 0.0).map(|p| request.amount / p),
+        outcome: Some(request.outcome.clone()),
+        contract_id: Some(request.contract_id.clone()),
+        is_filled: Some(true),
+        ..BetResponse::default()
+    }
+}
+
+/// The response to selling `request`'s shares at the simulated price: a bet
+/// of negative amount.
+fn simulated_sale(contract_id: &str, request: &SellRequest) -> BetResponse {
+    let yes = simulated_price(contract_id);
+    let price = if request.outcome == "NO" {
+        1.0 - yes
+    } else {
+        yes
+    };
+    BetResponse {
+        amount: Some(-request.shares.unwrap_or(0.0) * price),
+        shares: request.shares.map(|s| -s),
+        outcome: Some(request.outcome.clone()),
+        contract_id: Some(contract_id.to_string()),
+        ..BetResponse::default()
+    }
+}
+
+impl ManifoldApi for SimulatedManifold {
+    fn api_key(&self) -> &str {
+        ""
+    }
+
+    fn get_me(&self) -> BoxFuture<'_, Result<User, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(User {
+                id: "simulated".to_string(),
+                username: "simulated".to_string(),
+                name: "Simulated".to_string(),
+                balance: SIMULATED_BALANCE,
+            })
+        })
+    }
+
+    fn get_market<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Market, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(simulated_market(id))
+        })
+    }
+
+    fn get_user<'a>(&'a self, username: &'a str) -> BoxFuture<'a, Result<UserProfile, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(UserProfile {
+                username: username.to_string(),
+                created_time: crate::bot::now_epoch_millis().saturating_sub(365 * DAY_MS),
+                balance: SIMULATED_BALANCE,
+                ..UserProfile::default()
+            })
+        })
+    }
+
+    fn get_bets<'a>(
+        &'a self,
+        _contract_id: &'a str,
+        _limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<MarketBet>, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(Vec::new())
+        })
+    }
+
+    fn get_user_bets<'a>(
+        &'a self,
+        _user_id: &'a str,
+        _before: Option<&'a str>,
+        _limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<UserBet>, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(Vec::new())
+        })
+    }
+
+    fn get_open_orders<'a>(
+        &'a self,
+        _user_id: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<LimitOrder>, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(Vec::new())
+        })
+    }
+
+    fn get_markets<'a>(
+        &'a self,
+        _before: Option<&'a str>,
+        _limit: u32,
+    ) -> BoxFuture<'a, Result<Vec<Market>, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(Vec::new())
+        })
+    }
+
+    fn place_bet<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(simulated_fill(request))
+        })
+    }
+
+    fn cancel_bet<'a>(&'a self, _bet_id: &'a str) -> BoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(self.call())
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        _to_user_id: &'a str,
+        _amount: f64,
+        _message: &'a str,
+    ) -> BoxFuture<'a, Result<(), ApiError>> {
+        Box::pin(self.call())
+    }
+
+    fn sell_shares<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, ApiError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(simulated_sale(contract_id, request))
+        })
+    }
+}
+
+/// Fills each bet at its limit after a simulated delay, or fails like a
+/// dropped request. Cancels, sales and managrams likewise.
+pub struct SimulatedExecutor {
+    simulator: Arc<Simulator>,
+}
+
+impl SimulatedExecutor {
+    pub fn new(simulator: Arc<Simulator>) -> Self {
+        Self { simulator }
+    }
+
+    async fn call(&self) -> Result<(), BotError> {
+        let (latency, fails) = self.simulator.next_call();
+        tokio::time::sleep(latency).await;
+        if fails {
+            return Err(BotError::Request("simulated failure".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl ExecutionBackend for SimulatedExecutor {
+    fn execute<'a>(
+        &'a self,
+        request: &'a BetRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(simulated_fill(request))
+        })
+    }
+
+    fn cancel<'a>(&'a self, _bet_id: &'a str) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(self.call())
+    }
+
+    fn sell<'a>(
+        &'a self,
+        contract_id: &'a str,
+        request: &'a SellRequest,
+    ) -> BoxFuture<'a, Result<BetResponse, BotError>> {
+        Box::pin(async move {
+            self.call().await?;
+            Ok(simulated_sale(contract_id, request))
+        })
+    }
+
+    fn send_managram<'a>(
+        &'a self,
+        _to_user_id: &'a str,
+        _amount: f64,
+        _message: &'a str,
+    ) -> BoxFuture<'a, Result<(), BotError>> {
+        Box::pin(self.call())
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        "simulated"
+    }
+}
+
+/// `count` new-bet events spread over `markets` contracts (`sim-0`, `sim-1`,
+/// ...), each moving the price by up to 20 points either way.
+#[cfg(test)]
+pub fn synthetic_bets(rng: &mut SimRng, count: usize, markets: usize) -> Vec<BetData> {
+    (0..count)
+        .map(|_| {
+            let contract = rng.range(0, markets.max(1) as u64 - 1);
+            let prob_before = 0.05 + 0.9 * rng.next_f64();
+            let prob_after = (prob_before + 0.4 * rng.next_f64() - 0.2).clamp(0.01, 0.99);
+            BetData {
+                contract_id: format!("sim-{contract}"),
+                prob_before,
+                prob_after,
+                created_time: 0,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xai::{self, PredictionResult};
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = SimRng::new(42);
+        let mut b = SimRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+            let x = a.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert_eq!(x, b.next_f64());
+            let r = a.range(3, 7);
+            assert!((3..=7).contains(&r));
+            assert_eq!(r, b.range(3, 7));
+        }
+        assert_eq!(SimRng::new(0).range(5, 5), 5);
+    }
+
+    #[test]
+    fn test_simulated_prediction_parses() {
+        for question in ["Will X happen?", "Will Y happen by 2030?", ""] {
+            let text = simulated_prediction(question);
+            assert_eq!(text, simulated_prediction(question));
+            let Some(PredictionResult::Predict(p)) = xai::parse_prediction(&text) else {
+                panic!("no prediction for {question:?}");
+            };
+            assert!((0.05..=0.95).contains(&p.probability));
+        }
+    }
+
+    #[test]
+    fn test_synthetic_bets() {
+        let bets = synthetic_bets(&mut SimRng::new(7), 200, 5);
+        assert_eq!(bets.len(), 200);
+        for bet in &bets {
+            assert!(bet.contract_id.starts_with("sim-"));
+            assert!((0.01..=0.99).contains(&bet.prob_after));
+            assert!((bet.prob_after - bet.prob_before).abs() <= 0.2 + 1e-9);
+        }
+        let markets: std::collections::HashSet<_> = bets.iter().map(|b| &b.contract_id).collect();
+        assert_eq!(markets.len(), 5);
+    }
+}
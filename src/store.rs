@@ -0,0 +1,277 @@
+use crate::pnl::LedgerEntry;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS analyses (
+    id INTEGER PRIMARY KEY,
+    contract_id TEXT NOT NULL,
+    question TEXT,
+    -- NULL when the model skipped the market (or for imported cache entries)
+    prediction REAL,
+    reasoning TEXT,
+    analyzed_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS analyses_contract ON analyses (contract_id);
+
+CREATE TABLE IF NOT EXISTS bets (
+    id INTEGER PRIMARY KEY,
+    contract_id TEXT NOT NULL,
+    question TEXT NOT NULL,
+    outcome TEXT NOT NULL,
+    amount REAL NOT NULL,
+    filled REAL NOT NULL,
+    shares REAL NOT NULL,
+    limit_prob REAL NOT NULL,
+    prediction REAL NOT NULL,
+    market_prob REAL NOT NULL,
+    strategy TEXT NOT NULL,
+    -- Raw BetResponse JSON; NULL for bets imported from ledger.json
+    response TEXT,
+    placed_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS bets_contract ON bets (contract_id);
+
+CREATE TABLE IF NOT EXISTS resolutions (
+    contract_id TEXT PRIMARY KEY,
+    resolution TEXT NOT NULL,
+    resolved_at INTEGER NOT NULL
+);
+";
+
+/// Schema version stored in `PRAGMA user_version`.
+const SCHEMA_VERSION: u32 = 1;
+
+pub fn store_file_path() -> PathBuf {
+    crate::bot::data_dir().join("bot.db")
+}
+
+/// SQLite store of analyses, bets and resolutions for offline analysis.
+/// Written through alongside the JSON files, which remain the bot's own state.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open (or create) the database, importing the JSON analyzed cache and
+    /// ledger the first time.
+    pub fn open(
+        path: &Path,
+        analyzed: &HashMap<String, u64>,
+        ledger: &[LedgerEntry],
+    ) -> rusqlite::Result<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let store = Self {
+            conn: Connection::open(path)?,
+        };
+        store.migrate(analyzed, ledger)?;
+        Ok(store)
+    }
+
+    fn migrate(
+        &self,
+        analyzed: &HashMap<String, u64>,
+        ledger: &[LedgerEntry],
+    ) -> rusqlite::Result<()> {
+        let version: u32 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        for (contract_id, analyzed_at) in analyzed {
+            tx.execute(
+                "INSERT INTO analyses (contract_id, analyzed_at) VALUES (?1, ?2)",
+                params![contract_id, *analyzed_at as i64],
+            )?;
+        }
+        for entry in ledger {
+            insert_bet(&tx, entry, None)?;
+            if let Some(resolution) = &entry.resolution {
+                insert_resolution(&tx, &entry.contract_id, resolution, entry.placed_at)?;
+            }
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()
+    }
+
+    pub fn record_analysis(
+        &self,
+        contract_id: &str,
+        question: &str,
+        prediction: Option<f64>,
+        reasoning: &str,
+        analyzed_at: u64,
+    ) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO analyses (contract_id, question, prediction, reasoning, analyzed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                contract_id,
+                question,
+                prediction,
+                reasoning,
+                analyzed_at as i64
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_bet(&self, entry: &LedgerEntry, response: &str) -> rusqlite::Result<()> {
+        insert_bet(&self.conn, entry, Some(response))
+    }
+
+    pub fn record_resolution(
+        &self,
+        contract_id: &str,
+        resolution: &str,
+        resolved_at: u64,
+    ) -> rusqlite::Result<()> {
+        insert_resolution(&self.conn, contract_id, resolution, resolved_at)
+    }
+}
+
+fn insert_bet(
+    conn: &Connection,
+    entry: &LedgerEntry,
+    response: Option<&str>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO bets (contract_id, question, outcome, amount, filled, shares, limit_prob,
+                           prediction, market_prob, strategy, response, placed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            entry.contract_id,
+            entry.question,
+            entry.outcome,
+            entry.amount,
+            entry.filled,
+            entry.shares,
+            entry.limit_prob,
+            entry.prediction,
+            entry.market_prob,
+            entry.strategy.to_string(),
+            response,
+            entry.placed_at as i64,
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_resolution(
+    conn: &Connection,
+    contract_id: &str,
+    resolution: &str,
+    resolved_at: u64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO resolutions (contract_id, resolution, resolved_at) VALUES (?1, ?2, ?3)",
+        params![contract_id, resolution, resolved_at as i64],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pnl::Strategy;
+
+    fn entry(contract_id: &str, resolution: Option<&str>) -> LedgerEntry {
+        LedgerEntry {
+            contract_id: contract_id.to_string(),
+            question: "Q".to_string(),
+            outcome: "YES".to_string(),
+            amount: 10.0,
+            filled: 10.0,
+            shares: 20.0,
+            limit_prob: 0.6,
+            prediction: 0.7,
+            market_prob: 0.5,
+            placed_at: 1_700_000_000,
+            resolution: resolution.map(str::to_string),
+            strategy: Strategy::Reversion,
+            sold_for: None,
+            order_id: None,
+        }
+    }
+
+    fn count(store: &Store, table: &str) -> i64 {
+        store
+            .conn
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_migration_imports_json_once() {
+        let path = std::env::temp_dir().join(format!("bot-store-test-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let analyzed = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        let ledger = vec![entry("a", Some("YES")), entry("b", None)];
+        let store = Store::open(&path, &analyzed, &ledger).unwrap();
+        assert_eq!(count(&store, "analyses"), 2);
+        assert_eq!(count(&store, "bets"), 2);
+        assert_eq!(count(&store, "resolutions"), 1);
+        drop(store);
+
+        // Reopening doesn't import again
+        let store = Store::open(&path, &analyzed, &ledger).unwrap();
+        assert_eq!(count(&store, "bets"), 2);
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_record_and_query() {
+        let store = Store {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        store.migrate(&HashMap::new(), &[]).unwrap();
+
+        store
+            .record_analysis("a", "Will X?", Some(0.7), "Evidence", 100)
+            .unwrap();
+        store
+            .record_analysis("b", "Will Y?", None, "Subjective", 101)
+            .unwrap();
+        store
+            .record_bet(&entry("a", None), r#"{"betId":"x"}"#)
+            .unwrap();
+        store.record_resolution("a", "YES", 200).unwrap();
+        store.record_resolution("a", "YES", 300).unwrap();
+
+        // Join bets to their predictions and resolutions
+        let (strategy, prediction, resolution): (String, f64, String) = store
+            .conn
+            .query_row(
+                "SELECT b.strategy, a.prediction, r.resolution
+                 FROM bets b
+                 JOIN analyses a ON a.contract_id = b.contract_id
+                 JOIN resolutions r ON r.contract_id = b.contract_id",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(strategy, "reversion");
+        assert_eq!(prediction, 0.7);
+        assert_eq!(resolution, "YES");
+
+        let skipped: i64 = store
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM analyses WHERE prediction IS NULL",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(count(&store, "resolutions"), 1);
+    }
+}
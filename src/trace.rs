@@ -0,0 +1,282 @@
+//! Per-market decision traces for debugging a baffling trade, opt-in via
+//! `BotConfig::decision_traces`.
+
+use crate::api::{BetRequest, BetResponse};
+use crate::bot::BetDecision;
+use crate::pnl::Strategy;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Everything behind one market's decision, saved to `traces/<contract_id>.json`.
+/// Saved again as each stage completes, so a trace ending early shows where
+/// the bot stopped.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DecisionTrace {
+    pub contract_id: String,
+    pub question: String,
+    pub strategy: Strategy,
+    /// Epoch seconds.
+    pub started_at: u64,
+    /// xAI request body, exactly as sent.
+    pub xai_request: Option<serde_json::Value>,
+    /// Raw xAI output text.
+    pub xai_response: Option<String>,
+    pub sources: Vec<String>,
+    pub prediction: Option<f64>,
+    pub reasoning: Option<String>,
+    pub market_prob: Option<f64>,
+    pub liquidity: Option<f64>,
+    pub amount: Option<f64>,
+    pub required_edge: Option<f64>,
+    pub net_edge: Option<f64>,
+    /// The `BetDecision`, or why the bot stopped before deciding.
+    pub decision: Option<String>,
+    /// Side and limit price of a decided bet.
+    pub outcome: Option<String>,
+    pub limit_prob: Option<f64>,
+    /// Each order placed (several for a ladder).
+    pub bets: Vec<TracedBet>,
+    /// Why nothing was placed (pause, exposure cap).
+    pub bet_error: Option<String>,
+}
+
+/// One-line structured record of a finished decision, logged as
+/// `DECISION {json}` whether or not a bet was placed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecisionSummary {
+    pub contract_id: String,
+    pub question: String,
+    pub strategy: Strategy,
+    pub prediction: Option<f64>,
+    pub market_prob: Option<f64>,
+    pub edge: Option<f64>,
+    pub outcome: Option<String>,
+    pub amount: Option<f64>,
+    pub limit: Option<f64>,
+    pub filled: Option<f64>,
+    /// `placed`, `partial`, `failed`, `blocked` or `skipped`.
+    pub result: &'static str,
+    /// Why it wasn't (fully) placed.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TracedBet {
+    pub request: serde_json::Value,
+    pub response: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+fn trace_file_path(contract_id: &str) -> PathBuf {
+    crate::bot::data_dir()
+        .join("traces")
+        .join(format!("{contract_id}.json"))
+}
+
+/// Replace every occurrence of each non-empty secret.
+pub fn redact(text: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|s| !s.is_empty())
+        .fold(text.to_string(), |text, secret| {
+            text.replace(secret, "[REDACTED]")
+        })
+}
+
+impl DecisionTrace {
+    pub fn new(contract_id: &str, question: &str, strategy: Strategy, started_at: u64) -> Self {
+        Self {
+            contract_id: contract_id.to_string(),
+            question: question.to_string(),
+            strategy,
+            started_at,
+            ..Self::default()
+        }
+    }
+
+    pub fn record_decision(&mut self, decision: &BetDecision) {
+        self.decision = Some(format!("{decision:?}"));
+        let (BetDecision::Bet { net_edge, .. } | BetDecision::Skip { net_edge, .. }) = decision;
+        self.net_edge = Some(*net_edge);
+        if let BetDecision::Bet {
+            outcome,
+            limit_prob,
+            ..
+        } = decision
+        {
+            self.outcome = Some(outcome.to_string());
+            self.limit_prob = Some(*limit_prob);
+        }
+    }
+
+    pub fn summary(&self) -> DecisionSummary {
+        let placed = self.bets.iter().filter(|b| b.error.is_none()).count();
+        let first_error = self.bets.iter().find_map(|b| b.error.clone());
+        let (result, reason) = if let Some(error) = &self.bet_error {
+            ("blocked", Some(error.clone()))
+        } else if self.bets.is_empty() {
+            ("skipped", self.decision.clone())
+        } else if placed == self.bets.len() {
+            ("placed", None)
+        } else if placed > 0 {
+            ("partial", first_error)
+        } else {
+            ("failed", first_error)
+        };
+        DecisionSummary {
+            contract_id: self.contract_id.clone(),
+            question: self.question.clone(),
+            strategy: self.strategy,
+            prediction: self.prediction,
+            market_prob: self.market_prob,
+            edge: self.net_edge,
+            outcome: self.outcome.clone(),
+            amount: self.amount,
+            limit: self.limit_prob,
+            filled: (!self.bets.is_empty()).then(|| {
+                self.bets
+                    .iter()
+                    .filter_map(|b| b.response.as_ref()?["amount"].as_f64())
+                    .sum()
+            }),
+            result,
+            reason,
+        }
+    }
+
+    pub fn record_bet(&mut self, bet: &BetRequest, result: &Result<BetResponse, String>) {
+        self.bets.push(TracedBet {
+            request: serde_json::to_value(bet).unwrap_or_default(),
+            response: result
+                .as_ref()
+                .ok()
+                .and_then(|r| serde_json::to_value(r).ok()),
+            error: result.as_ref().err().cloned(),
+        });
+    }
+
+    /// JSON with `secrets` (API keys) redacted.
+    fn to_json(&self, secrets: &[&str]) -> String {
+        redact(
+            &serde_json::to_string_pretty(self).unwrap_or_default(),
+            secrets,
+        )
+    }
+
+    pub fn save(&self, secrets: &[&str]) {
+        let path = trace_file_path(&self.contract_id);
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, self.to_json(secrets));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact(
+                "key abc123 and xai-456, abc123 again",
+                &["abc123", "xai-456"]
+            ),
+            "key [REDACTED] and [REDACTED], [REDACTED] again"
+        );
+        // An unset key doesn't redact everything
+        assert_eq!(redact("nothing secret", &[""]), "nothing secret");
+    }
+
+    #[test]
+    fn test_trace_json() {
+        let mut trace = DecisionTrace::new("c1", "Will X?", Strategy::Reversion, 100);
+        trace.xai_response = Some("echoing key sk-secret".to_string());
+        trace.record_decision(&BetDecision::Skip {
+            abs_edge: 0.05,
+            net_edge: 0.03,
+            guarded: false,
+        });
+        let bet = BetRequest {
+            contract_id: "c1".to_string(),
+            amount: 10.0,
+            outcome: "YES".to_string(),
+            limit_prob: Some(0.6),
+            expires_at: None,
+            expires_millis_after: None,
+        };
+        trace.record_bet(&bet, &Err("market is closed".to_string()));
+
+        let json: serde_json::Value = serde_json::from_str(&trace.to_json(&["sk-secret"])).unwrap();
+        assert_eq!(json["strategy"], "reversion");
+        assert_eq!(json["net_edge"], 0.03);
+        assert_eq!(json["xai_response"], "echoing key [REDACTED]");
+        assert!(json["decision"].as_str().unwrap().starts_with("Skip"));
+        assert_eq!(json["bets"][0]["request"]["contractId"], "c1");
+        assert_eq!(json["bets"][0]["error"], "market is closed");
+        assert!(json["bets"][0]["response"].is_null());
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut trace = DecisionTrace::new("c1", "Will X?", Strategy::NewMarket, 100);
+        trace.prediction = Some(0.7);
+        trace.market_prob = Some(0.5);
+        trace.amount = Some(10.0);
+        trace.record_decision(&BetDecision::Skip {
+            abs_edge: 0.05,
+            net_edge: 0.03,
+            guarded: false,
+        });
+        let skipped = trace.summary();
+        assert_eq!(skipped.result, "skipped");
+        assert_eq!(skipped.edge, Some(0.03));
+        assert_eq!(skipped.outcome, None);
+        assert_eq!(skipped.filled, None);
+        assert!(skipped.reason.unwrap().starts_with("Skip"));
+
+        trace.record_decision(&BetDecision::Bet {
+            outcome: "YES",
+            limit_prob: 0.65,
+            abs_edge: 0.2,
+            net_edge: 0.15,
+            distance_capped: false,
+        });
+        let bet = |amount| BetRequest {
+            contract_id: "c1".to_string(),
+            amount,
+            outcome: "YES".to_string(),
+            limit_prob: Some(0.65),
+            expires_at: None,
+            expires_millis_after: None,
+        };
+        let filled = |amount| BetResponse {
+            bet_id: Some("b".to_string()),
+            amount: Some(amount),
+            shares: None,
+            outcome: Some("YES".to_string()),
+            contract_id: Some("c1".to_string()),
+            is_filled: None,
+        };
+        trace.record_bet(&bet(5.0), &Ok(filled(5.0)));
+        trace.record_bet(&bet(5.0), &Ok(filled(2.0)));
+        let placed = trace.summary();
+        assert_eq!(placed.result, "placed");
+        assert_eq!(placed.outcome.as_deref(), Some("YES"));
+        assert_eq!(placed.limit, Some(0.65));
+        assert_eq!(placed.filled, Some(7.0));
+
+        trace.record_bet(&bet(5.0), &Err("market is closed".to_string()));
+        let partial = trace.summary();
+        assert_eq!(partial.result, "partial");
+        assert_eq!(partial.reason.as_deref(), Some("market is closed"));
+
+        trace.bet_error = Some("trading paused".to_string());
+        assert_eq!(trace.summary().result, "blocked");
+
+        let json = serde_json::to_value(trace.summary()).unwrap();
+        assert_eq!(json["strategy"], "new-market");
+        assert_eq!(json["contract_id"], "c1");
+    }
+}
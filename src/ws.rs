@@ -1,6 +1,11 @@
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{header, HeaderValue, Request};
+use tokio_tungstenite::tungstenite::Bytes;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 const WS_URL: &str = "wss://api.manifold.markets/ws";
@@ -14,12 +19,62 @@ struct WsClientMsg {
     topics: Option<Vec<String>>,
 }
 
+impl WsClientMsg {
+    fn new(msg_type: &str, txid: u64, topics: Option<Vec<String>>) -> Self {
+        Self {
+            msg_type: msg_type.to_string(),
+            txid,
+            topics,
+        }
+    }
+
+    fn subscribe(txid: u64, topics: Vec<String>) -> Self {
+        Self::new("subscribe", txid, Some(topics))
+    }
+
+    fn unsubscribe(txid: u64, topics: Vec<String>) -> Self {
+        Self::new("unsubscribe", txid, Some(topics))
+    }
+
+    fn ping(txid: u64) -> Self {
+        Self::new("ping", txid, None)
+    }
+}
+
+/// The feed's default topics.
+pub const DEFAULT_TOPICS: [&str; 2] = ["global/new-contract", "global/new-bet"];
+
+/// Keepalive timing. Each ping also sends a protocol ping, whose pong counts
+/// as activity, so a quiet feed isn't mistaken for a dead connection.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WsConfig {
+    /// Ping the server this often.
+    pub ping_interval_secs: u64,
+    /// Reconnect after this long without any frame, pongs included.
+    pub stale_timeout_secs: u64,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval_secs: 20,
+            stale_timeout_secs: 90,
+        }
+    }
+}
+
+/// A change to the live subscription set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TopicCommand {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "lowercase")]
 pub enum WsMessage {
     Ack {
-        #[allow(dead_code)]
         txid: u64,
         success: bool,
     },
@@ -53,6 +108,8 @@ pub struct ContractData {
     pub probability: Option<f64>,
     pub p: Option<f64>,
     pub total_liquidity: Option<f64>,
+    /// Liquidity the creator seeded the market with, when the payload has it.
+    pub initial_liquidity: Option<f64>,
     pub text_description: Option<String>,
 }
 
@@ -70,6 +127,9 @@ pub struct BetData {
     pub contract_id: String,
     pub prob_before: f64,
     pub prob_after: f64,
+    /// Epoch milliseconds; 0 if the broadcast omitted it.
+    #[serde(default)]
+    pub created_time: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -81,14 +141,31 @@ struct NewBetBroadcast {
 pub enum WsEvent {
     Connected,
     NewContract(Box<NewContractBroadcast>),
-    NewBet(Box<BetData>),
+    /// A bet, with when it was received.
+    NewBet(Box<BetData>, Instant),
     Error(String),
+    /// Protocol detail only worth seeing while debugging, e.g. an unexpected frame.
+    Debug(String),
     Disconnected,
 }
 
-pub async fn run_ws(tx: mpsc::UnboundedSender<WsEvent>) {
+/// Connect and forward events to `tx`, reconnecting internally on failure.
+/// `tx` stays open across reconnects, so consumers keep their state; events
+/// broadcast while disconnected are lost (see `bot` backfill). Subscribes to
+/// `topics`, changed live through `topic_rx`; the current set is resubscribed
+/// after every reconnect. Keepalive follows `config`.
+pub async fn run_ws(
+    tx: mpsc::UnboundedSender<WsEvent>,
+    user_agent: String,
+    topics: Vec<String>,
+    mut topic_rx: mpsc::UnboundedReceiver<TopicCommand>,
+    config: WsConfig,
+) {
+    let mut topics = TopicManager::new(topics);
     loop {
-        if let Err(e) = connect_and_listen(&tx).await {
+        if let Err(e) =
+            connect_and_listen(&tx, &user_agent, &mut topics, &mut topic_rx, config).await
+        {
             let _ = tx.send(WsEvent::Error(format!("WS error: {e}")));
         }
         let _ = tx.send(WsEvent::Disconnected);
@@ -96,80 +173,173 @@ pub async fn run_ws(tx: mpsc::UnboundedSender<WsEvent>) {
     }
 }
 
+/// The WebSocket handshake request, identifying the bot via `User-Agent`.
+fn handshake_request(
+    user_agent: &str,
+) -> Result<Request<()>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut request = WS_URL.into_client_request()?;
+    request
+        .headers_mut()
+        .insert(header::USER_AGENT, HeaderValue::from_str(user_agent)?);
+    Ok(request)
+}
+
+/// The active topic set and the protocol messages that maintain it. Outlives
+/// each connection, so a reconnect resubscribes whatever is current.
+#[derive(Debug, Default)]
+struct TopicManager {
+    topics: BTreeSet<String>,
+    /// Last request id used; ids keep increasing across reconnects.
+    txid: u64,
+    /// What each unacknowledged request was, to report a failed one.
+    pending: HashMap<u64, String>,
+}
+
+impl TopicManager {
+    fn new(topics: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            topics: topics.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    fn next_txid(&mut self) -> u64 {
+        self.txid += 1;
+        self.txid
+    }
+
+    /// Subscribe a fresh connection to the whole set; `None` if it's empty.
+    /// Acks owed by the previous connection are forgotten.
+    fn resubscribe(&mut self) -> Option<WsClientMsg> {
+        self.pending.clear();
+        if self.topics.is_empty() {
+            return None;
+        }
+        let txid = self.next_txid();
+        self.pending.insert(txid, "Subscription".to_string());
+        Some(WsClientMsg::subscribe(
+            txid,
+            self.topics.iter().cloned().collect(),
+        ))
+    }
+
+    /// Apply `cmd` to the set; the message to send, or `None` if nothing changed.
+    fn apply(&mut self, cmd: TopicCommand) -> Option<WsClientMsg> {
+        let changed = match &cmd {
+            TopicCommand::Subscribe(topic) => self.topics.insert(topic.clone()),
+            TopicCommand::Unsubscribe(topic) => self.topics.remove(topic),
+        };
+        if !changed {
+            return None;
+        }
+        let txid = self.next_txid();
+        let msg = match &cmd {
+            TopicCommand::Subscribe(topic) => WsClientMsg::subscribe(txid, vec![topic.clone()]),
+            TopicCommand::Unsubscribe(topic) => WsClientMsg::unsubscribe(txid, vec![topic.clone()]),
+        };
+        self.pending.insert(txid, format!("{cmd:?}"));
+        Some(msg)
+    }
+
+    fn ping(&mut self) -> WsClientMsg {
+        WsClientMsg::ping(self.next_txid())
+    }
+
+    /// The request that failed, if `txid` is one we track and `success` is
+    /// false. Pings are acked too but never tracked.
+    fn ack(&mut self, txid: u64, success: bool) -> Option<String> {
+        let request = self.pending.remove(&txid)?;
+        (!success).then_some(request)
+    }
+}
+
 async fn connect_and_listen(
     tx: &mpsc::UnboundedSender<WsEvent>,
+    user_agent: &str,
+    topics: &mut TopicManager,
+    topic_rx: &mut mpsc::UnboundedReceiver<TopicCommand>,
+    config: WsConfig,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let (ws_stream, _) = connect_async(WS_URL).await?;
+    let (ws_stream, _) = connect_async(handshake_request(user_agent)?).await?;
     let (mut write, mut read) = ws_stream.split();
 
     // Subscribe
-    let sub = WsClientMsg {
-        msg_type: "subscribe".to_string(),
-        txid: 1,
-        topics: Some(vec![
-            "global/new-contract".to_string(),
-            "global/new-bet".to_string(),
-        ]),
-    };
-    write
-        .send(Message::Text(serde_json::to_string(&sub)?.into()))
-        .await?;
+    if let Some(sub) = topics.resubscribe() {
+        write
+            .send(Message::Text(serde_json::to_string(&sub)?.into()))
+            .await?;
+    }
 
     let _ = tx.send(WsEvent::Connected);
 
-    // JSON ping every 20s to keep connection alive
-    let mut ping_interval = tokio::time::interval(std::time::Duration::from_secs(20));
-    let mut ping_txid: u64 = 100;
+    // Ping regularly to keep the connection alive (`interval` panics on zero)
+    let mut ping_interval =
+        tokio::time::interval(Duration::from_secs(config.ping_interval_secs.max(1)));
 
-    // Staleness: if no message received for 90s, reconnect
-    let stale_timeout = std::time::Duration::from_secs(90);
+    // Staleness: if no frame received for a while, reconnect
+    let stale_timeout = config.stale_timeout_secs;
+    let mut liveness = Liveness::new(Duration::from_secs(stale_timeout), Instant::now());
 
     loop {
         tokio::select! {
+            Some(cmd) = topic_rx.recv() => {
+                if let Some(change) = topics.apply(cmd) {
+                    if write.send(Message::Text(serde_json::to_string(&change)?.into())).await.is_err() {
+                        break;
+                    }
+                }
+            }
             _ = ping_interval.tick() => {
-                ping_txid += 1;
-                let ping = WsClientMsg {
-                    msg_type: "ping".to_string(),
-                    txid: ping_txid,
-                    topics: None,
-                };
+                let ping = topics.ping();
                 if let Ok(json) = serde_json::to_string(&ping) {
                     if write.send(Message::Text(json.into())).await.is_err() {
                         break;
                     }
                 }
+                // The pong proves the socket is alive even if nothing else arrives
+                if write.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break;
+                }
             }
-            msg = tokio::time::timeout(stale_timeout, read.next()) => {
-                let msg = match msg {
-                    Ok(Some(Ok(m))) => m,
-                    Ok(Some(Err(e))) => {
+            next = read_next(&mut read, &liveness) => {
+                let msg = match next {
+                    Read::Frame(m) => m,
+                    Read::Error(e) => {
                         let _ = tx.send(WsEvent::Error(format!("WS read error: {e}")));
                         break;
                     }
-                    Ok(None) => break, // stream ended
-                    Err(_) => {
-                        let _ = tx.send(WsEvent::Error("WS stale — no message for 90s".to_string()));
+                    Read::Ended => break,
+                    Read::Stale => {
+                        let _ = tx.send(WsEvent::Error(format!(
+                            "WS stale — no message for {stale_timeout}s"
+                        )));
                         break;
                     }
                 };
-                match msg {
-                    Message::Text(text) => {
-                        if let Ok(ws_msg) = serde_json::from_str::<WsMessage>(&text) {
-                            match ws_msg {
-                                WsMessage::Ack { success, .. } => {
-                                    if !success {
-                                        let _ = tx.send(WsEvent::Error("Subscription failed".to_string()));
-                                    }
-                                }
-                                WsMessage::Broadcast { topic, data } => {
-                                    let event = parse_broadcast(&topic, data);
-                                    let _ = tx.send(event);
-                                }
-                            }
+                let frame = liveness.receive(msg, Instant::now());
+                // tungstenite queues a pong itself, but only flushes it on
+                // our next write; answer now so the server never waits
+                if let Some(reply) = frame.reply() {
+                    if write.send(reply).await.is_err() {
+                        break;
+                    }
+                }
+                match frame {
+                    Incoming::Message(WsMessage::Ack { txid, success }) => {
+                        if let Some(request) = topics.ack(txid, success) {
+                            let _ = tx.send(WsEvent::Error(format!("{request} failed")));
                         }
                     }
-                    Message::Close(_) => break,
-                    _ => {}
+                    Incoming::Message(WsMessage::Broadcast { topic, data }) => {
+                        let event = parse_broadcast(&topic, data);
+                        let _ = tx.send(event);
+                    }
+                    Incoming::Ping(_) => {}
+                    Incoming::Close => break,
+                    Incoming::Ignored(Some(note)) => {
+                        let _ = tx.send(WsEvent::Debug(note));
+                    }
+                    Incoming::Ignored(None) => {}
                 }
             }
         }
@@ -178,6 +348,115 @@ async fn connect_and_listen(
     Ok(())
 }
 
+/// What one frame from the server calls for. Fragmented messages arrive
+/// reassembled; tungstenite fails the read on ones over its size limit.
+#[derive(Debug)]
+enum Incoming {
+    Message(WsMessage),
+    /// A protocol-level ping, to answer with the same payload.
+    Ping(Bytes),
+    Close,
+    /// Nothing to act on, with a note if the frame was unexpected.
+    Ignored(Option<String>),
+}
+
+impl Incoming {
+    /// The frame to send back, if any.
+    fn reply(&self) -> Option<Message> {
+        match self {
+            Incoming::Ping(payload) => Some(Message::Pong(payload.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// When the connection last showed signs of life. Every frame counts,
+/// including pongs, so a quiet market doesn't look like a dead socket.
+#[derive(Debug)]
+struct Liveness {
+    last_activity: Instant,
+    timeout: Duration,
+}
+
+impl Liveness {
+    fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            last_activity: now,
+            timeout,
+        }
+    }
+
+    /// Note a frame received at `now` and classify it.
+    fn receive(&mut self, msg: Message, now: Instant) -> Incoming {
+        self.last_activity = now;
+        read_frame(msg)
+    }
+
+    /// The connection is stale if nothing arrives by then.
+    fn deadline(&self) -> Instant {
+        self.last_activity + self.timeout
+    }
+}
+
+/// The outcome of waiting for the next frame.
+#[derive(Debug)]
+enum Read {
+    Frame(Message),
+    Error(String),
+    /// The server closed the stream.
+    Ended,
+    /// Nothing arrived before `Liveness::deadline`.
+    Stale,
+}
+
+async fn read_next<S, E>(read: &mut S, liveness: &Liveness) -> Read
+where
+    S: futures_util::Stream<Item = Result<Message, E>> + Unpin,
+    E: std::fmt::Display,
+{
+    tokio::select! {
+        // A frame already waiting counts, even past the deadline
+        biased;
+        next = read.next() => match next {
+            Some(Ok(msg)) => Read::Frame(msg),
+            Some(Err(e)) => Read::Error(e.to_string()),
+            None => Read::Ended,
+        },
+        _ = tokio::time::sleep_until(liveness.deadline().into()) => Read::Stale,
+    }
+}
+
+/// Longest excerpt of an unrecognized message kept in its note.
+const FRAME_NOTE_LEN: usize = 200;
+
+fn read_frame(msg: Message) -> Incoming {
+    match msg {
+        Message::Text(text) => read_text(&text),
+        // Some servers send JSON as binary frames
+        Message::Binary(data) => match std::str::from_utf8(&data) {
+            Ok(text) => read_text(text),
+            Err(_) => Incoming::Ignored(Some(format!(
+                "Ignoring non-UTF-8 binary frame ({} bytes)",
+                data.len()
+            ))),
+        },
+        Message::Ping(payload) => Incoming::Ping(payload),
+        Message::Pong(_) => Incoming::Ignored(None),
+        Message::Close(_) => Incoming::Close,
+        Message::Frame(_) => Incoming::Ignored(Some("Ignoring raw frame".to_string())),
+    }
+}
+
+fn read_text(text: &str) -> Incoming {
+    match serde_json::from_str::<WsMessage>(text) {
+        Ok(msg) => Incoming::Message(msg),
+        Err(_) => {
+            let excerpt: String = text.chars().take(FRAME_NOTE_LEN).collect();
+            Incoming::Ignored(Some(format!("Ignoring unrecognized message: {excerpt}")))
+        }
+    }
+}
+
 fn parse_broadcast(topic: &str, data: serde_json::Value) -> WsEvent {
     match topic {
         "global/new-contract" => match serde_json::from_value::<NewContractBroadcast>(data) {
@@ -187,7 +466,7 @@ fn parse_broadcast(topic: &str, data: serde_json::Value) -> WsEvent {
         "global/new-bet" => match serde_json::from_value::<NewBetBroadcast>(data) {
             Ok(broadcast) => {
                 if let Some(bet) = broadcast.bets.into_iter().next() {
-                    WsEvent::NewBet(Box::new(bet))
+                    WsEvent::NewBet(Box::new(bet), Instant::now())
                 } else {
                     WsEvent::Error("Empty bets array in new-bet broadcast".to_string())
                 }
@@ -197,3 +476,199 @@ fn parse_broadcast(topic: &str, data: serde_json::Value) -> WsEvent {
         _ => WsEvent::Error(format!("Unknown topic: {topic}")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_messages() {
+        let mut topics = TopicManager::new(DEFAULT_TOPICS.map(String::from));
+
+        let msg = topics
+            .apply(TopicCommand::Subscribe("contract/abc".to_string()))
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&msg).unwrap(),
+            serde_json::json!({"type": "subscribe", "txid": 1, "topics": ["contract/abc"]})
+        );
+
+        let msg = topics
+            .apply(TopicCommand::Unsubscribe("global/new-bet".to_string()))
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&msg).unwrap(),
+            serde_json::json!({"type": "unsubscribe", "txid": 2, "topics": ["global/new-bet"]})
+        );
+
+        // No-op changes send nothing
+        assert!(topics
+            .apply(TopicCommand::Unsubscribe("global/new-bet".to_string()))
+            .is_none());
+        assert!(topics
+            .apply(TopicCommand::Subscribe("contract/abc".to_string()))
+            .is_none());
+
+        assert_eq!(
+            serde_json::to_value(topics.ping()).unwrap(),
+            serde_json::json!({"type": "ping", "txid": 3})
+        );
+    }
+
+    #[test]
+    fn test_resubscribe_after_reconnect() {
+        let mut topics = TopicManager::new(DEFAULT_TOPICS.map(String::from));
+        let first = topics.resubscribe().unwrap();
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::json!({
+                "type": "subscribe",
+                "txid": 1,
+                "topics": ["global/new-bet", "global/new-contract"],
+            })
+        );
+        assert_eq!(topics.ack(1, true), None);
+
+        topics.apply(TopicCommand::Subscribe("contract/abc".to_string()));
+        topics.apply(TopicCommand::Unsubscribe("global/new-bet".to_string()));
+        let unacked = topics.ping().txid - 1;
+
+        // Reconnect: the current set in one message, old acks forgotten
+        let again = topics.resubscribe().unwrap();
+        assert_eq!(
+            serde_json::to_value(&again).unwrap(),
+            serde_json::json!({
+                "type": "subscribe",
+                "txid": 5,
+                "topics": ["contract/abc", "global/new-contract"],
+            })
+        );
+        assert_eq!(topics.ack(unacked, false), None);
+        assert_eq!(topics.ack(5, false), Some("Subscription".to_string()));
+
+        // Nothing to subscribe to
+        let mut empty = TopicManager::default();
+        assert!(empty.resubscribe().is_none());
+    }
+
+    #[test]
+    fn test_read_frame() {
+        let ack = r#"{"type":"ack","txid":3,"success":true}"#;
+        assert!(matches!(
+            read_frame(Message::Text(ack.into())),
+            Incoming::Message(WsMessage::Ack {
+                txid: 3,
+                success: true
+            })
+        ));
+
+        // JSON in a binary frame is handled like text
+        let broadcast = r#"{"type":"broadcast","topic":"global/new-bet","data":{"bets":[]}}"#;
+        match read_frame(Message::Binary(Bytes::from(broadcast))) {
+            Incoming::Message(WsMessage::Broadcast { topic, .. }) => {
+                assert_eq!(topic, "global/new-bet")
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        match read_frame(Message::Binary(Bytes::from_static(&[0xff, 0xfe, 0x00]))) {
+            Incoming::Ignored(Some(note)) => assert!(note.contains("3 bytes")),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        // Pings are answered with their payload; pongs need nothing
+        match read_frame(Message::Ping(Bytes::from_static(b"keepalive"))) {
+            Incoming::Ping(payload) => assert_eq!(&payload[..], b"keepalive"),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(matches!(
+            read_frame(Message::Pong(Bytes::new())),
+            Incoming::Ignored(None)
+        ));
+        assert!(matches!(read_frame(Message::Close(None)), Incoming::Close));
+
+        // Unknown JSON is noted, truncated
+        let long = format!(r#"{{"type":"hello","padding":"{}"}}"#, "x".repeat(500));
+        match read_frame(Message::Text(long.into())) {
+            Incoming::Ignored(Some(note)) => {
+                assert!(note.starts_with("Ignoring unrecognized message: {\"type\":\"hello\""));
+                assert!(note.len() < 300);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_ping_liveness() {
+        let start = Instant::now();
+        let mut liveness = Liveness::new(Duration::from_secs(90), start);
+        assert_eq!(liveness.deadline(), start + Duration::from_secs(90));
+
+        // A ping is answered with a pong carrying its payload
+        let later = start + Duration::from_secs(60);
+        let frame = liveness.receive(Message::Ping(Bytes::from_static(b"srv")), later);
+        match frame.reply() {
+            Some(Message::Pong(payload)) => assert_eq!(&payload[..], b"srv"),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(liveness.deadline(), later + Duration::from_secs(90));
+
+        // A pong needs no reply but still proves the connection is alive
+        let latest = later + Duration::from_secs(60);
+        let frame = liveness.receive(Message::Pong(Bytes::new()), latest);
+        assert!(frame.reply().is_none());
+        assert_eq!(liveness.deadline(), latest + Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_stale_timeout() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let timeout = Duration::from_millis(50);
+            let mut liveness = Liveness::new(timeout, Instant::now());
+
+            // A pong arriving in time is a frame, and pushes the deadline back
+            let frames = vec![Ok::<_, String>(Message::Pong(Bytes::new()))];
+            let mut read =
+                futures_util::stream::iter(frames).chain(futures_util::stream::pending());
+            let Read::Frame(msg) = read_next(&mut read, &liveness).await else {
+                panic!("expected the pong");
+            };
+            let before = liveness.deadline();
+            liveness.receive(msg, Instant::now());
+            assert!(liveness.deadline() >= before);
+
+            // Then silence: stale once the timeout passes, not before
+            let received = Instant::now();
+            liveness.receive(Message::Pong(Bytes::new()), received);
+            assert!(matches!(read_next(&mut read, &liveness).await, Read::Stale));
+            assert!(received.elapsed() >= timeout);
+
+            let mut ended = futures_util::stream::empty::<Result<Message, String>>();
+            assert!(matches!(
+                read_next(&mut ended, &liveness).await,
+                Read::Ended
+            ));
+            let mut failing = futures_util::stream::iter(vec![Err::<Message, _>("reset")]);
+            assert!(
+                matches!(read_next(&mut failing, &liveness).await, Read::Error(e) if e == "reset")
+            );
+        });
+    }
+
+    #[test]
+    fn test_handshake_request_user_agent() {
+        let request = handshake_request("manifold-domination/1.2.3").unwrap();
+        assert_eq!(request.uri(), WS_URL);
+        assert_eq!(
+            request.headers()[header::USER_AGENT],
+            "manifold-domination/1.2.3"
+        );
+        // The usual WebSocket upgrade headers are still present
+        assert!(request.headers().contains_key(header::SEC_WEBSOCKET_KEY));
+
+        assert!(handshake_request("bad\nagent").is_err());
+    }
+}
@@ -6,6 +6,10 @@ struct XaiRequest {
     input: Vec<InputMessage>,
     tools: Vec<Tool>,
     text: TextFormat,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -31,12 +35,54 @@ struct InputMessage {
 struct Tool {
     #[serde(rename = "type")]
     tool_type: String,
+    /// Earliest post date searched, `YYYY-MM-DD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_search_results: Option<u32>,
+}
+
+/// Sampling and X search tuning for research requests. `None` keeps xAI's
+/// defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResearchParams {
+    /// Sampling temperature, 0–2. Lower gives more consistent probabilities
+    /// across runs.
+    pub temperature: Option<f64>,
+    /// Only search X posts from the last this many days (1–365).
+    pub recency_days: Option<u32>,
+    /// Cap on X search results considered (1–50).
+    pub max_results: Option<u32>,
+}
+
+impl ResearchParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(t) = self.temperature {
+            if !(0.0..=2.0).contains(&t) {
+                return Err(format!("xAI temperature {t} is outside 0–2"));
+            }
+        }
+        if let Some(days) = self.recency_days {
+            if !(1..=365).contains(&days) {
+                return Err(format!("x_search recency of {days} days is outside 1–365"));
+            }
+        }
+        if let Some(n) = self.max_results {
+            if !(1..=50).contains(&n) {
+                return Err(format!("x_search max results {n} is outside 1–50"));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct XaiResponse {
     pub output: Option<Vec<OutputItem>>,
     pub error: Option<ApiError>,
+    /// URLs of the sources the search tools used.
+    #[serde(default)]
+    pub citations: Vec<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -56,21 +102,33 @@ pub struct ContentBlock {
     #[serde(rename = "type")]
     pub block_type: String,
     pub text: Option<String>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+/// Inline citation of a source in the output text.
+#[derive(Deserialize, Debug)]
+pub struct Annotation {
+    pub url: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct XaiClient {
     http: reqwest::Client,
     api_key: String,
+    params: ResearchParams,
 }
 
 pub struct SearchResult {
     pub text: String,
+    /// Distinct source URLs cited for `text`, in citation order.
+    pub sources: Vec<String>,
 }
 
 impl SearchResult {
     fn from_response(resp: XaiResponse) -> Self {
         let mut text = String::new();
+        let mut sources = Vec::new();
         if let Some(output) = &resp.output {
             for item in output {
                 if item.item_type == "message" {
@@ -80,29 +138,58 @@ impl SearchResult {
                                 if let Some(t) = &block.text {
                                     text.push_str(t);
                                 }
+                                sources
+                                    .extend(block.annotations.iter().filter_map(|a| a.url.clone()));
                             }
                         }
                     }
                 }
             }
         }
-        Self { text }
+        sources.extend(resp.citations);
+        let mut seen = std::collections::HashSet::new();
+        sources.retain(|url| seen.insert(url.clone()));
+        Self { text, sources }
+    }
+
+    /// No answer text at all, e.g. the response held only tool calls.
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
     }
 }
 
 impl XaiClient {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(api_key: String, user_agent: &str) -> Self {
         Self {
-            http: reqwest::Client::new(),
+            http: reqwest::Client::builder()
+                .user_agent(user_agent)
+                .build()
+                .unwrap_or_default(),
             api_key,
+            params: ResearchParams::default(),
         }
     }
 
-    pub async fn research_market(
+    /// Use `params` for every research request. Validate them first.
+    pub fn with_params(mut self, params: ResearchParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// The `research_market` request body, for decision traces.
+    pub fn research_request_json(
         &self,
         question: &str,
         description: Option<&str>,
-    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> serde_json::Value {
+        serde_json::to_value(self.research_request(question, description)).unwrap_or_default()
+    }
+
+    fn research_request(&self, question: &str, description: Option<&str>) -> XaiRequest {
         let description_section = match description {
             Some(desc) if !desc.is_empty() => format!(
                 "\n\nResolution criteria / description:\n\"{desc}\""
@@ -115,8 +202,8 @@ impl XaiClient {
              prediction market question. Focus on finding concrete evidence: official announcements, \
              credible reporting, expert opinions, and sentiment from informed accounts.\n\n\
              Based ONLY on what you find on X, estimate the probability (0-100) that this \
-             resolves YES. If you find little or no relevant information on X, say so and \
-             give a low-confidence estimate near 50.\n\n\
+             resolves YES. If you find little or no relevant information on X, say so, set \
+             low_information to true and give a low-confidence estimate near 50.\n\n\
              If this market is subjective, personal, not objectively resolvable, \
              or depends on information you cannot access (e.g. private metrics, personal decisions, \
              inside knowledge), set action to \"skip\".\n\n\
@@ -138,13 +225,33 @@ impl XaiClient {
                 "reasoning": {
                     "type": "string",
                     "description": "One sentence summary of key evidence or why the market was skipped"
+                },
+                "low_information": {
+                    "type": "boolean",
+                    "description": "True if you found little or no relevant information and the probability is mostly a guess"
+                },
+                "evidence": {
+                    "type": "array",
+                    "description": "The key pieces of evidence behind the prediction, one claim each",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "claim": { "type": "string" },
+                            "source_url": {
+                                "type": "string",
+                                "description": "URL of the post or article supporting the claim"
+                            }
+                        },
+                        "required": ["claim", "source_url"],
+                        "additionalProperties": false
+                    }
                 }
             },
             "required": ["action", "reasoning"],
             "additionalProperties": false
         });
 
-        let request = XaiRequest {
+        XaiRequest {
             model: "grok-4-1-fast".to_string(),
             input: vec![InputMessage {
                 role: "user".to_string(),
@@ -152,6 +259,12 @@ impl XaiClient {
             }],
             tools: vec![Tool {
                 tool_type: "x_search".to_string(),
+                from_date: self.params.recency_days.map(|days| {
+                    let today = crate::bot::now_epoch_secs() / 86_400;
+                    let (y, m, d) = crate::sanity::civil_from_days(today as i64 - days as i64);
+                    format!("{y:04}-{m:02}-{d:02}")
+                }),
+                max_search_results: self.params.max_results,
             }],
             text: TextFormat {
                 format: FormatSpec {
@@ -160,15 +273,113 @@ impl XaiClient {
                     schema,
                 },
             },
-        };
+            stream: false,
+            temperature: self.params.temperature,
+        }
+    }
+
+    /// `research_request` followed up after a skip: asks the model to judge
+    /// resolvability against the stated criteria before punting again.
+    fn clarified_request(
+        &self,
+        question: &str,
+        description: Option<&str>,
+        skip_reason: &str,
+    ) -> XaiRequest {
+        let mut request = self.research_request(question, description);
+        request.input.push(InputMessage {
+            role: "user".to_string(),
+            content: format!(
+                "An earlier look at this market skipped it: \"{skip_reason}\"\n\n\
+                 Reconsider. Most markets resolve on an objective, publicly observable \
+                 outcome even when phrased informally. Only skip if the resolution \
+                 criteria are genuinely subjective or depend on information no one outside \
+                 the creator can see; otherwise give your best probability estimate."
+            ),
+        });
+        request
+    }
+
+    /// `research_request` followed up after a response with no answer text:
+    /// asks for the final JSON answer instead of more searching.
+    fn nudged_request(&self, question: &str, description: Option<&str>) -> XaiRequest {
+        let mut request = self.research_request(question, description);
+        request.input.push(InputMessage {
+            role: "user".to_string(),
+            content: "An earlier attempt at this question returned no answer. Search only \
+                      if you must, then reply with the final JSON answer."
+                .to_string(),
+        });
+        request
+    }
+
+    /// The `research_market_clarified` request body, for decision traces.
+    pub fn clarified_request_json(
+        &self,
+        question: &str,
+        description: Option<&str>,
+        skip_reason: &str,
+    ) -> serde_json::Value {
+        serde_json::to_value(self.clarified_request(question, description, skip_reason))
+            .unwrap_or_default()
+    }
+
+    pub async fn research_market(
+        &self,
+        question: &str,
+        description: Option<&str>,
+    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.send_research(&self.research_request(question, description))
+            .await
+    }
+
+    /// `research_market` with another model, for an independent second opinion.
+    /// `None` uses the default research model.
+    pub async fn research_market_with_model(
+        &self,
+        question: &str,
+        description: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+        let mut request = self.research_request(question, description);
+        if let Some(model) = model {
+            request.model = model.to_string();
+        }
+        self.send_research(&request).await
+    }
+
+    /// Re-ask about a market the model skipped, emphasizing objective resolvability.
+    pub async fn research_market_clarified(
+        &self,
+        question: &str,
+        description: Option<&str>,
+        skip_reason: &str,
+    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.send_research(&self.clarified_request(question, description, skip_reason))
+            .await
+    }
 
+    /// Re-ask after a response that carried no answer text.
+    pub async fn research_market_nudged(
+        &self,
+        question: &str,
+        description: Option<&str>,
+    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+        self.send_research(&self.nudged_request(question, description))
+            .await
+    }
+
+    async fn send_research(
+        &self,
+        request: &XaiRequest,
+    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
         let resp = self
             .http
             .post("https://api.x.ai/v1/responses")
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .timeout(std::time::Duration::from_secs(120))
-            .json(&request)
+            .json(request)
             .send()
             .await?;
 
@@ -186,11 +397,134 @@ impl XaiClient {
 
         Ok(SearchResult::from_response(response))
     }
+
+    /// Like `research_market`, but streams the response (SSE) and returns as soon
+    /// as the output text is a complete prediction, without waiting for the
+    /// response to finish. The result carries no sources.
+    pub async fn research_market_streaming(
+        &self,
+        question: &str,
+        description: Option<&str>,
+    ) -> Result<SearchResult, Box<dyn std::error::Error + Send + Sync>> {
+        let request = XaiRequest {
+            stream: true,
+            ..self.research_request(question, description)
+        };
+
+        let mut resp = self
+            .http
+            .post("https://api.x.ai/v1/responses")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .timeout(std::time::Duration::from_secs(120))
+            .json(&request)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await?;
+            return Err(format!("xAI API error {status}: {body}").into());
+        }
+
+        let mut stream = SseTextStream::default();
+        while let Some(chunk) = resp.chunk().await? {
+            if let Some(text) = stream.push(&chunk)? {
+                // Citations arrive with the completed response, after the text
+                return Ok(SearchResult {
+                    text,
+                    sources: Vec::new(),
+                });
+            }
+        }
+        Err("xAI stream ended before the response completed".into())
+    }
+}
+
+/// Accumulates output text from a `/v1/responses` SSE stream.
+#[derive(Default)]
+struct SseTextStream {
+    /// Bytes of an incomplete line (chunks may split lines and UTF-8 sequences).
+    pending: Vec<u8>,
+    text: String,
+}
+
+impl SseTextStream {
+    /// Feed a chunk; returns the final text once it is complete.
+    fn push(&mut self, chunk: &[u8]) -> Result<Option<String>, String> {
+        self.pending.extend_from_slice(chunk);
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim_end().strip_prefix("data:") else {
+                continue;
+            };
+            if let Some(text) = self.handle_event(data.trim())? {
+                return Ok(Some(text));
+            }
+        }
+        Ok(None)
+    }
+
+    fn handle_event(&mut self, data: &str) -> Result<Option<String>, String> {
+        if data == "[DONE]" {
+            return Ok(Some(std::mem::take(&mut self.text)));
+        }
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+            return Ok(None);
+        };
+        match event["type"].as_str().unwrap_or_default() {
+            "response.output_text.delta" => {
+                self.text
+                    .push_str(event["delta"].as_str().unwrap_or_default());
+                // Structured output is a single JSON object: done once it parses
+                if parse_prediction(&self.text).is_some() {
+                    return Ok(Some(std::mem::take(&mut self.text)));
+                }
+                Ok(None)
+            }
+            "response.output_text.done" => Ok(event["text"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| Some(std::mem::take(&mut self.text)))),
+            "response.completed" => {
+                let text = serde_json::from_value::<XaiResponse>(event["response"].clone())
+                    .map(|r| SearchResult::from_response(r).text)
+                    .unwrap_or_default();
+                if text.is_empty() {
+                    Ok(Some(std::mem::take(&mut self.text)))
+                } else {
+                    Ok(Some(text))
+                }
+            }
+            "error" | "response.failed" => {
+                let message = event["message"]
+                    .as_str()
+                    .or(event["response"]["error"]["message"].as_str())
+                    .unwrap_or("unknown error");
+                Err(format!("xAI stream error: {message}"))
+            }
+            _ => Ok(None),
+        }
+    }
 }
 
 pub struct Prediction {
     pub probability: f64,
+    /// One-sentence summary.
     pub reasoning: String,
+    /// Itemized evidence; empty if the model gave none.
+    pub evidence: Vec<Evidence>,
+    /// The model found little or no relevant information, so the probability
+    /// is a guess whatever it says.
+    pub low_information: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Evidence {
+    pub claim: String,
+    #[serde(default)]
+    pub source_url: Option<String>,
 }
 
 pub enum PredictionResult {
@@ -203,22 +537,98 @@ struct JsonPrediction {
     action: String,
     probability: Option<f64>,
     reasoning: String,
+    #[serde(default)]
+    evidence: Vec<Evidence>,
+    #[serde(default)]
+    low_information: bool,
+}
+
+/// Phrases models use for "I found nothing" when they omit `low_information`.
+const LOW_INFORMATION_PHRASES: &[&str] = &[
+    "little info",
+    "no info",
+    "no relevant info",
+    "no relevant posts",
+    "no relevant evidence",
+    "insufficient info",
+    "limited info",
+    "no evidence found",
+    "nothing relevant",
+];
+
+/// Whether `reasoning` admits the model found little or nothing to go on.
+fn mentions_low_information(reasoning: &str) -> bool {
+    let reasoning = reasoning.to_lowercase();
+    LOW_INFORMATION_PHRASES
+        .iter()
+        .any(|phrase| reasoning.contains(phrase))
+}
+
+/// Each balanced `{...}` in `text`, by position of its opening brace. Braces
+/// inside JSON strings don't count.
+fn json_objects(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices('{')
+        .filter_map(move |(start, _)| balanced_object(&text[start..]))
+}
+
+/// The object opening at the start of `text`, if it closes.
+fn balanced_object(text: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
 }
 
-/// Parse structured JSON prediction from the response text.
+/// The 0–100 probability the prompt asks for as a fraction. Models sometimes
+/// answer with a fraction anyway, so values strictly between 0 and 1 are read
+/// as one; exactly 1 could be either 1% or certainty and is rejected.
+fn percent_to_probability(value: f64) -> Option<f64> {
+    if value > 0.0 && value < 1.0 {
+        return Some(value);
+    }
+    let valid = (0.0..=100.0).contains(&value) && value != 1.0;
+    valid.then(|| value / 100.0)
+}
+
+/// Parse structured JSON prediction from the response text: the first JSON
+/// object shaped like a prediction, so markdown code fences and prose around
+/// it (which models add despite the structured-output request) are ignored.
 pub fn parse_prediction(text: &str) -> Option<PredictionResult> {
-    let parsed: JsonPrediction = serde_json::from_str(text).ok()?;
+    let parsed: JsonPrediction =
+        json_objects(text).find_map(|json| serde_json::from_str(json).ok())?;
 
     match parsed.action.as_str() {
         "skip" => Some(PredictionResult::Skip(parsed.reasoning)),
         "predict" => {
-            let pct = parsed.probability?;
-            if !(0.0..=100.0).contains(&pct) {
-                return None;
-            }
+            let probability = percent_to_probability(parsed.probability?)?;
             Some(PredictionResult::Predict(Prediction {
-                probability: pct / 100.0,
+                probability,
+                low_information: parsed.low_information
+                    || mentions_low_information(&parsed.reasoning),
                 reasoning: parsed.reasoning,
+                evidence: parsed.evidence,
             }))
         }
         _ => None,
@@ -229,6 +639,63 @@ pub fn parse_prediction(text: &str) -> Option<PredictionResult> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_clarified_request() {
+        let json = XaiClient::new(String::new(), "test").clarified_request_json(
+            "Will X?",
+            None,
+            "Subjective market",
+        );
+        let input = json["input"].as_array().unwrap();
+        assert_eq!(input.len(), 2);
+        assert!(input[0]["content"].as_str().unwrap().contains("Will X?"));
+        assert!(input[1]["content"]
+            .as_str()
+            .unwrap()
+            .contains("\"Subjective market\""));
+    }
+
+    #[test]
+    fn test_research_params() {
+        let client = XaiClient::new(String::new(), "test");
+        let json = client.research_request_json("Will X?", None);
+        assert!(json.get("temperature").is_none());
+        assert_eq!(json["tools"][0], serde_json::json!({"type": "x_search"}));
+
+        let params = ResearchParams {
+            temperature: Some(0.2),
+            recency_days: Some(7),
+            max_results: Some(15),
+        };
+        assert_eq!(params.validate(), Ok(()));
+        let json = client
+            .with_params(params)
+            .research_request_json("Will X?", None);
+        assert_eq!(json["temperature"], 0.2);
+        let tool = &json["tools"][0];
+        assert_eq!(tool["max_search_results"], 15);
+        let from_date = tool["from_date"].as_str().unwrap();
+        assert_eq!(from_date.len(), 10);
+        assert_eq!(&from_date[4..5], "-");
+
+        for invalid in [
+            ResearchParams {
+                temperature: Some(2.5),
+                ..ResearchParams::default()
+            },
+            ResearchParams {
+                recency_days: Some(0),
+                ..ResearchParams::default()
+            },
+            ResearchParams {
+                max_results: Some(100),
+                ..ResearchParams::default()
+            },
+        ] {
+            assert!(invalid.validate().is_err());
+        }
+    }
+
     #[test]
     fn test_parse_prediction() {
         let r = parse_prediction(
@@ -255,7 +722,20 @@ mod tests {
             PredictionResult::Skip(_) => panic!("expected Predict"),
         }
 
+        // A fraction despite the prompt is read as one; 1 is ambiguous
+        let probability = |value: &str| match parse_prediction(&format!(
+            r#"{{"action":"predict","probability":{value},"reasoning":""}}"#
+        )) {
+            Some(PredictionResult::Predict(p)) => Some(p.probability),
+            _ => None,
+        };
+        assert_eq!(probability("0.73"), Some(0.73));
+        assert_eq!(probability("0"), Some(0.0));
+        assert_eq!(probability("1"), None);
+        assert_eq!(probability("2"), Some(0.02));
+
         assert!(parse_prediction("not json at all").is_none());
+        assert!(parse_prediction(r#"{"action":"predict","probability":6"#).is_none());
 
         let r = parse_prediction(
             r#"{"action":"skip","reasoning":"Subjective market"}"#,
@@ -266,4 +746,234 @@ mod tests {
             PredictionResult::Predict(_) => panic!("expected Skip"),
         }
     }
+
+    #[test]
+    fn test_parse_prediction_wrapped() {
+        let probability = |text: &str| match parse_prediction(text) {
+            Some(PredictionResult::Predict(p)) => Some(p.probability),
+            _ => None,
+        };
+        let json = r#"{"action":"predict","probability":30,"reasoning":"Little {news} on \"X\""}"#;
+
+        assert_eq!(probability(json), Some(0.30));
+        assert_eq!(probability(&format!("```json\n{json}\n```")), Some(0.30));
+        assert_eq!(probability(&format!("```\n{json}\n```")), Some(0.30));
+        assert_eq!(
+            probability(&format!("Here's my answer:\n{json}")),
+            Some(0.30)
+        );
+        assert_eq!(
+            probability(&format!("{json}\n\nHope this helps {{ :)")),
+            Some(0.30)
+        );
+        // A stray brace in the prose before the object
+        assert_eq!(
+            probability(&format!("Answer {{as requested}}: {json}")),
+            Some(0.30)
+        );
+    }
+
+    #[test]
+    fn test_parse_prediction_evidence() {
+        let r = parse_prediction(
+            r#"{"action":"predict","probability":70,"reasoning":"Announced","evidence":[
+                {"claim":"CEO confirmed the date","source_url":"https://x.com/ceo/status/1"},
+                {"claim":"Shipping notices went out"}
+            ]}"#,
+        );
+        let Some(PredictionResult::Predict(p)) = r else {
+            panic!("expected Predict");
+        };
+        assert_eq!(p.reasoning, "Announced");
+        assert_eq!(
+            p.evidence,
+            [
+                Evidence {
+                    claim: "CEO confirmed the date".to_string(),
+                    source_url: Some("https://x.com/ceo/status/1".to_string()),
+                },
+                Evidence {
+                    claim: "Shipping notices went out".to_string(),
+                    source_url: None,
+                },
+            ]
+        );
+
+        // Absent or empty evidence is fine
+        for text in [
+            r#"{"action":"predict","probability":40,"reasoning":"Little info"}"#,
+            r#"{"action":"predict","probability":40,"reasoning":"Little info","evidence":[]}"#,
+        ] {
+            let Some(PredictionResult::Predict(p)) = parse_prediction(text) else {
+                panic!("expected Predict");
+            };
+            assert!(p.evidence.is_empty());
+        }
+
+        // Malformed evidence items fail the parse like other schema violations
+        assert!(parse_prediction(
+            r#"{"action":"predict","probability":40,"reasoning":"x","evidence":[{"source_url":"https://a"}]}"#
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_empty_output_response() {
+        // The model only ran searches: no message item, so no answer text
+        let response: XaiResponse = serde_json::from_str(
+            r#"{"output":[{"type":"x_search_call","id":"xs_1","status":"completed"}],"citations":["https://x.com/a/status/1"]}"#,
+        )
+        .unwrap();
+        let result = SearchResult::from_response(response);
+        assert!(result.is_empty());
+        assert!(parse_prediction(&result.text).is_none());
+
+        // A message without output_text blocks, or only whitespace, is empty too
+        for output in [
+            r#"[{"type":"message","content":[]}]"#,
+            r#"[{"type":"message"}]"#,
+            r#"[{"type":"message","content":[{"type":"output_text","text":"\n "}]}]"#,
+        ] {
+            let response: XaiResponse =
+                serde_json::from_str(&format!(r#"{{"output":{output}}}"#)).unwrap();
+            assert!(SearchResult::from_response(response).is_empty());
+        }
+        let response: XaiResponse = serde_json::from_str(r#"{}"#).unwrap();
+        assert!(SearchResult::from_response(response).is_empty());
+
+        // Unparseable but non-empty text is a parse failure, not an empty response
+        let response: XaiResponse = serde_json::from_str(
+            r#"{"output":[{"type":"message","content":[{"type":"output_text","text":"I think 70%"}]}]}"#,
+        )
+        .unwrap();
+        assert!(!SearchResult::from_response(response).is_empty());
+
+        let request = serde_json::to_value(
+            XaiClient::new(String::new(), "test").nudged_request("Will X?", None),
+        )
+        .unwrap();
+        let input = request["input"].as_array().unwrap();
+        assert_eq!(input.len(), 2);
+        assert!(input[1]["content"]
+            .as_str()
+            .unwrap()
+            .contains("final JSON answer"));
+    }
+
+    #[test]
+    fn test_low_information() {
+        let low = |text: &str| match parse_prediction(text) {
+            Some(PredictionResult::Predict(p)) => p.low_information,
+            _ => panic!("expected Predict"),
+        };
+        // Explicit flag, or the model saying so in its reasoning
+        assert!(low(
+            r#"{"action":"predict","probability":100,"reasoning":"x","low_information":true}"#
+        ));
+        assert!(low(
+            r#"{"action":"predict","probability":0,"reasoning":"Found little information on X"}"#
+        ));
+        assert!(low(
+            r#"{"action":"predict","probability":50,"reasoning":"No relevant posts; defaulting to 50"}"#
+        ));
+        assert!(!low(
+            r#"{"action":"predict","probability":100,"reasoning":"Officially announced","low_information":false}"#
+        ));
+        assert!(!low(
+            r#"{"action":"predict","probability":0,"reasoning":"Ruled out by the CEO"}"#
+        ));
+    }
+
+    #[test]
+    fn test_search_result_sources() {
+        let text = r#"{\"action\":\"predict\",\"probability\":70,\"reasoning\":\"x\"}"#;
+        let response = |annotations: &str, citations: &str| -> XaiResponse {
+            serde_json::from_str(&format!(
+                r#"{{"output":[{{"type":"message","content":[{{"type":"output_text","text":"{text}","annotations":[{annotations}]}}]}}],"citations":[{citations}]}}"#
+            ))
+            .unwrap()
+        };
+
+        let none = SearchResult::from_response(response("", ""));
+        assert!(none.sources.is_empty());
+        assert!(parse_prediction(&none.text).is_some());
+
+        let one = SearchResult::from_response(response(
+            r#"{"type":"url_citation","url":"https://x.com/a/status/1"}"#,
+            r#""https://x.com/a/status/1""#,
+        ));
+        assert_eq!(one.sources, ["https://x.com/a/status/1"]);
+
+        let several = SearchResult::from_response(response(
+            r#"{"type":"url_citation","url":"https://x.com/a/status/1"},{"type":"url_citation","url":"https://x.com/b/status/2"}"#,
+            r#""https://x.com/b/status/2","https://example.com/news""#,
+        ));
+        assert_eq!(
+            several.sources,
+            [
+                "https://x.com/a/status/1",
+                "https://x.com/b/status/2",
+                "https://example.com/news"
+            ]
+        );
+
+        // Responses without annotations or citations still parse
+        let bare: XaiResponse = serde_json::from_str(
+            r#"{"output":[{"type":"message","content":[{"type":"output_text","text":"hi"}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(SearchResult::from_response(bare).text, "hi");
+    }
+
+    #[test]
+    fn test_sse_stream_resolves_when_json_complete() {
+        let events = concat!(
+            "event: response.created\n",
+            "data: {\"type\":\"response.created\",\"response\":{}}\n\n",
+            "event: response.output_text.delta\n",
+            "data: {\"type\":\"response.output_text.delta\",\"delta\":\"{\\\"action\\\":\\\"predict\\\",\"}\n\n",
+            "event: response.output_text.delta\n",
+            "data: {\"type\":\"response.output_text.delta\",\"delta\":\"\\\"probability\\\":70,\\\"reasoning\\\":\\\"Préstamo\\\"}\"}\n\n",
+            "event: response.completed\n",
+            "data: {\"type\":\"response.completed\"}\n\n",
+        )
+        .as_bytes();
+
+        // Feed in small chunks that split lines and multi-byte characters
+        let mut stream = SseTextStream::default();
+        let mut result = None;
+        for chunk in events.chunks(7) {
+            if let Some(text) = stream.push(chunk).unwrap() {
+                result = Some(text);
+                break;
+            }
+        }
+        let text = result.expect("stream should resolve");
+        match parse_prediction(&text) {
+            Some(PredictionResult::Predict(p)) => {
+                assert_eq!(p.probability, 0.70);
+                assert_eq!(p.reasoning, "Préstamo");
+            }
+            _ => panic!("expected Predict, got {text}"),
+        }
+
+        // Text that isn't a prediction resolves on output_text.done
+        let mut stream = SseTextStream::default();
+        let done = "data: {\"type\":\"response.output_text.done\",\"text\":\"hello\"}\n";
+        assert_eq!(
+            stream.push(done.as_bytes()).unwrap().as_deref(),
+            Some("hello")
+        );
+
+        // Interrupted or failed streams surface an error
+        let mut stream = SseTextStream::default();
+        let failed = "data: {\"type\":\"error\",\"message\":\"overloaded\"}\n";
+        assert!(stream.push(failed.as_bytes()).is_err());
+        assert_eq!(
+            stream
+                .push(b"data: {\"type\":\"response.output_text.delta\",\"delta\":\"{\"}\n")
+                .unwrap(),
+            None
+        );
+    }
 }
@@ -0,0 +1,313 @@
+//! Rolling xAI success/error rate over the last few calls, persisted to
+//! `xai_health.json`. A dead key or exhausted quota makes every analysis fail,
+//! which otherwise just looks like a bot that stopped trading.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Least time between writes of `xai_health.json`: under load every call
+/// would otherwise rewrite it. A crash loses at most this much of the window.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Why an xAI call failed, as far as can be told from the error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum XaiErrorKind {
+    /// 401/403: bad key, or no credits left.
+    Auth,
+    /// 429: rate-limited or over quota.
+    RateLimit,
+    /// No response at all (connect error, timeout, dropped stream).
+    Network,
+    Other,
+}
+
+impl XaiErrorKind {
+    pub fn classify(err: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            return match err.status() {
+                Some(status) => Self::from_status(status.as_u16()),
+                None => Self::Network,
+            };
+        }
+        // `XaiClient` reports error responses as "xAI API error <status>: <body>"
+        let message = err.to_string();
+        match message.strip_prefix("xAI API error ") {
+            Some(rest) => rest
+                .split(|c: char| !c.is_ascii_digit())
+                .next()
+                .and_then(|code| code.parse().ok())
+                .map_or(Self::Other, Self::from_status),
+            None if message.contains("stream ended") => Self::Network,
+            None => Self::Other,
+        }
+    }
+
+    fn from_status(status: u16) -> Self {
+        match status {
+            401 | 403 => Self::Auth,
+            429 => Self::RateLimit,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for XaiErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XaiErrorKind::Auth => write!(f, "auth/credits"),
+            XaiErrorKind::RateLimit => write!(f, "rate limit"),
+            XaiErrorKind::Network => write!(f, "network"),
+            XaiErrorKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// The recent window of calls, for the dashboard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct XaiHealthStats {
+    pub calls: usize,
+    pub errors: usize,
+    pub auth: usize,
+    pub rate_limit: usize,
+    pub network: usize,
+    pub other: usize,
+    pub last_error: Option<String>,
+}
+
+impl XaiHealthStats {
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+
+    /// Errors spiking rather than the odd failed call: likely the key or quota.
+    pub fn is_spiking(&self) -> bool {
+        self.errors >= 3 && self.error_rate() >= 0.5
+    }
+
+    /// The most common error kind in the window, if any call failed.
+    pub fn dominant_error(&self) -> Option<XaiErrorKind> {
+        [
+            (XaiErrorKind::Auth, self.auth),
+            (XaiErrorKind::RateLimit, self.rate_limit),
+            (XaiErrorKind::Network, self.network),
+            (XaiErrorKind::Other, self.other),
+        ]
+        .into_iter()
+        .filter(|(_, n)| *n > 0)
+        .max_by_key(|(_, n)| *n)
+        .map(|(kind, _)| kind)
+    }
+}
+
+impl std::fmt::Display for XaiHealthStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} recent xAI calls failed ({} auth/credits, {} rate limit, {} network, {} other)",
+            self.errors, self.calls, self.auth, self.rate_limit, self.network, self.other
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Persisted {
+    /// Oldest first; `None` is a success.
+    outcomes: VecDeque<Option<XaiErrorKind>>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct XaiHealth {
+    state: Persisted,
+    /// How many calls the rate covers.
+    window: usize,
+    /// Where the window is persisted; `None` keeps it in memory only.
+    path: Option<PathBuf>,
+    /// When the window was last written.
+    saved_at: Option<Instant>,
+}
+
+fn health_file_path() -> PathBuf {
+    crate::bot::data_dir().join("xai_health.json")
+}
+
+impl XaiHealth {
+    pub fn load(window: usize) -> Self {
+        let path = health_file_path();
+        let mut state: Persisted = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok())
+            .unwrap_or_default();
+        while state.outcomes.len() > window {
+            state.outcomes.pop_front();
+        }
+        Self {
+            state,
+            window,
+            path: Some(path),
+            saved_at: None,
+        }
+    }
+
+    /// A window that is never written to disk.
+    pub fn in_memory(window: usize) -> Self {
+        Self {
+            window,
+            ..Self::default()
+        }
+    }
+
+    /// Write the window now, throttled or not.
+    pub fn flush(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        self.saved_at = Some(Instant::now());
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, serde_json::to_string(&self.state).unwrap_or_default());
+    }
+
+    pub fn record_success(&mut self) {
+        self.push(None);
+    }
+
+    pub fn record_error(&mut self, kind: XaiErrorKind, message: &str) {
+        self.state.last_error = Some(message.to_string());
+        self.push(Some(kind));
+    }
+
+    fn push(&mut self, outcome: Option<XaiErrorKind>) {
+        self.state.outcomes.push_back(outcome);
+        while self.state.outcomes.len() > self.window {
+            self.state.outcomes.pop_front();
+        }
+        if self.saved_at.is_none_or(|at| at.elapsed() >= SAVE_INTERVAL) {
+            self.flush();
+        }
+    }
+
+    /// Forget the window, e.g. after the key has been fixed.
+    pub fn reset(&mut self) {
+        self.state = Persisted::default();
+        self.flush();
+    }
+
+    pub fn stats(&self) -> XaiHealthStats {
+        let mut s = XaiHealthStats {
+            calls: self.state.outcomes.len(),
+            last_error: self.state.last_error.clone(),
+            ..XaiHealthStats::default()
+        };
+        for kind in self.state.outcomes.iter().flatten() {
+            s.errors += 1;
+            match kind {
+                XaiErrorKind::Auth => s.auth += 1,
+                XaiErrorKind::RateLimit => s.rate_limit += 1,
+                XaiErrorKind::Network => s.network += 1,
+                XaiErrorKind::Other => s.other += 1,
+            }
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(message: &str) -> XaiErrorKind {
+        let err: Box<dyn std::error::Error + Send + Sync> = message.into();
+        XaiErrorKind::classify(err.as_ref())
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify("xAI API error 401 Unauthorized: {\"error\":\"Incorrect API key\"}"),
+            XaiErrorKind::Auth
+        );
+        assert_eq!(
+            classify("xAI API error 403 Forbidden: no credits"),
+            XaiErrorKind::Auth
+        );
+        assert_eq!(
+            classify("xAI API error 429 Too Many Requests: slow down"),
+            XaiErrorKind::RateLimit
+        );
+        assert_eq!(
+            classify("xAI API error 500 Internal Server Error: oops"),
+            XaiErrorKind::Other
+        );
+        assert_eq!(
+            classify("xAI stream ended before the response completed"),
+            XaiErrorKind::Network
+        );
+        assert_eq!(classify("xAI error: bad request"), XaiErrorKind::Other);
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let mut health = XaiHealth {
+            window: 4,
+            ..XaiHealth::default()
+        };
+        assert_eq!(health.stats().error_rate(), 0.0);
+        assert_eq!(health.stats().dominant_error(), None);
+
+        health.record_success();
+        health.record_error(XaiErrorKind::RateLimit, "429");
+        health.record_error(XaiErrorKind::Auth, "401 a");
+        health.record_error(XaiErrorKind::Auth, "401 b");
+        let s = health.stats();
+        assert_eq!((s.calls, s.errors, s.auth, s.rate_limit), (4, 3, 2, 1));
+        assert!(s.is_spiking());
+        assert_eq!(s.dominant_error(), Some(XaiErrorKind::Auth));
+        assert_eq!(s.last_error.as_deref(), Some("401 b"));
+
+        // The oldest call (the success) drops out
+        health.record_error(XaiErrorKind::Network, "timeout");
+        let s = health.stats();
+        assert_eq!((s.calls, s.errors), (4, 4));
+        assert_eq!(s.error_rate(), 1.0);
+
+        health.reset();
+        assert_eq!(health.stats(), XaiHealthStats::default());
+    }
+
+    #[test]
+    fn test_saves_throttled() {
+        let path =
+            std::env::temp_dir().join(format!("xai-health-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut health = XaiHealth {
+            window: 10,
+            path: Some(path.clone()),
+            ..XaiHealth::default()
+        };
+        let saved_calls = || -> usize {
+            let data = std::fs::read_to_string(&path).unwrap();
+            serde_json::from_str::<Persisted>(&data)
+                .unwrap()
+                .outcomes
+                .len()
+        };
+
+        // The first call is written, the burst after it isn't yet
+        health.record_success();
+        health.record_error(XaiErrorKind::Auth, "401");
+        health.record_success();
+        assert_eq!(saved_calls(), 1);
+        health.flush();
+        assert_eq!(saved_calls(), 3);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,204 @@
+//! Record/replay of xAI research (`BotConfig::xai_replay`). Recording saves
+//! each prompt and raw response to `xai_recordings.json`, one per kind of
+//! call (research, retries, confirmation); replay serves those responses
+//! instead of calling xAI, so prompt or strategy changes can be re-run against
+//! historical model outputs for free.
+
+use crate::analysis_cache::fnv1a;
+use crate::xai::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReplayMode {
+    /// Call xAI as usual and save every research response.
+    Record,
+    /// Answer research from the recordings only; unrecorded markets fail.
+    Replay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recording {
+    /// Which call this answered, e.g. `research` or `confirmation`.
+    call: String,
+    question: String,
+    description: Option<String>,
+    /// The request body sent to xAI.
+    prompt: serde_json::Value,
+    /// Raw response text, parsed again on replay.
+    text: String,
+    #[serde(default)]
+    sources: Vec<String>,
+    /// Epoch seconds.
+    recorded_at: u64,
+}
+
+/// Recorded responses keyed on the kind of call and a hash of the exact
+/// question and description. Unlike `AnalysisCache`, entries never expire and questions
+/// aren't normalized, so replays are exact.
+#[derive(Debug)]
+pub struct XaiReplay {
+    mode: ReplayMode,
+    entries: HashMap<String, Recording>,
+    path: PathBuf,
+}
+
+fn recordings_file_path() -> PathBuf {
+    crate::bot::data_dir().join("xai_recordings.json")
+}
+
+fn replay_key(call: &str, question: &str, description: Option<&str>) -> String {
+    format!(
+        "{call}:{:016x}{:016x}",
+        fnv1a(question),
+        fnv1a(description.unwrap_or_default())
+    )
+}
+
+impl XaiReplay {
+    pub fn load(mode: ReplayMode) -> Self {
+        Self::load_from(mode, recordings_file_path())
+    }
+
+    pub(crate) fn load_from(mode: ReplayMode, path: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|d| serde_json::from_str(&d).ok())
+            .unwrap_or_default();
+        Self {
+            mode,
+            entries,
+            path,
+        }
+    }
+
+    fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(
+            &self.path,
+            serde_json::to_string(&self.entries).unwrap_or_default(),
+        );
+    }
+
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The recorded `call` response for exactly this question and description.
+    pub fn get(
+        &self,
+        call: &str,
+        question: &str,
+        description: Option<&str>,
+    ) -> Option<SearchResult> {
+        self.entries
+            .get(&replay_key(call, question, description))
+            .map(|r| SearchResult {
+                text: r.text.clone(),
+                sources: r.sources.clone(),
+            })
+    }
+
+    /// Save `result` as the response to `prompt`, replacing any earlier
+    /// response to the same call.
+    pub fn record(
+        &mut self,
+        call: &str,
+        question: &str,
+        description: Option<&str>,
+        prompt: serde_json::Value,
+        result: &SearchResult,
+        now: u64,
+    ) {
+        self.entries.insert(
+            replay_key(call, question, description),
+            Recording {
+                call: call.to_string(),
+                question: question.to_string(),
+                description: description.map(str::to_string),
+                prompt,
+                text: result.text.clone(),
+                sources: result.sources.clone(),
+                recorded_at: now,
+            },
+        );
+        self.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::{decide_bet, BotConfig};
+    use crate::xai::{self, PredictionResult};
+
+    #[test]
+    fn test_replay_reproduces_decision() {
+        let path =
+            std::env::temp_dir().join(format!("xai-replay-test-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let question = "Will X ship by June?";
+        let description = Some("Resolves YES on a public release.");
+        let recorded = SearchResult {
+            text: r#"{"action":"predict","probability":72,"reasoning":"CEO confirmed"}"#
+                .to_string(),
+            sources: vec!["https://x.com/ceo/status/1".to_string()],
+        };
+        let decide = |result: &SearchResult| {
+            let Some(PredictionResult::Predict(p)) = xai::parse_prediction(&result.text) else {
+                panic!("expected Predict");
+            };
+            decide_bet(
+                p.probability,
+                0.40,
+                25.0,
+                1000.0,
+                None,
+                &BotConfig::default(),
+            )
+        };
+
+        let mut recorder = XaiReplay::load_from(ReplayMode::Record, path.clone());
+        recorder.record(
+            "research",
+            question,
+            description,
+            serde_json::json!({"input": [{"role": "user", "content": question}]}),
+            &recorded,
+            1_700_000_000,
+        );
+        assert_eq!(recorder.len(), 1);
+
+        // A fresh session replays the same response and reaches the same decision
+        let replay = XaiReplay::load_from(ReplayMode::Replay, path.clone());
+        assert_eq!(replay.mode(), ReplayMode::Replay);
+        let replayed = replay.get("research", question, description).unwrap();
+        assert_eq!(replayed.text, recorded.text);
+        assert_eq!(replayed.sources, recorded.sources);
+        assert_eq!(decide(&replayed), decide(&recorded));
+
+        // Keys are exact: any other call, question or description is unrecorded
+        assert!(replay.get("confirmation", question, description).is_none());
+        assert!(replay
+            .get("research", "will x ship by june", description)
+            .is_none());
+        assert!(replay.get("research", question, None).is_none());
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let entry = saved.as_object().unwrap().values().next().unwrap();
+        assert_eq!(entry["prompt"]["input"][0]["content"], question);
+        let _ = std::fs::remove_file(&path);
+    }
+}